@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 mod parity_db_adapter;
+pub mod trace;
 mod upgrade;
 mod utils;
 
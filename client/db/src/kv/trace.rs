@@ -0,0 +1,561 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Store of the call traces of finalized blocks, backing the `trace_*` RPC.
+//!
+//! Traces are kept in a database of their own, next to the mapping database, and are
+//! indexed by block number, transaction hash and by the addresses taking part in a call.
+//! Blocks are written in order by the trace sync worker, which also prunes the blocks that
+//! fall out of the retention window.
+//!
+//! The address index has one empty entry per address and block, keyed by the address
+//! followed by the big-endian block number, so the blocks of an address are a key range.
+
+use std::{
+	collections::BTreeSet,
+	marker::PhantomData,
+	ops::{Range, RangeInclusive},
+	path::Path,
+	sync::Arc,
+};
+
+use parking_lot::Mutex;
+use scale_codec::{Decode, Encode};
+// Substrate
+use sp_core::{H160, H256};
+use sp_runtime::traits::Block as BlockT;
+// Frontier
+use fp_evm_tracing::CallFrame;
+
+use super::{
+	frontier_database_dir, parity_db_adapter::DbAdapter, Database, DatabaseSettings,
+	DatabaseSource, DbHash,
+};
+
+pub(crate) mod columns {
+	pub const NUM_COLUMNS: u32 = 4;
+
+	pub const META: u32 = 0;
+	pub const BLOCK_TRACES: u32 = 1;
+	pub const TRANSACTION_INDEX: u32 = 2;
+	pub const ADDRESS_INDEX: u32 = 3;
+}
+
+pub mod static_keys {
+	/// Highest block number the worker went through.
+	pub const SYNCED_TO: &[u8] = b"SYNCED_TO";
+	/// Lowest block number that has not been pruned.
+	pub const PRUNED_TO: &[u8] = b"PRUNED_TO";
+}
+
+/// Call trace of an Ethereum transaction.
+#[derive(Clone, Encode, Debug, Decode, Eq, PartialEq)]
+pub struct TransactionTraces {
+	pub transaction_hash: H256,
+	pub frame: CallFrame,
+}
+
+/// Call traces of the Ethereum transactions of a block, in block order.
+#[derive(Clone, Encode, Debug, Decode, Eq, PartialEq)]
+pub struct BlockTraces<Block: BlockT> {
+	pub block_hash: Block::Hash,
+	pub ethereum_block_hash: H256,
+	pub transactions: Vec<TransactionTraces>,
+}
+
+impl<Block: BlockT> BlockTraces<Block> {
+	/// Every address appearing as the sender or recipient of a call.
+	pub fn addresses(&self) -> BTreeSet<H160> {
+		fn collect(frame: &CallFrame, addresses: &mut BTreeSet<H160>) {
+			addresses.insert(frame.from);
+			addresses.insert(frame.to);
+			for call in &frame.calls {
+				collect(call, addresses);
+			}
+		}
+
+		let mut addresses = BTreeSet::new();
+		for transaction in &self.transactions {
+			collect(&transaction.frame, &mut addresses);
+		}
+		addresses
+	}
+}
+
+/// Key of the address index entry of `address` in block `number`.
+fn address_key(address: &H160, number: u32) -> Vec<u8> {
+	let mut key = address.as_bytes().to_vec();
+	key.extend_from_slice(&number.to_be_bytes());
+	key
+}
+
+/// Block number of an address index key.
+fn address_key_block(key: &[u8]) -> Option<u32> {
+	let number = key.get(H160::len_bytes()..)?.try_into().ok()?;
+	Some(u32::from_be_bytes(number))
+}
+
+/// Database of the trace store. On top of the mapping database operations, the address index
+/// needs ordered key scans.
+trait TraceStore: Database<DbHash> {
+	/// Keys of `column` starting with `prefix` and not lower than `from`, in ascending order.
+	fn keys_from<'a>(
+		&'a self,
+		column: u32,
+		prefix: &'a [u8],
+		from: &[u8],
+	) -> Box<dyn Iterator<Item = Result<Vec<u8>, String>> + 'a>;
+}
+
+pub struct TraceDb<Block: BlockT> {
+	db: Arc<dyn TraceStore>,
+	write_lock: Arc<Mutex<()>>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> TraceDb<Block> {
+	pub fn open(database: &DatabaseSource, db_config_dir: &Path) -> Result<Self, String> {
+		Self::new(&DatabaseSettings {
+			source: match database {
+				DatabaseSource::Auto { .. } => DatabaseSource::Auto {
+					rocksdb_path: frontier_database_dir(db_config_dir, "traces"),
+					paritydb_path: frontier_database_dir(db_config_dir, "traces-paritydb"),
+					cache_size: 0,
+				},
+				#[cfg(feature = "rocksdb")]
+				DatabaseSource::RocksDb { .. } => DatabaseSource::RocksDb {
+					path: frontier_database_dir(db_config_dir, "traces"),
+					cache_size: 0,
+				},
+				DatabaseSource::ParityDb { .. } => DatabaseSource::ParityDb {
+					path: frontier_database_dir(db_config_dir, "traces-paritydb"),
+				},
+				_ => {
+					return Err("Supported db sources: `auto` | `rocksdb` | `paritydb`".to_string())
+				}
+			},
+		})
+	}
+
+	pub fn new(config: &DatabaseSettings) -> Result<Self, String> {
+		let db: Arc<dyn TraceStore> = match &config.source {
+			DatabaseSource::Auto {
+				paritydb_path,
+				rocksdb_path,
+				..
+			} => match open_kvdb_rocksdb(rocksdb_path, false) {
+				Ok(db) => db,
+				Err(_) => open_parity_db(paritydb_path)?,
+			},
+			#[cfg(feature = "rocksdb")]
+			DatabaseSource::RocksDb { path, .. } => open_kvdb_rocksdb(path, true)?,
+			DatabaseSource::ParityDb { path } => open_parity_db(path)?,
+			_ => return Err("Supported db sources: `auto` | `rocksdb` | `paritydb`".to_string()),
+		};
+
+		Ok(Self {
+			db,
+			write_lock: Arc::new(Mutex::new(())),
+			_marker: PhantomData,
+		})
+	}
+
+	fn read<T: Decode>(&self, column: u32, key: &[u8]) -> Result<Option<T>, String> {
+		match self.db.get(column, key) {
+			Some(raw) => Ok(Some(
+				T::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?,
+			)),
+			None => Ok(None),
+		}
+	}
+
+	/// Highest block number the worker went through, if any.
+	pub fn synced_to(&self) -> Result<Option<u32>, String> {
+		self.read(columns::META, static_keys::SYNCED_TO)
+	}
+
+	/// Lowest block number that may still be stored, if any block was written.
+	pub fn pruned_to(&self) -> Result<Option<u32>, String> {
+		self.read(columns::META, static_keys::PRUNED_TO)
+	}
+
+	/// Block numbers the store may answer for.
+	pub fn indexed_range(&self) -> Result<Option<(u32, u32)>, String> {
+		Ok(match (self.pruned_to()?, self.synced_to()?) {
+			(Some(from), Some(to)) if from <= to => Some((from, to)),
+			_ => None,
+		})
+	}
+
+	pub fn block_traces(&self, number: u32) -> Result<Option<BlockTraces<Block>>, String> {
+		self.read(columns::BLOCK_TRACES, &number.encode())
+	}
+
+	/// Number of the block including the transaction.
+	pub fn transaction_block(&self, transaction_hash: &H256) -> Result<Option<u32>, String> {
+		self.read(columns::TRANSACTION_INDEX, &transaction_hash.encode())
+	}
+
+	/// Ascending numbers of the blocks of `range` in which the address sent or received a
+	/// call.
+	pub fn address_blocks(
+		&self,
+		address: &H160,
+		range: RangeInclusive<u32>,
+	) -> Result<Vec<u32>, String> {
+		self.address_keys(address, *range.start())
+			.take_while(|entry| {
+				entry
+					.as_ref()
+					.map_or(true, |(number, _)| number <= range.end())
+			})
+			.map(|entry| entry.map(|(number, _)| number))
+			.collect()
+	}
+
+	/// Address index entries of `address` from block `from` on, with their block number.
+	fn address_keys<'a>(
+		&'a self,
+		address: &'a H160,
+		from: u32,
+	) -> impl Iterator<Item = Result<(u32, Vec<u8>), String>> + 'a {
+		self.db
+			.keys_from(
+				columns::ADDRESS_INDEX,
+				address.as_bytes(),
+				&address_key(address, from),
+			)
+			.filter_map(|key| match key {
+				Ok(key) => address_key_block(&key).map(|number| Ok((number, key))),
+				Err(err) => Some(Err(err)),
+			})
+	}
+
+	/// Moves the sync cursor past a block that has nothing to store.
+	pub fn write_none(&self, number: u32) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut transaction = sp_database::Transaction::new();
+		if self.pruned_to()?.is_none() {
+			transaction.set(columns::META, static_keys::PRUNED_TO, &number.encode());
+		}
+		transaction.set(columns::META, static_keys::SYNCED_TO, &number.encode());
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	/// Stores the traces of the block and moves the sync cursor to it.
+	pub fn write_block(&self, number: u32, traces: BlockTraces<Block>) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut transaction = sp_database::Transaction::new();
+
+		for address in traces.addresses() {
+			transaction.set(columns::ADDRESS_INDEX, &address_key(&address, number), &[]);
+		}
+		for trace in &traces.transactions {
+			transaction.set(
+				columns::TRANSACTION_INDEX,
+				&trace.transaction_hash.encode(),
+				&number.encode(),
+			);
+		}
+		transaction.set(columns::BLOCK_TRACES, &number.encode(), &traces.encode());
+
+		if self.pruned_to()?.is_none() {
+			transaction.set(columns::META, static_keys::PRUNED_TO, &number.encode());
+		}
+		transaction.set(columns::META, static_keys::SYNCED_TO, &number.encode());
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	/// Removes every block below `number` along with its index entries.
+	pub fn prune(&self, number: u32) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let Some(pruned_to) = self.pruned_to()? else {
+			return Ok(());
+		};
+		if pruned_to >= number {
+			return Ok(());
+		}
+
+		let mut transaction = sp_database::Transaction::new();
+		let mut addresses = BTreeSet::new();
+		for block in pruned_to..number {
+			let Some(traces) = self.block_traces(block)? else {
+				continue;
+			};
+			addresses.extend(traces.addresses());
+			for trace in &traces.transactions {
+				transaction.remove(columns::TRANSACTION_INDEX, &trace.transaction_hash.encode());
+			}
+			transaction.remove(columns::BLOCK_TRACES, &block.encode());
+		}
+		for address in addresses {
+			self.remove_address_keys(&mut transaction, &address, pruned_to..number)?;
+		}
+		transaction.set(columns::META, static_keys::PRUNED_TO, &number.encode());
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	/// Removes the address index entries of `address` for the blocks of `range`.
+	fn remove_address_keys(
+		&self,
+		transaction: &mut sp_database::Transaction<DbHash>,
+		address: &H160,
+		range: Range<u32>,
+	) -> Result<(), String> {
+		for entry in self.address_keys(address, range.start) {
+			let (number, key) = entry?;
+			if number >= range.end {
+				break;
+			}
+			transaction.remove(columns::ADDRESS_INDEX, &key);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+struct RocksDbAdapter(kvdb_rocksdb::Database);
+
+#[cfg(feature = "rocksdb")]
+impl Database<DbHash> for RocksDbAdapter {
+	fn commit(
+		&self,
+		transaction: sp_database::Transaction<DbHash>,
+	) -> Result<(), sp_database::error::DatabaseError> {
+		let mut batch = self.0.transaction();
+		for change in transaction.0 {
+			match change {
+				sp_database::Change::Set(col, key, value) => batch.put_vec(col, &key, value),
+				sp_database::Change::Remove(col, key) => batch.delete(col, &key),
+				_ => unimplemented!(),
+			}
+		}
+		self.0
+			.write(batch)
+			.map_err(|err| sp_database::error::DatabaseError(Box::new(err)))
+	}
+
+	fn get(&self, col: sp_database::ColumnId, key: &[u8]) -> Option<Vec<u8>> {
+		match self.0.get(col, key) {
+			Ok(value) => value,
+			Err(e) => panic!("Critical database error: {:?}", e),
+		}
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+impl TraceStore for RocksDbAdapter {
+	fn keys_from<'a>(
+		&'a self,
+		column: u32,
+		prefix: &'a [u8],
+		from: &[u8],
+	) -> Box<dyn Iterator<Item = Result<Vec<u8>, String>> + 'a> {
+		let from = from.to_vec();
+		Box::new(
+			self.0
+				.iter_with_prefix(column, prefix)
+				.map(|entry| {
+					entry
+						.map(|(key, _)| key.to_vec())
+						.map_err(|err| format!("{}", err))
+				})
+				.skip_while(move |key| key.as_ref().map_or(false, |key| *key < from)),
+		)
+	}
+}
+
+impl TraceStore for DbAdapter {
+	fn keys_from<'a>(
+		&'a self,
+		column: u32,
+		prefix: &'a [u8],
+		from: &[u8],
+	) -> Box<dyn Iterator<Item = Result<Vec<u8>, String>> + 'a> {
+		let mut iter = match self.0.iter(column as u8) {
+			Ok(iter) => iter,
+			Err(err) => return Box::new(std::iter::once(Err(format!("{}", err)))),
+		};
+		if let Err(err) = iter.seek(from) {
+			return Box::new(std::iter::once(Err(format!("{}", err))));
+		}
+		let mut done = false;
+		Box::new(std::iter::from_fn(move || {
+			if done {
+				return None;
+			}
+			match iter.next() {
+				Ok(Some((key, _))) if key.starts_with(prefix) => Some(Ok(key)),
+				Ok(_) => {
+					done = true;
+					None
+				}
+				Err(err) => {
+					done = true;
+					Some(Err(format!("{}", err)))
+				}
+			}
+		}))
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+fn open_kvdb_rocksdb(path: &Path, create: bool) -> Result<Arc<dyn TraceStore>, String> {
+	let mut db_config = kvdb_rocksdb::DatabaseConfig::with_columns(columns::NUM_COLUMNS);
+	db_config.create_if_missing = create;
+
+	let db = kvdb_rocksdb::Database::open(&db_config, path).map_err(|err| format!("{}", err))?;
+	Ok(Arc::new(RocksDbAdapter(db)))
+}
+
+#[cfg(not(feature = "rocksdb"))]
+fn open_kvdb_rocksdb(_path: &Path, _create: bool) -> Result<Arc<dyn TraceStore>, String> {
+	Err("Missing feature flags `rocksdb`".to_string())
+}
+
+fn open_parity_db(path: &Path) -> Result<Arc<dyn TraceStore>, String> {
+	let mut config = parity_db::Options::with_columns(path, columns::NUM_COLUMNS as u8);
+	// Only ordered columns can be iterated.
+	config.columns[columns::ADDRESS_INDEX as usize].btree_index = true;
+
+	let db = parity_db::Db::open_or_create(&config).map_err(|err| format!("{}", err))?;
+	Ok(Arc::new(DbAdapter(db)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use fp_evm_tracing::CallType;
+	use sp_core::U256;
+	use substrate_test_runtime_client::runtime::Block;
+
+	fn call(from: u64, to: u64, calls: Vec<CallFrame>) -> CallFrame {
+		CallFrame {
+			call_type: CallType::Call,
+			from: H160::from_low_u64_be(from),
+			to: H160::from_low_u64_be(to),
+			value: Some(U256::zero()),
+			gas: 21_000,
+			gas_used: 21_000,
+			input: Vec::new(),
+			output: Vec::new(),
+			error: None,
+			calls,
+		}
+	}
+
+	fn block(transactions: Vec<(u64, CallFrame)>) -> BlockTraces<Block> {
+		BlockTraces {
+			block_hash: Default::default(),
+			ethereum_block_hash: H256::zero(),
+			transactions: transactions
+				.into_iter()
+				.map(|(hash, frame)| TransactionTraces {
+					transaction_hash: H256::from_low_u64_be(hash),
+					frame,
+				})
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn write_and_prune_blocks() {
+		let tmp = tempfile::tempdir().expect("create a temporary directory");
+		let db = TraceDb::<Block>::new(&DatabaseSettings {
+			source: DatabaseSource::RocksDb {
+				path: tmp.path().to_owned(),
+				cache_size: 0,
+			},
+		})
+		.expect("open trace db");
+		assert_eq!(db.indexed_range().unwrap(), None);
+
+		db.write_block(3, block(vec![(1, call(1, 2, vec![call(2, 3, vec![])]))]))
+			.unwrap();
+		db.write_none(4).unwrap();
+		db.write_block(5, block(vec![(2, call(1, 4, vec![]))]))
+			.unwrap();
+
+		assert_eq!(db.indexed_range().unwrap(), Some((3, 5)));
+		assert_eq!(
+			db.address_blocks(&H160::from_low_u64_be(1), 0..=u32::MAX)
+				.unwrap(),
+			vec![3, 5]
+		);
+		assert_eq!(
+			db.address_blocks(&H160::from_low_u64_be(1), 4..=5).unwrap(),
+			vec![5]
+		);
+		assert_eq!(
+			db.address_blocks(&H160::from_low_u64_be(1), 0..=4).unwrap(),
+			vec![3]
+		);
+		assert_eq!(
+			db.address_blocks(&H160::from_low_u64_be(3), 0..=u32::MAX)
+				.unwrap(),
+			vec![3]
+		);
+		// A neighbouring address does not leak into the scan.
+		assert!(db
+			.address_blocks(&H160::from_low_u64_be(0), 0..=u32::MAX)
+			.unwrap()
+			.is_empty());
+		assert_eq!(
+			db.transaction_block(&H256::from_low_u64_be(2)).unwrap(),
+			Some(5)
+		);
+		assert!(db.block_traces(4).unwrap().is_none());
+
+		db.prune(5).unwrap();
+
+		assert_eq!(db.indexed_range().unwrap(), Some((5, 5)));
+		assert!(db.block_traces(3).unwrap().is_none());
+		assert_eq!(
+			db.transaction_block(&H256::from_low_u64_be(1)).unwrap(),
+			None
+		);
+		assert_eq!(
+			db.address_blocks(&H160::from_low_u64_be(1), 0..=u32::MAX)
+				.unwrap(),
+			vec![5]
+		);
+		assert!(db
+			.address_blocks(&H160::from_low_u64_be(3), 0..=u32::MAX)
+			.unwrap()
+			.is_empty());
+	}
+}
@@ -18,9 +18,13 @@
 
 #![allow(clippy::too_many_arguments)]
 
+mod trace;
 mod worker;
 
-pub use worker::MappingSyncWorker;
+pub use self::{
+	trace::{sync_traces, TraceSyncWorker, TraceSyncWorkerConfig},
+	worker::MappingSyncWorker,
+};
 
 use std::sync::Arc;
 
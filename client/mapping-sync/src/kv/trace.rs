@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Background worker filling the trace store with the call traces of finalized blocks.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::{FutureExt, StreamExt};
+use futures_timer::Delay;
+// Substrate
+use sc_client_api::{backend::Backend, client::FinalityNotifications, BlockBackend};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto};
+// Frontier
+use fc_db::kv::trace::{BlockTraces, TraceDb, TransactionTraces};
+use fp_evm_tracing::{DebugRuntimeApi, TraceResult, TracerConfig};
+use fp_rpc::EthereumRuntimeRPCApi;
+
+pub struct TraceSyncWorkerConfig {
+	/// Number of finalized blocks to keep traces of, all of them when `None`. Blocks whose
+	/// parent state was pruned cannot be replayed and are never traced.
+	pub retention: Option<u32>,
+	/// Maximum number of blocks traced or pruned in one go.
+	pub batch_size: u32,
+	/// Interval at which the worker checks for new blocks without a finality notification.
+	pub check_interval: Duration,
+}
+
+/// Traces finalized blocks into the trace store, in order, and prunes the ones that
+/// fall out of the retention window.
+pub struct TraceSyncWorker<Block, Client> {
+	_phantom: std::marker::PhantomData<(Block, Client)>,
+}
+
+impl<Block: BlockT, Client> TraceSyncWorker<Block, Client>
+where
+	Client: ProvideRuntimeApi<Block>,
+	Client::Api: EthereumRuntimeRPCApi<Block> + DebugRuntimeApi<Block>,
+	Client: HeaderBackend<Block> + BlockBackend<Block> + 'static,
+{
+	pub async fn run<BE: Backend<Block>>(
+		client: Arc<Client>,
+		backend: Arc<BE>,
+		trace_db: Arc<TraceDb<Block>>,
+		finality_notifications: FinalityNotifications<Block>,
+		worker_config: TraceSyncWorkerConfig,
+	) {
+		let mut notifications = finality_notifications.fuse();
+		loop {
+			match sync_traces(
+				client.as_ref(),
+				backend.as_ref(),
+				trace_db.as_ref(),
+				worker_config.retention,
+				worker_config.batch_size,
+			) {
+				Ok(true) => continue,
+				Ok(false) => (),
+				Err(e) => {
+					log::warn!(target: "trace-sync", "Tracing failed with error {:?}, retrying.", e);
+				}
+			}
+
+			let mut timeout = Delay::new(worker_config.check_interval).fuse();
+			futures::select! {
+				_ = timeout => (),
+				notification = notifications.next() => {
+					if notification.is_none() {
+						return;
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Prunes and traces up to `batch_size` blocks each. Returns whether there is more to do.
+///
+/// Tracing starts at the oldest block whose parent state the node still has, so a node
+/// without archive state begins at the bottom of its pruning window instead of block 1.
+pub fn sync_traces<Block: BlockT, C, BE>(
+	client: &C,
+	backend: &BE,
+	trace_db: &TraceDb<Block>,
+	retention: Option<u32>,
+	batch_size: u32,
+) -> Result<bool, String>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: EthereumRuntimeRPCApi<Block> + DebugRuntimeApi<Block>,
+	C: HeaderBackend<Block> + BlockBackend<Block>,
+	BE: Backend<Block>,
+{
+	let finalized: u32 = client.info().finalized_number.unique_saturated_into();
+	// The genesis block has no transactions to trace.
+	let first_retained = retention
+		.map_or(1, |retention| {
+			finalized.saturating_sub(retention).saturating_add(1)
+		})
+		.max(1);
+
+	let mut have_next = false;
+	if let Some(pruned_to) = trace_db.pruned_to()? {
+		if pruned_to < first_retained {
+			let prune_to = first_retained.min(pruned_to.saturating_add(batch_size));
+			trace_db.prune(prune_to)?;
+			have_next = prune_to < first_retained;
+		}
+	}
+
+	let mut next = trace_db
+		.synced_to()?
+		.map_or(1, |synced_to| synced_to.saturating_add(1))
+		.max(first_retained);
+	if next > finalized {
+		return Ok(have_next);
+	}
+	if !has_parent_state(client, backend, next)? {
+		let first = first_with_parent_state(client, backend, next, finalized)?
+			.ok_or_else(|| format!("State of block #{} is pruned", finalized - 1))?;
+		log::warn!(
+			target: "trace-sync",
+			"State before block #{} is pruned, blocks #{}..#{} are not traced.",
+			first,
+			next,
+			first - 1,
+		);
+		// Drop what is stored below the gap so the indexed range stays contiguous.
+		trace_db.prune(first)?;
+		next = first;
+	}
+	let last = finalized.min(next.saturating_add(batch_size.saturating_sub(1)));
+	for number in next..=last {
+		trace_block(client, trace_db, number)?;
+	}
+
+	Ok(have_next || last < finalized)
+}
+
+/// Whether the state block `number` is executed on is still available.
+fn has_parent_state<Block: BlockT, C, BE>(
+	client: &C,
+	backend: &BE,
+	number: u32,
+) -> Result<bool, String>
+where
+	C: HeaderBackend<Block>,
+	BE: Backend<Block>,
+{
+	let parent = number.saturating_sub(1);
+	let hash = client
+		.hash(parent.into())
+		.map_err(|e| format!("{:?}", e))?
+		.ok_or_else(|| format!("Block #{} not found", parent))?;
+	Ok(backend.have_state_at(hash, parent.into()))
+}
+
+/// Lowest block in `from..=to` whose parent state is available. State is pruned from the
+/// oldest block up, so the availability is monotonic and can be bisected.
+fn first_with_parent_state<Block: BlockT, C, BE>(
+	client: &C,
+	backend: &BE,
+	from: u32,
+	to: u32,
+) -> Result<Option<u32>, String>
+where
+	C: HeaderBackend<Block>,
+	BE: Backend<Block>,
+{
+	let (mut low, mut high) = (from, to.saturating_add(1));
+	while low < high {
+		let middle = low + (high - low) / 2;
+		if has_parent_state(client, backend, middle)? {
+			high = middle;
+		} else {
+			low = middle + 1;
+		}
+	}
+	Ok((low <= to).then_some(low))
+}
+
+fn trace_block<Block: BlockT, C>(
+	client: &C,
+	trace_db: &TraceDb<Block>,
+	number: u32,
+) -> Result<(), String>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: EthereumRuntimeRPCApi<Block> + DebugRuntimeApi<Block>,
+	C: HeaderBackend<Block> + BlockBackend<Block>,
+{
+	let hash = client
+		.hash(number.into())
+		.map_err(|e| format!("{:?}", e))?
+		.ok_or_else(|| format!("Block #{} not found", number))?;
+	let header = client
+		.header(hash)
+		.map_err(|e| format!("{:?}", e))?
+		.ok_or_else(|| format!("Header of block #{} not found", number))?;
+	let extrinsics = client
+		.block_body(hash)
+		.map_err(|e| format!("{:?}", e))?
+		.ok_or_else(|| format!("Body of block #{} not found", number))?;
+	let parent_hash = *header.parent_hash();
+
+	let api = client.runtime_api();
+	if !api
+		.has_api::<dyn DebugRuntimeApi<Block>>(parent_hash)
+		.map_err(|e| format!("{:?}", e))?
+	{
+		return trace_db.write_none(number);
+	}
+	let ethereum_block_hash = match api.current_block(hash).map_err(|e| format!("{:?}", e))? {
+		Some(block) => block.header.hash(),
+		None => return trace_db.write_none(number),
+	};
+
+	let traces = match client
+		.runtime_api()
+		.trace_block(
+			parent_hash,
+			extrinsics,
+			&header,
+			TracerConfig::CallTracer {
+				only_top_call: false,
+			},
+		)
+		.map_err(|e| format!("{:?}", e))?
+	{
+		Ok(traces) => traces,
+		Err(e) => {
+			// Replaying the block again will fail the same way, leave it out of the store.
+			log::warn!(target: "trace-sync", "Tracing block #{} failed with {:?}, skipping.", number, e);
+			return trace_db.write_none(number);
+		}
+	};
+
+	trace_db.write_block(
+		number,
+		BlockTraces {
+			block_hash: hash,
+			ethereum_block_hash,
+			transactions: traces
+				.into_iter()
+				.filter_map(|(transaction_hash, result)| match result {
+					TraceResult::CallTracer(frame) => Some(TransactionTraces {
+						transaction_hash,
+						frame,
+					}),
+					_ => None,
+				})
+				.collect(),
+		},
+	)
+}
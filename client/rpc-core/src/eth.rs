@@ -137,6 +137,10 @@ pub trait EthApi {
 	#[method(name = "eth_getTransactionReceipt")]
 	async fn transaction_receipt(&self, hash: H256) -> RpcResult<Option<Receipt>>;
 
+	/// Returns the receipts of every transaction in a block.
+	#[method(name = "eth_getBlockReceipts")]
+	async fn block_receipts(&self, number: BlockNumber) -> RpcResult<Option<Vec<Receipt>>>;
+
 	// ########################################################################
 	// State
 	// ########################################################################
@@ -166,6 +170,17 @@ pub trait EthApi {
 	#[method(name = "eth_getCode")]
 	async fn code_at(&self, address: H160, number: Option<BlockNumber>) -> RpcResult<Bytes>;
 
+	/// Returns the account and the given storage slots at given time (block number), with
+	/// proofs of them against the state root of the block (EIP-1186). The balance is the
+	/// proven free balance, which may exceed what `eth_getBalance` reports as spendable.
+	#[method(name = "eth_getProof")]
+	async fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number: Option<BlockNumber>,
+	) -> RpcResult<EthAccount>;
+
 	// ########################################################################
 	// Execute
 	// ########################################################################
@@ -187,6 +202,15 @@ pub trait EthApi {
 		number: Option<BlockNumber>,
 	) -> RpcResult<U256>;
 
+	/// Generates the access list of a call, along with the gas it uses with the list
+	/// applied (EIP-2930).
+	#[method(name = "eth_createAccessList")]
+	async fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> RpcResult<AccessListResult>;
+
 	// ########################################################################
 	// Fee
 	// ########################################################################
@@ -20,9 +20,11 @@
 
 pub mod types;
 
+mod debug;
 mod eth;
 mod eth_pubsub;
 mod net;
+mod trace;
 #[cfg(feature = "txpool")]
 mod txpool;
 mod web3;
@@ -30,8 +32,10 @@ mod web3;
 #[cfg(feature = "txpool")]
 pub use self::txpool::TxPoolApiServer;
 pub use self::{
+	debug::DebugApiServer,
 	eth::{EthApiServer, EthFilterApiServer},
 	eth_pubsub::EthPubSubApiServer,
 	net::NetApiServer,
+	trace::TraceApiServer,
 	web3::Web3ApiServer,
 };
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Trace rpc interface.
+
+use ethereum_types::H256;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+use crate::types::*;
+
+/// Trace rpc interface, answered from the store of indexed call traces.
+#[rpc(server)]
+#[async_trait]
+pub trait TraceApi {
+	/// Returns the call traces of the block with given number.
+	#[method(name = "trace_block")]
+	async fn trace_block(&self, number: BlockNumber) -> RpcResult<Option<Vec<LocalizedTrace>>>;
+
+	/// Returns the call traces of the transaction with given hash.
+	#[method(name = "trace_transaction")]
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+	) -> RpcResult<Option<Vec<LocalizedTrace>>>;
+
+	/// Returns the call traces matching the filter.
+	#[method(name = "trace_filter")]
+	async fn trace_filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTrace>>;
+}
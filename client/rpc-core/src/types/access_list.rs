@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Return type of `eth_createAccessList`.
+
+use ethereum::AccessListItem;
+use ethereum_types::U256;
+use serde::Serialize;
+
+/// Access list generated for a call.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+	pub access_list: Vec<AccessListItem>,
+	/// Gas used by the call with the access list applied.
+	pub gas_used: U256,
+	/// Why the call failed, the access list then covers what it touched until then.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum_types::{H160, H256};
+
+	#[test]
+	fn access_list_result_serialize() {
+		let result = AccessListResult {
+			access_list: vec![AccessListItem {
+				address: H160::repeat_byte(1),
+				storage_keys: vec![H256::zero()],
+			}],
+			gas_used: U256::from(30_000),
+			error: None,
+		};
+		assert_eq!(
+			serde_json::to_string(&result).unwrap(),
+			format!(
+				r#"{{"accessList":[{{"address":"0x{}","storageKeys":["0x{}"]}}],"gasUsed":"0x7530"}}"#,
+				"01".repeat(20),
+				"00".repeat(32),
+			)
+		);
+	}
+}
@@ -229,4 +229,33 @@ mod tests {
 		assert_eq!(match_block_number(bn_tag_finalized).unwrap(), 999);
 		assert_eq!(match_block_number(bn_tag_pending).unwrap(), 1001);
 	}
+
+	#[test]
+	fn block_hash_deserialize() {
+		let hash = H256::repeat_byte(0xab);
+		let bn_object: BlockNumber =
+			serde_json::from_str(&format!(r#"{{"blockHash":"{:?}"}}"#, hash)).unwrap();
+		let bn_canonical: BlockNumber = serde_json::from_str(&format!(
+			r#"{{"blockHash":"{:?}","requireCanonical":true}}"#,
+			hash
+		))
+		.unwrap();
+
+		assert_eq!(
+			bn_object,
+			BlockNumber::Hash {
+				hash,
+				require_canonical: false
+			}
+		);
+		assert_eq!(
+			bn_canonical,
+			BlockNumber::Hash {
+				hash,
+				require_canonical: true
+			}
+		);
+		// A bare hash is not a block number; EIP-1898 callers pass the object instead.
+		assert!(serde_json::from_str::<BlockNumber>(&format!(r#""{:?}""#, hash)).is_err());
+	}
 }
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Types of the `debug_trace*` methods, in the geth format.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Bytes;
+
+/// Options of the `debug_trace*` methods.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceParams {
+	/// `callTracer` or `prestateTracer`, the struct logger when missing.
+	pub tracer: Option<String>,
+	pub tracer_config: Option<TracerConfig>,
+	#[serde(default)]
+	pub disable_storage: bool,
+	#[serde(default)]
+	pub disable_stack: bool,
+	#[serde(default)]
+	pub enable_memory: bool,
+	/// Accepted for compatibility, tracing is not interrupted.
+	pub timeout: Option<String>,
+}
+
+/// Options of the built-in tracers.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracerConfig {
+	/// Only trace the top call of the `callTracer`.
+	#[serde(default)]
+	pub only_top_call: bool,
+}
+
+/// Output of the struct logger.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogTrace {
+	pub gas: u64,
+	pub failed: bool,
+	/// Hex encoded, without `0x` prefix.
+	pub return_value: String,
+	pub struct_logs: Vec<StructLog>,
+}
+
+/// A single executed opcode.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	pub pc: u64,
+	pub op: String,
+	pub gas: u64,
+	pub gas_cost: u64,
+	pub depth: u32,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// 32-byte words, hex encoded without `0x` prefix.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Slots and values, hex encoded without `0x` prefix.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+/// A call frame of the `callTracer`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+	#[serde(rename = "type")]
+	pub call_type: String,
+	pub from: H160,
+	pub to: H160,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<U256>,
+	pub gas: U256,
+	pub gas_used: U256,
+	pub input: Bytes,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub output: Option<Bytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub revert_reason: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+}
+
+/// State of an account before the traced transaction, as reported by the `prestateTracer`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct PrestateAccount {
+	pub balance: U256,
+	pub nonce: u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, H256>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+	StructLog(StructLogTrace),
+	CallTracer(CallFrame),
+	PrestateTracer(BTreeMap<H160, PrestateAccount>),
+}
+
+/// Trace of one transaction of a block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTrace {
+	pub tx_hash: H256,
+	pub result: TraceResult,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn trace_params_deserialize() {
+		let params: TraceParams = serde_json::from_str(
+			r#"{"tracer":"callTracer","tracerConfig":{"onlyTopCall":true},"timeout":"10s"}"#,
+		)
+		.unwrap();
+		assert_eq!(params.tracer.as_deref(), Some("callTracer"));
+		assert_eq!(
+			params.tracer_config,
+			Some(TracerConfig {
+				only_top_call: true
+			})
+		);
+
+		let params: TraceParams =
+			serde_json::from_str(r#"{"disableStorage":true,"enableMemory":true}"#).unwrap();
+		assert!(params.tracer.is_none());
+		assert!(params.disable_storage && params.enable_memory && !params.disable_stack);
+	}
+
+	#[test]
+	fn call_frame_serialize() {
+		let frame = CallFrame {
+			call_type: "CALL".into(),
+			from: H160::repeat_byte(1),
+			to: H160::repeat_byte(2),
+			value: Some(U256::zero()),
+			gas: U256::from(21_000),
+			gas_used: U256::from(21_000),
+			input: Bytes(vec![]),
+			output: None,
+			error: None,
+			revert_reason: None,
+			calls: vec![],
+		};
+		assert_eq!(
+			serde_json::to_string(&frame).unwrap(),
+			r#"{"type":"CALL","from":"0x0101010101010101010101010101010101010101","to":"0x0202020202020202020202020202020202020202","value":"0x0","gas":"0x5208","gasUsed":"0x5208","input":"0x"}"#
+		);
+	}
+}
@@ -18,17 +18,20 @@
 
 //! RPC types
 
+mod access_list;
 mod account_info;
 mod block;
 mod block_number;
 mod bytes;
 mod call_request;
+mod debug;
 mod fee;
 mod filter;
 mod index;
 mod log;
 mod receipt;
 mod sync;
+mod trace;
 mod transaction;
 mod transaction_request;
 #[cfg(feature = "txpool")]
@@ -42,11 +45,16 @@ use serde::{de::Error, Deserialize, Deserializer};
 #[cfg(feature = "txpool")]
 pub use self::txpool::{Get, Summary, TransactionMap, TxPoolResult, TxPoolTransaction};
 pub use self::{
+	access_list::AccessListResult,
 	account_info::{AccountInfo, EthAccount, ExtAccountInfo, RecoveredAccount, StorageProof},
 	block::{Block, BlockTransactions, Header, Rich, RichBlock, RichHeader},
 	block_number::BlockNumber,
 	bytes::Bytes,
 	call_request::{CallRequest, CallStateOverride},
+	debug::{
+		CallFrame, PrestateAccount, StructLog, StructLogTrace, TraceParams, TraceResult,
+		TracerConfig, TransactionTrace,
+	},
 	fee::{FeeHistory, FeeHistoryCache, FeeHistoryCacheItem, FeeHistoryCacheLimit},
 	filter::{
 		Filter, FilterAddress, FilterChanges, FilterPool, FilterPoolItem, FilterType,
@@ -59,6 +67,10 @@ pub use self::{
 		ChainStatus, EthProtocolInfo, PeerCount, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 		Peers, PipProtocolInfo, SyncInfo, SyncStatus, TransactionStats,
 	},
+	trace::{
+		CallAction, CallOutput, CreateAction, CreateOutput, LocalizedTrace, SuicideAction,
+		TraceAction, TraceFilter, TraceOutput,
+	},
 	transaction::{LocalTransactionStatus, RichRawTransaction, Transaction},
 	transaction_request::{TransactionMessage, TransactionRequest},
 	work::Work,
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Types of the `trace_*` methods, in the Parity format.
+
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{BlockNumber, Bytes};
+
+/// Options of `trace_filter`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFilter {
+	pub from_block: Option<BlockNumber>,
+	pub to_block: Option<BlockNumber>,
+	/// Senders to match, any sender when missing.
+	pub from_address: Option<Vec<H160>>,
+	/// Recipients to match, any recipient when missing.
+	pub to_address: Option<Vec<H160>>,
+	/// Number of matching traces to skip.
+	pub after: Option<usize>,
+	/// Maximum number of traces to return.
+	pub count: Option<usize>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallAction {
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+	pub gas: U256,
+	pub input: Bytes,
+	/// `call`, `staticcall`, `delegatecall` or `callcode`.
+	pub call_type: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAction {
+	pub from: H160,
+	pub value: U256,
+	pub gas: U256,
+	pub init: Bytes,
+	/// `create` or `create2`.
+	pub creation_method: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuicideAction {
+	pub address: H160,
+	pub refund_address: H160,
+	pub balance: U256,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceAction {
+	Call(CallAction),
+	Create(CreateAction),
+	Suicide(SuicideAction),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallOutput {
+	pub gas_used: U256,
+	pub output: Bytes,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOutput {
+	pub gas_used: U256,
+	pub code: Bytes,
+	pub address: H160,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceOutput {
+	Call(CallOutput),
+	Create(CreateOutput),
+}
+
+/// A call of a transaction, located in its block and in the call tree.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedTrace {
+	pub action: TraceAction,
+	pub block_hash: H256,
+	pub block_number: u64,
+	/// Missing when the call failed or for a self-destruct.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub result: Option<TraceOutput>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	pub subtraces: usize,
+	/// Indices of the call and of its ancestors among their siblings.
+	pub trace_address: Vec<usize>,
+	pub transaction_hash: H256,
+	pub transaction_position: usize,
+	/// `call`, `create` or `suicide`.
+	#[serde(rename = "type")]
+	pub trace_type: String,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn trace_filter_deserialize() {
+		let filter: TraceFilter = serde_json::from_str(
+			r#"{"fromBlock":"0x1","toBlock":"latest","toAddress":["0x0101010101010101010101010101010101010101"],"count":10}"#,
+		)
+		.unwrap();
+		assert_eq!(filter.from_block, Some(BlockNumber::Num(1)));
+		assert_eq!(filter.to_block, Some(BlockNumber::Latest));
+		assert_eq!(filter.from_address, None);
+		assert_eq!(filter.to_address, Some(vec![H160::repeat_byte(1)]));
+		assert_eq!((filter.after, filter.count), (None, Some(10)));
+	}
+
+	#[test]
+	fn localized_trace_serialize() {
+		let trace = LocalizedTrace {
+			action: TraceAction::Call(CallAction {
+				from: H160::repeat_byte(1),
+				to: H160::repeat_byte(2),
+				value: U256::zero(),
+				gas: U256::from(21_000),
+				input: Bytes(vec![]),
+				call_type: "call".into(),
+			}),
+			block_hash: H256::zero(),
+			block_number: 1,
+			result: None,
+			error: Some("Reverted".into()),
+			subtraces: 0,
+			trace_address: vec![],
+			transaction_hash: H256::zero(),
+			transaction_position: 0,
+			trace_type: "call".into(),
+		};
+		assert_eq!(
+			serde_json::to_string(&trace).unwrap(),
+			format!(
+				r#"{{"action":{{"from":"0x{}","to":"0x{}","value":"0x0","gas":"0x5208","input":"0x","callType":"call"}},"blockHash":"0x{}","blockNumber":1,"error":"Reverted","subtraces":0,"traceAddress":[],"transactionHash":"0x{}","transactionPosition":0,"type":"call"}}"#,
+				"01".repeat(20),
+				"02".repeat(20),
+				"00".repeat(32),
+				"00".repeat(32),
+			)
+		);
+	}
+}
@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `debug` namespace: re-execution of transactions under the EVM tracer.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use ethereum_types::{H256, U256};
+use jsonrpsee::core::{async_trait, RpcResult};
+// Substrate
+use sc_client_api::BlockBackend;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Zero};
+// Frontier
+use fc_rpc_core::{types::*, DebugApiServer};
+use fp_evm_tracing::{DebugRuntimeApi, ExitError, ExitReason, TraceError, TracerConfig};
+
+use crate::{frontier_backend_client, internal_err};
+
+/// Debug API implementation.
+pub struct Debug<B: BlockT, C> {
+	client: Arc<C>,
+	backend: Arc<dyn fc_db::BackendReader<B> + Send + Sync>,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C> Debug<B, C> {
+	pub fn new(client: Arc<C>, backend: Arc<dyn fc_db::BackendReader<B> + Send + Sync>) -> Self {
+		Self {
+			client,
+			backend,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C> Debug<B, C>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B>,
+	C::Api: DebugRuntimeApi<B>,
+	C: HeaderBackend<B> + BlockBackend<B> + Send + Sync + 'static,
+{
+	/// Parent hash, extrinsics and header of the block to replay.
+	fn replay_inputs(
+		&self,
+		substrate_hash: B::Hash,
+	) -> RpcResult<Option<(B::Hash, Vec<B::Extrinsic>, B::Header)>> {
+		let header = self
+			.client
+			.header(substrate_hash)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("header not found"))?;
+		if header.number().is_zero() {
+			return Ok(None);
+		}
+		let extrinsics = self
+			.client
+			.block_body(substrate_hash)
+			.map_err(|err| internal_err(format!("fetch block body failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("block body not found"))?;
+		let parent_hash = *header.parent_hash();
+
+		let has_api = self
+			.client
+			.runtime_api()
+			.has_api::<dyn DebugRuntimeApi<B>>(parent_hash)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		if !has_api {
+			return Err(tracing_unsupported());
+		}
+
+		Ok(Some((parent_hash, extrinsics, header)))
+	}
+
+	async fn trace_block(
+		&self,
+		substrate_hash: B::Hash,
+		params: Option<TraceParams>,
+	) -> RpcResult<Vec<TransactionTrace>> {
+		let tracer = tracer_config(params)?;
+		let Some((parent_hash, extrinsics, header)) = self.replay_inputs(substrate_hash)? else {
+			return Ok(Vec::new());
+		};
+
+		let client = self.client.clone();
+		let traces = replay(move || {
+			client
+				.runtime_api()
+				.trace_block(parent_hash, extrinsics, &header, tracer)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(trace_err)
+		})
+		.await?;
+
+		Ok(traces
+			.into_iter()
+			.map(|(tx_hash, result)| TransactionTrace {
+				tx_hash,
+				result: trace_result(result),
+			})
+			.collect())
+	}
+}
+
+#[async_trait]
+impl<B, C> DebugApiServer for Debug<B, C>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B>,
+	C::Api: DebugRuntimeApi<B>,
+	C: HeaderBackend<B> + BlockBackend<B> + Send + Sync + 'static,
+{
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		params: Option<TraceParams>,
+	) -> RpcResult<TraceResult> {
+		let tracer = tracer_config(params)?;
+		let client = self.client.as_ref();
+		let backend = self.backend.as_ref();
+
+		let (ethereum_block_hash, _) = frontier_backend_client::load_transactions::<B, C>(
+			client,
+			backend,
+			transaction_hash,
+			true,
+		)
+		.await?
+		.ok_or_else(|| internal_err("transaction not found"))?;
+		let substrate_hash =
+			frontier_backend_client::load_hash::<B, C>(client, backend, ethereum_block_hash)
+				.await?
+				.ok_or_else(|| internal_err("block not found"))?;
+		let (parent_hash, extrinsics, header) = self
+			.replay_inputs(substrate_hash)?
+			.ok_or_else(|| internal_err("genesis block has no transactions"))?;
+
+		let client = self.client.clone();
+		let result = replay(move || {
+			client
+				.runtime_api()
+				.trace_transaction(parent_hash, extrinsics, &header, transaction_hash, tracer)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(trace_err)
+		})
+		.await?;
+
+		Ok(trace_result(result))
+	}
+
+	async fn trace_block_by_number(
+		&self,
+		number: BlockNumber,
+		params: Option<TraceParams>,
+	) -> RpcResult<Vec<TransactionTrace>> {
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number),
+		)
+		.await?
+		.ok_or_else(|| internal_err("pending block cannot be traced"))?;
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+		self.trace_block(substrate_hash, params).await
+	}
+
+	async fn trace_block_by_hash(
+		&self,
+		hash: H256,
+		params: Option<TraceParams>,
+	) -> RpcResult<Vec<TransactionTrace>> {
+		let substrate_hash = frontier_backend_client::load_hash::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			hash,
+		)
+		.await?
+		.ok_or_else(|| internal_err("block not found"))?;
+
+		self.trace_block(substrate_hash, params).await
+	}
+
+	async fn trace_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		params: Option<TraceParams>,
+	) -> RpcResult<TraceResult> {
+		let tracer = tracer_config(params)?;
+		let substrate_hash = match frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+		)
+		.await?
+		{
+			Some(id) => self
+				.client
+				.expect_block_hash_from_id(&id)
+				.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?,
+			None => self.client.info().best_hash,
+		};
+
+		let CallRequest {
+			from,
+			to,
+			gas_price,
+			max_fee_per_gas,
+			max_priority_fee_per_gas,
+			gas,
+			value,
+			data,
+			nonce,
+			access_list,
+			..
+		} = request;
+
+		let result = self
+			.client
+			.runtime_api()
+			.trace_call(
+				substrate_hash,
+				from.unwrap_or_default(),
+				to,
+				data.map(|d| d.into_vec()).unwrap_or_default(),
+				value.unwrap_or_default(),
+				gas.unwrap_or(U256::MAX),
+				max_fee_per_gas.or(gas_price),
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.map(|list| {
+					list.into_iter()
+						.map(|item| (item.address, item.storage_keys))
+						.collect()
+				}),
+				tracer,
+			)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(trace_err)?;
+
+		Ok(trace_result(result))
+	}
+}
+
+/// Run a block replay on the blocking pool: it takes about as long as the block took to
+/// build, and would hold an RPC worker for all of it.
+async fn replay<T, F>(f: F) -> RpcResult<T>
+where
+	T: Send + 'static,
+	F: FnOnce() -> RpcResult<T> + Send + 'static,
+{
+	tokio::task::spawn_blocking(f)
+		.await
+		.map_err(|err| internal_err(format!("replay task failed: {:?}", err)))?
+}
+
+fn tracer_config(params: Option<TraceParams>) -> RpcResult<TracerConfig> {
+	let params = params.unwrap_or_default();
+	match params.tracer.as_deref() {
+		None => Ok(TracerConfig::StructLog {
+			disable_storage: params.disable_storage,
+			disable_memory: !params.enable_memory,
+			disable_stack: params.disable_stack,
+		}),
+		Some("callTracer") => Ok(TracerConfig::CallTracer {
+			only_top_call: params
+				.tracer_config
+				.map_or(false, |config| config.only_top_call),
+		}),
+		Some("prestateTracer") => Ok(TracerConfig::PrestateTracer),
+		Some(tracer) => Err(internal_err(format!("tracer not supported: {}", tracer))),
+	}
+}
+
+pub(crate) fn trace_err(err: TraceError) -> jsonrpsee::core::Error {
+	match err {
+		TraceError::TransactionNotFound => internal_err("transaction not found in block"),
+		TraceError::InvalidTransaction(err) => {
+			internal_err(format!("block replay failed: {:?}", err))
+		}
+		TraceError::Execution(err) => internal_err(format!("execution failed: {:?}", err)),
+		TraceError::Unsupported => tracing_unsupported(),
+	}
+}
+
+/// The runtime of the block was built without the EVM tracer. Not a node failure, so it is
+/// reported as an unavailable method, as geth does for disabled APIs.
+fn tracing_unsupported() -> jsonrpsee::core::Error {
+	crate::err(
+		jsonrpsee::types::error::METHOD_NOT_FOUND_CODE,
+		"tracing is not available for this block: its runtime was built without the \
+		 `evm-tracing` feature, override it with `--wasm-runtime-overrides`",
+		None,
+	)
+}
+
+/// Error message of an exit reason, worded as geth does where there is an equivalent.
+fn error_message(reason: &ExitReason) -> String {
+	match reason {
+		ExitReason::Revert(_) => "execution reverted".into(),
+		ExitReason::Error(err) => match err {
+			ExitError::OutOfGas => "out of gas".into(),
+			ExitError::OutOfFund => "insufficient balance for transfer".into(),
+			ExitError::StackUnderflow => "stack underflow".into(),
+			ExitError::StackOverflow => "stack limit reached 1024".into(),
+			ExitError::InvalidJump => "invalid jump destination".into(),
+			ExitError::InvalidRange => "return data out of bounds".into(),
+			ExitError::CallTooDeep => "max call depth exceeded".into(),
+			ExitError::CreateCollision => "contract address collision".into(),
+			ExitError::CreateContractLimit => "max code size exceeded".into(),
+			ExitError::Other(message) => message.to_string(),
+			err => format!("{:?}", err),
+		},
+		reason => format!("{:?}", reason),
+	}
+}
+
+/// Message of a Solidity `Error(string)` revert.
+fn revert_reason(output: &[u8]) -> Option<String> {
+	const SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+	let data = output.strip_prefix(&SELECTOR[..])?;
+	let len = U256::from_big_endian(data.get(32..64)?);
+	if len > U256::from(data.len()) {
+		return None;
+	}
+	let message = data.get(64..64 + len.as_usize())?;
+	String::from_utf8(message.to_vec()).ok()
+}
+
+fn call_frame(frame: fp_evm_tracing::CallFrame) -> CallFrame {
+	use fp_evm_tracing::CallType;
+
+	let call_type = match frame.call_type {
+		CallType::Call => "CALL",
+		CallType::StaticCall => "STATICCALL",
+		CallType::DelegateCall => "DELEGATECALL",
+		CallType::CallCode => "CALLCODE",
+		CallType::Create => "CREATE",
+		CallType::Create2 => "CREATE2",
+		CallType::SelfDestruct => "SELFDESTRUCT",
+	};
+	let revert_reason = match frame.error {
+		Some(ExitReason::Revert(_)) => revert_reason(&frame.output),
+		_ => None,
+	};
+
+	CallFrame {
+		call_type: call_type.into(),
+		from: frame.from,
+		to: frame.to,
+		value: frame.value,
+		gas: frame.gas.into(),
+		gas_used: frame.gas_used.into(),
+		input: Bytes(frame.input),
+		output: (!frame.output.is_empty()).then_some(Bytes(frame.output)),
+		error: frame.error.as_ref().map(error_message),
+		revert_reason,
+		calls: frame.calls.into_iter().map(call_frame).collect(),
+	}
+}
+
+fn trace_result(result: fp_evm_tracing::TraceResult) -> TraceResult {
+	match result {
+		fp_evm_tracing::TraceResult::StructLog(trace) => TraceResult::StructLog(StructLogTrace {
+			gas: trace.gas,
+			failed: trace.failed,
+			return_value: hex::encode(trace.return_value),
+			struct_logs: trace
+				.struct_logs
+				.into_iter()
+				.map(|log| StructLog {
+					pc: log.pc,
+					op: fp_evm_tracing::opcode_name(log.op).into(),
+					gas: log.gas,
+					gas_cost: log.gas_cost,
+					depth: log.depth,
+					stack: log.stack.map(|stack| {
+						stack
+							.iter()
+							.map(|item| U256::from_big_endian(item.as_bytes()))
+							.collect()
+					}),
+					memory: log.memory.map(|memory| {
+						memory
+							.chunks(32)
+							.map(|word| {
+								let mut padded = [0u8; 32];
+								padded[..word.len()].copy_from_slice(word);
+								hex::encode(padded)
+							})
+							.collect()
+					}),
+					storage: log.storage.map(|storage| {
+						storage
+							.into_iter()
+							.map(|(slot, value)| (hex::encode(slot), hex::encode(value)))
+							.collect()
+					}),
+					error: log.error.as_ref().map(error_message),
+				})
+				.collect(),
+		}),
+		fp_evm_tracing::TraceResult::CallTracer(frame) => {
+			TraceResult::CallTracer(call_frame(frame))
+		}
+		fp_evm_tracing::TraceResult::PrestateTracer(accounts) => TraceResult::PrestateTracer(
+			accounts
+				.into_iter()
+				.map(|(address, account)| {
+					(
+						address,
+						PrestateAccount {
+							balance: account.balance,
+							nonce: account.nonce.low_u64(),
+							code: (!account.code.is_empty()).then_some(Bytes(account.code)),
+							storage: account.storage,
+						},
+					)
+				})
+				.collect(),
+		),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn revert_reason_decodes_error_string() {
+		let output = hex::decode(
+			"08c379a0\
+			 0000000000000000000000000000000000000000000000000000000000000020\
+			 000000000000000000000000000000000000000000000000000000000000000d\
+			 6e6f7420746865206f776e657200000000000000000000000000000000000000",
+		)
+		.unwrap();
+		assert_eq!(revert_reason(&output).as_deref(), Some("not the owner"));
+		assert_eq!(revert_reason(&output[..40]), None);
+		assert_eq!(revert_reason(&[0u8; 4]), None);
+	}
+
+	#[test]
+	fn tracer_config_from_params() {
+		assert_eq!(
+			tracer_config(None).unwrap(),
+			TracerConfig::StructLog {
+				disable_storage: false,
+				disable_memory: true,
+				disable_stack: false,
+			}
+		);
+		let params = TraceParams {
+			tracer: Some("callTracer".into()),
+			tracer_config: Some(fc_rpc_core::types::TracerConfig {
+				only_top_call: true,
+			}),
+			..Default::default()
+		};
+		assert_eq!(
+			tracer_config(Some(params)).unwrap(),
+			TracerConfig::CallTracer {
+				only_top_call: true
+			}
+		);
+		let params = TraceParams {
+			tracer: Some("4byteTracer".into()),
+			..Default::default()
+		};
+		assert!(tracer_config(Some(params)).is_err());
+	}
+
+	#[test]
+	fn unsupported_tracing_is_not_an_internal_error() {
+		let jsonrpsee::core::Error::Call(jsonrpsee::types::error::CallError::Custom(err)) =
+			trace_err(TraceError::Unsupported)
+		else {
+			panic!("not a call error");
+		};
+		assert_eq!(err.code(), jsonrpsee::types::error::METHOD_NOT_FOUND_CODE);
+		assert!(err.message().contains("evm-tracing"));
+	}
+}
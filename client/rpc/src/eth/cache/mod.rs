@@ -24,7 +24,7 @@ use std::{
 	sync::{Arc, Mutex},
 };
 
-use ethereum::BlockV2 as EthereumBlock;
+use ethereum::{BlockV2 as EthereumBlock, ReceiptV3 as EthereumReceipt};
 use ethereum_types::U256;
 use futures::StreamExt;
 use tokio::sync::{mpsc, oneshot};
@@ -67,9 +67,19 @@ enum EthBlockDataCacheMessage<B: BlockT> {
 		block_hash: B::Hash,
 		statuses: Option<Vec<TransactionStatus>>,
 	},
+
+	RequestCurrentReceipts {
+		block_hash: B::Hash,
+		schema: EthereumStorageSchema,
+		response_tx: oneshot::Sender<Option<Vec<EthereumReceipt>>>,
+	},
+	FetchedCurrentReceipts {
+		block_hash: B::Hash,
+		receipts: Option<Vec<EthereumReceipt>>,
+	},
 }
 
-/// Manage LRU caches for block data and their transaction statuses.
+/// Manage LRU caches for block data, their transaction statuses and receipts.
 /// These are large and take a lot of time to fetch from the database.
 /// Storing them in an LRU cache will allow to reduce database accesses
 /// when many subsequent requests are related to the same blocks.
@@ -81,6 +91,7 @@ impl<B: BlockT> EthBlockDataCacheTask<B> {
 		overrides: Arc<OverrideHandle<B>>,
 		blocks_cache_max_size: usize,
 		statuses_cache_max_size: usize,
+		receipts_cache_max_size: usize,
 		prometheus_registry: Option<prometheus_endpoint::Registry>,
 	) -> Self {
 		let (task_tx, mut task_rx) = mpsc::channel(100);
@@ -96,6 +107,11 @@ impl<B: BlockT> EthBlockDataCacheTask<B> {
 			let mut statuses_cache = LRUCacheByteLimited::<B::Hash, Vec<TransactionStatus>>::new(
 				"statuses_cache",
 				statuses_cache_max_size as u64,
+				prometheus_registry.clone(),
+			);
+			let mut receipts_cache = LRUCacheByteLimited::<B::Hash, Vec<EthereumReceipt>>::new(
+				"receipts_cache",
+				receipts_cache_max_size as u64,
 				prometheus_registry,
 			);
 
@@ -103,6 +119,8 @@ impl<B: BlockT> EthBlockDataCacheTask<B> {
 				HashMap::<B::Hash, Vec<oneshot::Sender<Option<EthereumBlock>>>>::new();
 			let mut awaiting_statuses =
 				HashMap::<B::Hash, Vec<oneshot::Sender<Option<Vec<TransactionStatus>>>>>::new();
+			let mut awaiting_receipts =
+				HashMap::<B::Hash, Vec<oneshot::Sender<Option<Vec<EthereumReceipt>>>>>::new();
 
 			// Handle all incoming messages.
 			// Exits when there are no more senders.
@@ -173,6 +191,39 @@ impl<B: BlockT> EthBlockDataCacheTask<B> {
 							statuses_cache.put(block_hash, statuses);
 						}
 					}
+
+					RequestCurrentReceipts {
+						block_hash,
+						schema,
+						response_tx,
+					} => Self::request_current(
+						&spawn_handle,
+						&mut receipts_cache,
+						&mut awaiting_receipts,
+						Arc::clone(&overrides),
+						block_hash,
+						schema,
+						response_tx,
+						task_tx.clone(),
+						move |handler| FetchedCurrentReceipts {
+							block_hash,
+							receipts: handler.current_receipts(block_hash),
+						},
+					),
+					FetchedCurrentReceipts {
+						block_hash,
+						receipts,
+					} => {
+						if let Some(wait_list) = awaiting_receipts.remove(&block_hash) {
+							for sender in wait_list {
+								let _ = sender.send(receipts.clone());
+							}
+						}
+
+						if let Some(receipts) = receipts {
+							receipts_cache.put(block_hash, receipts);
+						}
+					}
 				}
 			}
 		});
@@ -265,6 +316,26 @@ impl<B: BlockT> EthBlockDataCacheTask<B> {
 
 		response_rx.await.ok()?
 	}
+
+	/// Cache for `handler.current_receipts`.
+	pub async fn current_receipts(
+		&self,
+		schema: EthereumStorageSchema,
+		block_hash: B::Hash,
+	) -> Option<Vec<EthereumReceipt>> {
+		let (response_tx, response_rx) = oneshot::channel();
+
+		self.0
+			.send(EthBlockDataCacheMessage::RequestCurrentReceipts {
+				block_hash,
+				schema,
+				response_tx,
+			})
+			.await
+			.ok()?;
+
+		response_rx.await.ok()?
+	}
 }
 
 pub struct EthTask<B, C, BE>(PhantomData<(B, C, BE)>);
@@ -16,8 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
+use std::{
+	cell::RefCell,
+	collections::{BTreeMap, BTreeSet},
+	sync::Arc,
+};
 
+use ethereum::AccessListItem;
 use ethereum_types::{H160, H256, U256};
 use evm::{ExitError, ExitReason};
 use jsonrpsee::core::RpcResult;
@@ -31,12 +36,14 @@ use sp_api::{
 };
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
+use sp_core::hashing::keccak_256;
 use sp_io::hashing::{blake2_128, twox_128};
 use sp_runtime::{traits::Block as BlockT, DispatchError, SaturatedConversion};
 use sp_state_machine::OverlayedChanges;
 // Frontier
 use fc_rpc_core::types::*;
 use fp_evm::{ExecutionInfo, ExecutionInfoV2};
+use fp_evm_tracing::{DebugRuntimeApi, PrestateAccount, TracerConfig};
 use fp_rpc::{EthereumRuntimeRPCApi, RuntimeStorageOverride};
 use fp_storage::{EVM_ACCOUNT_CODES, PALLET_EVM};
 
@@ -48,6 +55,10 @@ use crate::{
 /// Default JSONRPC error code return by geth
 pub const JSON_RPC_ERROR_DEFAULT: i32 = -32000;
 
+/// Number of times `eth_createAccessList` traces the call before returning the list found so
+/// far, settled or not.
+const MAX_ACCESS_LIST_ROUNDS: usize = 16;
+
 /// Allow to adapt a request for `estimate_gas`.
 /// Can be used to estimate gas of some contracts using a different function
 /// in the case the normal gas estimation doesn't work.
@@ -963,6 +974,313 @@ where
 	}
 }
 
+impl<B, C, P, CT, BE, A: ChainApi, EC: EthConfig<B, C>> Eth<B, C, P, CT, BE, A, EC>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeRPCApi<B> + DebugRuntimeApi<B>,
+	C: HeaderBackend<B> + StorageProvider<B, BE> + 'static,
+	BE: Backend<B> + 'static,
+	A: ChainApi<Block = B> + 'static,
+{
+	pub async fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> RpcResult<AccessListResult> {
+		let CallRequest {
+			from,
+			to,
+			gas_price,
+			max_fee_per_gas,
+			max_priority_fee_per_gas,
+			gas,
+			value,
+			data,
+			nonce,
+			access_list,
+			..
+		} = request;
+
+		let (max_fee_per_gas, max_priority_fee_per_gas) = {
+			let details = fee_details(gas_price, max_fee_per_gas, max_priority_fee_per_gas)?;
+			(
+				details.max_fee_per_gas.or(details.gas_price),
+				details.max_priority_fee_per_gas,
+			)
+		};
+
+		let substrate_hash = match frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+		)
+		.await?
+		{
+			Some(id) => self
+				.client
+				.expect_block_hash_from_id(&id)
+				.map_err(|_| crate::err(JSON_RPC_ERROR_DEFAULT, "header not found", None))?,
+			None => self.client.info().best_hash,
+		};
+
+		let api = self.client.runtime_api();
+		let api_version = match api.api_version::<dyn EthereumRuntimeRPCApi<B>>(substrate_hash) {
+			Ok(Some(api_version)) if api_version >= 4 => api_version,
+			Ok(Some(_)) => {
+				return Err(internal_err(
+					"access lists are not supported by this runtime version",
+				))
+			}
+			_ => return Err(internal_err("failed to retrieve Runtime Api version")),
+		};
+
+		let gas_limit = match gas {
+			Some(gas) => gas,
+			None => {
+				api.current_block(substrate_hash)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+					.ok_or_else(|| internal_err("block unavailable, cannot query gas limit"))?
+					.header
+					.gas_limit
+			}
+		};
+
+		let from = from.unwrap_or_default();
+		let data = data.map(|d| d.into_vec()).unwrap_or_default();
+		let value = value.unwrap_or_default();
+
+		// The sender and the callee are warm regardless, geth leaves them out of the list
+		// unless their storage is read.
+		let target = match to {
+			Some(to) => to,
+			None => {
+				let nonce = match nonce {
+					Some(nonce) => nonce,
+					None => {
+						api.account_basic(substrate_hash, from)
+							.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+							.nonce
+					}
+				};
+				let mut stream = rlp::RlpStream::new_list(2);
+				stream.append(&from);
+				stream.append(&nonce);
+				H160::from(H256::from(keccak_256(&stream.out())))
+			}
+		};
+
+		let mut list = BTreeMap::<H160, BTreeSet<H256>>::new();
+		for item in access_list.unwrap_or_default() {
+			list.entry(item.address)
+				.or_default()
+				.extend(item.storage_keys);
+		}
+
+		// Warming an entry can change the execution path, so trace again with the list found
+		// so far until it no longer grows. Like geth, a list still growing after the last
+		// round is returned as it stands, with the gas the call uses with it.
+		let mut precompiles = BTreeSet::new();
+		settle_access_list(
+			&mut list,
+			MAX_ACCESS_LIST_ROUNDS,
+			from,
+			target,
+			|list| match self
+				.client
+				.runtime_api()
+				.trace_call(
+					substrate_hash,
+					from,
+					to,
+					data.clone(),
+					value,
+					gas_limit,
+					max_fee_per_gas,
+					max_priority_fee_per_gas,
+					nonce,
+					Some(runtime_access_list(list)),
+					TracerConfig::PrestateTracer,
+				)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			{
+				Ok(fp_evm_tracing::TraceResult::PrestateTracer(accounts)) => Ok(accounts),
+				Ok(_) => Err(internal_err("unexpected tracer output")),
+				Err(err) => Err(crate::debug::trace_err(err)),
+			},
+			|address| {
+				if precompiles.contains(&address) {
+					return Ok(true);
+				}
+				let is_precompile = self
+					.client
+					.runtime_api()
+					.is_precompile(substrate_hash, address)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+				if is_precompile {
+					precompiles.insert(address);
+				}
+				Ok(is_precompile)
+			},
+		)?;
+
+		let access_list = runtime_access_list(&list);
+		let (exit_reason, used_gas) = match to {
+			Some(to) if api_version == 4 => {
+				#[allow(deprecated)]
+				let info = api.call_before_version_5(
+					substrate_hash,
+					from,
+					to,
+					data,
+					value,
+					gas_limit,
+					max_fee_per_gas,
+					max_priority_fee_per_gas,
+					nonce,
+					false,
+					Some(access_list),
+				)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+				(info.exit_reason, info.used_gas)
+			}
+			Some(to) => {
+				let info = api
+					.call(
+						substrate_hash,
+						from,
+						to,
+						data,
+						value,
+						gas_limit,
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						false,
+						Some(access_list),
+					)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+				(info.exit_reason, info.used_gas.effective)
+			}
+			None if api_version == 4 => {
+				#[allow(deprecated)]
+				let info = api.create_before_version_5(
+					substrate_hash,
+					from,
+					data,
+					value,
+					gas_limit,
+					max_fee_per_gas,
+					max_priority_fee_per_gas,
+					nonce,
+					false,
+					Some(access_list),
+				)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+				(info.exit_reason, info.used_gas)
+			}
+			None => {
+				let info = api
+					.create(
+						substrate_hash,
+						from,
+						data,
+						value,
+						gas_limit,
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						false,
+						Some(access_list),
+					)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+				(info.exit_reason, info.used_gas.effective)
+			}
+		};
+
+		let error = match exit_reason {
+			ExitReason::Succeed(_) => None,
+			ExitReason::Revert(_) => Some("execution reverted".to_string()),
+			ExitReason::Error(ExitError::OutOfGas) => Some("out of gas".to_string()),
+			other => Some(format!("evm error: {:?}", other)),
+		};
+
+		Ok(AccessListResult {
+			access_list: list
+				.into_iter()
+				.map(|(address, slots)| AccessListItem {
+					address,
+					storage_keys: slots.into_iter().collect(),
+				})
+				.collect(),
+			gas_used: used_gas,
+			error,
+		})
+	}
+}
+
+/// Traces the call with the access list found so far until tracing adds nothing to it, for
+/// at most `rounds` rounds.
+fn settle_access_list<E, T, P>(
+	list: &mut BTreeMap<H160, BTreeSet<H256>>,
+	rounds: usize,
+	from: H160,
+	target: H160,
+	mut trace: T,
+	mut is_precompile: P,
+) -> Result<(), E>
+where
+	T: FnMut(&BTreeMap<H160, BTreeSet<H256>>) -> Result<BTreeMap<H160, PrestateAccount>, E>,
+	P: FnMut(H160) -> Result<bool, E>,
+{
+	for _ in 0..rounds {
+		let touched = trace(list)?;
+		if !extend_access_list(list, touched, from, target, &mut is_precompile)? {
+			break;
+		}
+	}
+	Ok(())
+}
+
+/// Adds every account and slot touched by a traced execution to the access list and tells
+/// whether it grew. The sender, the target and the precompiles are warm regardless, geth
+/// leaves them out unless their storage was read.
+fn extend_access_list<E>(
+	list: &mut BTreeMap<H160, BTreeSet<H256>>,
+	touched: BTreeMap<H160, PrestateAccount>,
+	from: H160,
+	target: H160,
+	mut is_precompile: impl FnMut(H160) -> Result<bool, E>,
+) -> Result<bool, E> {
+	let mut grown = false;
+	for (address, account) in touched {
+		if account.storage.is_empty()
+			&& !list.contains_key(&address)
+			&& (address == from || address == target || is_precompile(address)?)
+		{
+			continue;
+		}
+		let slots = list.entry(address).or_insert_with(|| {
+			grown = true;
+			BTreeSet::new()
+		});
+		for slot in account.storage.into_keys() {
+			grown |= slots.insert(slot);
+		}
+	}
+	Ok(grown)
+}
+
+fn runtime_access_list(list: &BTreeMap<H160, BTreeSet<H256>>) -> Vec<(H160, Vec<H256>)> {
+	list.iter()
+		.map(|(address, slots)| (*address, slots.iter().copied().collect()))
+		.collect()
+}
+
 pub fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> RpcResult<()> {
 	match reason {
 		ExitReason::Succeed(_) => Ok(()),
@@ -1055,3 +1373,155 @@ fn fee_details(
 		}),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALICE: H160 = H160::repeat_byte(0xaa);
+	const BOB: H160 = H160::repeat_byte(0xbb);
+	const CONTRACT: H160 = H160::repeat_byte(0xc1);
+	const CHILD: H160 = H160::repeat_byte(0xc2);
+	const EXTRA: H160 = H160::repeat_byte(0xc3);
+	const PRECOMPILE: H160 = H160::repeat_byte(0x01);
+
+	fn account(code: &[u8], slots: &[H256]) -> PrestateAccount {
+		PrestateAccount {
+			code: code.to_vec(),
+			storage: slots.iter().map(|slot| (*slot, H256::zero())).collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn access_list_keeps_every_account_a_nested_call_touches() {
+		let slot = H256::repeat_byte(1);
+		// ALICE calls CONTRACT, which calls a precompile and CHILD. CHILD reads a slot of
+		// EXTRA through a nested call and the balance of BOB.
+		let touched: BTreeMap<_, _> = [
+			(ALICE, account(&[], &[])),
+			(CONTRACT, account(&[0x60], &[])),
+			(PRECOMPILE, account(&[], &[])),
+			(CHILD, account(&[0x60], &[])),
+			(EXTRA, account(&[0x60], &[slot])),
+			(BOB, account(&[], &[])),
+		]
+		.into_iter()
+		.collect();
+		let is_precompile = |address| Ok::<_, ()>(address == PRECOMPILE);
+
+		let mut list = BTreeMap::new();
+		assert_eq!(
+			extend_access_list(&mut list, touched.clone(), ALICE, CONTRACT, is_precompile),
+			Ok(true)
+		);
+		assert_eq!(
+			list,
+			[
+				(BOB, BTreeSet::new()),
+				(CHILD, BTreeSet::new()),
+				(EXTRA, [slot].into_iter().collect()),
+			]
+			.into_iter()
+			.collect()
+		);
+
+		// Tracing again with the list warm finds nothing new.
+		assert_eq!(
+			extend_access_list(&mut list, touched, ALICE, CONTRACT, is_precompile),
+			Ok(false)
+		);
+	}
+
+	#[test]
+	fn access_list_keeps_precompiles_the_caller_listed() {
+		let slot = H256::repeat_byte(1);
+		let touched = [(PRECOMPILE, account(&[], &[slot]))].into_iter().collect();
+		let mut list: BTreeMap<_, _> = [(PRECOMPILE, BTreeSet::new())].into_iter().collect();
+
+		assert_eq!(
+			extend_access_list(&mut list, touched, ALICE, CONTRACT, |_| Ok::<_, ()>(true)),
+			Ok(true)
+		);
+		assert_eq!(list[&PRECOMPILE], [slot].into_iter().collect());
+	}
+
+	#[test]
+	fn access_list_keeps_the_storage_the_target_reads() {
+		let slot = H256::repeat_byte(2);
+		// CONTRACT reads its own slot, ALICE and the precompile only have their balances read.
+		let touched = [
+			(ALICE, account(&[], &[])),
+			(CONTRACT, account(&[0x60], &[slot])),
+			(PRECOMPILE, account(&[], &[])),
+		]
+		.into_iter()
+		.collect();
+		let mut list = BTreeMap::new();
+
+		assert_eq!(
+			extend_access_list(&mut list, touched, ALICE, CONTRACT, |address| {
+				Ok::<_, ()>(address == PRECOMPILE)
+			}),
+			Ok(true)
+		);
+		assert_eq!(
+			list,
+			[(CONTRACT, [slot].into_iter().collect())]
+				.into_iter()
+				.collect()
+		);
+	}
+
+	#[test]
+	fn access_list_stops_growing_after_the_last_round() {
+		// Every round, warming the previous entries leads the call to one more account.
+		let mut rounds = 0u8;
+		let mut list = BTreeMap::new();
+		assert_eq!(
+			settle_access_list(
+				&mut list,
+				MAX_ACCESS_LIST_ROUNDS,
+				ALICE,
+				CONTRACT,
+				|list| {
+					rounds += 1;
+					assert_eq!(list.len(), usize::from(rounds) - 1);
+					Ok::<_, ()>(
+						[(H160::repeat_byte(rounds), account(&[0x60], &[]))]
+							.into_iter()
+							.collect(),
+					)
+				},
+				|_| Ok(false),
+			),
+			Ok(())
+		);
+
+		assert_eq!(usize::from(rounds), MAX_ACCESS_LIST_ROUNDS);
+		assert_eq!(list.len(), MAX_ACCESS_LIST_ROUNDS);
+	}
+
+	#[test]
+	fn access_list_settles_once_a_round_adds_nothing() {
+		let mut rounds = 0;
+		let mut list = BTreeMap::new();
+		assert_eq!(
+			settle_access_list(
+				&mut list,
+				MAX_ACCESS_LIST_ROUNDS,
+				ALICE,
+				CONTRACT,
+				|_| {
+					rounds += 1;
+					Ok::<_, ()>([(CHILD, account(&[0x60], &[]))].into_iter().collect())
+				},
+				|_| Ok(false),
+			),
+			Ok(())
+		);
+
+		assert_eq!(rounds, 2);
+		assert_eq!(list, [(CHILD, BTreeSet::new())].into_iter().collect());
+	}
+}
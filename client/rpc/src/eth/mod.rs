@@ -34,7 +34,10 @@ use ethereum::{BlockV2 as EthereumBlock, TransactionV2 as EthereumTransaction};
 use ethereum_types::{H160, H256, H512, H64, U256, U64};
 use jsonrpsee::core::{async_trait, RpcResult};
 // Substrate
-use sc_client_api::backend::{Backend, StorageProvider};
+use sc_client_api::{
+	backend::{Backend, StorageProvider},
+	ProofProvider,
+};
 use sc_network_sync::SyncingService;
 use sc_transaction_pool::{ChainApi, Pool};
 use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
@@ -46,6 +49,7 @@ use sp_runtime::traits::{Block as BlockT, UniqueSaturatedInto};
 // Frontier
 use fc_rpc_core::{types::*, EthApiServer};
 use fc_storage::OverrideHandle;
+use fp_evm_tracing::DebugRuntimeApi;
 use fp_rpc::{
 	ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi,
 	RuntimeStorageOverride, TransactionStatus,
@@ -63,11 +67,14 @@ pub use self::{
 pub trait EthConfig<B: BlockT, C>: Send + Sync + 'static {
 	type EstimateGasAdapter: EstimateGasAdapter + Send + Sync;
 	type RuntimeStorageOverride: RuntimeStorageOverride<B, C>;
+	/// Where the runtime stores the accounts `eth_getProof` proves.
+	type AccountLayout: fp_account_proof::AccountLayout;
 }
 
 impl<B: BlockT, C> EthConfig<B, C> for () {
 	type EstimateGasAdapter = ();
 	type RuntimeStorageOverride = ();
+	type AccountLayout = ();
 }
 
 /// Eth API implementation.
@@ -173,8 +180,11 @@ impl<B, C, P, CT, BE, A, EC> EthApiServer for Eth<B, C, P, CT, BE, A, EC>
 where
 	B: BlockT,
 	C: CallApiAt<B> + ProvideRuntimeApi<B>,
-	C::Api: BlockBuilderApi<B> + ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
-	C: HeaderBackend<B> + StorageProvider<B, BE> + 'static,
+	C::Api: BlockBuilderApi<B>
+		+ ConvertTransactionRuntimeApi<B>
+		+ EthereumRuntimeRPCApi<B>
+		+ DebugRuntimeApi<B>,
+	C: HeaderBackend<B> + StorageProvider<B, BE> + ProofProvider<B> + 'static,
 	BE: Backend<B> + 'static,
 	P: TransactionPool<Block = B> + 'static,
 	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
@@ -289,6 +299,10 @@ where
 		self.transaction_receipt(hash).await
 	}
 
+	async fn block_receipts(&self, number: BlockNumber) -> RpcResult<Option<Vec<Receipt>>> {
+		self.block_receipts(number).await
+	}
+
 	// ########################################################################
 	// State
 	// ########################################################################
@@ -318,6 +332,15 @@ where
 		self.code_at(address, number).await
 	}
 
+	async fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number: Option<BlockNumber>,
+	) -> RpcResult<EthAccount> {
+		self.proof(address, storage_keys, number).await
+	}
+
 	// ########################################################################
 	// Execute
 	// ########################################################################
@@ -339,6 +362,14 @@ where
 		self.estimate_gas(request, number).await
 	}
 
+	async fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> RpcResult<AccessListResult> {
+		self.create_access_list(request, number).await
+	}
+
 	// ########################################################################
 	// Fee
 	// ########################################################################
@@ -18,17 +18,22 @@
 
 use ethereum_types::{H160, H256, U256};
 use jsonrpsee::core::RpcResult;
-use scale_codec::Encode;
+use scale_codec::{Decode, Encode};
 // Substrate
-use sc_client_api::backend::{Backend, StorageProvider};
+use sc_client_api::{
+	backend::{Backend, StorageProvider},
+	ProofProvider, StorageKey,
+};
 use sc_transaction_pool::ChainApi;
 use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
-use sp_runtime::traits::Block as BlockT;
+use sp_core::hashing::keccak_256;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 // Frontier
 use fc_rpc_core::types::*;
+use fp_account_proof::keys;
 use fp_rpc::EthereumRuntimeRPCApi;
 
 use crate::{
@@ -201,4 +206,107 @@ where
 			Ok(Bytes(vec![]))
 		}
 	}
+
+	pub async fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number: Option<BlockNumber>,
+	) -> RpcResult<EthAccount>
+	where
+		C: ProofProvider<B>,
+	{
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+		)
+		.await?
+		.ok_or_else(|| internal_err("pending block state cannot be proven"))?;
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+		let header = self
+			.client
+			.header(substrate_hash)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("header not found"))?;
+
+		let read = |key: &[u8]| {
+			self.client
+				.storage(substrate_hash, &StorageKey(key.to_vec()))
+				.map(|data| data.map(|data| data.0))
+				.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))
+		};
+		let prove = |entries: &[Vec<u8>]| {
+			self.client
+				.read_proof(substrate_hash, &mut entries.iter().map(|key| &key[..]))
+				.map(|proof| proof.into_iter_nodes().map(Bytes).collect::<Vec<_>>())
+				.map_err(|err| internal_err(format!("generate proof failed: {:?}", err)))
+		};
+
+		// The account the address maps to, as `UnifiedAccounts::native_account` resolves it.
+		let mapping_key = keys::evm_to_native::<EC::AccountLayout>(address);
+		let linked = match &mapping_key {
+			Some(key) => read(key)?
+				.map(|raw| <[u8; 32]>::decode(&mut &raw[..]))
+				.transpose()
+				.map_err(|err| internal_err(format!("decode linked account failed: {:?}", err)))?,
+			None => None,
+		};
+		let account_id = linked.unwrap_or_else(|| keys::hashed_account_id(address));
+		let account_key = keys::system_account::<EC::AccountLayout>(&account_id);
+		// Nonce and balance as proven by `accountProof`. The balance is the free balance, not
+		// the spendable one `eth_getBalance` reports, which leaves out frozen funds and the
+		// existential deposit.
+		let (nonce, balance) = match read(&account_key)? {
+			Some(raw) => {
+				let info = fp_account_proof::decode_account_info::<EC::AccountLayout>(&raw)
+					.map_err(|err| internal_err(format!("decode account failed: {:?}", err)))?;
+				(info.nonce.into(), info.free.into())
+			}
+			None => (U256::zero(), U256::zero()),
+		};
+		let code_key = keys::account_code::<EC::AccountLayout>(address);
+		let code = match read(&code_key)? {
+			Some(raw) => Vec::<u8>::decode(&mut &raw[..])
+				.map_err(|err| internal_err(format!("decode code failed: {:?}", err)))?,
+			None => Vec::new(),
+		};
+
+		let account_proof = prove(
+			&mapping_key
+				.into_iter()
+				.chain([account_key, code_key])
+				.collect::<Vec<_>>(),
+		)?;
+		let storage_proof = storage_keys
+			.into_iter()
+			.map(|index| {
+				let key = keys::account_storage::<EC::AccountLayout>(address, index);
+				let value = match read(&key)? {
+					Some(raw) => H256::decode(&mut &raw[..])
+						.map_err(|err| internal_err(format!("decode storage failed: {:?}", err)))?,
+					None => H256::zero(),
+				};
+				Ok(StorageProof {
+					key: U256::from_big_endian(index.as_bytes()),
+					value: U256::from_big_endian(value.as_bytes()),
+					proof: prove(&[key])?,
+				})
+			})
+			.collect::<RpcResult<Vec<_>>>()?;
+
+		Ok(EthAccount {
+			address,
+			balance,
+			nonce,
+			code_hash: H256::from(keccak_256(&code)),
+			// There is no per-account storage trie, every proof is against the state root.
+			storage_hash: H256::from_slice(header.state_root().as_ref()),
+			account_proof,
+			storage_proof,
+		})
+	}
 }
@@ -18,7 +18,7 @@
 
 use std::sync::Arc;
 
-use ethereum::TransactionV2 as EthereumTransaction;
+use ethereum::{BlockV2 as EthereumBlock, TransactionV2 as EthereumTransaction};
 use ethereum_types::{H256, U256, U64};
 use jsonrpsee::core::RpcResult;
 // Substrate
@@ -31,7 +31,7 @@ use sp_core::hashing::keccak_256;
 use sp_runtime::traits::Block as BlockT;
 // Frontier
 use fc_rpc_core::types::*;
-use fp_rpc::EthereumRuntimeRPCApi;
+use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus};
 
 use crate::{
 	eth::{transaction_build, Eth, EthConfig},
@@ -269,8 +269,6 @@ where
 
 	pub async fn transaction_receipt(&self, hash: H256) -> RpcResult<Option<Receipt>> {
 		let client = Arc::clone(&self.client);
-		let overrides = Arc::clone(&self.overrides);
-		let block_data_cache = Arc::clone(&self.block_data_cache);
 		let backend = Arc::clone(&self.backend);
 
 		let (hash, index) = match frontier_backend_client::load_transactions::<B, C>(
@@ -298,155 +296,255 @@ where
 			_ => return Ok(None),
 		};
 
-		let schema = fc_storage::onchain_storage_schema(client.as_ref(), substrate_hash);
-		let handler = overrides
+		Ok(self
+			.receipts_at(substrate_hash)
+			.await?
+			.and_then(|receipts| receipts.into_iter().nth(index)))
+	}
+
+	pub async fn block_receipts(&self, number: BlockNumber) -> RpcResult<Option<Vec<Receipt>>> {
+		let id = match frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number),
+		)
+		.await?
+		{
+			Some(id) => id,
+			None => return Ok(None),
+		};
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+		self.receipts_at(substrate_hash).await
+	}
+
+	/// Receipts of all the transactions of a block, read through the block data cache.
+	async fn receipts_at(&self, substrate_hash: B::Hash) -> RpcResult<Option<Vec<Receipt>>> {
+		let schema = fc_storage::onchain_storage_schema(self.client.as_ref(), substrate_hash);
+		let handler = self
+			.overrides
 			.schemas
 			.get(&schema)
-			.unwrap_or(&overrides.fallback);
+			.unwrap_or(&self.overrides.fallback);
 
-		let block = block_data_cache.current_block(schema, substrate_hash).await;
-		let statuses = block_data_cache
+		let block = self
+			.block_data_cache
+			.current_block(schema, substrate_hash)
+			.await;
+		let statuses = self
+			.block_data_cache
 			.current_transaction_statuses(schema, substrate_hash)
 			.await;
-
-		let receipts = handler.current_receipts(substrate_hash);
-		let is_eip1559 = handler.is_eip1559(substrate_hash);
+		let receipts = self
+			.block_data_cache
+			.current_receipts(schema, substrate_hash)
+			.await;
 
 		match (block, statuses, receipts) {
 			(Some(block), Some(statuses), Some(receipts)) => {
-				let block_hash = H256::from(keccak_256(&rlp::encode(&block.header)));
-				let receipt = receipts[index].clone();
+				let is_eip1559 = handler.is_eip1559(substrate_hash);
+				let base_fee = self
+					.client
+					.runtime_api()
+					.gas_price(substrate_hash)
+					.unwrap_or_default();
+				receipts_build(block, statuses, receipts, is_eip1559, base_fee).map(Some)
+			}
+			_ => Ok(None),
+		}
+	}
+}
 
-				let (logs, logs_bloom, status_code, cumulative_gas_used, gas_used) = if !is_eip1559
-				{
-					// Pre-london frontier update stored receipts require cumulative gas calculation.
-					match receipt {
-						ethereum::ReceiptV3::Legacy(ref d) => {
-							let index = core::cmp::min(receipts.len(), index + 1);
-							let cumulative_gas: u32 = receipts[..index]
-								.iter()
-								.map(|r| match r {
-									ethereum::ReceiptV3::Legacy(d) => Ok(d.used_gas.as_u32()),
-									_ => Err(internal_err(format!(
-										"Unknown receipt for request {}",
-										hash
-									))),
-								})
-								.sum::<RpcResult<u32>>()?;
-							(
-								d.logs.clone(),
-								d.logs_bloom,
-								d.status_code,
-								U256::from(cumulative_gas),
-								d.used_gas,
-							)
-						}
-						_ => {
-							return Err(internal_err(format!(
-								"Unknown receipt for request {}",
-								hash
-							)))
-						}
-					}
-				} else {
-					match receipt {
-						ethereum::ReceiptV3::Legacy(ref d)
-						| ethereum::ReceiptV3::EIP2930(ref d)
-						| ethereum::ReceiptV3::EIP1559(ref d) => {
-							let cumulative_gas = d.used_gas;
-							let gas_used = if index > 0 {
-								let previous_receipt = receipts[index - 1].clone();
-								let previous_gas_used = match previous_receipt {
-									ethereum::ReceiptV3::Legacy(d)
-									| ethereum::ReceiptV3::EIP2930(d)
-									| ethereum::ReceiptV3::EIP1559(d) => d.used_gas,
-								};
-								cumulative_gas.saturating_sub(previous_gas_used)
-							} else {
-								cumulative_gas
-							};
-							(
-								d.logs.clone(),
-								d.logs_bloom,
-								d.status_code,
-								cumulative_gas,
-								gas_used,
-							)
-						}
-					}
-				};
+/// Builds the RPC receipts of a block in one pass, accumulating gas and log indices.
+fn receipts_build(
+	block: EthereumBlock,
+	statuses: Vec<TransactionStatus>,
+	receipts: Vec<ethereum::ReceiptV3>,
+	is_eip1559: bool,
+	base_fee: U256,
+) -> RpcResult<Vec<Receipt>> {
+	let block_hash = H256::from(keccak_256(&rlp::encode(&block.header)));
+	if statuses.len() != receipts.len() || block.transactions.len() != receipts.len() {
+		return Err(internal_err(format!(
+			"Inconsistent receipts for block {:?}",
+			block_hash
+		)));
+	}
 
-				let status = statuses[index].clone();
-				let mut cumulative_receipts = receipts;
-				cumulative_receipts.truncate((status.transaction_index + 1) as usize);
-				let transaction = block.transactions[index].clone();
-				let effective_gas_price = match transaction {
-					EthereumTransaction::Legacy(t) => t.gas_price,
-					EthereumTransaction::EIP2930(t) => t.gas_price,
-					EthereumTransaction::EIP1559(t) => client
-						.runtime_api()
-						.gas_price(substrate_hash)
-						.unwrap_or_default()
-						.checked_add(t.max_priority_fee_per_gas)
-						.unwrap_or_else(U256::max_value)
-						.min(t.max_fee_per_gas),
-				};
+	let mut cumulative_gas_used = U256::zero();
+	let mut log_index = 0usize;
+	let mut rpc_receipts = Vec::with_capacity(receipts.len());
+	for ((transaction, status), receipt) in block.transactions.iter().zip(statuses).zip(receipts) {
+		let (d, transaction_type) = match receipt {
+			ethereum::ReceiptV3::Legacy(d) => (d, 0u8),
+			ethereum::ReceiptV3::EIP2930(d) => (d, 1),
+			ethereum::ReceiptV3::EIP1559(d) => (d, 2),
+		};
 
-				return Ok(Some(Receipt {
-					transaction_hash: Some(status.transaction_hash),
-					transaction_index: Some(status.transaction_index.into()),
-					block_hash: Some(block_hash),
-					from: Some(status.from),
-					to: status.to,
-					block_number: Some(block.header.number),
-					cumulative_gas_used,
-					gas_used: Some(gas_used),
-					contract_address: status.contract_address,
-					logs: {
-						let mut pre_receipts_log_index = None;
-						if cumulative_receipts.len() > 0 {
-							cumulative_receipts.truncate(cumulative_receipts.len() - 1);
-							pre_receipts_log_index = Some(
-								cumulative_receipts
-									.iter()
-									.map(|r| match r {
-										ethereum::ReceiptV3::Legacy(d)
-										| ethereum::ReceiptV3::EIP2930(d)
-										| ethereum::ReceiptV3::EIP1559(d) => d.logs.len() as u32,
-									})
-									.sum::<u32>(),
-							);
-						}
-						logs.iter()
-							.enumerate()
-							.map(|(i, log)| Log {
-								address: log.address,
-								topics: log.topics.clone(),
-								data: Bytes(log.data.clone()),
-								block_hash: Some(block_hash),
-								block_number: Some(block.header.number),
-								transaction_hash: Some(status.transaction_hash),
-								transaction_index: Some(status.transaction_index.into()),
-								log_index: Some(U256::from(
-									(pre_receipts_log_index.unwrap_or(0)) + i as u32,
-								)),
-								transaction_log_index: Some(U256::from(i)),
-								removed: false,
-							})
-							.collect()
-					},
-					status_code: Some(U64::from(status_code)),
-					logs_bloom,
-					state_root: None,
-					effective_gas_price,
-					transaction_type: match receipt {
-						ethereum::ReceiptV3::Legacy(_) => U256::from(0),
-						ethereum::ReceiptV3::EIP2930(_) => U256::from(1),
-						ethereum::ReceiptV3::EIP1559(_) => U256::from(2),
-					},
-				}));
+		let gas_used = if is_eip1559 {
+			// Receipts store the gas used by the block so far.
+			let gas_used = d.used_gas.saturating_sub(cumulative_gas_used);
+			cumulative_gas_used = d.used_gas;
+			gas_used
+		} else {
+			// Pre-london frontier update stored receipts require cumulative gas calculation.
+			if transaction_type != 0 {
+				return Err(internal_err(format!(
+					"Unknown receipt for request {}",
+					status.transaction_hash
+				)));
 			}
-			_ => Ok(None),
+			cumulative_gas_used = cumulative_gas_used.saturating_add(d.used_gas);
+			d.used_gas
+		};
+
+		let effective_gas_price = match transaction {
+			EthereumTransaction::Legacy(t) => t.gas_price,
+			EthereumTransaction::EIP2930(t) => t.gas_price,
+			EthereumTransaction::EIP1559(t) => base_fee
+				.checked_add(t.max_priority_fee_per_gas)
+				.unwrap_or_else(U256::max_value)
+				.min(t.max_fee_per_gas),
+		};
+
+		let logs = d
+			.logs
+			.iter()
+			.enumerate()
+			.map(|(i, log)| Log {
+				address: log.address,
+				topics: log.topics.clone(),
+				data: Bytes(log.data.clone()),
+				block_hash: Some(block_hash),
+				block_number: Some(block.header.number),
+				transaction_hash: Some(status.transaction_hash),
+				transaction_index: Some(status.transaction_index.into()),
+				log_index: Some(U256::from(log_index + i)),
+				transaction_log_index: Some(U256::from(i)),
+				removed: false,
+			})
+			.collect::<Vec<_>>();
+		log_index += logs.len();
+
+		rpc_receipts.push(Receipt {
+			transaction_hash: Some(status.transaction_hash),
+			transaction_index: Some(status.transaction_index.into()),
+			block_hash: Some(block_hash),
+			from: Some(status.from),
+			to: status.to,
+			block_number: Some(block.header.number),
+			cumulative_gas_used,
+			gas_used: Some(gas_used),
+			contract_address: status.contract_address,
+			logs,
+			status_code: Some(U64::from(d.status_code)),
+			logs_bloom: d.logs_bloom,
+			state_root: None,
+			effective_gas_price,
+			transaction_type: U256::from(transaction_type),
+		});
+	}
+
+	Ok(rpc_receipts)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum::{
+		EIP1559Transaction, EIP658ReceiptData, PartialHeader, ReceiptV3, TransactionAction,
+	};
+	use ethereum_types::{Bloom, H160, H64};
+
+	fn transaction(nonce: u64) -> EthereumTransaction {
+		EthereumTransaction::EIP1559(EIP1559Transaction {
+			chain_id: 42,
+			nonce: nonce.into(),
+			max_priority_fee_per_gas: 2.into(),
+			max_fee_per_gas: 100.into(),
+			gas_limit: 100_000.into(),
+			action: TransactionAction::Call(H160::repeat_byte(2)),
+			value: U256::zero(),
+			input: Vec::new(),
+			access_list: Vec::new(),
+			odd_y_parity: false,
+			r: H256::repeat_byte(1),
+			s: H256::repeat_byte(1),
+		})
+	}
+
+	fn log() -> ethereum::Log {
+		ethereum::Log {
+			address: H160::repeat_byte(2),
+			topics: Vec::new(),
+			data: Vec::new(),
 		}
 	}
+
+	#[test]
+	fn receipts_build_accumulates_gas_and_log_indices() {
+		let transactions = vec![transaction(0), transaction(1)];
+		let block = EthereumBlock::new(
+			PartialHeader {
+				parent_hash: H256::zero(),
+				beneficiary: H160::zero(),
+				state_root: H256::zero(),
+				receipts_root: H256::zero(),
+				logs_bloom: Bloom::default(),
+				difficulty: U256::zero(),
+				number: 7.into(),
+				gas_limit: 1_000_000.into(),
+				gas_used: 50_000.into(),
+				timestamp: 0,
+				extra_data: Vec::new(),
+				mix_hash: H256::zero(),
+				nonce: H64::zero(),
+			},
+			transactions.clone(),
+			Vec::new(),
+		);
+		let statuses = transactions
+			.iter()
+			.enumerate()
+			.map(|(i, t)| TransactionStatus {
+				transaction_hash: t.hash(),
+				transaction_index: i as u32,
+				from: H160::repeat_byte(1),
+				to: Some(H160::repeat_byte(2)),
+				contract_address: None,
+				logs: Vec::new(),
+				logs_bloom: Bloom::default(),
+			})
+			.collect();
+		// Post-london receipts carry the cumulative gas of the block.
+		let receipts = vec![
+			ReceiptV3::EIP1559(EIP658ReceiptData {
+				status_code: 1,
+				used_gas: 21_000.into(),
+				logs_bloom: Bloom::default(),
+				logs: vec![log(), log()],
+			}),
+			ReceiptV3::EIP1559(EIP658ReceiptData {
+				status_code: 1,
+				used_gas: 50_000.into(),
+				logs_bloom: Bloom::default(),
+				logs: vec![log()],
+			}),
+		];
+
+		let receipts = receipts_build(block, statuses, receipts, true, 10.into()).unwrap();
+
+		assert_eq!(receipts.len(), 2);
+		assert_eq!(receipts[1].cumulative_gas_used, U256::from(50_000));
+		assert_eq!(receipts[1].gas_used, Some(U256::from(29_000)));
+		assert_eq!(receipts[1].effective_gas_price, U256::from(12));
+		assert_eq!(receipts[1].logs[0].log_index, Some(U256::from(2)));
+		assert_eq!(
+			receipts[1].logs[0].transaction_log_index,
+			Some(U256::zero())
+		);
+	}
 }
@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `trace` namespace: Parity-style call traces, served from the trace store.
+
+use std::{collections::BTreeSet, marker::PhantomData, sync::Arc};
+
+use ethereum_types::{H160, H256};
+use jsonrpsee::core::{async_trait, RpcResult};
+// Substrate
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, UniqueSaturatedInto};
+// Frontier
+use fc_db::kv::trace::{BlockTraces, TraceDb};
+use fc_rpc_core::{types::*, TraceApiServer};
+use fp_evm_tracing::{CallType, ExitError, ExitReason};
+
+use crate::{frontier_backend_client, internal_err};
+
+/// Trace API implementation.
+pub struct Trace<B: BlockT, C> {
+	client: Arc<C>,
+	backend: Arc<dyn fc_db::BackendReader<B> + Send + Sync>,
+	trace_db: Arc<TraceDb<B>>,
+	max_count: usize,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C> Trace<B, C> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<dyn fc_db::BackendReader<B> + Send + Sync>,
+		trace_db: Arc<TraceDb<B>>,
+		max_count: usize,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			trace_db,
+			max_count,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C> Trace<B, C>
+where
+	B: BlockT<Hash = H256>,
+	C: HeaderBackend<B> + 'static,
+{
+	fn indexed_range(&self) -> RpcResult<(u32, u32)> {
+		self.trace_db
+			.indexed_range()
+			.map_err(|err| internal_err(format!("fetch trace store failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("no block has been traced yet"))
+	}
+
+	fn block_traces(&self, number: u32) -> RpcResult<Option<BlockTraces<B>>> {
+		self.trace_db
+			.block_traces(number)
+			.map_err(|err| internal_err(format!("fetch trace store failed: {:?}", err)))
+	}
+
+	/// Resolves a block number, the tags pointing at the last traced block.
+	async fn block_number(&self, number: BlockNumber) -> RpcResult<u32> {
+		match number {
+			BlockNumber::Num(number) => Ok(number.unique_saturated_into()),
+			BlockNumber::Earliest => Ok(0),
+			BlockNumber::Latest
+			| BlockNumber::Pending
+			| BlockNumber::Safe
+			| BlockNumber::Finalized => Ok(self.indexed_range()?.1),
+			BlockNumber::Hash { hash, .. } => {
+				let substrate_hash = frontier_backend_client::load_hash::<B, C>(
+					self.client.as_ref(),
+					self.backend.as_ref(),
+					hash,
+				)
+				.await?
+				.ok_or_else(|| internal_err("block not found"))?;
+				let number = self
+					.client
+					.number(substrate_hash)
+					.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+					.ok_or_else(|| internal_err("block not found"))?;
+				Ok(number.unique_saturated_into())
+			}
+		}
+	}
+}
+
+#[async_trait]
+impl<B, C> TraceApiServer for Trace<B, C>
+where
+	B: BlockT<Hash = H256>,
+	C: HeaderBackend<B> + 'static,
+{
+	async fn trace_block(&self, number: BlockNumber) -> RpcResult<Option<Vec<LocalizedTrace>>> {
+		let number = self.block_number(number).await?;
+		let (from, to) = self.indexed_range()?;
+		if number < from || number > to {
+			return Err(internal_err(format!(
+				"block #{} is out of the traced range #{}..=#{}",
+				number, from, to
+			)));
+		}
+
+		Ok(self
+			.block_traces(number)?
+			.map(|traces| localized_traces(number, traces, None, |_| true)))
+	}
+
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+	) -> RpcResult<Option<Vec<LocalizedTrace>>> {
+		let number = match self
+			.trace_db
+			.transaction_block(&transaction_hash)
+			.map_err(|err| internal_err(format!("fetch trace store failed: {:?}", err)))?
+		{
+			Some(number) => number,
+			None => return Ok(None),
+		};
+
+		Ok(self
+			.block_traces(number)?
+			.map(|traces| localized_traces(number, traces, Some(transaction_hash), |_| true)))
+	}
+
+	async fn trace_filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTrace>> {
+		let (first_traced, last_traced) = self.indexed_range()?;
+		let from_block = self
+			.block_number(filter.from_block.unwrap_or(BlockNumber::Latest))
+			.await?;
+		let to_block = self
+			.block_number(filter.to_block.unwrap_or(BlockNumber::Latest))
+			.await?
+			.min(last_traced);
+		if from_block < first_traced {
+			return Err(internal_err(format!(
+				"traces before block #{} are not available",
+				first_traced
+			)));
+		}
+
+		let count = filter.count.unwrap_or(self.max_count);
+		if count > self.max_count {
+			return Err(internal_err(format!(
+				"count exceeds the limit of {}",
+				self.max_count
+			)));
+		}
+		let after = filter.after.unwrap_or(0);
+
+		let from_address = filter.from_address.unwrap_or_default();
+		let to_address = filter.to_address.unwrap_or_default();
+		let matches = |frame: &fp_evm_tracing::CallFrame| {
+			(from_address.is_empty() || from_address.contains(&frame.from))
+				&& (to_address.is_empty() || to_address.contains(&frame.to))
+		};
+
+		// Only visit the blocks the filtered addresses appear in.
+		let blocks: Box<dyn Iterator<Item = u32>> =
+			if from_address.is_empty() && to_address.is_empty() {
+				Box::new(from_block..=to_block)
+			} else {
+				let mut blocks = BTreeSet::new();
+				for address in from_address.iter().chain(to_address.iter()) {
+					blocks.extend(
+						self.trace_db
+							.address_blocks(address, from_block..=to_block)
+							.map_err(|err| {
+								internal_err(format!("fetch trace store failed: {:?}", err))
+							})?,
+					);
+				}
+				Box::new(blocks.into_iter())
+			};
+
+		let mut traces = Vec::new();
+		for number in blocks {
+			if traces.len() >= after.saturating_add(count) {
+				break;
+			}
+			if let Some(block) = self.block_traces(number)? {
+				traces.extend(localized_traces(number, block, None, matches));
+			}
+		}
+
+		Ok(traces.into_iter().skip(after).take(count).collect())
+	}
+}
+
+/// Error message of an exit reason, worded as Parity does where there is an equivalent.
+fn error_message(reason: &ExitReason) -> String {
+	match reason {
+		ExitReason::Revert(_) => "Reverted".into(),
+		ExitReason::Error(err) => match err {
+			ExitError::OutOfGas => "Out of gas".into(),
+			ExitError::StackUnderflow => "Stack underflow".into(),
+			ExitError::StackOverflow => "Out of stack".into(),
+			ExitError::InvalidJump => "Bad jump destination".into(),
+			ExitError::InvalidCode(_) | ExitError::DesignatedInvalid => "Bad instruction".into(),
+			ExitError::CallTooDeep => "Call depth limit reached".into(),
+			ExitError::Other(message) => message.to_string(),
+			err => format!("{:?}", err),
+		},
+		reason => format!("{:?}", reason),
+	}
+}
+
+/// Flattens the call trees of a block, depth first, keeping the frames `matches` accepts.
+fn localized_traces<B: BlockT>(
+	number: u32,
+	block: BlockTraces<B>,
+	transaction_hash: Option<H256>,
+	matches: impl Fn(&fp_evm_tracing::CallFrame) -> bool,
+) -> Vec<LocalizedTrace> {
+	fn walk(
+		frame: fp_evm_tracing::CallFrame,
+		trace_address: Vec<usize>,
+		location: &(H256, u64, H256, usize),
+		matches: &impl Fn(&fp_evm_tracing::CallFrame) -> bool,
+		traces: &mut Vec<LocalizedTrace>,
+	) {
+		let (block_hash, block_number, transaction_hash, transaction_position) = *location;
+		if matches(&frame) {
+			let value = frame.value.unwrap_or_default();
+			let (action, result, trace_type) = match frame.call_type {
+				CallType::Create | CallType::Create2 => (
+					TraceAction::Create(CreateAction {
+						from: frame.from,
+						value,
+						gas: frame.gas.into(),
+						init: Bytes(frame.input.clone()),
+						creation_method: if frame.call_type == CallType::Create {
+							"create".into()
+						} else {
+							"create2".into()
+						},
+					}),
+					Some(TraceOutput::Create(CreateOutput {
+						gas_used: frame.gas_used.into(),
+						code: Bytes(frame.output.clone()),
+						address: frame.to,
+					})),
+					"create",
+				),
+				CallType::SelfDestruct => (
+					TraceAction::Suicide(SuicideAction {
+						address: frame.from,
+						refund_address: frame.to,
+						balance: value,
+					}),
+					None,
+					"suicide",
+				),
+				call_type => (
+					TraceAction::Call(CallAction {
+						from: frame.from,
+						to: frame.to,
+						value,
+						gas: frame.gas.into(),
+						input: Bytes(frame.input.clone()),
+						call_type: match call_type {
+							CallType::StaticCall => "staticcall",
+							CallType::DelegateCall => "delegatecall",
+							CallType::CallCode => "callcode",
+							_ => "call",
+						}
+						.into(),
+					}),
+					Some(TraceOutput::Call(CallOutput {
+						gas_used: frame.gas_used.into(),
+						output: Bytes(frame.output.clone()),
+					})),
+					"call",
+				),
+			};
+
+			traces.push(LocalizedTrace {
+				action,
+				block_hash,
+				block_number,
+				result: result.filter(|_| frame.error.is_none()),
+				error: frame.error.as_ref().map(error_message),
+				subtraces: frame.calls.len(),
+				trace_address: trace_address.clone(),
+				transaction_hash,
+				transaction_position,
+				trace_type: trace_type.into(),
+			});
+		}
+
+		for (index, call) in frame.calls.into_iter().enumerate() {
+			let mut child_address = trace_address.clone();
+			child_address.push(index);
+			walk(call, child_address, location, matches, traces);
+		}
+	}
+
+	let mut traces = Vec::new();
+	for (position, transaction) in block.transactions.into_iter().enumerate() {
+		if transaction_hash.map_or(false, |hash| hash != transaction.transaction_hash) {
+			continue;
+		}
+		let location = (
+			block.ethereum_block_hash,
+			number as u64,
+			transaction.transaction_hash,
+			position,
+		);
+		walk(
+			transaction.frame,
+			Vec::new(),
+			&location,
+			&matches,
+			&mut traces,
+		);
+	}
+	traces
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use fc_db::kv::trace::TransactionTraces;
+	use evm::ExitRevert;
+	use fp_evm_tracing::CallFrame;
+	use substrate_test_runtime_client::runtime::Block;
+
+	fn frame(call_type: CallType, from: u64, to: u64, calls: Vec<CallFrame>) -> CallFrame {
+		CallFrame {
+			call_type,
+			from: H160::from_low_u64_be(from),
+			to: H160::from_low_u64_be(to),
+			value: None,
+			gas: 100_000,
+			gas_used: 50_000,
+			input: vec![],
+			output: vec![],
+			error: None,
+			calls,
+		}
+	}
+
+	#[test]
+	fn localized_traces_flatten_call_tree() {
+		let mut reverted = frame(CallType::StaticCall, 2, 4, vec![]);
+		reverted.error = Some(ExitReason::Revert(ExitRevert::Reverted));
+		let block = BlockTraces::<Block> {
+			block_hash: Default::default(),
+			ethereum_block_hash: H256::repeat_byte(1),
+			transactions: vec![TransactionTraces {
+				transaction_hash: H256::repeat_byte(2),
+				frame: frame(
+					CallType::Call,
+					1,
+					2,
+					vec![
+						frame(
+							CallType::Create,
+							2,
+							3,
+							vec![frame(CallType::SelfDestruct, 3, 1, vec![])],
+						),
+						reverted,
+					],
+				),
+			}],
+		};
+
+		let traces = localized_traces(7, block.clone(), None, |_| true);
+		assert_eq!(
+			traces
+				.iter()
+				.map(|trace| (
+					trace.trace_type.as_str(),
+					trace.trace_address.clone(),
+					trace.subtraces
+				))
+				.collect::<Vec<_>>(),
+			vec![
+				("call", vec![], 2),
+				("create", vec![0], 1),
+				("suicide", vec![0, 0], 0),
+				("call", vec![1], 0),
+			]
+		);
+		assert!(traces.iter().all(|trace| trace.block_number == 7));
+		assert_eq!(traces[3].error.as_deref(), Some("Reverted"));
+		assert_eq!(traces[3].result, None);
+
+		let traces = localized_traces(7, block, None, |frame| frame.to == H160::from_low_u64_be(1));
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0].trace_address, vec![0, 0]);
+	}
+}
@@ -712,6 +712,13 @@ impl<T: Config> Pallet<T> {
 		<CurrentBlock<T>>::get().map(|block| block.header.hash())
 	}
 
+	/// Get the status and receipt of the last transaction applied to the block being built.
+	pub fn last_pending() -> Option<(TransactionStatus, Receipt)> {
+		Pending::<T>::get()
+			.pop()
+			.map(|(_, status, receipt)| (status, receipt))
+	}
+
 	/// Execute an Ethereum transaction.
 	pub fn execute(
 		from: H160,
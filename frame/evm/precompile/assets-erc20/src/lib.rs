@@ -0,0 +1,713 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! ERC-20 precompiles over the assets of a `pallet_assets` instance.
+//!
+//! Asset `id` is reachable at `{prefix}{12 zero bytes}{id}`, with the 4 byte prefix chosen
+//! per instance and `id` as a big-endian `u32`. The address answers as soon as the asset
+//! exists; [`Erc20AssetsCallback`] additionally gives it a small revert code so Solidity's
+//! `extcodesize` check passes.
+//!
+//! Transfers, approvals, mints and burns are dispatched as the matching `pallet_assets`
+//! calls from the caller's account, so the pallet's own checks apply: approvals take the
+//! pallet's approval deposit, `mint` needs the issuer role and `burn` the admin role.
+//!
+//! ```solidity
+//! interface IERC20Asset {
+//!     event Transfer(address indexed from, address indexed to, uint256 value);
+//!     event Approval(address indexed owner, address indexed spender, uint256 value);
+//!
+//!     function name() external view returns (string memory);
+//!     function symbol() external view returns (string memory);
+//!     function decimals() external view returns (uint8);
+//!     function totalSupply() external view returns (uint256);
+//!     function balanceOf(address owner) external view returns (uint256);
+//!     function allowance(address owner, address spender) external view returns (uint256);
+//!     function transfer(address to, uint256 value) external returns (bool);
+//!     function approve(address spender, uint256 value) external returns (bool);
+//!     function transferFrom(address from, address to, uint256 value) external returns (bool);
+//!     function mint(address to, uint256 value) external returns (bool);
+//!     function burn(address from, uint256 value) external returns (bool);
+//!
+//!     function permit(
+//!         address owner,
+//!         address spender,
+//!         uint256 value,
+//!         uint256 deadline,
+//!         uint8 v,
+//!         bytes32 r,
+//!         bytes32 s
+//!     ) external;
+//!     function nonces(address owner) external view returns (uint256);
+//!     function DOMAIN_SEPARATOR() external view returns (bytes32);
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{IsPrecompileResult, PrecompileHandle, PrecompileResult, PrecompileSet};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	storage_alias,
+	traits::{
+		fungibles::{
+			approvals::Inspect as ApprovalsInspect, metadata::Inspect as MetadataInspect, Inspect,
+			InspectEnumerable,
+		},
+		Get, OnRuntimeUpgrade, Time,
+	},
+	weights::Weight,
+	Blake2_128Concat,
+};
+use pallet_assets::AssetsCallback;
+use pallet_evm::{AddressMapping, GasWeightMapping};
+use precompile_utils::{
+	eip2612, ensure_direct_call, ensure_non_payable, ensure_not_static, insert_revert_code,
+	keccak256, record_db_reads, revert, succeed, try_dispatch, Address, Bytes, EvmDataReader,
+	EvmDataWriter, EvmResult, LogsBuilder, REVERT_CODE,
+};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::{StaticLookup, UniqueSaturatedInto, Zero};
+use sp_std::vec::Vec;
+
+/// `name()`
+pub const SELECTOR_NAME: u32 = 0x06fdde03;
+/// `symbol()`
+pub const SELECTOR_SYMBOL: u32 = 0x95d89b41;
+/// `decimals()`
+pub const SELECTOR_DECIMALS: u32 = 0x313ce567;
+/// `totalSupply()`
+pub const SELECTOR_TOTAL_SUPPLY: u32 = 0x18160ddd;
+/// `balanceOf(address)`
+pub const SELECTOR_BALANCE_OF: u32 = 0x70a08231;
+/// `allowance(address,address)`
+pub const SELECTOR_ALLOWANCE: u32 = 0xdd62ed3e;
+/// `transfer(address,uint256)`
+pub const SELECTOR_TRANSFER: u32 = 0xa9059cbb;
+/// `approve(address,uint256)`
+pub const SELECTOR_APPROVE: u32 = 0x095ea7b3;
+/// `transferFrom(address,address,uint256)`
+pub const SELECTOR_TRANSFER_FROM: u32 = 0x23b872dd;
+/// `mint(address,uint256)`
+pub const SELECTOR_MINT: u32 = 0x40c10f19;
+/// `burn(address,uint256)`
+pub const SELECTOR_BURN: u32 = 0x9dc29fac;
+/// `permit(address,address,uint256,uint256,uint8,bytes32,bytes32)`
+pub const SELECTOR_PERMIT: u32 = 0xd505accf;
+/// `nonces(address)`
+pub const SELECTOR_NONCES: u32 = 0x7ecebe00;
+/// `DOMAIN_SEPARATOR()`
+pub const SELECTOR_DOMAIN_SEPARATOR: u32 = 0x3644e515;
+
+/// Version of the EIP-2612 signing domain.
+pub const PERMIT_VERSION: &[u8] = b"1";
+
+/// Gas charged for recovering a permit signer, as the `ecrecover` precompile does.
+const ECRECOVER_GAS: u64 = 3_000;
+
+/// Next permit nonce of each owner, by token address.
+#[storage_alias]
+pub type Nonces = StorageDoubleMap<
+	Erc20AssetsPermit,
+	Blake2_128Concat,
+	H160,
+	Blake2_128Concat,
+	H160,
+	U256,
+	ValueQuery,
+>;
+
+type AssetIdOf<Runtime, Instance> = <Runtime as pallet_assets::Config<Instance>>::AssetId;
+type BalanceOf<Runtime, Instance> = <Runtime as pallet_assets::Config<Instance>>::Balance;
+
+/// Address of `id` under `prefix`.
+pub fn asset_address(prefix: u32, id: u32) -> H160 {
+	let mut address = [0u8; 20];
+	address[..4].copy_from_slice(&prefix.to_be_bytes());
+	address[16..].copy_from_slice(&id.to_be_bytes());
+	H160(address)
+}
+
+/// Asset id encoded in `address`, if it lies under `prefix`.
+pub fn asset_id(prefix: u32, address: H160) -> Option<u32> {
+	let bytes = address.as_bytes();
+	if bytes[..4] != prefix.to_be_bytes() || bytes[4..16].iter().any(|b| *b != 0) {
+		return None;
+	}
+	let mut id = [0u8; 4];
+	id.copy_from_slice(&bytes[16..]);
+	Some(u32::from_be_bytes(id))
+}
+
+pub struct Erc20AssetsPrecompileSet<Runtime, Instance, Prefix>(
+	PhantomData<(Runtime, Instance, Prefix)>,
+);
+
+impl<Runtime, Instance, Prefix> Erc20AssetsPrecompileSet<Runtime, Instance, Prefix> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<Runtime, Instance, Prefix> PrecompileSet
+	for Erc20AssetsPrecompileSet<Runtime, Instance, Prefix>
+where
+	Runtime: pallet_evm::Config + pallet_assets::Config<Instance>,
+	Instance: 'static,
+	Prefix: Get<u32>,
+	AssetIdOf<Runtime, Instance>: From<u32> + Into<u32> + Copy,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_assets::Call<Runtime, Instance>>,
+	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+		let id: AssetIdOf<Runtime, Instance> =
+			asset_id(Prefix::get(), handle.code_address())?.into();
+		if !<pallet_assets::Pallet<Runtime, Instance> as Inspect<Runtime::AccountId>>::asset_exists(
+			id,
+		) {
+			return None;
+		}
+		Some(Erc20Asset::<Runtime, Instance>::execute(handle, id))
+	}
+
+	fn is_precompile(&self, address: H160, _gas: u64) -> IsPrecompileResult {
+		match asset_id(Prefix::get(), address) {
+			// Only addresses under the prefix pay for the asset lookup.
+			Some(id) => IsPrecompileResult::Answer {
+				is_precompile: <pallet_assets::Pallet<Runtime, Instance> as Inspect<
+					Runtime::AccountId,
+				>>::asset_exists(id.into()),
+				extra_cost: Runtime::GasWeightMapping::weight_to_gas(
+					Runtime::DbWeight::get().reads(1),
+				),
+			},
+			None => IsPrecompileResult::Answer {
+				is_precompile: false,
+				extra_cost: 0,
+			},
+		}
+	}
+}
+
+/// The ERC-20 view of one asset.
+struct Erc20Asset<Runtime, Instance>(PhantomData<(Runtime, Instance)>);
+
+impl<Runtime, Instance> Erc20Asset<Runtime, Instance>
+where
+	Runtime: pallet_evm::Config + pallet_assets::Config<Instance>,
+	Instance: 'static,
+	AssetIdOf<Runtime, Instance>: From<u32> + Into<u32> + Copy,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_assets::Call<Runtime, Instance>>,
+	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn execute(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> PrecompileResult {
+		let selector = EvmDataReader::read_selector(handle.input())?;
+
+		let output = match selector {
+			SELECTOR_NAME => Self::name(handle, id)?,
+			SELECTOR_SYMBOL => Self::symbol(handle, id)?,
+			SELECTOR_DECIMALS => Self::decimals(handle, id)?,
+			SELECTOR_TOTAL_SUPPLY => Self::total_supply(handle, id)?,
+			SELECTOR_BALANCE_OF => Self::balance_of(handle, id)?,
+			SELECTOR_ALLOWANCE => Self::allowance(handle, id)?,
+			SELECTOR_TRANSFER => Self::transfer(handle, id)?,
+			SELECTOR_APPROVE => Self::approve(handle, id)?,
+			SELECTOR_TRANSFER_FROM => Self::transfer_from(handle, id)?,
+			SELECTOR_MINT => Self::mint(handle, id)?,
+			SELECTOR_BURN => Self::burn(handle, id)?,
+			SELECTOR_PERMIT => Self::permit(handle, id)?,
+			SELECTOR_NONCES => Self::nonces(handle)?,
+			SELECTOR_DOMAIN_SEPARATOR => Self::domain_separator(handle, id)?,
+			_ => return Err(revert("unknown selector")),
+		};
+
+		Ok(succeed(output))
+	}
+
+	fn name(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+		let name = <pallet_assets::Pallet<Runtime, Instance> as MetadataInspect<
+			Runtime::AccountId,
+		>>::name(id);
+		Ok(EvmDataWriter::new().write(Bytes(name)).build())
+	}
+
+	fn symbol(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+		let symbol = <pallet_assets::Pallet<Runtime, Instance> as MetadataInspect<
+			Runtime::AccountId,
+		>>::symbol(id);
+		Ok(EvmDataWriter::new().write(Bytes(symbol)).build())
+	}
+
+	fn decimals(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+		let decimals = <pallet_assets::Pallet<Runtime, Instance> as MetadataInspect<
+			Runtime::AccountId,
+		>>::decimals(id);
+		Ok(EvmDataWriter::new().write(decimals).build())
+	}
+
+	fn total_supply(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+		let supply = <pallet_assets::Pallet<Runtime, Instance> as Inspect<Runtime::AccountId>>::total_issuance(id);
+		Ok(EvmDataWriter::new().write(Self::to_u256(supply)).build())
+	}
+
+	fn balance_of(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let owner: Address = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		let balance =
+			<pallet_assets::Pallet<Runtime, Instance> as Inspect<Runtime::AccountId>>::balance(
+				id,
+				&Self::account(owner.0),
+			);
+		Ok(EvmDataWriter::new().write(Self::to_u256(balance)).build())
+	}
+
+	fn allowance(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let owner: Address = input.read()?;
+		let spender: Address = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		Ok(EvmDataWriter::new()
+			.write(Self::to_u256(Self::allowance_of(id, owner.0, spender.0)))
+			.build())
+	}
+
+	fn transfer(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let to: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		let from = handle.context().caller;
+		Self::ensure_not_zero(to.0)?;
+		Self::dispatch(
+			handle,
+			from,
+			pallet_assets::Call::<Runtime, Instance>::transfer {
+				id: id.into(),
+				target: Self::lookup(to.0),
+				amount: Self::to_balance(value)?,
+			},
+		)?;
+		Self::log_transfer(handle, from, to.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn approve(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let spender: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		let owner = handle.context().caller;
+		Self::do_approve(handle, id, owner, spender.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn transfer_from(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(3)?;
+		let from: Address = input.read()?;
+		let to: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		let spender = handle.context().caller;
+		Self::ensure_not_zero(to.0)?;
+		let amount = Self::to_balance(value)?;
+		if spender == from.0 {
+			Self::dispatch(
+				handle,
+				spender,
+				pallet_assets::Call::<Runtime, Instance>::transfer {
+					id: id.into(),
+					target: Self::lookup(to.0),
+					amount,
+				},
+			)?;
+		} else {
+			Self::dispatch(
+				handle,
+				spender,
+				pallet_assets::Call::<Runtime, Instance>::transfer_approved {
+					id: id.into(),
+					owner: Self::lookup(from.0),
+					destination: Self::lookup(to.0),
+					amount,
+				},
+			)?;
+		}
+		Self::log_transfer(handle, from.0, to.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn mint(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let to: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		Self::ensure_not_zero(to.0)?;
+		Self::dispatch(
+			handle,
+			handle.context().caller,
+			pallet_assets::Call::<Runtime, Instance>::mint {
+				id: id.into(),
+				beneficiary: Self::lookup(to.0),
+				amount: Self::to_balance(value)?,
+			},
+		)?;
+		Self::log_transfer(handle, H160::zero(), to.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn burn(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let from: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		// `burn` takes at most the balance of `from`, so the log reports what actually left.
+		let who = Self::account(from.0);
+		let before =
+			<pallet_assets::Pallet<Runtime, Instance> as Inspect<Runtime::AccountId>>::balance(
+				id, &who,
+			);
+		Self::dispatch(
+			handle,
+			handle.context().caller,
+			pallet_assets::Call::<Runtime, Instance>::burn {
+				id: id.into(),
+				who: Self::lookup(from.0),
+				amount: Self::to_balance(value)?,
+			},
+		)?;
+		let after =
+			<pallet_assets::Pallet<Runtime, Instance> as Inspect<Runtime::AccountId>>::balance(
+				id, &who,
+			);
+		Self::log_transfer(
+			handle,
+			from.0,
+			H160::zero(),
+			Self::to_u256(before.saturating_sub(after)),
+		)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn permit(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(7)?;
+		let owner: Address = input.read()?;
+		let spender: Address = input.read()?;
+		let value: U256 = input.read()?;
+		let deadline: U256 = input.read()?;
+		let v: u8 = input.read()?;
+		let r: H256 = input.read()?;
+		let s: H256 = input.read()?;
+
+		handle.record_cost(ECRECOVER_GAS)?;
+		record_db_reads::<Runtime>(handle, 2)?;
+
+		let now_millis: u64 = Runtime::Timestamp::now().unique_saturated_into();
+		if deadline < U256::from(now_millis / 1000) {
+			return Err(revert("permit expired"));
+		}
+
+		let token = handle.code_address();
+		let nonce = Nonces::get(token, owner.0);
+		let digest = eip2612::permit_digest(
+			Self::separator(token, id),
+			owner.0,
+			spender.0,
+			value,
+			nonce,
+			deadline,
+		);
+		match eip2612::recover_signer(digest, v, r, s) {
+			Some(signer) if signer == owner.0 && !signer.is_zero() => {}
+			_ => return Err(revert("invalid permit signature")),
+		}
+
+		Nonces::insert(token, owner.0, nonce.saturating_add(U256::one()));
+		Self::do_approve(handle, id, owner.0, spender.0, value)?;
+
+		Ok(Vec::new())
+	}
+
+	fn nonces(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let owner: Address = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		Ok(EvmDataWriter::new()
+			.write(Nonces::get(handle.code_address(), owner.0))
+			.build())
+	}
+
+	fn domain_separator(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+	) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+		Ok(EvmDataWriter::new()
+			.write(Self::separator(handle.code_address(), id))
+			.build())
+	}
+
+	/// Replaces the allowance of `spender` over `owner`'s tokens by `value`.
+	///
+	/// `pallet_assets` only adds to approvals, so an existing one is cancelled first.
+	fn do_approve(
+		handle: &mut impl PrecompileHandle,
+		id: AssetIdOf<Runtime, Instance>,
+		owner: H160,
+		spender: H160,
+		value: U256,
+	) -> EvmResult {
+		record_db_reads::<Runtime>(handle, 1)?;
+		if !Self::allowance_of(id, owner, spender).is_zero() {
+			Self::dispatch(
+				handle,
+				owner,
+				pallet_assets::Call::<Runtime, Instance>::cancel_approval {
+					id: id.into(),
+					delegate: Self::lookup(spender),
+				},
+			)?;
+		}
+		if !value.is_zero() {
+			Self::dispatch(
+				handle,
+				owner,
+				pallet_assets::Call::<Runtime, Instance>::approve_transfer {
+					id: id.into(),
+					delegate: Self::lookup(spender),
+					amount: Self::to_balance(value)?,
+				},
+			)?;
+		}
+
+		LogsBuilder::new(handle.code_address())
+			.log3(
+				keccak256("Approval(address,address,uint256)"),
+				owner,
+				spender,
+				EvmDataWriter::new().write(value).build(),
+			)
+			.record(handle)
+	}
+
+	fn log_transfer(
+		handle: &mut impl PrecompileHandle,
+		from: H160,
+		to: H160,
+		value: U256,
+	) -> EvmResult {
+		LogsBuilder::new(handle.code_address())
+			.log3(
+				keccak256("Transfer(address,address,uint256)"),
+				from,
+				to,
+				EvmDataWriter::new().write(value).build(),
+			)
+			.record(handle)
+	}
+
+	fn dispatch(
+		handle: &mut impl PrecompileHandle,
+		origin: H160,
+		call: pallet_assets::Call<Runtime, Instance>,
+	) -> EvmResult {
+		let origin = frame_system::RawOrigin::Signed(Self::account(origin)).into();
+		try_dispatch::<Runtime>(handle, origin, call.into())?;
+		Ok(())
+	}
+
+	fn ensure_mutable(handle: &impl PrecompileHandle) -> EvmResult {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)
+	}
+
+	fn ensure_not_zero(to: H160) -> EvmResult {
+		if to.is_zero() {
+			return Err(revert("transfer to the zero address"));
+		}
+		Ok(())
+	}
+
+	fn separator(token: H160, id: AssetIdOf<Runtime, Instance>) -> H256 {
+		let name = <pallet_assets::Pallet<Runtime, Instance> as MetadataInspect<
+			Runtime::AccountId,
+		>>::name(id);
+		eip2612::domain_separator(&name, PERMIT_VERSION, Runtime::ChainId::get(), token)
+	}
+
+	fn allowance_of(
+		id: AssetIdOf<Runtime, Instance>,
+		owner: H160,
+		spender: H160,
+	) -> BalanceOf<Runtime, Instance> {
+		<pallet_assets::Pallet<Runtime, Instance> as ApprovalsInspect<Runtime::AccountId>>::allowance(
+			id,
+			&Self::account(owner),
+			&Self::account(spender),
+		)
+	}
+
+	fn account(address: H160) -> Runtime::AccountId {
+		Runtime::AddressMapping::into_account_id(address)
+	}
+
+	fn lookup(address: H160) -> <Runtime::Lookup as StaticLookup>::Source {
+		Runtime::Lookup::unlookup(Self::account(address))
+	}
+
+	fn to_balance(value: U256) -> EvmResult<BalanceOf<Runtime, Instance>> {
+		u128::try_from(value)
+			.ok()
+			.and_then(|value| value.try_into().ok())
+			.ok_or_else(|| revert("value is too large"))
+	}
+
+	fn to_u256(balance: BalanceOf<Runtime, Instance>) -> U256 {
+		let balance: u128 = balance.unique_saturated_into();
+		U256::from(balance)
+	}
+}
+
+/// Stores [`REVERT_CODE`] at the address of every created asset and removes it when the
+/// asset is destroyed.
+pub struct Erc20AssetsCallback<Runtime, Prefix>(PhantomData<(Runtime, Prefix)>);
+
+impl<Runtime, Prefix, AssetId, AccountId> AssetsCallback<AssetId, AccountId>
+	for Erc20AssetsCallback<Runtime, Prefix>
+where
+	Runtime: pallet_evm::Config,
+	Prefix: Get<u32>,
+	AssetId: Copy + Into<u32>,
+{
+	fn created(id: &AssetId, _owner: &AccountId) -> Result<(), ()> {
+		pallet_evm::Pallet::<Runtime>::create_account(
+			asset_address(Prefix::get(), (*id).into()),
+			REVERT_CODE.to_vec(),
+		);
+		Ok(())
+	}
+
+	fn destroyed(id: &AssetId) -> Result<(), ()> {
+		pallet_evm::Pallet::<Runtime>::remove_account(&asset_address(Prefix::get(), (*id).into()));
+		Ok(())
+	}
+}
+
+/// Stores [`REVERT_CODE`] at the address of every asset created before
+/// [`Erc20AssetsCallback`] was installed. Assets that already have code are left alone.
+pub struct InsertAssetCode<Runtime, Instance, Prefix>(PhantomData<(Runtime, Instance, Prefix)>);
+
+impl<Runtime, Instance, Prefix> OnRuntimeUpgrade for InsertAssetCode<Runtime, Instance, Prefix>
+where
+	Runtime: pallet_evm::Config + pallet_assets::Config<Instance>,
+	Instance: 'static,
+	Prefix: Get<u32>,
+	AssetIdOf<Runtime, Instance>: Into<u32>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		let prefix = Prefix::get();
+		let mut weight = Weight::zero();
+		for id in <pallet_assets::Pallet<Runtime, Instance> as InspectEnumerable<
+			Runtime::AccountId,
+		>>::asset_ids()
+		{
+			let address = asset_address(prefix, id.into());
+			// The asset id, then the code if it is missing.
+			weight = weight
+				.saturating_add(Runtime::DbWeight::get().reads(1))
+				.saturating_add(insert_revert_code::<Runtime>(address));
+		}
+		weight
+	}
+}
@@ -0,0 +1,450 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::mock::*;
+
+use frame_support::{assert_ok, traits::Currency};
+use precompile_utils::testing::MockHandle;
+use sp_core::{ecdsa, Pair};
+
+type Assets20 = Erc20AssetsPrecompileSet<Test, pallet_assets::Instance1, AssetsPrefix>;
+
+const ALICE: H160 = H160::repeat_byte(0xaa);
+const BOB: H160 = H160::repeat_byte(0xbb);
+const CHARLIE: H160 = H160::repeat_byte(0xcc);
+
+fn input(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut input = selector.to_be_bytes().to_vec();
+	input.extend(args.build());
+	input
+}
+
+fn evm_account(address: H160) -> AccountId {
+	<Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn execute(handle: &mut MockHandle) -> PrecompileResult {
+	Assets20::new().execute(handle).expect("the asset exists")
+}
+
+fn call(caller: H160, selector: u32, args: EvmDataWriter) -> MockHandle {
+	MockHandle::new(asset(0), caller, input(selector, args))
+}
+
+/// Asset 0, "Gold" owned by `ALICE`, with `balances` minted to their holders.
+fn new_asset(balances: &[(H160, u128)]) {
+	Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+	assert_ok!(Assets::force_create(
+		RuntimeOrigin::root(),
+		0,
+		evm_account(ALICE),
+		true,
+		1
+	));
+	assert_ok!(Assets::force_set_metadata(
+		RuntimeOrigin::root(),
+		0,
+		b"Gold".to_vec(),
+		b"GLD".to_vec(),
+		12,
+		false
+	));
+	for (holder, amount) in balances {
+		assert_ok!(Assets::mint(
+			RuntimeOrigin::signed(evm_account(ALICE)),
+			0,
+			evm_account(*holder),
+			*amount
+		));
+	}
+}
+
+fn balance(address: H160) -> Balance {
+	Assets::balance(0, evm_account(address))
+}
+
+fn transfer(caller: H160, to: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_TRANSFER,
+		EvmDataWriter::new()
+			.write(Address(to))
+			.write(U256::from(value)),
+	)
+}
+
+fn approve(caller: H160, spender: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_APPROVE,
+		EvmDataWriter::new()
+			.write(Address(spender))
+			.write(U256::from(value)),
+	)
+}
+
+fn transfer_from(caller: H160, from: H160, to: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_TRANSFER_FROM,
+		EvmDataWriter::new()
+			.write(Address(from))
+			.write(Address(to))
+			.write(U256::from(value)),
+	)
+}
+
+fn mint(caller: H160, to: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_MINT,
+		EvmDataWriter::new()
+			.write(Address(to))
+			.write(U256::from(value)),
+	)
+}
+
+fn burn(caller: H160, from: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_BURN,
+		EvmDataWriter::new()
+			.write(Address(from))
+			.write(U256::from(value)),
+	)
+}
+
+fn view(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut handle = call(ALICE, selector, args).static_call();
+	execute(&mut handle).unwrap().output
+}
+
+fn allowance(owner: H160, spender: H160) -> U256 {
+	let output = view(
+		SELECTOR_ALLOWANCE,
+		EvmDataWriter::new()
+			.write(Address(owner))
+			.write(Address(spender)),
+	);
+	EvmDataReader::new(&output).read().unwrap()
+}
+
+fn nonce(owner: H160) -> U256 {
+	let output = view(SELECTOR_NONCES, EvmDataWriter::new().write(Address(owner)));
+	EvmDataReader::new(&output).read().unwrap()
+}
+
+fn transfer_log(from: H160, to: H160, value: u128) -> precompile_utils::Log {
+	LogsBuilder::new(asset(0)).log3(
+		keccak256("Transfer(address,address,uint256)"),
+		from,
+		to,
+		EvmDataWriter::new().write(U256::from(value)).build(),
+	)
+}
+
+fn approval_log(owner: H160, spender: H160, value: u128) -> precompile_utils::Log {
+	LogsBuilder::new(asset(0)).log3(
+		keccak256("Approval(address,address,uint256)"),
+		owner,
+		spender,
+		EvmDataWriter::new().write(U256::from(value)).build(),
+	)
+}
+
+/// Key signing permits, and its address, with enough native balance for approval deposits.
+fn owner() -> (ecdsa::Pair, H160) {
+	let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+	let address = H160::from(fp_account::AccountId20::from(pair.public()));
+	Balances::make_free_balance_be(&evm_account(address), 1_000);
+	(pair, address)
+}
+
+/// `permit` call for `value` until `deadline`, signed by `pair` with its `nonce`.
+fn permit(pair: &ecdsa::Pair, spender: H160, value: u128, nonce: u64, deadline: u64) -> MockHandle {
+	let owner = H160::from(fp_account::AccountId20::from(pair.public()));
+	let domain = eip2612::domain_separator(b"Gold", PERMIT_VERSION, 1299, asset(0));
+	let digest = eip2612::permit_digest(
+		domain,
+		owner,
+		spender,
+		value.into(),
+		nonce.into(),
+		deadline.into(),
+	);
+	let signature = pair.sign_prehashed(&digest.0).0;
+	call(
+		CHARLIE,
+		SELECTOR_PERMIT,
+		EvmDataWriter::new()
+			.write(Address(owner))
+			.write(Address(spender))
+			.write(U256::from(value))
+			.write(U256::from(deadline))
+			.write(signature[64] + 27)
+			.write(H256::from_slice(&signature[..32]))
+			.write(H256::from_slice(&signature[32..64])),
+	)
+}
+
+#[test]
+fn addresses_round_trip() {
+	let prefix = 0xffff_ffff;
+	let address = asset_address(prefix, 0x0102_0304);
+	assert_eq!(
+		address,
+		H160::from_slice(&[
+			0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4
+		])
+	);
+	assert_eq!(asset_id(prefix, address), Some(0x0102_0304));
+	assert_eq!(asset_id(0xffff_fffe, address), None);
+
+	let mut dirty = address;
+	dirty.0[10] = 1;
+	assert_eq!(asset_id(prefix, dirty), None);
+}
+
+#[test]
+fn only_existing_assets_answer() {
+	new_test_ext().execute_with(|| {
+		let mut handle = call(ALICE, SELECTOR_TOTAL_SUPPLY, EvmDataWriter::new()).static_call();
+		assert!(Assets20::new().execute(&mut handle).is_none());
+
+		new_asset(&[]);
+
+		assert!(Assets20::new().execute(&mut handle).is_some());
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(asset(0)),
+			REVERT_CODE.to_vec()
+		);
+	});
+}
+
+#[test]
+fn insert_asset_code_fills_in_missing_code_once() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[]);
+		// An asset created before the callback was installed.
+		pallet_evm::Pallet::<Test>::remove_account(&asset(0));
+
+		InsertAssetCode::<Test, pallet_assets::Instance1, AssetsPrefix>::on_runtime_upgrade();
+		InsertAssetCode::<Test, pallet_assets::Instance1, AssetsPrefix>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(asset(0)),
+			REVERT_CODE.to_vec()
+		);
+		assert_eq!(System::account(evm_account(asset(0))).sufficients, 1);
+	});
+}
+
+#[test]
+fn reads_the_asset_metadata_and_supply() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[(ALICE, 700), (BOB, 300)]);
+
+		assert_eq!(
+			view(SELECTOR_NAME, EvmDataWriter::new()),
+			EvmDataWriter::new().write(Bytes(b"Gold".to_vec())).build()
+		);
+		assert_eq!(
+			view(SELECTOR_SYMBOL, EvmDataWriter::new()),
+			EvmDataWriter::new().write(Bytes(b"GLD".to_vec())).build()
+		);
+		assert_eq!(
+			view(SELECTOR_DECIMALS, EvmDataWriter::new()),
+			EvmDataWriter::new().write(12u8).build()
+		);
+		assert_eq!(
+			view(SELECTOR_TOTAL_SUPPLY, EvmDataWriter::new()),
+			EvmDataWriter::new().write(U256::from(1_000)).build()
+		);
+		assert_eq!(
+			view(SELECTOR_BALANCE_OF, EvmDataWriter::new().write(Address(BOB))),
+			EvmDataWriter::new().write(U256::from(300)).build()
+		);
+	});
+}
+
+#[test]
+fn transfer_moves_the_asset_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[(ALICE, 1_000)]);
+		let mut handle = transfer(ALICE, BOB, 400);
+
+		let output = execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(true).build());
+		assert_eq!(balance(ALICE), 600);
+		assert_eq!(balance(BOB), 400);
+		assert_eq!(handle.logs, vec![transfer_log(ALICE, BOB, 400)]);
+
+		assert!(execute(&mut transfer(ALICE, BOB, 2_000)).is_err());
+		assert_eq!(
+			execute(&mut transfer(ALICE, H160::zero(), 100)).unwrap_err(),
+			revert("transfer to the zero address")
+		);
+		assert_eq!(balance(ALICE), 600);
+	});
+}
+
+#[test]
+fn approve_replaces_the_allowance_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[(ALICE, 1_000)]);
+		let mut handle = approve(ALICE, BOB, 300);
+
+		let output = execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(true).build());
+		assert_eq!(allowance(ALICE, BOB), U256::from(300));
+		assert_eq!(handle.logs, vec![approval_log(ALICE, BOB, 300)]);
+
+		// `pallet_assets` would add to the approval; the ERC-20 view replaces it.
+		assert_ok!(execute(&mut approve(ALICE, BOB, 100)));
+		assert_eq!(allowance(ALICE, BOB), U256::from(100));
+
+		let mut handle = approve(ALICE, BOB, 0);
+		assert_ok!(execute(&mut handle));
+		assert_eq!(allowance(ALICE, BOB), U256::zero());
+		assert_eq!(handle.logs, vec![approval_log(ALICE, BOB, 0)]);
+	});
+}
+
+#[test]
+fn transfer_from_spends_the_allowance_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[(ALICE, 1_000)]);
+		assert_ok!(execute(&mut approve(ALICE, BOB, 300)));
+
+		let mut handle = transfer_from(BOB, ALICE, CHARLIE, 200);
+		let output = execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(true).build());
+		assert_eq!(balance(ALICE), 800);
+		assert_eq!(balance(CHARLIE), 200);
+		assert_eq!(allowance(ALICE, BOB), U256::from(100));
+		assert_eq!(handle.logs, vec![transfer_log(ALICE, CHARLIE, 200)]);
+
+		assert!(execute(&mut transfer_from(BOB, ALICE, CHARLIE, 200)).is_err());
+		assert_eq!(balance(ALICE), 800);
+	});
+}
+
+#[test]
+fn transfer_from_the_caller_needs_no_allowance() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[(ALICE, 1_000)]);
+		let mut handle = transfer_from(ALICE, ALICE, BOB, 250);
+
+		assert_ok!(execute(&mut handle));
+
+		assert_eq!(balance(ALICE), 750);
+		assert_eq!(balance(BOB), 250);
+		assert_eq!(handle.logs, vec![transfer_log(ALICE, BOB, 250)]);
+	});
+}
+
+#[test]
+fn mint_needs_the_issuer_role() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[]);
+
+		assert_eq!(
+			execute(&mut mint(BOB, BOB, 500)).unwrap_err(),
+			revert("dispatch failed: NoPermission")
+		);
+		assert_eq!(balance(BOB), 0);
+
+		let mut handle = mint(ALICE, BOB, 500);
+		assert_ok!(execute(&mut handle));
+		assert_eq!(balance(BOB), 500);
+		assert_eq!(handle.logs, vec![transfer_log(H160::zero(), BOB, 500)]);
+	});
+}
+
+#[test]
+fn burn_needs_the_admin_role_and_logs_what_was_burned() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[(BOB, 500)]);
+
+		assert_eq!(
+			execute(&mut burn(BOB, BOB, 200)).unwrap_err(),
+			revert("dispatch failed: NoPermission")
+		);
+		assert_eq!(balance(BOB), 500);
+
+		let mut handle = burn(ALICE, BOB, 200);
+		assert_ok!(execute(&mut handle));
+		assert_eq!(balance(BOB), 300);
+		assert_eq!(handle.logs, vec![transfer_log(BOB, H160::zero(), 200)]);
+
+		// Burning more than the balance takes what there is.
+		let mut handle = burn(ALICE, BOB, 1_000);
+		assert_ok!(execute(&mut handle));
+		assert_eq!(balance(BOB), 0);
+		assert_eq!(handle.logs, vec![transfer_log(BOB, H160::zero(), 300)]);
+	});
+}
+
+#[test]
+fn permit_approves_and_bumps_the_nonce() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[]);
+		let (pair, owner) = owner();
+		let mut handle = permit(&pair, BOB, 500, 0, u64::MAX);
+
+		assert_ok!(execute(&mut handle));
+
+		assert_eq!(allowance(owner, BOB), U256::from(500));
+		assert_eq!(nonce(owner), U256::one());
+		assert_eq!(handle.logs, vec![approval_log(owner, BOB, 500)]);
+		assert_eq!(
+			view(SELECTOR_DOMAIN_SEPARATOR, EvmDataWriter::new()),
+			EvmDataWriter::new()
+				.write(eip2612::domain_separator(
+					b"Gold",
+					PERMIT_VERSION,
+					1299,
+					asset(0)
+				))
+				.build()
+		);
+	});
+}
+
+#[test]
+fn permit_rejects_an_expired_deadline() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[]);
+		Timestamp::set_timestamp(100_000);
+		let (pair, owner) = owner();
+
+		assert_eq!(
+			execute(&mut permit(&pair, BOB, 500, 0, 99)).unwrap_err(),
+			revert("permit expired")
+		);
+		assert_eq!(nonce(owner), U256::zero());
+
+		assert_ok!(execute(&mut permit(&pair, BOB, 500, 0, 100)));
+		assert_eq!(allowance(owner, BOB), U256::from(500));
+	});
+}
+
+#[test]
+fn permit_cannot_be_replayed() {
+	new_test_ext().execute_with(|| {
+		new_asset(&[]);
+		let (pair, owner) = owner();
+		assert_ok!(execute(&mut permit(&pair, BOB, 500, 0, u64::MAX)));
+		assert_ok!(execute(&mut approve(owner, BOB, 0)));
+
+		assert_eq!(
+			execute(&mut permit(&pair, BOB, 500, 0, u64::MAX)).unwrap_err(),
+			revert("invalid permit signature")
+		);
+		assert_eq!(allowance(owner, BOB), U256::zero());
+		assert_eq!(nonce(owner), U256::one());
+	});
+}
@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! ERC-20 precompile over the native balance of `pallet_evm` accounts.
+//!
+//! Balances are those of `pallet_evm::Config::Currency` for the account mapped from each
+//! address, so the token and the native balance never diverge and no wrapping is needed.
+//! Allowances and permit nonces are kept in storage owned by the precompile.
+//!
+//! ```solidity
+//! interface IERC20Native {
+//!     event Transfer(address indexed from, address indexed to, uint256 value);
+//!     event Approval(address indexed owner, address indexed spender, uint256 value);
+//!
+//!     function name() external view returns (string memory);
+//!     function symbol() external view returns (string memory);
+//!     function decimals() external view returns (uint8);
+//!     function totalSupply() external view returns (uint256);
+//!     function balanceOf(address owner) external view returns (uint256);
+//!     function allowance(address owner, address spender) external view returns (uint256);
+//!     function transfer(address to, uint256 value) external returns (bool);
+//!     function approve(address spender, uint256 value) external returns (bool);
+//!     function transferFrom(address from, address to, uint256 value) external returns (bool);
+//!
+//!     function permit(
+//!         address owner,
+//!         address spender,
+//!         uint256 value,
+//!         uint256 deadline,
+//!         uint8 v,
+//!         bytes32 r,
+//!         bytes32 s
+//!     ) external;
+//!     function nonces(address owner) external view returns (uint256);
+//!     function DOMAIN_SEPARATOR() external view returns (bytes32);
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{Precompile, PrecompileHandle, PrecompileResult};
+use frame_support::{
+	storage_alias,
+	traits::{Currency, ExistenceRequirement, Get, Time},
+	Blake2_128Concat,
+};
+use pallet_evm::AddressMapping;
+use precompile_utils::{
+	eip2612, ensure_direct_call, ensure_non_payable, ensure_not_static, keccak256,
+	record_db_reads, record_weight, revert, succeed, Address, Bytes, EvmDataReader,
+	EvmDataWriter, EvmResult, LogsBuilder,
+};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::UniqueSaturatedInto;
+use sp_std::vec::Vec;
+
+/// `name()`
+pub const SELECTOR_NAME: u32 = 0x06fdde03;
+/// `symbol()`
+pub const SELECTOR_SYMBOL: u32 = 0x95d89b41;
+/// `decimals()`
+pub const SELECTOR_DECIMALS: u32 = 0x313ce567;
+/// `totalSupply()`
+pub const SELECTOR_TOTAL_SUPPLY: u32 = 0x18160ddd;
+/// `balanceOf(address)`
+pub const SELECTOR_BALANCE_OF: u32 = 0x70a08231;
+/// `allowance(address,address)`
+pub const SELECTOR_ALLOWANCE: u32 = 0xdd62ed3e;
+/// `transfer(address,uint256)`
+pub const SELECTOR_TRANSFER: u32 = 0xa9059cbb;
+/// `approve(address,uint256)`
+pub const SELECTOR_APPROVE: u32 = 0x095ea7b3;
+/// `transferFrom(address,address,uint256)`
+pub const SELECTOR_TRANSFER_FROM: u32 = 0x23b872dd;
+/// `permit(address,address,uint256,uint256,uint8,bytes32,bytes32)`
+pub const SELECTOR_PERMIT: u32 = 0xd505accf;
+/// `nonces(address)`
+pub const SELECTOR_NONCES: u32 = 0x7ecebe00;
+/// `DOMAIN_SEPARATOR()`
+pub const SELECTOR_DOMAIN_SEPARATOR: u32 = 0x3644e515;
+
+/// Version of the EIP-2612 signing domain.
+pub const PERMIT_VERSION: &[u8] = b"1";
+
+/// Gas charged for recovering a permit signer, as the `ecrecover` precompile does.
+const ECRECOVER_GAS: u64 = 3_000;
+
+/// Allowances, by owner and spender.
+#[storage_alias]
+pub type Approves =
+	StorageDoubleMap<Erc20NativeBalances, Blake2_128Concat, H160, Blake2_128Concat, H160, U256, ValueQuery>;
+
+/// Next permit nonce of each owner.
+#[storage_alias]
+pub type Nonces = StorageMap<Erc20NativeBalances, Blake2_128Concat, H160, U256, ValueQuery>;
+
+/// Token metadata reported by the precompile.
+pub trait Erc20Metadata {
+	fn name() -> &'static str;
+	fn symbol() -> &'static str;
+	fn decimals() -> u8;
+}
+
+type BalanceOf<Runtime> = pallet_evm::BalanceOf<Runtime>;
+
+pub struct Erc20BalancesPrecompile<Runtime, Metadata>(PhantomData<(Runtime, Metadata)>);
+
+impl<Runtime, Metadata> Precompile for Erc20BalancesPrecompile<Runtime, Metadata>
+where
+	Runtime: pallet_evm::Config,
+	Metadata: Erc20Metadata,
+{
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let selector = EvmDataReader::read_selector(handle.input())?;
+
+		let output = match selector {
+			SELECTOR_NAME => Self::name(handle)?,
+			SELECTOR_SYMBOL => Self::symbol(handle)?,
+			SELECTOR_DECIMALS => Self::decimals(handle)?,
+			SELECTOR_TOTAL_SUPPLY => Self::total_supply(handle)?,
+			SELECTOR_BALANCE_OF => Self::balance_of(handle)?,
+			SELECTOR_ALLOWANCE => Self::allowance(handle)?,
+			SELECTOR_TRANSFER => Self::transfer(handle)?,
+			SELECTOR_APPROVE => Self::approve(handle)?,
+			SELECTOR_TRANSFER_FROM => Self::transfer_from(handle)?,
+			SELECTOR_PERMIT => Self::permit(handle)?,
+			SELECTOR_NONCES => Self::nonces(handle)?,
+			SELECTOR_DOMAIN_SEPARATOR => Self::domain_separator(handle)?,
+			_ => return Err(revert("unknown selector")),
+		};
+
+		Ok(succeed(output))
+	}
+}
+
+impl<Runtime, Metadata> Erc20BalancesPrecompile<Runtime, Metadata>
+where
+	Runtime: pallet_evm::Config,
+	Metadata: Erc20Metadata,
+{
+	fn name(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		Ok(EvmDataWriter::new().write(Bytes::from(Metadata::name())).build())
+	}
+
+	fn symbol(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		Ok(EvmDataWriter::new().write(Bytes::from(Metadata::symbol())).build())
+	}
+
+	fn decimals(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		Ok(EvmDataWriter::new().write(Metadata::decimals()).build())
+	}
+
+	fn total_supply(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+
+		let issuance = <Runtime::Currency as Currency<Runtime::AccountId>>::total_issuance();
+		Ok(EvmDataWriter::new().write(Self::to_u256(issuance)).build())
+	}
+
+	fn balance_of(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let owner: Address = input.read()?;
+
+		// Same figure as the `BALANCE` opcode.
+		let (account, weight) = pallet_evm::Pallet::<Runtime>::account_basic(&owner.0);
+		record_weight::<Runtime>(handle, weight)?;
+
+		Ok(EvmDataWriter::new().write(account.balance).build())
+	}
+
+	fn allowance(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let owner: Address = input.read()?;
+		let spender: Address = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+
+		Ok(EvmDataWriter::new().write(Approves::get(owner.0, spender.0)).build())
+	}
+
+	fn transfer(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let to: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		let from = handle.context().caller;
+		Self::do_transfer(handle, from, to.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn approve(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let spender: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		record_weight::<Runtime>(handle, Runtime::DbWeight::get().writes(1))?;
+
+		let owner = handle.context().caller;
+		Self::do_approve(handle, owner, spender.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn transfer_from(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(3)?;
+		let from: Address = input.read()?;
+		let to: Address = input.read()?;
+		let value: U256 = input.read()?;
+
+		let spender = handle.context().caller;
+		if spender != from.0 {
+			record_weight::<Runtime>(handle, Runtime::DbWeight::get().reads_writes(1, 1))?;
+
+			let allowance = Approves::get(from.0, spender);
+			// An allowance of `type(uint256).max` is never spent down.
+			if allowance != U256::MAX {
+				let remaining = allowance
+					.checked_sub(value)
+					.ok_or_else(|| revert("insufficient allowance"))?;
+				Approves::insert(from.0, spender, remaining);
+			}
+		}
+
+		Self::do_transfer(handle, from.0, to.0, value)?;
+
+		Ok(EvmDataWriter::new().write(true).build())
+	}
+
+	fn permit(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(7)?;
+		let owner: Address = input.read()?;
+		let spender: Address = input.read()?;
+		let value: U256 = input.read()?;
+		let deadline: U256 = input.read()?;
+		let v: u8 = input.read()?;
+		let r: H256 = input.read()?;
+		let s: H256 = input.read()?;
+
+		handle.record_cost(ECRECOVER_GAS)?;
+		record_weight::<Runtime>(handle, Runtime::DbWeight::get().reads_writes(2, 2))?;
+
+		let now_millis: u64 = Runtime::Timestamp::now().unique_saturated_into();
+		if deadline < U256::from(now_millis / 1000) {
+			return Err(revert("permit expired"));
+		}
+
+		let nonce = Nonces::get(owner.0);
+		let digest = eip2612::permit_digest(
+			Self::separator(handle.code_address()),
+			owner.0,
+			spender.0,
+			value,
+			nonce,
+			deadline,
+		);
+		match eip2612::recover_signer(digest, v, r, s) {
+			Some(signer) if signer == owner.0 && !signer.is_zero() => {}
+			_ => return Err(revert("invalid permit signature")),
+		}
+
+		Nonces::insert(owner.0, nonce.saturating_add(U256::one()));
+		Self::do_approve(handle, owner.0, spender.0, value)?;
+
+		Ok(Vec::new())
+	}
+
+	fn nonces(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let owner: Address = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+
+		Ok(EvmDataWriter::new().write(Nonces::get(owner.0)).build())
+	}
+
+	fn domain_separator(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		Ok(EvmDataWriter::new().write(Self::separator(handle.code_address())).build())
+	}
+
+	fn separator(address: H160) -> H256 {
+		eip2612::domain_separator(
+			Metadata::name().as_bytes(),
+			PERMIT_VERSION,
+			Runtime::ChainId::get(),
+			address,
+		)
+	}
+
+	fn do_transfer(handle: &mut impl PrecompileHandle, from: H160, to: H160, value: U256) -> EvmResult {
+		if to.is_zero() {
+			return Err(revert("transfer to the zero address"));
+		}
+		let amount: BalanceOf<Runtime> = u128::try_from(value)
+			.ok()
+			.and_then(|value| value.try_into().ok())
+			.ok_or_else(|| revert("value is too large"))?;
+
+		record_weight::<Runtime>(handle, Runtime::DbWeight::get().reads_writes(2, 2))?;
+
+		<Runtime::Currency as Currency<Runtime::AccountId>>::transfer(
+			&Runtime::AddressMapping::into_account_id(from),
+			&Runtime::AddressMapping::into_account_id(to),
+			amount,
+			ExistenceRequirement::AllowDeath,
+		)
+		.map_err(|e| revert(<&'static str>::from(e)))?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("Transfer(address,address,uint256)"),
+				from,
+				to,
+				EvmDataWriter::new().write(value).build(),
+			)
+			.record(handle)
+	}
+
+	fn do_approve(handle: &mut impl PrecompileHandle, owner: H160, spender: H160, value: U256) -> EvmResult {
+		Approves::insert(owner, spender, value);
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("Approval(address,address,uint256)"),
+				owner,
+				spender,
+				EvmDataWriter::new().write(value).build(),
+			)
+			.record(handle)
+	}
+
+	fn to_u256(balance: BalanceOf<Runtime>) -> U256 {
+		let balance: u128 = balance.unique_saturated_into();
+		U256::from(balance)
+	}
+}
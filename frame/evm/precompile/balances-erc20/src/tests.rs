@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::mock::*;
+
+use frame_support::{
+	assert_ok,
+	traits::{Currency, OnRuntimeUpgrade},
+};
+use precompile_utils::{testing::MockHandle, InsertRevertCode, REVERT_CODE};
+use sp_core::{ecdsa, Pair};
+
+type Token = Erc20BalancesPrecompile<Test, TestMetadata>;
+
+const ALICE: H160 = H160::repeat_byte(0xaa);
+const BOB: H160 = H160::repeat_byte(0xbb);
+const CHARLIE: H160 = H160::repeat_byte(0xcc);
+
+fn input(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut input = selector.to_be_bytes().to_vec();
+	input.extend(args.build());
+	input
+}
+
+fn evm_account(address: H160) -> AccountId {
+	<Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn balance(address: H160) -> Balance {
+	Balances::free_balance(evm_account(address))
+}
+
+fn call(caller: H160, selector: u32, args: EvmDataWriter) -> MockHandle {
+	MockHandle::new(precompile_address(), caller, input(selector, args))
+}
+
+fn transfer(caller: H160, to: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_TRANSFER,
+		EvmDataWriter::new()
+			.write(Address(to))
+			.write(U256::from(value)),
+	)
+}
+
+fn approve(caller: H160, spender: H160, value: U256) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_APPROVE,
+		EvmDataWriter::new().write(Address(spender)).write(value),
+	)
+}
+
+fn transfer_from(caller: H160, from: H160, to: H160, value: u128) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_TRANSFER_FROM,
+		EvmDataWriter::new()
+			.write(Address(from))
+			.write(Address(to))
+			.write(U256::from(value)),
+	)
+}
+
+fn allowance(owner: H160, spender: H160) -> U256 {
+	let mut handle = call(
+		ALICE,
+		SELECTOR_ALLOWANCE,
+		EvmDataWriter::new()
+			.write(Address(owner))
+			.write(Address(spender)),
+	)
+	.static_call();
+	let output = Token::execute(&mut handle).unwrap().output;
+	EvmDataReader::new(&output).read().unwrap()
+}
+
+fn transfer_log(from: H160, to: H160, value: u128) -> precompile_utils::Log {
+	LogsBuilder::new(precompile_address()).log3(
+		keccak256("Transfer(address,address,uint256)"),
+		from,
+		to,
+		EvmDataWriter::new().write(U256::from(value)).build(),
+	)
+}
+
+fn approval_log(owner: H160, spender: H160, value: U256) -> precompile_utils::Log {
+	LogsBuilder::new(precompile_address()).log3(
+		keccak256("Approval(address,address,uint256)"),
+		owner,
+		spender,
+		EvmDataWriter::new().write(value).build(),
+	)
+}
+
+/// Order of the secp256k1 curve.
+const SECP256K1_ORDER: [u8; 32] = [
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+	0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Key signing permits, and its address.
+fn owner() -> (ecdsa::Pair, H160) {
+	let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+	let address = H160::from(fp_account::AccountId20::from(pair.public()));
+	(pair, address)
+}
+
+/// `permit` call for `value` until `deadline`, signed by `pair` with its `nonce`.
+fn permit(pair: &ecdsa::Pair, spender: H160, value: u128, nonce: u64, deadline: u64) -> MockHandle {
+	let owner = H160::from(fp_account::AccountId20::from(pair.public()));
+	let domain =
+		eip2612::domain_separator(b"Argochain", PERMIT_VERSION, 1299, precompile_address());
+	let digest = eip2612::permit_digest(
+		domain,
+		owner,
+		spender,
+		value.into(),
+		nonce.into(),
+		deadline.into(),
+	);
+	let signature = pair.sign_prehashed(&digest.0).0;
+	signed_permit(owner, spender, value, deadline, signature)
+}
+
+fn signed_permit(
+	owner: H160,
+	spender: H160,
+	value: u128,
+	deadline: u64,
+	signature: [u8; 65],
+) -> MockHandle {
+	call(
+		CHARLIE,
+		SELECTOR_PERMIT,
+		EvmDataWriter::new()
+			.write(Address(owner))
+			.write(Address(spender))
+			.write(U256::from(value))
+			.write(U256::from(deadline))
+			.write(signature[64] + 27)
+			.write(H256::from_slice(&signature[..32]))
+			.write(H256::from_slice(&signature[32..64])),
+	)
+}
+
+fn nonce(owner: H160) -> U256 {
+	let mut handle = call(
+		ALICE,
+		SELECTOR_NONCES,
+		EvmDataWriter::new().write(Address(owner)),
+	)
+	.static_call();
+	let output = Token::execute(&mut handle).unwrap().output;
+	EvmDataReader::new(&output).read().unwrap()
+}
+
+#[test]
+fn transfer_moves_balance_and_logs() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = transfer(ALICE, BOB, 400);
+
+		let output = Token::execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(true).build());
+		assert_eq!(balance(ALICE), 600);
+		assert_eq!(balance(BOB), 400);
+		assert_eq!(handle.logs, vec![transfer_log(ALICE, BOB, 400)]);
+	});
+}
+
+#[test]
+fn transfer_reverts_without_funds_or_to_the_zero_address() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		assert!(Token::execute(&mut transfer(ALICE, BOB, 2_000)).is_err());
+		assert_eq!(
+			Token::execute(&mut transfer(ALICE, H160::zero(), 400)).unwrap_err(),
+			revert("transfer to the zero address")
+		);
+		assert_eq!(balance(ALICE), 1_000);
+	});
+}
+
+#[test]
+fn approve_sets_the_allowance_and_logs() {
+	new_test_ext().execute_with(|| {
+		let mut handle = approve(ALICE, BOB, U256::from(300));
+
+		let output = Token::execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(true).build());
+		assert_eq!(allowance(ALICE, BOB), U256::from(300));
+		assert_eq!(handle.logs, vec![approval_log(ALICE, BOB, U256::from(300))]);
+	});
+}
+
+#[test]
+fn transfer_from_spends_the_allowance() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Token::execute(&mut approve(ALICE, BOB, U256::from(300))));
+
+		let mut handle = transfer_from(BOB, ALICE, CHARLIE, 200);
+		assert_ok!(Token::execute(&mut handle));
+
+		assert_eq!(balance(ALICE), 800);
+		assert_eq!(balance(CHARLIE), 200);
+		assert_eq!(allowance(ALICE, BOB), U256::from(100));
+		assert_eq!(handle.logs, vec![transfer_log(ALICE, CHARLIE, 200)]);
+
+		assert_eq!(
+			Token::execute(&mut transfer_from(BOB, ALICE, CHARLIE, 200)).unwrap_err(),
+			revert("insufficient allowance")
+		);
+		assert_eq!(balance(ALICE), 800);
+	});
+}
+
+#[test]
+fn transfer_from_never_spends_an_infinite_allowance() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Token::execute(&mut approve(ALICE, BOB, U256::MAX)));
+
+		assert_ok!(Token::execute(&mut transfer_from(BOB, ALICE, CHARLIE, 200)));
+
+		assert_eq!(allowance(ALICE, BOB), U256::MAX);
+	});
+}
+
+#[test]
+fn permit_approves_and_bumps_the_nonce() {
+	new_test_ext().execute_with(|| {
+		let (pair, owner) = owner();
+		let mut handle = permit(&pair, BOB, 500, 0, u64::MAX);
+
+		assert_ok!(Token::execute(&mut handle));
+
+		assert_eq!(allowance(owner, BOB), U256::from(500));
+		assert_eq!(nonce(owner), U256::one());
+		assert_eq!(handle.logs, vec![approval_log(owner, BOB, U256::from(500))]);
+	});
+}
+
+#[test]
+fn permit_rejects_an_expired_deadline() {
+	new_test_ext().execute_with(|| {
+		Timestamp::set_timestamp(100_000);
+		let (pair, owner) = owner();
+
+		assert_eq!(
+			Token::execute(&mut permit(&pair, BOB, 500, 0, 99)).unwrap_err(),
+			revert("permit expired")
+		);
+		assert_ok!(Token::execute(&mut permit(&pair, BOB, 500, 0, 100)));
+		assert_eq!(allowance(owner, BOB), U256::from(500));
+	});
+}
+
+#[test]
+fn permit_cannot_be_replayed() {
+	new_test_ext().execute_with(|| {
+		let (pair, owner) = owner();
+		assert_ok!(Token::execute(&mut permit(&pair, BOB, 500, 0, u64::MAX)));
+		assert_ok!(Token::execute(&mut approve(owner, BOB, U256::zero())));
+
+		assert_eq!(
+			Token::execute(&mut permit(&pair, BOB, 500, 0, u64::MAX)).unwrap_err(),
+			revert("invalid permit signature")
+		);
+		assert_eq!(allowance(owner, BOB), U256::zero());
+	});
+}
+
+#[test]
+fn permit_rejects_other_signers_and_high_s() {
+	new_test_ext().execute_with(|| {
+		let (pair, owner) = owner();
+		let mut handle = permit(&pair, BOB, 500, 0, u64::MAX);
+		// Claim the signature was made for `ALICE`.
+		handle.input[4 + 12..4 + 32].copy_from_slice(ALICE.as_bytes());
+		assert_eq!(
+			Token::execute(&mut handle).unwrap_err(),
+			revert("invalid permit signature")
+		);
+
+		// (r, n - s, v ^ 1) recovers the same key, but is not accepted.
+		let domain =
+			eip2612::domain_separator(b"Argochain", PERMIT_VERSION, 1299, precompile_address());
+		let digest = eip2612::permit_digest(
+			domain,
+			owner,
+			BOB,
+			500.into(),
+			U256::zero(),
+			u64::MAX.into(),
+		);
+		let mut signature = pair.sign_prehashed(&digest.0).0;
+		let order = U256::from_big_endian(&SECP256K1_ORDER);
+		let high_s = order - U256::from_big_endian(&signature[32..64]);
+		high_s.to_big_endian(&mut signature[32..64]);
+		signature[64] ^= 1;
+		assert_eq!(
+			Token::execute(&mut signed_permit(owner, BOB, 500, u64::MAX, signature)).unwrap_err(),
+			revert("invalid permit signature")
+		);
+
+		assert_eq!(allowance(owner, BOB), U256::zero());
+		assert_eq!(nonce(owner), U256::zero());
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_delegatecall() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(0xdd);
+		let (pair, owner) = owner();
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Token::execute(&mut approve(ALICE, BOB, U256::from(300))));
+
+		for handle in [
+			transfer(ALICE, BOB, 400),
+			approve(ALICE, BOB, U256::from(400)),
+			transfer_from(BOB, ALICE, CHARLIE, 200),
+			permit(&pair, BOB, 500, 0, u64::MAX),
+		] {
+			assert_eq!(
+				Token::execute(&mut handle.delegated_from(contract)).unwrap_err(),
+				revert("cannot be called with DELEGATECALL or CALLCODE")
+			);
+		}
+
+		assert_eq!(balance(ALICE), 1_000);
+		assert_eq!(allowance(ALICE, BOB), U256::from(300));
+		assert_eq!(allowance(owner, BOB), U256::zero());
+		assert_eq!(nonce(owner), U256::zero());
+	});
+}
+
+#[test]
+fn insert_revert_code_runs_once() {
+	new_test_ext().execute_with(|| {
+		frame_support::parameter_types! {
+			pub Address: H160 = precompile_address();
+		}
+
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(precompile_address()),
+			REVERT_CODE.to_vec()
+		);
+		assert_eq!(System::account(evm_account(precompile_address())).sufficients, 1);
+	});
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Precompile exposing the `pallet_counter` bridge to EVM callers.
+//!
+//! `msg.sender` authenticates the H160 side of every call, so contracts and wallets
+//! can move funds to a Substrate account without a separate ECDSA signature.
+//!
+//! A transfer above the bridge's delay threshold is queued instead of executed: it emits
+//! `TransferQueued` rather than `TransferToSubstrate` and returns the id of the pending
+//! transfer.
+//!
+//! ```solidity
+//! interface ICounterBridge {
+//!     event TransferToSubstrate(address indexed from, bytes32 indexed to, uint256 amount);
+//!     event TransferQueued(
+//!         uint64 indexed id,
+//!         address indexed from,
+//!         bytes32 indexed to,
+//!         uint256 amount
+//!     );
+//!     event IpfsHashRegistered(address indexed owner, bytes cid);
+//!
+//!     function transferToSubstrate(bytes32 account, uint256 amount)
+//!         external
+//!         returns (bool queued, uint64 id);
+//!     function lockedBalanceOf(bytes32 account) external view returns (uint256);
+//!     function registerIpfsHash(
+//!         bytes calldata cid,
+//!         bytes calldata tag,
+//!         bytes32[] calldata attestors,
+//!         bytes[] calldata signatures
+//!     ) external;
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{Precompile, PrecompileHandle, PrecompileResult};
+use pallet_counter::WeightInfo;
+use pallet_evm::AddressMapping;
+use precompile_utils::{
+	ensure_direct_call, ensure_non_payable, ensure_not_static, keccak256, record_db_reads,
+	record_weight, revert, succeed, u256_topic, Bytes, EvmDataReader, EvmDataWriter, EvmResult,
+	LogsBuilder,
+};
+use sp_core::{sr25519, H160, H256, U256};
+use sp_runtime::traits::UniqueSaturatedInto;
+use sp_std::vec::Vec;
+
+/// `transferToSubstrate(bytes32,uint256)`
+pub const SELECTOR_TRANSFER_TO_SUBSTRATE: u32 = 0xb3fd28c4;
+/// `lockedBalanceOf(bytes32)`
+pub const SELECTOR_LOCKED_BALANCE_OF: u32 = 0x8b5ee542;
+/// `registerIpfsHash(bytes,bytes,bytes32[],bytes[])`
+pub const SELECTOR_REGISTER_IPFS_HASH: u32 = 0x68eded8e;
+
+pub struct CounterBridgePrecompile<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> Precompile for CounterBridgePrecompile<Runtime>
+where
+	Runtime: pallet_counter::Config + pallet_evm::Config,
+	Runtime::AccountId: From<[u8; 32]>,
+{
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let selector = EvmDataReader::read_selector(handle.input())?;
+
+		let output = match selector {
+			SELECTOR_TRANSFER_TO_SUBSTRATE => Self::transfer_to_substrate(handle)?,
+			SELECTOR_LOCKED_BALANCE_OF => Self::locked_balance_of(handle)?,
+			SELECTOR_REGISTER_IPFS_HASH => Self::register_ipfs_hash(handle)?,
+			_ => return Err(revert("unknown selector")),
+		};
+
+		Ok(succeed(output))
+	}
+}
+
+impl<Runtime> CounterBridgePrecompile<Runtime>
+where
+	Runtime: pallet_counter::Config + pallet_evm::Config,
+	Runtime::AccountId: From<[u8; 32]>,
+{
+	fn transfer_to_substrate(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		ensure_direct_call(handle)?;
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let account: H256 = input.read()?;
+		let amount: U256 = input.read()?;
+
+		record_weight::<Runtime>(
+			handle,
+			<Runtime as pallet_counter::Config>::WeightInfo::transfer_to_substrate(),
+		)?;
+
+		let caller = handle.context().caller;
+		let dest = Runtime::AccountId::from(account.0);
+
+		let queued = frame_support::storage::with_storage_layer(|| {
+			pallet_counter::Pallet::<Runtime>::do_evm_to_substrate(caller, dest, amount)
+		})
+		.map_err(|e| revert(<&'static str>::from(e)))?;
+
+		let logs = LogsBuilder::new(handle.context().address);
+		let data = EvmDataWriter::new().write(amount).build();
+		match queued {
+			Some(id) => logs
+				.log4(
+					keccak256("TransferQueued(uint64,address,bytes32,uint256)"),
+					u256_topic(U256::from(id)),
+					caller,
+					account,
+					data,
+				)
+				.record(handle)?,
+			None => logs
+				.log3(
+					keccak256("TransferToSubstrate(address,bytes32,uint256)"),
+					caller,
+					account,
+					data,
+				)
+				.record(handle)?,
+		}
+
+		Ok(EvmDataWriter::new()
+			.write(queued.is_some())
+			.write(queued.unwrap_or_default())
+			.build())
+	}
+
+	fn locked_balance_of(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let account: H256 = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+
+		let locked: u128 = pallet_counter::Pallet::<Runtime>::locked_balance(
+			&Runtime::AccountId::from(account.0),
+		)
+		.unique_saturated_into();
+
+		Ok(EvmDataWriter::new().write(U256::from(locked)).build())
+	}
+
+	fn register_ipfs_hash(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		ensure_direct_call(handle)?;
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(4)?;
+		let cid: Bytes = input.read()?;
+		let tag: Bytes = input.read()?;
+		let attestors: Vec<H256> = input.read()?;
+		let signatures: Vec<Bytes> = input.read()?;
+
+		if attestors.len() != signatures.len() {
+			return Err(revert("attestors and signatures length mismatch"));
+		}
+
+		let attestations = attestors
+			.into_iter()
+			.zip(signatures)
+			.map(|(attestor, signature)| {
+				let signature = sr25519::Signature::try_from(signature.0.as_slice())
+					.map_err(|_| revert("invalid sr25519 signature"))?;
+				Ok((sr25519::Public::from_raw(attestor.0), signature))
+			})
+			.collect::<EvmResult<Vec<_>>>()?;
+
+		let ipfs_hash: pallet_counter::CidOf<Runtime> =
+			cid.0.clone().try_into().map_err(|_| revert("cid too long"))?;
+		let tag: Option<pallet_counter::IpfsTagOf<Runtime>> = if tag.0.is_empty() {
+			None
+		} else {
+			Some(tag.0.try_into().map_err(|_| revert("tag too long"))?)
+		};
+
+		record_weight::<Runtime>(
+			handle,
+			<Runtime as pallet_counter::Config>::WeightInfo::include_ipfs_hash(attestations.len() as u32),
+		)?;
+
+		let caller = handle.context().caller;
+		let owner = <Runtime as pallet_evm::Config>::AddressMapping::into_account_id(caller);
+
+		frame_support::storage::with_storage_layer(|| {
+			pallet_counter::Pallet::<Runtime>::do_include_ipfs_hash(owner, ipfs_hash, tag, &attestations)
+		})
+		.map_err(|e| revert(<&'static str>::from(e)))?;
+
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256("IpfsHashRegistered(address,bytes)"),
+				caller,
+				EvmDataWriter::new().write(cid).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+}
@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::mock::*;
+
+use frame_support::{
+	assert_ok,
+	traits::{Currency, OnRuntimeUpgrade, ReservableCurrency},
+};
+use pallet_counter::{limits::BridgeLimits, locks::LockRelease, BridgeDirection};
+use pallet_evm::GasWeightMapping;
+use precompile_utils::{testing::MockHandle, InsertRevertCode, REVERT_CODE};
+use sp_core::Pair;
+
+type Bridge = CounterBridgePrecompile<Test>;
+
+const ALICE: H160 = H160::repeat_byte(0xaa);
+const BOB: [u8; 32] = [2u8; 32];
+const CID: &[u8] = b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+fn input(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut input = selector.to_be_bytes().to_vec();
+	input.extend(args.build());
+	input
+}
+
+fn evm_account(address: H160) -> AccountId {
+	<Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn transfer(amount: u128) -> MockHandle {
+	MockHandle::new(
+		precompile_address(),
+		ALICE,
+		input(
+			SELECTOR_TRANSFER_TO_SUBSTRATE,
+			EvmDataWriter::new()
+				.write(H256(BOB))
+				.write(U256::from(amount)),
+		),
+	)
+}
+
+#[test]
+fn transfer_to_substrate_moves_funds_and_logs() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = transfer(400);
+
+		let output = Bridge::execute(&mut handle).unwrap().output;
+
+		assert_eq!(
+			output,
+			EvmDataWriter::new().write(false).write(0u64).build()
+		);
+		assert_eq!(Balances::free_balance(AccountId::from(BOB)), 400);
+		assert_eq!(Balances::free_balance(evm_account(ALICE)), 600);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("TransferToSubstrate(address,bytes32,uint256)"),
+				ALICE,
+				H256(BOB),
+				EvmDataWriter::new().write(U256::from(400)).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn transfer_to_substrate_above_the_threshold_is_queued() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(PalletCounter::set_bridge_limits(
+			RuntimeOrigin::root(),
+			BridgeDirection::EvmToSubstrate,
+			Some(BridgeLimits {
+				window: 100,
+				per_transfer_cap: 1_000,
+				per_account_cap: 1_000,
+				global_cap: 1_000,
+				delay_threshold: 100,
+				delay: 10,
+			}),
+		));
+		let mut handle = transfer(400);
+
+		let output = Bridge::execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(true).write(0u64).build());
+		assert_eq!(Balances::free_balance(AccountId::from(BOB)), 0);
+		assert_eq!(Balances::reserved_balance(evm_account(ALICE)), 400);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log4(
+				keccak256("TransferQueued(uint64,address,bytes32,uint256)"),
+				u256_topic(U256::zero()),
+				ALICE,
+				H256(BOB),
+				EvmDataWriter::new().write(U256::from(400)).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn transfer_to_substrate_charges_its_benchmarked_weight() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = transfer(400);
+
+		assert_ok!(Bridge::execute(&mut handle));
+
+		let weight = <() as pallet_counter::WeightInfo>::transfer_to_substrate();
+		let gas = <Test as pallet_evm::Config>::GasWeightMapping::weight_to_gas(weight);
+		assert!(handle.gas_used >= gas);
+	});
+}
+
+#[test]
+fn transfer_to_substrate_reverts_without_funds() {
+	new_test_ext().execute_with(|| {
+		let mut handle = transfer(400);
+
+		assert!(Bridge::execute(&mut handle).is_err());
+		assert_eq!(Balances::free_balance(AccountId::from(BOB)), 0);
+		assert!(handle.logs.is_empty());
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_delegatecall() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(0xcc);
+		Balances::make_free_balance_be(&evm_account(contract), 1_000);
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		let mut handle = transfer(400).delegated_from(contract);
+		assert_eq!(
+			Bridge::execute(&mut handle).unwrap_err(),
+			revert("cannot be called with DELEGATECALL or CALLCODE")
+		);
+
+		let register = input(
+			SELECTOR_REGISTER_IPFS_HASH,
+			EvmDataWriter::new()
+				.write(Bytes::from(CID))
+				.write(Bytes::from(""))
+				.write(Vec::<H256>::new())
+				.write(Vec::<Bytes>::new()),
+		);
+		let mut handle =
+			MockHandle::new(precompile_address(), ALICE, register).delegated_from(contract);
+		assert_eq!(
+			Bridge::execute(&mut handle).unwrap_err(),
+			revert("cannot be called with DELEGATECALL or CALLCODE")
+		);
+
+		assert_eq!(Balances::free_balance(AccountId::from(BOB)), 0);
+	});
+}
+
+#[test]
+fn transfer_to_substrate_rejects_static_and_payable_calls() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		assert!(Bridge::execute(&mut transfer(400).static_call()).is_err());
+		assert!(Bridge::execute(&mut transfer(400).with_value(U256::one())).is_err());
+		assert_eq!(Balances::free_balance(AccountId::from(BOB)), 0);
+	});
+}
+
+#[test]
+fn locked_balance_of_reads_the_locks_of_the_account() {
+	new_test_ext().execute_with(|| {
+		let bob = AccountId::from(BOB);
+		Balances::make_free_balance_be(&bob, 1_000);
+		assert_ok!(PalletCounter::lock(
+			RuntimeOrigin::signed(bob),
+			*b"lock0000",
+			300,
+			LockRelease::Anytime,
+			Default::default(),
+		));
+
+		let mut handle = MockHandle::new(
+			precompile_address(),
+			ALICE,
+			input(
+				SELECTOR_LOCKED_BALANCE_OF,
+				EvmDataWriter::new().write(H256(BOB)),
+			),
+		)
+		.static_call();
+
+		let output = Bridge::execute(&mut handle).unwrap().output;
+		assert_eq!(output, EvmDataWriter::new().write(U256::from(300)).build());
+	});
+}
+
+#[test]
+fn register_ipfs_hash_registers_to_the_mapped_caller() {
+	new_test_ext().execute_with(|| {
+		let attestor = sr25519::Pair::from_seed(&[9u8; 32]);
+		assert_ok!(PalletCounter::add_attestor(
+			RuntimeOrigin::root(),
+			attestor.public()
+		));
+		let owner = evm_account(ALICE);
+		let payload = PalletCounter::ipfs_attestation_payload(&owner, 0, CID);
+		let signature = attestor.sign(&payload);
+
+		let mut handle = MockHandle::new(
+			precompile_address(),
+			ALICE,
+			input(
+				SELECTOR_REGISTER_IPFS_HASH,
+				EvmDataWriter::new()
+					.write(Bytes::from(CID))
+					.write(Bytes::from(""))
+					.write(vec![H256(attestor.public().0)])
+					.write(vec![Bytes(signature.0.to_vec())]),
+			),
+		);
+
+		assert_ok!(Bridge::execute(&mut handle));
+
+		assert_eq!(
+			PalletCounter::ipfs_record(CID).map(|record| record.owner),
+			Some(owner)
+		);
+		assert_eq!(handle.logs.len(), 1);
+	});
+}
+
+#[test]
+fn insert_revert_code_runs_once() {
+	new_test_ext().execute_with(|| {
+		frame_support::parameter_types! {
+			pub Address: H160 = precompile_address();
+		}
+
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(precompile_address()),
+			REVERT_CODE.to_vec()
+		);
+		let account = evm_account(precompile_address());
+		assert_eq!(System::account(account).sufficients, 1);
+	});
+}
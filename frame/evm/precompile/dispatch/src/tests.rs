@@ -21,7 +21,8 @@ use super::*;
 use crate::mock::*;
 
 use fp_evm::Context;
-use frame_support::{assert_err, assert_ok};
+use frame_support::{assert_err, assert_ok, traits::OnRuntimeUpgrade};
+use precompile_utils::{InsertRevertCode, REVERT_CODE};
 use scale_codec::Encode;
 use sp_core::{H160, U256};
 use sp_runtime::BuildStorage;
@@ -129,3 +130,21 @@ fn dispatch_validator_works_well() {
 		);
 	});
 }
+
+#[test]
+fn insert_revert_code_runs_once() {
+	new_test_ext().execute_with(|| {
+		frame_support::parameter_types! {
+			pub Address: H160 = H160::from_low_u64_be(1043);
+		}
+
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(Address::get()),
+			REVERT_CODE.to_vec()
+		);
+		assert_eq!(System::account(Address::get()).sufficients, 1);
+	});
+}
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Precompile exposing `Referenda`, `ConvictionVoting` and `Democracy` to EVM callers.
+//!
+//! Every call is dispatched from the account `msg.sender` maps to through the runtime's
+//! `AddressMapping`, and each emits a log mirroring the pallet event. Tracks are named by
+//! their `u16` id; `submit` asks the runtime for the origin a track's proposals run with.
+//! Convictions are `0` (no lock, 0.1x votes) to `6` (32x lock, 6x votes).
+//!
+//! `referendumStatus` returns `0` for an unknown referendum, then `1` ongoing, `2`
+//! approved, `3` rejected, `4` cancelled, `5` timed out and `6` killed.
+//!
+//! ```solidity
+//! interface IGovernance {
+//!     event Voted(address indexed voter, uint32 indexed pollIndex, bool aye, uint256 amount, uint8 conviction);
+//!     event VoteRemoved(address indexed voter, uint32 indexed pollIndex);
+//!     event Delegated(address indexed who, address indexed target, uint16 trackId);
+//!     event Undelegated(address indexed who, uint16 trackId);
+//!     event Submitted(uint32 indexed referendumIndex, uint16 indexed trackId, bytes32 proposalHash);
+//!     event DecisionDepositPlaced(uint32 indexed referendumIndex, address indexed who, uint256 amount);
+//!     event DemocracyVoted(address indexed voter, uint32 indexed refIndex, bool aye, uint256 amount, uint8 conviction);
+//!     event DemocracyVoteRemoved(address indexed voter, uint32 indexed refIndex);
+//!
+//!     function vote(uint32 pollIndex, bool aye, uint256 amount, uint8 conviction) external;
+//!     function removeVote(uint32 pollIndex) external;
+//!     function removeVoteForTrack(uint16 trackId, uint32 pollIndex) external;
+//!     function delegate(uint16 trackId, address representative, uint8 conviction, uint256 amount) external;
+//!     function undelegate(uint16 trackId) external;
+//!
+//!     function submit(uint16 trackId, bytes32 preimageHash, uint32 preimageLength, uint32 enactmentDelay)
+//!         external returns (uint32 referendumIndex);
+//!     function placeDecisionDeposit(uint32 referendumIndex) external;
+//!     function referendumStatus(uint32 referendumIndex)
+//!         external view returns (uint8 status, uint16 trackId, bool decisionDepositPlaced);
+//!     function referendumTally(uint32 referendumIndex)
+//!         external view returns (uint256 ayes, uint256 nays, uint256 support);
+//!
+//!     function democracyVote(uint32 refIndex, bool aye, uint256 amount, uint8 conviction) external;
+//!     function democracyRemoveVote(uint32 refIndex) external;
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{Precompile, PrecompileHandle, PrecompileResult};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{schedule::DispatchTime, Bounded},
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use pallet_evm::AddressMapping;
+use pallet_referenda::{PalletsOriginOf, ReferendumInfo, ReferendumInfoFor, TrackIdOf};
+use precompile_utils::{
+	ensure_direct_call, ensure_non_payable, ensure_not_static, keccak256, record_db_reads, revert,
+	succeed, try_dispatch, Address, EvmDataReader, EvmDataWriter, EvmResult, LogsBuilder,
+};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::{Convert, StaticLookup};
+use sp_std::{boxed::Box, vec::Vec};
+
+/// `vote(uint32,bool,uint256,uint8)`
+pub const SELECTOR_VOTE: u32 = 0x35ee6e0e;
+/// `removeVote(uint32)`
+pub const SELECTOR_REMOVE_VOTE: u32 = 0x79cae220;
+/// `removeVoteForTrack(uint16,uint32)`
+pub const SELECTOR_REMOVE_VOTE_FOR_TRACK: u32 = 0xb8051cc8;
+/// `delegate(uint16,address,uint8,uint256)`
+pub const SELECTOR_DELEGATE: u32 = 0x681750e8;
+/// `undelegate(uint16)`
+pub const SELECTOR_UNDELEGATE: u32 = 0x98be4094;
+/// `submit(uint16,bytes32,uint32,uint32)`
+pub const SELECTOR_SUBMIT: u32 = 0x7c91184f;
+/// `placeDecisionDeposit(uint32)`
+pub const SELECTOR_PLACE_DECISION_DEPOSIT: u32 = 0x245ce18d;
+/// `referendumStatus(uint32)`
+pub const SELECTOR_REFERENDUM_STATUS: u32 = 0x8d407c0b;
+/// `referendumTally(uint32)`
+pub const SELECTOR_REFERENDUM_TALLY: u32 = 0x61b95999;
+/// `democracyVote(uint32,bool,uint256,uint8)`
+pub const SELECTOR_DEMOCRACY_VOTE: u32 = 0xacd2792d;
+/// `democracyRemoveVote(uint32)`
+pub const SELECTOR_DEMOCRACY_REMOVE_VOTE: u32 = 0xf527abc3;
+
+type VotingBalanceOf<Runtime> = pallet_conviction_voting::BalanceOf<Runtime>;
+type DemocracyBalanceOf<Runtime> = pallet_democracy::BalanceOf<Runtime>;
+type ClassOf<Runtime> = pallet_conviction_voting::ClassOf<Runtime>;
+type PollIndexOf<Runtime> = pallet_conviction_voting::PollIndexOf<Runtime>;
+
+/// Governance precompile; `TrackOrigin` gives the origin proposals of a track run with, or
+/// `None` for unknown tracks.
+pub struct GovernancePrecompile<Runtime, TrackOrigin>(PhantomData<(Runtime, TrackOrigin)>);
+
+impl<Runtime, TrackOrigin> Precompile for GovernancePrecompile<Runtime, TrackOrigin>
+where
+	Runtime: pallet_evm::Config
+		+ pallet_conviction_voting::Config
+		+ pallet_democracy::Config
+		+ pallet_referenda::Config<Tally = pallet_conviction_voting::TallyOf<Runtime>>,
+	TrackOrigin: Convert<u16, Option<PalletsOriginOf<Runtime>>>,
+	BlockNumberFor<Runtime>: From<u32>,
+	ClassOf<Runtime>: From<u16>,
+	PollIndexOf<Runtime>: From<u32>,
+	TrackIdOf<Runtime, ()>: Into<u16>,
+	VotingBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	DemocracyBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	pallet_referenda::BalanceOf<Runtime>: Into<u128>,
+	<Runtime as frame_system::Config>::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_conviction_voting::Call<Runtime>>
+		+ From<pallet_democracy::Call<Runtime>>
+		+ From<pallet_referenda::Call<Runtime>>,
+	<<Runtime as frame_system::Config>::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let selector = EvmDataReader::read_selector(handle.input())?;
+
+		let output = match selector {
+			SELECTOR_VOTE => Self::vote(handle)?,
+			SELECTOR_REMOVE_VOTE => Self::remove_vote(handle, false)?,
+			SELECTOR_REMOVE_VOTE_FOR_TRACK => Self::remove_vote(handle, true)?,
+			SELECTOR_DELEGATE => Self::delegate(handle)?,
+			SELECTOR_UNDELEGATE => Self::undelegate(handle)?,
+			SELECTOR_SUBMIT => Self::submit(handle)?,
+			SELECTOR_PLACE_DECISION_DEPOSIT => Self::place_decision_deposit(handle)?,
+			SELECTOR_REFERENDUM_STATUS => Self::referendum_status(handle)?,
+			SELECTOR_REFERENDUM_TALLY => Self::referendum_tally(handle)?,
+			SELECTOR_DEMOCRACY_VOTE => Self::democracy_vote(handle)?,
+			SELECTOR_DEMOCRACY_REMOVE_VOTE => Self::democracy_remove_vote(handle)?,
+			_ => return Err(revert("unknown selector")),
+		};
+
+		Ok(succeed(output))
+	}
+}
+
+impl<Runtime, TrackOrigin> GovernancePrecompile<Runtime, TrackOrigin>
+where
+	Runtime: pallet_evm::Config
+		+ pallet_conviction_voting::Config
+		+ pallet_democracy::Config
+		+ pallet_referenda::Config<Tally = pallet_conviction_voting::TallyOf<Runtime>>,
+	TrackOrigin: Convert<u16, Option<PalletsOriginOf<Runtime>>>,
+	BlockNumberFor<Runtime>: From<u32>,
+	ClassOf<Runtime>: From<u16>,
+	PollIndexOf<Runtime>: From<u32>,
+	TrackIdOf<Runtime, ()>: Into<u16>,
+	VotingBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	DemocracyBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	pallet_referenda::BalanceOf<Runtime>: Into<u128>,
+	<Runtime as frame_system::Config>::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_conviction_voting::Call<Runtime>>
+		+ From<pallet_democracy::Call<Runtime>>
+		+ From<pallet_referenda::Call<Runtime>>,
+	<<Runtime as frame_system::Config>::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn vote(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 4)?;
+		let poll_index: u32 = input.read()?;
+		let aye: bool = input.read()?;
+		let amount: U256 = input.read()?;
+		let conviction: u8 = input.read()?;
+
+		let vote = pallet_conviction_voting::AccountVote::Standard {
+			vote: pallet_conviction_voting::Vote {
+				aye,
+				conviction: pallet_conviction_voting::Conviction::try_from(conviction)
+					.map_err(|_| revert("unknown conviction"))?,
+			},
+			balance: Self::to_balance(amount)?,
+		};
+		Self::dispatch(
+			handle,
+			pallet_conviction_voting::Call::<Runtime>::vote {
+				poll_index: poll_index.into(),
+				vote,
+			},
+		)?;
+
+		let voter = handle.context().caller;
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("Voted(address,uint32,bool,uint256,uint8)"),
+				voter,
+				H256::from_low_u64_be(poll_index.into()),
+				EvmDataWriter::new()
+					.write(aye)
+					.write(amount)
+					.write(conviction)
+					.build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	/// `removeVote`, or `removeVoteForTrack` when `with_track` is set. The track can only be
+	/// left out while the poll is ongoing.
+	fn remove_vote(handle: &mut impl PrecompileHandle, with_track: bool) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, if with_track { 2 } else { 1 })?;
+		let class = if with_track {
+			Some(ClassOf::<Runtime>::from(input.read::<u16>()?))
+		} else {
+			None
+		};
+		let poll_index: u32 = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_conviction_voting::Call::<Runtime>::remove_vote {
+				class,
+				index: poll_index.into(),
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("VoteRemoved(address,uint32)"),
+				handle.context().caller,
+				H256::from_low_u64_be(poll_index.into()),
+				Vec::new(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn delegate(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 4)?;
+		let track: u16 = input.read()?;
+		let representative: Address = input.read()?;
+		let conviction: u8 = input.read()?;
+		let amount: U256 = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_conviction_voting::Call::<Runtime>::delegate {
+				class: track.into(),
+				to: Runtime::Lookup::unlookup(Self::account(representative.0)),
+				conviction: pallet_conviction_voting::Conviction::try_from(conviction)
+					.map_err(|_| revert("unknown conviction"))?,
+				balance: Self::to_balance(amount)?,
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("Delegated(address,address,uint16)"),
+				handle.context().caller,
+				representative.0,
+				EvmDataWriter::new().write(track).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn undelegate(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let track: u16 = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_conviction_voting::Call::<Runtime>::undelegate {
+				class: track.into(),
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256("Undelegated(address,uint16)"),
+				handle.context().caller,
+				EvmDataWriter::new().write(track).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	/// Submits the noted preimage `preimage_hash` on `track`, enacted `enactment_delay`
+	/// blocks after approval. Returns the new referendum's index.
+	fn submit(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 4)?;
+		let track: u16 = input.read()?;
+		let preimage_hash: H256 = input.read()?;
+		let preimage_length: u32 = input.read()?;
+		let enactment_delay: u32 = input.read()?;
+
+		let origin = TrackOrigin::convert(track).ok_or_else(|| revert("unknown track"))?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		let index = pallet_referenda::ReferendumCount::<Runtime>::get();
+		Self::dispatch(
+			handle,
+			pallet_referenda::Call::<Runtime>::submit {
+				proposal_origin: Box::new(origin),
+				proposal: Bounded::Lookup {
+					hash: preimage_hash,
+					len: preimage_length,
+				},
+				enactment_moment: DispatchTime::After(enactment_delay.into()),
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("Submitted(uint32,uint16,bytes32)"),
+				H256::from_low_u64_be(index.into()),
+				H256::from_low_u64_be(track.into()),
+				EvmDataWriter::new().write(preimage_hash).build(),
+			)
+			.record(handle)?;
+
+		Ok(EvmDataWriter::new().write(index).build())
+	}
+
+	fn place_decision_deposit(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let index: u32 = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_referenda::Call::<Runtime>::place_decision_deposit { index },
+		)?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		let amount: u128 = match ReferendumInfoFor::<Runtime>::get(index) {
+			Some(ReferendumInfo::Ongoing(status)) => status
+				.decision_deposit
+				.map(|deposit| deposit.amount.into())
+				.unwrap_or_default(),
+			_ => 0,
+		};
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("DecisionDepositPlaced(uint32,address,uint256)"),
+				H256::from_low_u64_be(index.into()),
+				handle.context().caller,
+				EvmDataWriter::new().write(U256::from(amount)).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn referendum_status(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let index: u32 = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		let (status, track, decision_deposit_placed) =
+			match ReferendumInfoFor::<Runtime>::get(index) {
+				None => (0u8, 0u16, false),
+				Some(ReferendumInfo::Ongoing(status)) => {
+					(1, status.track.into(), status.decision_deposit.is_some())
+				}
+				Some(ReferendumInfo::Approved(_, _, decision)) => (2, 0, decision.is_some()),
+				Some(ReferendumInfo::Rejected(_, _, decision)) => (3, 0, decision.is_some()),
+				Some(ReferendumInfo::Cancelled(_, _, decision)) => (4, 0, decision.is_some()),
+				Some(ReferendumInfo::TimedOut(_, _, decision)) => (5, 0, decision.is_some()),
+				Some(ReferendumInfo::Killed(_)) => (6, 0, false),
+			};
+		Ok(EvmDataWriter::new()
+			.write(status)
+			.write(track)
+			.write(decision_deposit_placed)
+			.build())
+	}
+
+	/// Tally of an ongoing referendum; finished referenda keep no tally.
+	fn referendum_tally(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let index: u32 = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 1)?;
+		let tally = match ReferendumInfoFor::<Runtime>::get(index) {
+			Some(ReferendumInfo::Ongoing(status)) => status.tally,
+			_ => return Err(revert("referendum is not ongoing")),
+		};
+		let (ayes, nays, support): (u128, u128, u128) =
+			(tally.ayes.into(), tally.nays.into(), tally.support.into());
+		Ok(EvmDataWriter::new()
+			.write(U256::from(ayes))
+			.write(U256::from(nays))
+			.write(U256::from(support))
+			.build())
+	}
+
+	fn democracy_vote(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 4)?;
+		let ref_index: u32 = input.read()?;
+		let aye: bool = input.read()?;
+		let amount: U256 = input.read()?;
+		let conviction: u8 = input.read()?;
+
+		let vote = pallet_democracy::AccountVote::Standard {
+			vote: pallet_democracy::Vote {
+				aye,
+				conviction: pallet_democracy::Conviction::try_from(conviction)
+					.map_err(|_| revert("unknown conviction"))?,
+			},
+			balance: u128::try_from(amount)
+				.ok()
+				.and_then(|amount| amount.try_into().ok())
+				.ok_or_else(|| revert("value is too large"))?,
+		};
+		Self::dispatch(
+			handle,
+			pallet_democracy::Call::<Runtime>::vote { ref_index, vote },
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("DemocracyVoted(address,uint32,bool,uint256,uint8)"),
+				handle.context().caller,
+				H256::from_low_u64_be(ref_index.into()),
+				EvmDataWriter::new()
+					.write(aye)
+					.write(amount)
+					.write(conviction)
+					.build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn democracy_remove_vote(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let index: u32 = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_democracy::Call::<Runtime>::remove_vote { index },
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("DemocracyVoteRemoved(address,uint32)"),
+				handle.context().caller,
+				H256::from_low_u64_be(index.into()),
+				Vec::new(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	/// Checks a state changing call and returns its `args` arguments.
+	fn mutating_input(handle: &impl PrecompileHandle, args: usize) -> EvmResult<EvmDataReader<'_>> {
+		ensure_direct_call(handle)?;
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+
+		let input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(args)?;
+		Ok(input)
+	}
+
+	fn dispatch(
+		handle: &mut impl PrecompileHandle,
+		call: impl Into<<Runtime as frame_system::Config>::RuntimeCall>,
+	) -> EvmResult {
+		let origin = frame_system::RawOrigin::Signed(Self::account(handle.context().caller));
+		try_dispatch::<Runtime>(handle, origin.into(), call.into())?;
+		Ok(())
+	}
+
+	fn account(address: H160) -> Runtime::AccountId {
+		Runtime::AddressMapping::into_account_id(address)
+	}
+
+	fn to_balance(value: U256) -> EvmResult<VotingBalanceOf<Runtime>> {
+		u128::try_from(value)
+			.ok()
+			.and_then(|value| value.try_into().ok())
+			.ok_or_else(|| revert("value is too large"))
+	}
+}
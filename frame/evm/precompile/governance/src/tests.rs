@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::mock::*;
+
+use frame_support::{
+	assert_ok,
+	traits::{Currency, OnRuntimeUpgrade, ReservableCurrency, StorePreimage},
+};
+use pallet_conviction_voting::{Voting, VotingFor};
+use precompile_utils::{testing::MockHandle, InsertRevertCode, REVERT_CODE};
+
+type Governance = GovernancePrecompile<Test, TrackOrigins>;
+
+const ALICE: H160 = H160::repeat_byte(0xaa);
+const BOB: H160 = H160::repeat_byte(0xbb);
+const PROPOSAL: H256 = H256::repeat_byte(0x11);
+
+fn input(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut input = selector.to_be_bytes().to_vec();
+	input.extend(args.build());
+	input
+}
+
+fn evm_account(address: H160) -> AccountId {
+	<Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn call(selector: u32, args: EvmDataWriter) -> MockHandle {
+	MockHandle::new(precompile_address(), ALICE, input(selector, args))
+}
+
+fn vote(poll_index: u32, amount: u128, conviction: u8) -> MockHandle {
+	call(
+		SELECTOR_VOTE,
+		EvmDataWriter::new()
+			.write(poll_index)
+			.write(true)
+			.write(U256::from(amount))
+			.write(conviction),
+	)
+}
+
+fn remove_vote(poll_index: u32) -> MockHandle {
+	call(SELECTOR_REMOVE_VOTE, EvmDataWriter::new().write(poll_index))
+}
+
+fn delegate(track: u16, representative: H160, amount: u128) -> MockHandle {
+	call(
+		SELECTOR_DELEGATE,
+		EvmDataWriter::new()
+			.write(track)
+			.write(Address(representative))
+			.write(1u8)
+			.write(U256::from(amount)),
+	)
+}
+
+fn undelegate(track: u16) -> MockHandle {
+	call(SELECTOR_UNDELEGATE, EvmDataWriter::new().write(track))
+}
+
+fn submit(track: u16) -> MockHandle {
+	call(
+		SELECTOR_SUBMIT,
+		EvmDataWriter::new()
+			.write(track)
+			.write(PROPOSAL)
+			.write(10u32)
+			.write(5u32),
+	)
+}
+
+fn place_decision_deposit(index: u32) -> MockHandle {
+	call(
+		SELECTOR_PLACE_DECISION_DEPOSIT,
+		EvmDataWriter::new().write(index),
+	)
+}
+
+fn democracy_vote(ref_index: u32, amount: u128) -> MockHandle {
+	call(
+		SELECTOR_DEMOCRACY_VOTE,
+		EvmDataWriter::new()
+			.write(ref_index)
+			.write(true)
+			.write(U256::from(amount))
+			.write(1u8),
+	)
+}
+
+fn view(selector: u32, index: u32) -> Vec<u8> {
+	let mut handle = call(selector, EvmDataWriter::new().write(index)).static_call();
+	Governance::execute(&mut handle).unwrap().output
+}
+
+/// Referendum 0 on track 0, submitted by `BOB`.
+fn new_referendum() {
+	Balances::make_free_balance_be(&evm_account(BOB), 1_000);
+	let mut handle = submit(0);
+	handle.context.caller = BOB;
+	assert_ok!(Governance::execute(&mut handle));
+}
+
+/// Democracy referendum 0, voting on a remark.
+fn new_democracy_referendum() {
+	let proposal = Preimage::bound(RuntimeCall::System(frame_system::Call::remark {
+		remark: Vec::new(),
+	}))
+	.unwrap();
+	Democracy::internal_start_referendum(
+		proposal,
+		pallet_democracy::VoteThreshold::SuperMajorityApprove,
+		0,
+	);
+}
+
+#[test]
+fn submit_opens_a_referendum_and_logs() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = submit(0);
+
+		let output = Governance::execute(&mut handle).unwrap().output;
+
+		assert_eq!(output, EvmDataWriter::new().write(0u32).build());
+		assert_eq!(Balances::reserved_balance(evm_account(ALICE)), 10);
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_STATUS, 0),
+			EvmDataWriter::new()
+				.write(1u8)
+				.write(0u16)
+				.write(false)
+				.build()
+		);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("Submitted(uint32,uint16,bytes32)"),
+				H256::zero(),
+				H256::zero(),
+				EvmDataWriter::new().write(PROPOSAL).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn submit_rejects_unknown_tracks() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		assert_eq!(
+			Governance::execute(&mut submit(1)).unwrap_err(),
+			revert("unknown track")
+		);
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_STATUS, 0),
+			EvmDataWriter::new()
+				.write(0u8)
+				.write(0u16)
+				.write(false)
+				.build()
+		);
+	});
+}
+
+#[test]
+fn place_decision_deposit_reserves_the_track_deposit_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_referendum();
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = place_decision_deposit(0);
+
+		assert_ok!(Governance::execute(&mut handle));
+
+		assert_eq!(Balances::reserved_balance(evm_account(ALICE)), 50);
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_STATUS, 0),
+			EvmDataWriter::new()
+				.write(1u8)
+				.write(0u16)
+				.write(true)
+				.build()
+		);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("DecisionDepositPlaced(uint32,address,uint256)"),
+				H256::zero(),
+				ALICE,
+				EvmDataWriter::new().write(U256::from(50)).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn vote_counts_towards_the_tally_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_referendum();
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = vote(0, 100, 1);
+
+		assert_ok!(Governance::execute(&mut handle));
+
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_TALLY, 0),
+			EvmDataWriter::new()
+				.write(U256::from(100))
+				.write(U256::zero())
+				.write(U256::from(100))
+				.build()
+		);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("Voted(address,uint32,bool,uint256,uint8)"),
+				ALICE,
+				H256::zero(),
+				EvmDataWriter::new()
+					.write(true)
+					.write(U256::from(100))
+					.write(1u8)
+					.build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn remove_vote_takes_the_vote_out_of_the_tally_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_referendum();
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Governance::execute(&mut vote(0, 100, 1)));
+		let mut handle = remove_vote(0);
+
+		assert_ok!(Governance::execute(&mut handle));
+
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_TALLY, 0),
+			EvmDataWriter::new()
+				.write(U256::zero())
+				.write(U256::zero())
+				.write(U256::zero())
+				.build()
+		);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("VoteRemoved(address,uint32)"),
+				ALICE,
+				H256::zero(),
+				Vec::new(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn delegate_and_undelegate_log() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = delegate(0, BOB, 100);
+
+		assert_ok!(Governance::execute(&mut handle));
+
+		assert!(matches!(
+			VotingFor::<Test>::get(evm_account(ALICE), 0),
+			Voting::Delegating(ref delegating) if delegating.target == evm_account(BOB)
+		));
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("Delegated(address,address,uint16)"),
+				ALICE,
+				BOB,
+				EvmDataWriter::new().write(0u16).build(),
+			)]
+		);
+
+		let mut handle = undelegate(0);
+		assert_ok!(Governance::execute(&mut handle));
+
+		assert!(matches!(
+			VotingFor::<Test>::get(evm_account(ALICE), 0),
+			Voting::Casting(_)
+		));
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log2(
+				keccak256("Undelegated(address,uint16)"),
+				ALICE,
+				EvmDataWriter::new().write(0u16).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn democracy_vote_counts_towards_the_tally_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_democracy_referendum();
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = democracy_vote(0, 100);
+
+		assert_ok!(Governance::execute(&mut handle));
+
+		assert_eq!(Democracy::referendum_status(0).unwrap().tally.ayes, 100);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("DemocracyVoted(address,uint32,bool,uint256,uint8)"),
+				ALICE,
+				H256::zero(),
+				EvmDataWriter::new()
+					.write(true)
+					.write(U256::from(100))
+					.write(1u8)
+					.build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_delegatecall() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(0xcc);
+		new_referendum();
+		new_democracy_referendum();
+		Balances::make_free_balance_be(&evm_account(contract), 1_000);
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		for handle in [
+			vote(0, 100, 1),
+			remove_vote(0),
+			call(
+				SELECTOR_REMOVE_VOTE_FOR_TRACK,
+				EvmDataWriter::new().write(0u16).write(0u32),
+			),
+			delegate(0, BOB, 100),
+			undelegate(0),
+			submit(0),
+			place_decision_deposit(0),
+			democracy_vote(0, 100),
+			call(
+				SELECTOR_DEMOCRACY_REMOVE_VOTE,
+				EvmDataWriter::new().write(0u32),
+			),
+		] {
+			assert_eq!(
+				Governance::execute(&mut handle.delegated_from(contract)).unwrap_err(),
+				revert("cannot be called with DELEGATECALL or CALLCODE")
+			);
+		}
+
+		assert_eq!(Balances::reserved_balance(evm_account(ALICE)), 0);
+		assert_eq!(Balances::reserved_balance(evm_account(contract)), 0);
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_STATUS, 1),
+			EvmDataWriter::new()
+				.write(0u8)
+				.write(0u16)
+				.write(false)
+				.build()
+		);
+		assert_eq!(Democracy::referendum_status(0).unwrap().tally.ayes, 0);
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_static_and_payable_calls() {
+	new_test_ext().execute_with(|| {
+		new_referendum();
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		assert!(Governance::execute(&mut vote(0, 100, 1).static_call()).is_err());
+		assert!(Governance::execute(&mut vote(0, 100, 1).with_value(U256::one())).is_err());
+		assert_eq!(
+			view(SELECTOR_REFERENDUM_TALLY, 0),
+			EvmDataWriter::new()
+				.write(U256::zero())
+				.write(U256::zero())
+				.write(U256::zero())
+				.build()
+		);
+	});
+}
+
+#[test]
+fn insert_revert_code_runs_once() {
+	new_test_ext().execute_with(|| {
+		frame_support::parameter_types! {
+			pub Address: H160 = precompile_address();
+		}
+
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(precompile_address()),
+			REVERT_CODE.to_vec()
+		);
+		assert_eq!(System::account(evm_account(precompile_address())).sufficients, 1);
+	});
+}
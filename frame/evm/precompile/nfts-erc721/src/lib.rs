@@ -0,0 +1,685 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! ERC-721 precompiles over the collections of `pallet_nfts`.
+//!
+//! Collection `id` is reachable at `{prefix}{12 zero bytes}{id}`, with `id` as a big-endian
+//! `u32`, and its items are the tokens of that contract. The address answers while the
+//! collection exists. [`InsertCodeOnCreate`] additionally gives it a small revert code when
+//! the collection is created, so Solidity's `extcodesize` check passes, and
+//! [`InsertCollectionCode`] does the same for the collections that existed before it.
+//! `force_create` gives its origin check no collection id, so collections created by root
+//! have no code until [`InsertCollectionCode`] next runs.
+//!
+//! Transfers and approvals are dispatched as `pallet_nfts` calls from the caller's account,
+//! so the pallet's checks apply: only the owner or an approved delegate can transfer, and
+//! only the owner can approve. `pallet_nfts` has no per-collection operators, so
+//! `setApprovalForAll` reverts and `isApprovedForAll` is always false.
+//!
+//! Substrate accounts cannot in general be turned back into EVM addresses. `ownerOf` reports
+//! the address given by the runtime's [`AccountAddress`], such as the one linked to the
+//! account, or else the address the account last used to reach the precompile, as long as
+//! the address still maps to the owner; otherwise it reverts. Likewise `getApproved` only
+//! knows approvals granted through `approve`.
+//!
+//! ```solidity
+//! interface IERC721Collection {
+//!     event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+//!     event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId);
+//!
+//!     function supportsInterface(bytes4 interfaceId) external view returns (bool);
+//!     function name() external view returns (string memory);
+//!     function symbol() external view returns (string memory);
+//!     function tokenURI(uint256 tokenId) external view returns (string memory);
+//!     function balanceOf(address owner) external view returns (uint256);
+//!     function ownerOf(uint256 tokenId) external view returns (address);
+//!     function transferFrom(address from, address to, uint256 tokenId) external;
+//!     function safeTransferFrom(address from, address to, uint256 tokenId) external;
+//!     function safeTransferFrom(address from, address to, uint256 tokenId, bytes calldata data) external;
+//!     function approve(address to, uint256 tokenId) external;
+//!     function getApproved(uint256 tokenId) external view returns (address);
+//!     function setApprovalForAll(address operator, bool approved) external;
+//!     function isApprovedForAll(address owner, address operator) external view returns (bool);
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{
+	Context, ExitReason, IsPrecompileResult, PrecompileHandle, PrecompileResult, PrecompileSet,
+};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	storage_alias,
+	traits::{
+		tokens::nonfungibles_v2::{Inspect, InspectEnumerable},
+		EnsureOriginWithArg, Get, OnRuntimeUpgrade,
+	},
+	weights::Weight,
+	Blake2_128Concat,
+};
+use pallet_evm::{AddressMapping, GasWeightMapping};
+use precompile_utils::{
+	ensure_direct_call, ensure_non_payable, ensure_not_static, insert_revert_code, keccak256,
+	record_db_reads, revert, succeed, try_dispatch, u256_topic, Address, Bytes, EvmDataReader,
+	EvmDataWriter, EvmResult, LogsBuilder, REVERT_CODE,
+};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::StaticLookup;
+use sp_std::vec::Vec;
+
+/// `supportsInterface(bytes4)`
+pub const SELECTOR_SUPPORTS_INTERFACE: u32 = 0x01ffc9a7;
+/// `name()`
+pub const SELECTOR_NAME: u32 = 0x06fdde03;
+/// `symbol()`
+pub const SELECTOR_SYMBOL: u32 = 0x95d89b41;
+/// `tokenURI(uint256)`
+pub const SELECTOR_TOKEN_URI: u32 = 0xc87b56dd;
+/// `balanceOf(address)`
+pub const SELECTOR_BALANCE_OF: u32 = 0x70a08231;
+/// `ownerOf(uint256)`
+pub const SELECTOR_OWNER_OF: u32 = 0x6352211e;
+/// `transferFrom(address,address,uint256)`
+pub const SELECTOR_TRANSFER_FROM: u32 = 0x23b872dd;
+/// `safeTransferFrom(address,address,uint256)`
+pub const SELECTOR_SAFE_TRANSFER_FROM: u32 = 0x42842e0e;
+/// `safeTransferFrom(address,address,uint256,bytes)`
+pub const SELECTOR_SAFE_TRANSFER_FROM_WITH_DATA: u32 = 0xb88d4fde;
+/// `approve(address,uint256)`
+pub const SELECTOR_APPROVE: u32 = 0x095ea7b3;
+/// `getApproved(uint256)`
+pub const SELECTOR_GET_APPROVED: u32 = 0x081812fc;
+/// `setApprovalForAll(address,bool)`
+pub const SELECTOR_SET_APPROVAL_FOR_ALL: u32 = 0xa22cb465;
+/// `isApprovedForAll(address,address)`
+pub const SELECTOR_IS_APPROVED_FOR_ALL: u32 = 0xe985e9c5;
+/// `onERC721Received(address,address,uint256,bytes)`, also its expected return value.
+pub const SELECTOR_ON_ERC721_RECEIVED: u32 = 0x150b7a02;
+
+/// ERC-165 interface ids the collections implement.
+const INTERFACE_ERC165: u32 = 0x01ffc9a7;
+const INTERFACE_ERC721: u32 = 0x80ac58cd;
+const INTERFACE_ERC721_METADATA: u32 = 0x5b5e139f;
+
+/// Collection attribute read by `name()`.
+pub const NAME_ATTRIBUTE: &[u8] = b"name";
+/// Collection attribute read by `symbol()`.
+pub const SYMBOL_ATTRIBUTE: &[u8] = b"symbol";
+
+/// EVM address each account last used to reach a collection.
+#[storage_alias]
+pub type EvmAddresses<T: frame_system::Config> = StorageMap<
+	Erc721Nfts,
+	Blake2_128Concat,
+	<T as frame_system::Config>::AccountId,
+	H160,
+	OptionQuery,
+>;
+
+/// Address approved through `approve` for an item, with the owner that approved it.
+#[storage_alias]
+pub type Approvals<T: frame_system::Config> = StorageDoubleMap<
+	Erc721Nfts,
+	Blake2_128Concat,
+	H160,
+	Blake2_128Concat,
+	u32,
+	(H160, <T as frame_system::Config>::AccountId),
+	OptionQuery,
+>;
+
+/// EVM address of a Substrate account, where the runtime knows one.
+pub trait AccountAddress<AccountId> {
+	fn address_of(account: &AccountId) -> Option<H160>;
+}
+
+impl<AccountId> AccountAddress<AccountId> for () {
+	fn address_of(_: &AccountId) -> Option<H160> {
+		None
+	}
+}
+
+type CollectionIdOf<Runtime> = <Runtime as pallet_nfts::Config>::CollectionId;
+type ItemIdOf<Runtime> = <Runtime as pallet_nfts::Config>::ItemId;
+
+/// Address of collection `id` under `prefix`.
+pub fn collection_address(prefix: u32, id: u32) -> H160 {
+	let mut address = [0u8; 20];
+	address[..4].copy_from_slice(&prefix.to_be_bytes());
+	address[16..].copy_from_slice(&id.to_be_bytes());
+	H160(address)
+}
+
+/// Collection id encoded in `address`, if it lies under `prefix`.
+pub fn collection_id(prefix: u32, address: H160) -> Option<u32> {
+	let bytes = address.as_bytes();
+	if bytes[..4] != prefix.to_be_bytes() || bytes[4..16].iter().any(|b| *b != 0) {
+		return None;
+	}
+	let mut id = [0u8; 4];
+	id.copy_from_slice(&bytes[16..]);
+	Some(u32::from_be_bytes(id))
+}
+
+/// Address reported for `account`, see the crate documentation.
+pub fn address_of<Runtime, Addresses>(account: &Runtime::AccountId) -> Option<H160>
+where
+	Runtime: pallet_evm::Config,
+	Addresses: AccountAddress<Runtime::AccountId>,
+{
+	let maps_to_account =
+		|address: &H160| Runtime::AddressMapping::into_account_id(*address) == *account;
+	Addresses::address_of(account)
+		.filter(maps_to_account)
+		.or_else(|| EvmAddresses::<Runtime>::get(account).filter(maps_to_account))
+}
+
+pub struct Erc721NftsPrecompileSet<Runtime, Prefix, Addresses = ()>(
+	PhantomData<(Runtime, Prefix, Addresses)>,
+);
+
+impl<Runtime, Prefix, Addresses> Erc721NftsPrecompileSet<Runtime, Prefix, Addresses> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<Runtime, Prefix, Addresses> PrecompileSet
+	for Erc721NftsPrecompileSet<Runtime, Prefix, Addresses>
+where
+	Runtime: pallet_evm::Config + pallet_nfts::Config,
+	Prefix: Get<u32>,
+	Addresses: AccountAddress<Runtime::AccountId>,
+	CollectionIdOf<Runtime>: From<u32> + Into<u32> + Copy,
+	ItemIdOf<Runtime>: From<u32> + Into<u32> + Copy,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_nfts::Call<Runtime>>,
+	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+		let address = handle.code_address();
+		let id: CollectionIdOf<Runtime> = collection_id(Prefix::get(), address)?.into();
+		if <pallet_nfts::Pallet<Runtime> as Inspect<Runtime::AccountId>>::collection_owner(&id)
+			.is_none()
+		{
+			return None;
+		}
+		Some(Erc721Collection::<Runtime, Addresses>::execute(handle, id))
+	}
+
+	fn is_precompile(&self, address: H160, _gas: u64) -> IsPrecompileResult {
+		match collection_id(Prefix::get(), address) {
+			// Only addresses under the prefix pay for the collection lookup.
+			Some(id) => IsPrecompileResult::Answer {
+				is_precompile:
+					<pallet_nfts::Pallet<Runtime> as Inspect<Runtime::AccountId>>::collection_owner(
+						&id.into(),
+					)
+					.is_some(),
+				extra_cost: Runtime::GasWeightMapping::weight_to_gas(
+					Runtime::DbWeight::get().reads(1),
+				),
+			},
+			None => IsPrecompileResult::Answer {
+				is_precompile: false,
+				extra_cost: 0,
+			},
+		}
+	}
+}
+
+/// The ERC-721 view of one collection.
+struct Erc721Collection<Runtime, Addresses>(PhantomData<(Runtime, Addresses)>);
+
+impl<Runtime, Addresses> Erc721Collection<Runtime, Addresses>
+where
+	Runtime: pallet_evm::Config + pallet_nfts::Config,
+	Addresses: AccountAddress<Runtime::AccountId>,
+	CollectionIdOf<Runtime>: From<u32> + Into<u32> + Copy,
+	ItemIdOf<Runtime>: From<u32> + Into<u32> + Copy,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_nfts::Call<Runtime>>,
+	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn execute(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+	) -> PrecompileResult {
+		let selector = EvmDataReader::read_selector(handle.input())?;
+
+		let output = match selector {
+			SELECTOR_SUPPORTS_INTERFACE => Self::supports_interface(handle)?,
+			SELECTOR_NAME => Self::collection_string(handle, collection, NAME_ATTRIBUTE)?,
+			SELECTOR_SYMBOL => Self::collection_string(handle, collection, SYMBOL_ATTRIBUTE)?,
+			SELECTOR_TOKEN_URI => Self::token_uri(handle, collection)?,
+			SELECTOR_BALANCE_OF => Self::balance_of(handle, collection)?,
+			SELECTOR_OWNER_OF => Self::owner_of(handle, collection)?,
+			SELECTOR_TRANSFER_FROM => Self::transfer_from(handle, collection, None)?,
+			SELECTOR_SAFE_TRANSFER_FROM => {
+				Self::transfer_from(handle, collection, Some(Vec::new()))?
+			}
+			SELECTOR_SAFE_TRANSFER_FROM_WITH_DATA => {
+				let data = {
+					let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+					input.expect_arguments(4)?;
+					input.read::<Address>()?;
+					input.read::<Address>()?;
+					input.read::<U256>()?;
+					input.read::<Bytes>()?.0
+				};
+				Self::transfer_from(handle, collection, Some(data))?
+			}
+			SELECTOR_APPROVE => Self::approve(handle, collection)?,
+			SELECTOR_GET_APPROVED => Self::get_approved(handle, collection)?,
+			SELECTOR_SET_APPROVAL_FOR_ALL => return Err(revert("operators are not supported")),
+			SELECTOR_IS_APPROVED_FOR_ALL => {
+				let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+				input.expect_arguments(2)?;
+				EvmDataWriter::new().write(false).build()
+			}
+			_ => return Err(revert("unknown selector")),
+		};
+
+		Ok(succeed(output))
+	}
+
+	fn supports_interface(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let interface: H256 = input.read()?;
+
+		let mut id = [0u8; 4];
+		id.copy_from_slice(&interface.as_bytes()[..4]);
+		let supported = matches!(
+			u32::from_be_bytes(id),
+			INTERFACE_ERC165 | INTERFACE_ERC721 | INTERFACE_ERC721_METADATA
+		);
+		Ok(EvmDataWriter::new().write(supported).build())
+	}
+
+	fn collection_string(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+		key: &[u8],
+	) -> EvmResult<Vec<u8>> {
+		EvmDataReader::new_skip_selector(handle.input())?.expect_arguments(0)?;
+		record_db_reads::<Runtime>(handle, 1)?;
+		let value =
+			<pallet_nfts::Pallet<Runtime> as Inspect<Runtime::AccountId>>::collection_attribute(
+				&collection,
+				key,
+			)
+			.unwrap_or_default();
+		Ok(EvmDataWriter::new().write(Bytes(value)).build())
+	}
+
+	/// The item's metadata, or the collection's metadata followed by the token id when the
+	/// item has none.
+	fn token_uri(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+	) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let token_id: U256 = input.read()?;
+
+		let item = Self::item(token_id)?;
+		record_db_reads::<Runtime>(handle, 2)?;
+		Self::owner(collection, item)?;
+
+		let uri = match <pallet_nfts::Pallet<Runtime> as Inspect<Runtime::AccountId>>::attribute(
+			&collection,
+			&item,
+			&[],
+		) {
+			Some(metadata) if !metadata.is_empty() => metadata,
+			_ => {
+				record_db_reads::<Runtime>(handle, 1)?;
+				let mut base =
+					<pallet_nfts::Pallet<Runtime> as Inspect<Runtime::AccountId>>::collection_attribute(
+						&collection,
+						&[],
+					)
+					.unwrap_or_default();
+				if !base.is_empty() {
+					let item: u32 = item.into();
+					base.extend_from_slice(u32_to_decimal(item).as_slice());
+				}
+				base
+			}
+		};
+		Ok(EvmDataWriter::new().write(Bytes(uri)).build())
+	}
+
+	fn balance_of(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+	) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let owner: Address = input.read()?;
+
+		if owner.0.is_zero() {
+			return Err(revert("zero address is not a valid owner"));
+		}
+
+		// `pallet_nfts` keeps no per-collection count, so every owned item is a read.
+		let who = Self::account(owner.0);
+		let mut balance = 0u64;
+		record_db_reads::<Runtime>(handle, 1)?;
+		for _ in <pallet_nfts::Pallet<Runtime> as InspectEnumerable<Runtime::AccountId>>::owned_in_collection(
+			&collection,
+			&who,
+		) {
+			record_db_reads::<Runtime>(handle, 1)?;
+			balance += 1;
+		}
+		Ok(EvmDataWriter::new().write(U256::from(balance)).build())
+	}
+
+	fn owner_of(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+	) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let token_id: U256 = input.read()?;
+
+		// The item, then both places the owner's address may be known.
+		record_db_reads::<Runtime>(handle, 4)?;
+		let owner = Self::owner(collection, Self::item(token_id)?)?;
+		let address = address_of::<Runtime, Addresses>(&owner)
+			.ok_or_else(|| revert("owner has no known EVM address"))?;
+		Ok(EvmDataWriter::new().write(Address(address)).build())
+	}
+
+	/// `transferFrom`, or `safeTransferFrom` when `data` is given.
+	fn transfer_from(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+		data: Option<Vec<u8>>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		let (from, to, token_id) = {
+			input.expect_arguments(3)?;
+			let from: Address = input.read()?;
+			let to: Address = input.read()?;
+			let token_id: U256 = input.read()?;
+			(from.0, to.0, token_id)
+		};
+		let item = Self::item(token_id)?;
+
+		if to.is_zero() {
+			return Err(revert("transfer to the zero address"));
+		}
+		record_db_reads::<Runtime>(handle, 1)?;
+		if Self::owner(collection, item)? != Self::account(from) {
+			return Err(revert("transfer from incorrect owner"));
+		}
+
+		let operator = handle.context().caller;
+		Self::dispatch(
+			handle,
+			operator,
+			pallet_nfts::Call::<Runtime>::transfer {
+				collection,
+				item,
+				dest: Self::lookup(to),
+			},
+		)?;
+		Approvals::<Runtime>::remove(handle.code_address(), item.into());
+		EvmAddresses::<Runtime>::insert(Self::account(to), to);
+
+		LogsBuilder::new(handle.code_address())
+			.log4(
+				keccak256("Transfer(address,address,uint256)"),
+				from,
+				to,
+				u256_topic(token_id),
+				Vec::new(),
+			)
+			.record(handle)?;
+
+		if let Some(data) = data {
+			Self::check_receiver(handle, operator, from, to, token_id, data)?;
+		}
+
+		Ok(Vec::new())
+	}
+
+	fn approve(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+	) -> EvmResult<Vec<u8>> {
+		Self::ensure_mutable(handle)?;
+
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let approved: Address = input.read()?;
+		let token_id: U256 = input.read()?;
+		let item = Self::item(token_id)?;
+
+		// ERC-721 keeps a single approval per token, so earlier ones are dropped first.
+		let owner = handle.context().caller;
+		Self::dispatch(
+			handle,
+			owner,
+			pallet_nfts::Call::<Runtime>::clear_all_transfer_approvals { collection, item },
+		)?;
+		if approved.0.is_zero() {
+			Approvals::<Runtime>::remove(handle.code_address(), item.into());
+		} else {
+			Self::dispatch(
+				handle,
+				owner,
+				pallet_nfts::Call::<Runtime>::approve_transfer {
+					collection,
+					item,
+					delegate: Self::lookup(approved.0),
+					maybe_deadline: None,
+				},
+			)?;
+			Approvals::<Runtime>::insert(
+				handle.code_address(),
+				item.into(),
+				(approved.0, Self::account(owner)),
+			);
+		}
+
+		LogsBuilder::new(handle.code_address())
+			.log4(
+				keccak256("Approval(address,address,uint256)"),
+				owner,
+				approved.0,
+				u256_topic(token_id),
+				Vec::new(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn get_approved(
+		handle: &mut impl PrecompileHandle,
+		collection: CollectionIdOf<Runtime>,
+	) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let token_id: U256 = input.read()?;
+		let item = Self::item(token_id)?;
+
+		record_db_reads::<Runtime>(handle, 3)?;
+		let owner = Self::owner(collection, item)?;
+		// An approval recorded for an earlier owner was cleared by the transfer.
+		let approved = Approvals::<Runtime>::get(handle.code_address(), item.into())
+			.filter(|(_, approver)| *approver == owner)
+			.map(|(approved, _)| approved)
+			.unwrap_or_default();
+		Ok(EvmDataWriter::new().write(Address(approved)).build())
+	}
+
+	/// Runs `onERC721Received` on `to` if it holds code.
+	fn check_receiver(
+		handle: &mut impl PrecompileHandle,
+		operator: H160,
+		from: H160,
+		to: H160,
+		token_id: U256,
+		data: Vec<u8>,
+	) -> EvmResult {
+		record_db_reads::<Runtime>(handle, 1)?;
+		if pallet_evm::AccountCodes::<Runtime>::decode_len(to).unwrap_or(0) == 0 {
+			return Ok(());
+		}
+
+		let mut call = SELECTOR_ON_ERC721_RECEIVED.to_be_bytes().to_vec();
+		call.extend(
+			EvmDataWriter::new()
+				.write(Address(operator))
+				.write(Address(from))
+				.write(token_id)
+				.write(Bytes(data))
+				.build(),
+		);
+		let context = Context {
+			address: to,
+			caller: handle.code_address(),
+			apparent_value: U256::zero(),
+		};
+		let (reason, output) = handle.call(to, None, call, None, false, &context);
+
+		let accepted = matches!(reason, ExitReason::Succeed(_))
+			&& output.len() >= 4
+			&& output[..4] == SELECTOR_ON_ERC721_RECEIVED.to_be_bytes();
+		if !accepted {
+			return Err(revert("transfer to non ERC721Receiver implementer"));
+		}
+		Ok(())
+	}
+
+	fn dispatch(
+		handle: &mut impl PrecompileHandle,
+		origin: H160,
+		call: pallet_nfts::Call<Runtime>,
+	) -> EvmResult {
+		let account = Self::account(origin);
+		EvmAddresses::<Runtime>::insert(&account, origin);
+		try_dispatch::<Runtime>(
+			handle,
+			frame_system::RawOrigin::Signed(account).into(),
+			call.into(),
+		)?;
+		Ok(())
+	}
+
+	fn ensure_mutable(handle: &impl PrecompileHandle) -> EvmResult {
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+		ensure_direct_call(handle)
+	}
+
+	fn owner(
+		collection: CollectionIdOf<Runtime>,
+		item: ItemIdOf<Runtime>,
+	) -> EvmResult<Runtime::AccountId> {
+		<pallet_nfts::Pallet<Runtime> as Inspect<Runtime::AccountId>>::owner(&collection, &item)
+			.ok_or_else(|| revert("invalid token ID"))
+	}
+
+	fn item(token_id: U256) -> EvmResult<ItemIdOf<Runtime>> {
+		u32::try_from(token_id)
+			.map(Into::into)
+			.map_err(|_| revert("invalid token ID"))
+	}
+
+	fn account(address: H160) -> Runtime::AccountId {
+		Runtime::AddressMapping::into_account_id(address)
+	}
+
+	fn lookup(address: H160) -> <Runtime::Lookup as StaticLookup>::Source {
+		Runtime::Lookup::unlookup(Self::account(address))
+	}
+}
+
+fn u32_to_decimal(mut value: u32) -> Vec<u8> {
+	let mut digits = Vec::new();
+	loop {
+		digits.push(b'0' + (value % 10) as u8);
+		value /= 10;
+		if value == 0 {
+			break;
+		}
+	}
+	digits.reverse();
+	digits
+}
+
+/// `pallet_nfts::Config::CreateOrigin` that leaves the decision to `Inner` and stores
+/// [`REVERT_CODE`] at the address of the collection being created.
+///
+/// `create` is transactional, so the code goes away again if the creation fails.
+pub struct InsertCodeOnCreate<Inner, Runtime, Prefix>(PhantomData<(Inner, Runtime, Prefix)>);
+
+impl<Origin, Inner, Runtime, Prefix, CollectionId> EnsureOriginWithArg<Origin, CollectionId>
+	for InsertCodeOnCreate<Inner, Runtime, Prefix>
+where
+	Inner: EnsureOriginWithArg<Origin, CollectionId>,
+	Runtime: pallet_evm::Config,
+	Prefix: Get<u32>,
+	CollectionId: Copy + Into<u32>,
+{
+	type Success = Inner::Success;
+
+	fn try_origin(origin: Origin, collection: &CollectionId) -> Result<Self::Success, Origin> {
+		let success = Inner::try_origin(origin, collection)?;
+		pallet_evm::Pallet::<Runtime>::create_account(
+			collection_address(Prefix::get(), (*collection).into()),
+			REVERT_CODE.to_vec(),
+		);
+		Ok(success)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn try_successful_origin(collection: &CollectionId) -> Result<Origin, ()> {
+		Inner::try_successful_origin(collection)
+	}
+}
+
+/// Stores [`REVERT_CODE`] at the address of every collection created before
+/// [`InsertCodeOnCreate`] was installed, or by root. Collections that already have code are
+/// left alone.
+pub struct InsertCollectionCode<Runtime, Prefix>(PhantomData<(Runtime, Prefix)>);
+
+impl<Runtime, Prefix> OnRuntimeUpgrade for InsertCollectionCode<Runtime, Prefix>
+where
+	Runtime: pallet_evm::Config + pallet_nfts::Config,
+	Prefix: Get<u32>,
+	CollectionIdOf<Runtime>: Into<u32>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		let prefix = Prefix::get();
+		let mut weight = Weight::zero();
+		for id in
+			<pallet_nfts::Pallet<Runtime> as InspectEnumerable<Runtime::AccountId>>::collections()
+		{
+			let address = collection_address(prefix, id.into());
+			// The collection id, then the code if it is missing.
+			weight = weight
+				.saturating_add(Runtime::DbWeight::get().reads(1))
+				.saturating_add(insert_revert_code::<Runtime>(address));
+		}
+		weight
+	}
+}
@@ -0,0 +1,527 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::mock::*;
+
+use fp_evm::{ExitRevert, ExitSucceed};
+use frame_support::{assert_ok, traits::Currency};
+use pallet_nfts::{CollectionConfig, ItemConfig};
+use precompile_utils::testing::{MockHandle, Subcall};
+
+type Nfts721 = Erc721NftsPrecompileSet<Test, NftsPrefix, LinkedAddresses>;
+
+const ALICE: H160 = H160::repeat_byte(0xaa);
+const BOB: H160 = H160::repeat_byte(0xbb);
+/// A contract holding code, so transfers to it ask `onERC721Received`.
+const RECEIVER: H160 = H160::repeat_byte(0xee);
+
+fn input(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut input = selector.to_be_bytes().to_vec();
+	input.extend(args.build());
+	input
+}
+
+fn evm_account(address: H160) -> AccountId {
+	<Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn execute(handle: &mut MockHandle) -> PrecompileResult {
+	Nfts721::new()
+		.execute(handle)
+		.expect("the collection exists")
+}
+
+fn call(caller: H160, selector: u32, args: EvmDataWriter) -> MockHandle {
+	MockHandle::new(collection(0), caller, input(selector, args))
+}
+
+fn config() -> CollectionConfig<Balance, u64, u32> {
+	CollectionConfig {
+		settings: Default::default(),
+		max_supply: None,
+		mint_settings: Default::default(),
+	}
+}
+
+/// Collection 0, created by `owner` through `create`, with `items` minted to their holders.
+fn new_collection(owner: AccountId, items: &[(u32, AccountId)]) {
+	Balances::make_free_balance_be(&owner, 1_000);
+	assert_ok!(Nfts::create(
+		RuntimeOrigin::signed(owner.clone()),
+		owner,
+		config()
+	));
+	for (item, holder) in items {
+		Balances::make_free_balance_be(holder, 1_000);
+		assert_ok!(Nfts::force_mint(
+			RuntimeOrigin::root(),
+			0,
+			*item,
+			holder.clone(),
+			ItemConfig::default(),
+		));
+	}
+}
+
+fn owner_of(item: u32) -> EvmResult<H160> {
+	let mut handle = call(
+		BOB,
+		SELECTOR_OWNER_OF,
+		EvmDataWriter::new().write(U256::from(item)),
+	)
+	.static_call();
+	let output = execute(&mut handle)?.output;
+	Ok(EvmDataReader::new(&output).read::<Address>()?.0)
+}
+
+fn transfer_from(caller: H160, from: H160, to: H160, item: u32) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_TRANSFER_FROM,
+		EvmDataWriter::new()
+			.write(Address(from))
+			.write(Address(to))
+			.write(U256::from(item)),
+	)
+}
+
+fn safe_transfer_from(caller: H160, from: H160, to: H160, item: u32, data: &[u8]) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_SAFE_TRANSFER_FROM_WITH_DATA,
+		EvmDataWriter::new()
+			.write(Address(from))
+			.write(Address(to))
+			.write(U256::from(item))
+			.write(Bytes(data.to_vec())),
+	)
+}
+
+/// Input of the `onERC721Received` call a safe transfer makes.
+fn on_received(operator: H160, from: H160, item: u32, data: &[u8]) -> Vec<u8> {
+	input(
+		SELECTOR_ON_ERC721_RECEIVED,
+		EvmDataWriter::new()
+			.write(Address(operator))
+			.write(Address(from))
+			.write(U256::from(item))
+			.write(Bytes(data.to_vec())),
+	)
+}
+
+fn token_uri(item: u32) -> EvmResult<Vec<u8>> {
+	let mut handle = call(
+		BOB,
+		SELECTOR_TOKEN_URI,
+		EvmDataWriter::new().write(U256::from(item)),
+	)
+	.static_call();
+	Ok(execute(&mut handle)?.output)
+}
+
+fn approve(caller: H160, approved: H160, item: u32) -> MockHandle {
+	call(
+		caller,
+		SELECTOR_APPROVE,
+		EvmDataWriter::new()
+			.write(Address(approved))
+			.write(U256::from(item)),
+	)
+}
+
+#[test]
+fn addresses_round_trip() {
+	let prefix = 0xffff_fffd;
+	let address = collection_address(prefix, 0x0102_0304);
+	assert_eq!(collection_id(prefix, address), Some(0x0102_0304));
+	assert_eq!(collection_id(0xffff_ffff, address), None);
+}
+
+#[test]
+fn token_ids_render_in_decimal() {
+	assert_eq!(u32_to_decimal(0), b"0".to_vec());
+	assert_eq!(u32_to_decimal(4_294_967_295), b"4294967295".to_vec());
+}
+
+#[test]
+fn create_stores_the_revert_code() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[]);
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(collection(0)),
+			REVERT_CODE.to_vec()
+		);
+	});
+}
+
+#[test]
+fn calls_do_not_store_code() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nfts::force_create(
+			RuntimeOrigin::root(),
+			evm_account(ALICE),
+			config()
+		));
+
+		let mut handle = call(
+			ALICE,
+			SELECTOR_SUPPORTS_INTERFACE,
+			EvmDataWriter::new().write(H256::from_low_u64_be(0)),
+		);
+		assert_ok!(execute(&mut handle));
+
+		assert!(!pallet_evm::AccountCodes::<Test>::contains_key(collection(
+			0
+		)));
+	});
+}
+
+#[test]
+fn insert_collection_code_fills_in_missing_code_once() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nfts::force_create(
+			RuntimeOrigin::root(),
+			evm_account(ALICE),
+			config()
+		));
+
+		InsertCollectionCode::<Test, NftsPrefix>::on_runtime_upgrade();
+		InsertCollectionCode::<Test, NftsPrefix>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(collection(0)),
+			REVERT_CODE.to_vec()
+		);
+		assert_eq!(System::account(evm_account(collection(0))).sufficients, 1);
+	});
+}
+
+#[test]
+fn owner_of_reports_addresses_that_map_to_the_owner() {
+	new_test_ext().execute_with(|| {
+		new_collection(
+			evm_account(ALICE),
+			&[
+				(1, evm_account(ALICE)),
+				(2, evm_account(LINKED)),
+				(3, AccountId::from(STALE)),
+			],
+		);
+
+		// Nothing ties ALICE's account to an address until it uses the precompile.
+		assert_eq!(owner_of(1), Err(revert("owner has no known EVM address")));
+		assert_ok!(execute(&mut approve(ALICE, BOB, 1)));
+		assert_eq!(owner_of(1), Ok(ALICE));
+
+		assert_eq!(owner_of(2), Ok(LINKED));
+		assert_eq!(owner_of(3), Err(revert("owner has no known EVM address")));
+		assert_eq!(owner_of(4), Err(revert("invalid token ID")));
+	});
+}
+
+#[test]
+fn transfer_from_moves_the_item_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+		let mut handle = transfer_from(ALICE, ALICE, BOB, 1);
+
+		assert_ok!(execute(&mut handle));
+
+		assert_eq!(
+			<Nfts as Inspect<AccountId>>::owner(&0, &1),
+			Some(evm_account(BOB))
+		);
+		assert_eq!(owner_of(1), Ok(BOB));
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(collection(0)).log4(
+				keccak256("Transfer(address,address,uint256)"),
+				ALICE,
+				BOB,
+				u256_topic(U256::one()),
+				Vec::new(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn transfer_from_checks_owner_and_caller() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+
+		assert_eq!(
+			execute(&mut transfer_from(BOB, BOB, ALICE, 1)).unwrap_err(),
+			revert("transfer from incorrect owner")
+		);
+		assert!(execute(&mut transfer_from(BOB, ALICE, BOB, 1)).is_err());
+		assert_eq!(
+			<Nfts as Inspect<AccountId>>::owner(&0, &1),
+			Some(evm_account(ALICE))
+		);
+	});
+}
+
+#[test]
+fn approved_address_can_transfer() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+		let mut handle = approve(ALICE, BOB, 1);
+
+		assert_ok!(execute(&mut handle));
+
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(collection(0)).log4(
+				keccak256("Approval(address,address,uint256)"),
+				ALICE,
+				BOB,
+				u256_topic(U256::one()),
+				Vec::new(),
+			)]
+		);
+		let mut get_approved = call(
+			ALICE,
+			SELECTOR_GET_APPROVED,
+			EvmDataWriter::new().write(U256::one()),
+		)
+		.static_call();
+		assert_eq!(
+			execute(&mut get_approved).unwrap().output,
+			EvmDataWriter::new().write(Address(BOB)).build()
+		);
+
+		assert_ok!(execute(&mut transfer_from(BOB, ALICE, BOB, 1)));
+		assert_eq!(
+			<Nfts as Inspect<AccountId>>::owner(&0, &1),
+			Some(evm_account(BOB))
+		);
+	});
+}
+
+#[test]
+fn balance_of_counts_owned_items() {
+	new_test_ext().execute_with(|| {
+		new_collection(
+			evm_account(ALICE),
+			&[(1, evm_account(ALICE)), (2, evm_account(ALICE))],
+		);
+		let balance_of = |owner: H160| {
+			let mut handle = call(
+				BOB,
+				SELECTOR_BALANCE_OF,
+				EvmDataWriter::new().write(Address(owner)),
+			)
+			.static_call();
+			execute(&mut handle).unwrap().output
+		};
+
+		assert_eq!(
+			balance_of(ALICE),
+			EvmDataWriter::new().write(U256::from(2)).build()
+		);
+		assert_eq!(
+			balance_of(BOB),
+			EvmDataWriter::new().write(U256::zero()).build()
+		);
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_delegatecall() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(0xcc);
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+
+		for handle in [transfer_from(ALICE, ALICE, BOB, 1), approve(ALICE, BOB, 1)] {
+			assert_eq!(
+				execute(&mut handle.delegated_from(contract)).unwrap_err(),
+				revert("cannot be called with DELEGATECALL or CALLCODE")
+			);
+		}
+
+		assert_eq!(
+			<Nfts as Inspect<AccountId>>::owner(&0, &1),
+			Some(evm_account(ALICE))
+		);
+	});
+}
+
+#[test]
+fn safe_transfer_to_an_account_without_code_makes_no_subcall() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+		let mut handle = safe_transfer_from(ALICE, ALICE, BOB, 1, b"hi");
+
+		assert_ok!(execute(&mut handle));
+
+		assert!(handle.subcalls.is_empty());
+		assert_eq!(owner_of(1), Ok(BOB));
+	});
+}
+
+#[test]
+fn safe_transfer_asks_the_receiver() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+		pallet_evm::Pallet::<Test>::create_account(RECEIVER, REVERT_CODE.to_vec());
+		// `bytes4` comes back left-aligned in a word.
+		let mut accepted = SELECTOR_ON_ERC721_RECEIVED.to_be_bytes().to_vec();
+		accepted.resize(32, 0);
+		let mut handle = safe_transfer_from(ALICE, ALICE, RECEIVER, 1, b"hi")
+			.with_subcall_result(ExitReason::Succeed(ExitSucceed::Returned), accepted);
+
+		assert_ok!(execute(&mut handle));
+
+		assert_eq!(
+			handle.subcalls,
+			vec![Subcall {
+				address: RECEIVER,
+				input: on_received(ALICE, ALICE, 1, b"hi"),
+				caller: collection(0),
+				is_static: false,
+			}]
+		);
+		assert_eq!(owner_of(1), Ok(RECEIVER));
+	});
+}
+
+#[test]
+fn safe_transfer_reverts_unless_the_receiver_accepts() {
+	for (reason, output) in [
+		// Answers with some other selector.
+		(
+			ExitReason::Succeed(ExitSucceed::Returned),
+			EvmDataWriter::new().write(H256::zero()).build(),
+		),
+		// Returns nothing, as a fallback function would.
+		(ExitReason::Succeed(ExitSucceed::Stopped), Vec::new()),
+		// Reverts, even with the expected selector in its output.
+		(
+			ExitReason::Revert(ExitRevert::Reverted),
+			SELECTOR_ON_ERC721_RECEIVED.to_be_bytes().to_vec(),
+		),
+	] {
+		new_test_ext().execute_with(|| {
+			new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+			pallet_evm::Pallet::<Test>::create_account(RECEIVER, REVERT_CODE.to_vec());
+			let mut handle =
+				safe_transfer_from(ALICE, ALICE, RECEIVER, 1, &[]).with_subcall_result(reason, output);
+
+			assert_eq!(
+				execute(&mut handle).unwrap_err(),
+				revert("transfer to non ERC721Receiver implementer")
+			);
+			assert_eq!(
+				handle.subcalls,
+				vec![Subcall {
+					address: RECEIVER,
+					input: on_received(ALICE, ALICE, 1, &[]),
+					caller: collection(0),
+					is_static: false,
+				}]
+			);
+		});
+	}
+}
+
+#[test]
+fn token_uri_prefers_the_item_metadata() {
+	new_test_ext().execute_with(|| {
+		new_collection(
+			evm_account(ALICE),
+			&[(1, evm_account(ALICE)), (2, evm_account(ALICE))],
+		);
+		assert_ok!(Nfts::set_metadata(
+			RuntimeOrigin::signed(evm_account(ALICE)),
+			0,
+			1,
+			b"ipfs://item-one".to_vec().try_into().unwrap(),
+		));
+
+		assert_eq!(
+			token_uri(1),
+			Ok(EvmDataWriter::new()
+				.write(Bytes(b"ipfs://item-one".to_vec()))
+				.build())
+		);
+		// Without collection metadata, an item without its own has no URI.
+		assert_eq!(
+			token_uri(2),
+			Ok(EvmDataWriter::new().write(Bytes(Vec::new())).build())
+		);
+		assert_eq!(token_uri(3), Err(revert("invalid token ID")));
+	});
+}
+
+#[test]
+fn token_uri_falls_back_to_the_collection_base() {
+	new_test_ext().execute_with(|| {
+		new_collection(
+			evm_account(ALICE),
+			&[(1, evm_account(ALICE)), (42, evm_account(ALICE))],
+		);
+		assert_ok!(Nfts::set_collection_metadata(
+			RuntimeOrigin::signed(evm_account(ALICE)),
+			0,
+			b"ipfs://base/".to_vec().try_into().unwrap(),
+		));
+		assert_ok!(Nfts::set_metadata(
+			RuntimeOrigin::signed(evm_account(ALICE)),
+			0,
+			1,
+			b"ipfs://item-one".to_vec().try_into().unwrap(),
+		));
+
+		assert_eq!(
+			token_uri(42),
+			Ok(EvmDataWriter::new()
+				.write(Bytes(b"ipfs://base/42".to_vec()))
+				.build())
+		);
+		assert_eq!(
+			token_uri(1),
+			Ok(EvmDataWriter::new()
+				.write(Bytes(b"ipfs://item-one".to_vec()))
+				.build())
+		);
+	});
+}
+
+#[test]
+fn approving_the_zero_address_clears_the_approval() {
+	new_test_ext().execute_with(|| {
+		new_collection(evm_account(ALICE), &[(1, evm_account(ALICE))]);
+		assert_ok!(execute(&mut approve(ALICE, BOB, 1)));
+		let mut handle = approve(ALICE, H160::zero(), 1);
+
+		assert_ok!(execute(&mut handle));
+
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(collection(0)).log4(
+				keccak256("Approval(address,address,uint256)"),
+				ALICE,
+				H160::zero(),
+				u256_topic(U256::one()),
+				Vec::new(),
+			)]
+		);
+		let mut get_approved = call(
+			ALICE,
+			SELECTOR_GET_APPROVED,
+			EvmDataWriter::new().write(U256::one()),
+		)
+		.static_call();
+		assert_eq!(
+			execute(&mut get_approved).unwrap().output,
+			EvmDataWriter::new().write(Address(H160::zero())).build()
+		);
+		assert!(execute(&mut transfer_from(BOB, ALICE, BOB, 1)).is_err());
+		assert_eq!(
+			<Nfts as Inspect<AccountId>>::owner(&0, &1),
+			Some(evm_account(ALICE))
+		);
+	});
+}
@@ -0,0 +1,458 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Precompile exposing `pallet_staking` and the nomination pools to EVM callers.
+//!
+//! Every call is dispatched from the account `msg.sender` maps to through the runtime's
+//! `AddressMapping`, so an EVM-only user can stake directly or through a pool. Validators
+//! are named by their 32 byte account id. The reward views return what
+//! `NominationPoolsApi` reports for the same account. State changing calls emit a log
+//! mirroring the pallet event; `Bonded`, `Unbonded` and `PayoutClaimed` carry the amount
+//! the pallet actually moved.
+//!
+//! `payee` of `bond` is `0` to restake rewards, `1` to pay them to the stash and `2` to
+//! forgo them.
+//!
+//! ```solidity
+//! interface IStaking {
+//!     event Bonded(address indexed stash, uint256 amount);
+//!     event Unbonded(address indexed stash, uint256 amount);
+//!     event Withdrawn(address indexed stash);
+//!     event Nominated(address indexed stash, bytes32[] targets);
+//!     event PoolJoined(address indexed member, uint32 indexed poolId, uint256 amount);
+//!     event PoolUnbonded(address indexed member, uint256 points);
+//!     event PoolWithdrawn(address indexed member);
+//!     event PayoutClaimed(address indexed member, uint256 amount);
+//!
+//!     function bond(uint256 value, uint8 payee) external;
+//!     function bondExtra(uint256 value) external;
+//!     function unbond(uint256 value) external;
+//!     function withdrawUnbonded(uint32 numSlashingSpans) external;
+//!     function nominate(bytes32[] calldata targets) external;
+//!     function bonded(address stash) external view returns (uint256);
+//!
+//!     function joinPool(uint256 amount, uint32 poolId) external;
+//!     function poolUnbond(uint256 points) external;
+//!     function poolWithdrawUnbonded(uint32 numSlashingSpans) external;
+//!     function claimPayout() external;
+//!     function pendingRewards(address member) external view returns (uint256);
+//!     function pointsToBalance(uint32 poolId, uint256 points) external view returns (uint256);
+//!     function balanceToPoints(uint32 poolId, uint256 amount) external view returns (uint256);
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+use fp_evm::{Precompile, PrecompileHandle, PrecompileResult};
+use frame_support::dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo};
+use pallet_evm::AddressMapping;
+use pallet_nomination_pools::PoolId;
+use pallet_staking::RewardDestination;
+use precompile_utils::{
+	ensure_direct_call, ensure_non_payable, ensure_not_static, keccak256, record_db_reads, revert,
+	succeed, try_dispatch, Address, EvmDataReader, EvmDataWriter, EvmResult, LogsBuilder,
+};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::StaticLookup;
+use sp_std::vec::Vec;
+
+/// `bond(uint256,uint8)`
+pub const SELECTOR_BOND: u32 = 0xd5c90b29;
+/// `bondExtra(uint256)`
+pub const SELECTOR_BOND_EXTRA: u32 = 0xeaca88de;
+/// `unbond(uint256)`
+pub const SELECTOR_UNBOND: u32 = 0x27de9e32;
+/// `withdrawUnbonded(uint32)`
+pub const SELECTOR_WITHDRAW_UNBONDED: u32 = 0x548a6706;
+/// `nominate(bytes32[])`
+pub const SELECTOR_NOMINATE: u32 = 0xf5330e96;
+/// `bonded(address)`
+pub const SELECTOR_BONDED: u32 = 0xf19e2a21;
+/// `joinPool(uint256,uint32)`
+pub const SELECTOR_JOIN_POOL: u32 = 0x15a5da0f;
+/// `poolUnbond(uint256)`
+pub const SELECTOR_POOL_UNBOND: u32 = 0xd7d2ea87;
+/// `poolWithdrawUnbonded(uint32)`
+pub const SELECTOR_POOL_WITHDRAW_UNBONDED: u32 = 0x869c9ea4;
+/// `claimPayout()`
+pub const SELECTOR_CLAIM_PAYOUT: u32 = 0xe88499a9;
+/// `pendingRewards(address)`
+pub const SELECTOR_PENDING_REWARDS: u32 = 0x31d7a262;
+/// `pointsToBalance(uint32,uint256)`
+pub const SELECTOR_POINTS_TO_BALANCE: u32 = 0x4cf2e083;
+/// `balanceToPoints(uint32,uint256)`
+pub const SELECTOR_BALANCE_TO_POINTS: u32 = 0x9eba1a46;
+
+type StakingBalanceOf<Runtime> = pallet_staking::BalanceOf<Runtime>;
+type PoolsBalanceOf<Runtime> = pallet_nomination_pools::BalanceOf<Runtime>;
+
+pub struct StakingPrecompile<Runtime>(PhantomData<Runtime>);
+
+impl<Runtime> Precompile for StakingPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config + pallet_staking::Config + pallet_nomination_pools::Config,
+	Runtime::AccountId: From<[u8; 32]>,
+	StakingBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	PoolsBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_staking::Call<Runtime>>
+		+ From<pallet_nomination_pools::Call<Runtime>>,
+	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let selector = EvmDataReader::read_selector(handle.input())?;
+
+		let output = match selector {
+			SELECTOR_BOND => Self::bond(handle)?,
+			SELECTOR_BOND_EXTRA => Self::bond_extra(handle)?,
+			SELECTOR_UNBOND => Self::unbond(handle)?,
+			SELECTOR_WITHDRAW_UNBONDED => Self::withdraw_unbonded(handle)?,
+			SELECTOR_NOMINATE => Self::nominate(handle)?,
+			SELECTOR_BONDED => Self::bonded(handle)?,
+			SELECTOR_JOIN_POOL => Self::join_pool(handle)?,
+			SELECTOR_POOL_UNBOND => Self::pool_unbond(handle)?,
+			SELECTOR_POOL_WITHDRAW_UNBONDED => Self::pool_withdraw_unbonded(handle)?,
+			SELECTOR_CLAIM_PAYOUT => Self::claim_payout(handle)?,
+			SELECTOR_PENDING_REWARDS => Self::pending_rewards(handle)?,
+			SELECTOR_POINTS_TO_BALANCE => Self::points_to_balance(handle)?,
+			SELECTOR_BALANCE_TO_POINTS => Self::balance_to_points(handle)?,
+			_ => return Err(revert("unknown selector")),
+		};
+
+		Ok(succeed(output))
+	}
+}
+
+impl<Runtime> StakingPrecompile<Runtime>
+where
+	Runtime: pallet_evm::Config + pallet_staking::Config + pallet_nomination_pools::Config,
+	Runtime::AccountId: From<[u8; 32]>,
+	StakingBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	PoolsBalanceOf<Runtime>: TryFrom<u128> + Into<u128>,
+	Runtime::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<pallet_staking::Call<Runtime>>
+		+ From<pallet_nomination_pools::Call<Runtime>>,
+	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin:
+		From<frame_system::RawOrigin<Runtime::AccountId>>,
+{
+	fn bond(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 2)?;
+		let value: U256 = input.read()?;
+		let payee = match input.read::<u8>()? {
+			0 => RewardDestination::Staked,
+			1 => RewardDestination::Stash,
+			2 => RewardDestination::None,
+			_ => return Err(revert("unknown reward destination")),
+		};
+
+		let stash = Self::account(handle.context().caller);
+		let before = Self::active(handle, &stash)?;
+		Self::dispatch(
+			handle,
+			pallet_staking::Call::<Runtime>::bond {
+				value: Self::to_balance(value)?,
+				payee,
+			},
+		)?;
+
+		let bonded = Self::active(handle, &stash)?.saturating_sub(before);
+		Self::log_amount(handle, "Bonded(address,uint256)", bonded)?;
+
+		Ok(Vec::new())
+	}
+
+	fn bond_extra(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let value: U256 = input.read()?;
+
+		let stash = Self::account(handle.context().caller);
+		let before = Self::active(handle, &stash)?;
+		Self::dispatch(
+			handle,
+			pallet_staking::Call::<Runtime>::bond_extra {
+				max_additional: Self::to_balance(value)?,
+			},
+		)?;
+
+		let bonded = Self::active(handle, &stash)?.saturating_sub(before);
+		Self::log_amount(handle, "Bonded(address,uint256)", bonded)?;
+
+		Ok(Vec::new())
+	}
+
+	fn unbond(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let value: U256 = input.read()?;
+
+		let stash = Self::account(handle.context().caller);
+		let before = Self::active(handle, &stash)?;
+		Self::dispatch(
+			handle,
+			pallet_staking::Call::<Runtime>::unbond {
+				value: Self::to_balance(value)?,
+			},
+		)?;
+
+		let unbonded = before.saturating_sub(Self::active(handle, &stash)?);
+		Self::log_amount(handle, "Unbonded(address,uint256)", unbonded)?;
+
+		Ok(Vec::new())
+	}
+
+	fn withdraw_unbonded(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let num_slashing_spans: u32 = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_staking::Call::<Runtime>::withdraw_unbonded { num_slashing_spans },
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256("Withdrawn(address)"),
+				handle.context().caller,
+				Vec::new(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn nominate(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let targets: Vec<H256> = input.read()?;
+
+		let lookups = targets
+			.iter()
+			.map(|target| Runtime::Lookup::unlookup(Runtime::AccountId::from(target.0)))
+			.collect();
+		Self::dispatch(
+			handle,
+			pallet_staking::Call::<Runtime>::nominate { targets: lookups },
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256("Nominated(address,bytes32[])"),
+				handle.context().caller,
+				EvmDataWriter::new().write(targets).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn bonded(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let stash: Address = input.read()?;
+
+		let active = Self::active(handle, &Self::account(stash.0))?;
+		Ok(EvmDataWriter::new().write(U256::from(active)).build())
+	}
+
+	fn join_pool(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 2)?;
+		let amount: U256 = input.read()?;
+		let pool_id: PoolId = input.read()?;
+
+		Self::dispatch(
+			handle,
+			pallet_nomination_pools::Call::<Runtime>::join {
+				amount: Self::to_pool_balance(amount)?,
+				pool_id,
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log3(
+				keccak256("PoolJoined(address,uint32,uint256)"),
+				handle.context().caller,
+				H256::from_low_u64_be(pool_id.into()),
+				EvmDataWriter::new().write(amount).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn pool_unbond(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let points: U256 = input.read()?;
+
+		let member = Self::account(handle.context().caller);
+		Self::dispatch(
+			handle,
+			pallet_nomination_pools::Call::<Runtime>::unbond {
+				member_account: Runtime::Lookup::unlookup(member),
+				unbonding_points: Self::to_pool_balance(points)?,
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256("PoolUnbonded(address,uint256)"),
+				handle.context().caller,
+				EvmDataWriter::new().write(points).build(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn pool_withdraw_unbonded(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = Self::mutating_input(handle, 1)?;
+		let num_slashing_spans: u32 = input.read()?;
+
+		let member = Self::account(handle.context().caller);
+		Self::dispatch(
+			handle,
+			pallet_nomination_pools::Call::<Runtime>::withdraw_unbonded {
+				member_account: Runtime::Lookup::unlookup(member),
+				num_slashing_spans,
+			},
+		)?;
+
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256("PoolWithdrawn(address)"),
+				handle.context().caller,
+				Vec::new(),
+			)
+			.record(handle)?;
+
+		Ok(Vec::new())
+	}
+
+	fn claim_payout(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		Self::mutating_input(handle, 0)?;
+
+		// The whole pending reward is paid out.
+		let member = Self::account(handle.context().caller);
+		let payout = Self::pending(handle, member)?;
+		Self::dispatch(
+			handle,
+			pallet_nomination_pools::Call::<Runtime>::claim_payout {},
+		)?;
+
+		Self::log_amount(handle, "PayoutClaimed(address,uint256)", payout)?;
+
+		Ok(Vec::new())
+	}
+
+	fn pending_rewards(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(1)?;
+		let member: Address = input.read()?;
+
+		let rewards = Self::pending(handle, Self::account(member.0))?;
+		Ok(EvmDataWriter::new().write(U256::from(rewards)).build())
+	}
+
+	fn points_to_balance(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let pool_id: PoolId = input.read()?;
+		let points: U256 = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 2)?;
+		let balance: u128 = pallet_nomination_pools::Pallet::<Runtime>::api_points_to_balance(
+			pool_id,
+			Self::to_pool_balance(points)?,
+		)
+		.into();
+		Ok(EvmDataWriter::new().write(U256::from(balance)).build())
+	}
+
+	fn balance_to_points(handle: &mut impl PrecompileHandle) -> EvmResult<Vec<u8>> {
+		let mut input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(2)?;
+		let pool_id: PoolId = input.read()?;
+		let amount: U256 = input.read()?;
+
+		record_db_reads::<Runtime>(handle, 2)?;
+		let points: u128 = pallet_nomination_pools::Pallet::<Runtime>::api_balance_to_points(
+			pool_id,
+			Self::to_pool_balance(amount)?,
+		)
+		.into();
+		Ok(EvmDataWriter::new().write(U256::from(points)).build())
+	}
+
+	/// Checks a state changing call and returns its `args` arguments.
+	fn mutating_input(handle: &impl PrecompileHandle, args: usize) -> EvmResult<EvmDataReader<'_>> {
+		ensure_direct_call(handle)?;
+		ensure_non_payable(handle)?;
+		ensure_not_static(handle)?;
+
+		let input = EvmDataReader::new_skip_selector(handle.input())?;
+		input.expect_arguments(args)?;
+		Ok(input)
+	}
+
+	fn dispatch(
+		handle: &mut impl PrecompileHandle,
+		call: impl Into<Runtime::RuntimeCall>,
+	) -> EvmResult {
+		let origin = frame_system::RawOrigin::Signed(Self::account(handle.context().caller));
+		try_dispatch::<Runtime>(handle, origin.into(), call.into())?;
+		Ok(())
+	}
+
+	/// Logs `event` with the caller as its only topic and `amount` as its data.
+	fn log_amount(handle: &mut impl PrecompileHandle, event: &str, amount: u128) -> EvmResult {
+		LogsBuilder::new(handle.context().address)
+			.log2(
+				keccak256(event),
+				handle.context().caller,
+				EvmDataWriter::new().write(U256::from(amount)).build(),
+			)
+			.record(handle)
+	}
+
+	/// Active bonded balance of `stash`.
+	fn active(handle: &mut impl PrecompileHandle, stash: &Runtime::AccountId) -> EvmResult<u128> {
+		record_db_reads::<Runtime>(handle, 1)?;
+		Ok(pallet_staking::Pallet::<Runtime>::ledger(stash)
+			.map(|ledger| ledger.active.into())
+			.unwrap_or_default())
+	}
+
+	/// Rewards `member` can claim from its pool.
+	fn pending(handle: &mut impl PrecompileHandle, member: Runtime::AccountId) -> EvmResult<u128> {
+		// Member, both pools and the reward counter, as in `NominationPoolsApi::pending_rewards`.
+		record_db_reads::<Runtime>(handle, 4)?;
+		Ok(
+			pallet_nomination_pools::Pallet::<Runtime>::api_pending_rewards(member)
+				.unwrap_or_default()
+				.into(),
+		)
+	}
+
+	fn account(address: H160) -> Runtime::AccountId {
+		Runtime::AddressMapping::into_account_id(address)
+	}
+
+	fn to_balance(value: U256) -> EvmResult<StakingBalanceOf<Runtime>> {
+		u128::try_from(value)
+			.ok()
+			.and_then(|value| value.try_into().ok())
+			.ok_or_else(|| revert("value is too large"))
+	}
+
+	fn to_pool_balance(value: U256) -> EvmResult<PoolsBalanceOf<Runtime>> {
+		u128::try_from(value)
+			.ok()
+			.and_then(|value| value.try_into().ok())
+			.ok_or_else(|| revert("value is too large"))
+	}
+}
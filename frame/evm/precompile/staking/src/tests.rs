@@ -0,0 +1,297 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::mock::*;
+
+use frame_support::{
+	assert_ok,
+	traits::{Currency, OnRuntimeUpgrade},
+};
+use pallet_nomination_pools::PoolMembers;
+use precompile_utils::{testing::MockHandle, InsertRevertCode, REVERT_CODE};
+
+type Stake = StakingPrecompile<Test>;
+
+const ALICE: H160 = H160::repeat_byte(0xaa);
+const BOB: [u8; 32] = [2u8; 32];
+const VALIDATOR: [u8; 32] = [3u8; 32];
+
+fn input(selector: u32, args: EvmDataWriter) -> Vec<u8> {
+	let mut input = selector.to_be_bytes().to_vec();
+	input.extend(args.build());
+	input
+}
+
+fn evm_account(address: H160) -> AccountId {
+	<Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn call(selector: u32, args: EvmDataWriter) -> MockHandle {
+	MockHandle::new(precompile_address(), ALICE, input(selector, args))
+}
+
+fn bond(value: u128, payee: u8) -> MockHandle {
+	call(
+		SELECTOR_BOND,
+		EvmDataWriter::new().write(U256::from(value)).write(payee),
+	)
+}
+
+fn bond_extra(value: u128) -> MockHandle {
+	call(
+		SELECTOR_BOND_EXTRA,
+		EvmDataWriter::new().write(U256::from(value)),
+	)
+}
+
+fn unbond(value: u128) -> MockHandle {
+	call(
+		SELECTOR_UNBOND,
+		EvmDataWriter::new().write(U256::from(value)),
+	)
+}
+
+fn nominate(targets: Vec<H256>) -> MockHandle {
+	call(SELECTOR_NOMINATE, EvmDataWriter::new().write(targets))
+}
+
+fn join_pool(amount: u128, pool_id: PoolId) -> MockHandle {
+	call(
+		SELECTOR_JOIN_POOL,
+		EvmDataWriter::new()
+			.write(U256::from(amount))
+			.write(pool_id),
+	)
+}
+
+fn pool_unbond(points: u128) -> MockHandle {
+	call(
+		SELECTOR_POOL_UNBOND,
+		EvmDataWriter::new().write(U256::from(points)),
+	)
+}
+
+fn bonded(stash: H160) -> U256 {
+	let mut handle =
+		call(SELECTOR_BONDED, EvmDataWriter::new().write(Address(stash))).static_call();
+	let output = Stake::execute(&mut handle).unwrap().output;
+	EvmDataReader::new(&output).read().unwrap()
+}
+
+fn amount_log(event: &str, amount: u128) -> precompile_utils::Log {
+	LogsBuilder::new(precompile_address()).log2(
+		keccak256(event),
+		ALICE,
+		EvmDataWriter::new().write(U256::from(amount)).build(),
+	)
+}
+
+/// Pool 1, created and bonded by `BOB` with `amount`.
+fn new_pool(amount: u128) {
+	let bob = AccountId::from(BOB);
+	Balances::make_free_balance_be(&bob, 1_000);
+	assert_ok!(Pools::create(
+		RuntimeOrigin::signed(bob.clone()),
+		amount,
+		bob.clone(),
+		bob.clone(),
+		bob,
+	));
+}
+
+#[test]
+fn bond_stakes_the_caller_and_logs() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = bond(400, 0);
+
+		assert_ok!(Stake::execute(&mut handle));
+
+		assert_eq!(bonded(ALICE), U256::from(400));
+		assert_eq!(
+			pallet_staking::Pallet::<Test>::payee(evm_account(ALICE)),
+			RewardDestination::Staked
+		);
+		assert_eq!(
+			handle.logs,
+			vec![amount_log("Bonded(address,uint256)", 400)]
+		);
+	});
+}
+
+#[test]
+fn bond_rejects_unknown_reward_destinations() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		assert_eq!(
+			Stake::execute(&mut bond(400, 3)).unwrap_err(),
+			revert("unknown reward destination")
+		);
+		assert_eq!(bonded(ALICE), U256::zero());
+	});
+}
+
+#[test]
+fn bond_extra_logs_the_amount_actually_bonded() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Stake::execute(&mut bond(400, 0)));
+		let mut handle = bond_extra(2_000);
+
+		assert_ok!(Stake::execute(&mut handle));
+
+		assert_eq!(bonded(ALICE), U256::from(1_000));
+		assert_eq!(
+			handle.logs,
+			vec![amount_log("Bonded(address,uint256)", 600)]
+		);
+	});
+}
+
+#[test]
+fn unbond_reduces_the_active_stake_and_logs() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Stake::execute(&mut bond(400, 0)));
+		let mut handle = unbond(150);
+
+		assert_ok!(Stake::execute(&mut handle));
+
+		assert_eq!(bonded(ALICE), U256::from(250));
+		assert_eq!(
+			handle.logs,
+			vec![amount_log("Unbonded(address,uint256)", 150)]
+		);
+	});
+}
+
+#[test]
+fn nominate_records_the_targets_and_logs() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Stake::execute(&mut bond(400, 0)));
+		let mut handle = nominate(vec![H256(VALIDATOR)]);
+
+		assert_ok!(Stake::execute(&mut handle));
+
+		assert_eq!(
+			pallet_staking::Nominators::<Test>::get(evm_account(ALICE))
+				.map(|nominations| nominations.targets.into_inner()),
+			Some(vec![AccountId::from(VALIDATOR)])
+		);
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log2(
+				keccak256("Nominated(address,bytes32[])"),
+				ALICE,
+				EvmDataWriter::new().write(vec![H256(VALIDATOR)]).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn join_pool_adds_the_caller_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_pool(100);
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		let mut handle = join_pool(200, 1);
+
+		assert_ok!(Stake::execute(&mut handle));
+
+		let member = PoolMembers::<Test>::get(evm_account(ALICE)).unwrap();
+		assert_eq!((member.pool_id, member.points), (1, 200));
+		assert_eq!(
+			handle.logs,
+			vec![LogsBuilder::new(precompile_address()).log3(
+				keccak256("PoolJoined(address,uint32,uint256)"),
+				ALICE,
+				H256::from_low_u64_be(1),
+				EvmDataWriter::new().write(U256::from(200)).build(),
+			)]
+		);
+	});
+}
+
+#[test]
+fn pool_unbond_unbonds_the_callers_points_and_logs() {
+	new_test_ext().execute_with(|| {
+		new_pool(100);
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+		assert_ok!(Stake::execute(&mut join_pool(200, 1)));
+		let mut handle = pool_unbond(50);
+
+		assert_ok!(Stake::execute(&mut handle));
+
+		let member = PoolMembers::<Test>::get(evm_account(ALICE)).unwrap();
+		assert_eq!(member.points, 150);
+		assert_eq!(
+			handle.logs,
+			vec![amount_log("PoolUnbonded(address,uint256)", 50)]
+		);
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_delegatecall() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(0xcc);
+		new_pool(100);
+		Balances::make_free_balance_be(&evm_account(contract), 1_000);
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		for handle in [
+			bond(400, 0),
+			bond_extra(100),
+			unbond(100),
+			call(SELECTOR_WITHDRAW_UNBONDED, EvmDataWriter::new().write(0u32)),
+			nominate(vec![H256(VALIDATOR)]),
+			join_pool(200, 1),
+			pool_unbond(50),
+			call(
+				SELECTOR_POOL_WITHDRAW_UNBONDED,
+				EvmDataWriter::new().write(0u32),
+			),
+			call(SELECTOR_CLAIM_PAYOUT, EvmDataWriter::new()),
+		] {
+			assert_eq!(
+				Stake::execute(&mut handle.delegated_from(contract)).unwrap_err(),
+				revert("cannot be called with DELEGATECALL or CALLCODE")
+			);
+		}
+
+		assert_eq!(bonded(ALICE), U256::zero());
+		assert_eq!(bonded(contract), U256::zero());
+		assert!(PoolMembers::<Test>::get(evm_account(ALICE)).is_none());
+	});
+}
+
+#[test]
+fn state_changing_functions_reject_static_and_payable_calls() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&evm_account(ALICE), 1_000);
+
+		assert!(Stake::execute(&mut bond(400, 0).static_call()).is_err());
+		assert!(Stake::execute(&mut bond(400, 0).with_value(U256::one())).is_err());
+		assert_eq!(bonded(ALICE), U256::zero());
+	});
+}
+
+#[test]
+fn insert_revert_code_runs_once() {
+	new_test_ext().execute_with(|| {
+		frame_support::parameter_types! {
+			pub Address: H160 = precompile_address();
+		}
+
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+		InsertRevertCode::<Test, Address>::on_runtime_upgrade();
+
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(precompile_address()),
+			REVERT_CODE.to_vec()
+		);
+		assert_eq!(System::account(evm_account(precompile_address())).sufficients, 1);
+	});
+}
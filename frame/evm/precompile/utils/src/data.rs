@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Solidity ABI encoding and decoding of precompile arguments and return values.
+
+use crate::{revert, EvmResult};
+use core::ops::Range;
+use sp_core::{H160, H256, U256};
+use sp_std::{vec, vec::Vec};
+
+/// An Ethereum address, ABI-encoded as a left-padded 32 byte word.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Address(pub H160);
+
+impl From<H160> for Address {
+	fn from(a: H160) -> Self {
+		Self(a)
+	}
+}
+
+impl From<Address> for H160 {
+	fn from(a: Address) -> Self {
+		a.0
+	}
+}
+
+/// Dynamic `bytes` / `string`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for Bytes {
+	fn from(b: Vec<u8>) -> Self {
+		Self(b)
+	}
+}
+
+impl From<&[u8]> for Bytes {
+	fn from(b: &[u8]) -> Self {
+		Self(b.to_vec())
+	}
+}
+
+impl From<&str> for Bytes {
+	fn from(s: &str) -> Self {
+		Self(s.as_bytes().to_vec())
+	}
+}
+
+/// Reads ABI-encoded arguments from precompile input.
+pub struct EvmDataReader<'a> {
+	input: &'a [u8],
+	cursor: usize,
+}
+
+impl<'a> EvmDataReader<'a> {
+	/// Create a reader over `input`, which must not contain a selector.
+	pub fn new(input: &'a [u8]) -> Self {
+		Self { input, cursor: 0 }
+	}
+
+	/// Create a reader over the arguments that follow the 4 byte selector.
+	pub fn new_skip_selector(input: &'a [u8]) -> EvmResult<Self> {
+		if input.len() < 4 {
+			return Err(revert("tried to parse selector out of bounds"));
+		}
+		Ok(Self::new(&input[4..]))
+	}
+
+	/// Read the 4 byte function selector at the start of `input`.
+	pub fn read_selector(input: &[u8]) -> EvmResult<u32> {
+		if input.len() < 4 {
+			return Err(revert("tried to parse selector out of bounds"));
+		}
+		let mut buffer = [0u8; 4];
+		buffer.copy_from_slice(&input[..4]);
+		Ok(u32::from_be_bytes(buffer))
+	}
+
+	/// Fail unless at least `args` more 32 byte words are available.
+	pub fn expect_arguments(&self, args: usize) -> EvmResult {
+		if self.input.len() >= self.cursor.saturating_add(args.saturating_mul(32)) {
+			Ok(())
+		} else {
+			Err(revert("expected more arguments"))
+		}
+	}
+
+	/// Read the next argument.
+	pub fn read<T: EvmData>(&mut self) -> EvmResult<T> {
+		T::read(self)
+	}
+
+	fn read_word(&mut self) -> EvmResult<&'a [u8]> {
+		let range = self.move_cursor(32)?;
+		Ok(&self.input[range])
+	}
+
+	fn move_cursor(&mut self, len: usize) -> EvmResult<Range<usize>> {
+		let start = self.cursor;
+		let end = start
+			.checked_add(len)
+			.filter(|end| *end <= self.input.len())
+			.ok_or_else(|| revert("tried to parse out of bounds"))?;
+		self.cursor = end;
+		Ok(start..end)
+	}
+
+	/// Follow an offset word to the tail section of a dynamic value.
+	fn read_pointer(&mut self) -> EvmResult<EvmDataReader<'a>> {
+		let offset: usize = self
+			.read::<U256>()?
+			.try_into()
+			.map_err(|_| revert("offset is too large"))?;
+		if offset >= self.input.len() {
+			return Err(revert("offset out of bounds"));
+		}
+		Ok(EvmDataReader::new(&self.input[offset..]))
+	}
+}
+
+/// Builds ABI-encoded output.
+#[derive(Default)]
+pub struct EvmDataWriter {
+	data: Vec<u8>,
+	tails: Vec<(usize, Vec<u8>)>,
+}
+
+impl EvmDataWriter {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Append a value.
+	pub fn write<T: EvmData>(mut self, value: T) -> Self {
+		T::write(&mut self, value);
+		self
+	}
+
+	/// Return the encoded head followed by the tails of dynamic values.
+	pub fn build(mut self) -> Vec<u8> {
+		for (pointer, tail) in core::mem::take(&mut self.tails) {
+			let offset = U256::from(self.data.len());
+			offset.to_big_endian(&mut self.data[pointer..pointer + 32]);
+			self.data.extend_from_slice(&tail);
+		}
+		self.data
+	}
+
+	fn write_pointer(&mut self, tail: Vec<u8>) {
+		let pointer = self.data.len();
+		self.data.extend_from_slice(&[0u8; 32]);
+		self.tails.push((pointer, tail));
+	}
+}
+
+/// A type that can be ABI-encoded as a precompile argument or return value.
+pub trait EvmData: Sized {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self>;
+	fn write(writer: &mut EvmDataWriter, value: Self);
+	fn has_static_size() -> bool {
+		true
+	}
+}
+
+impl EvmData for H256 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(H256::from_slice(reader.read_word()?))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		writer.data.extend_from_slice(value.as_bytes());
+	}
+}
+
+impl EvmData for Address {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let word = reader.read_word()?;
+		if word[..12].iter().any(|b| *b != 0) {
+			return Err(revert("address has dirty high bytes"));
+		}
+		Ok(Address(H160::from_slice(&word[12..])))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		H256::write(writer, value.0.into());
+	}
+}
+
+impl EvmData for U256 {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		Ok(U256::from_big_endian(reader.read_word()?))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut word = [0u8; 32];
+		value.to_big_endian(&mut word);
+		writer.data.extend_from_slice(&word);
+	}
+}
+
+macro_rules! impl_evmdata_for_uints {
+	($($uint:ty),*) => {
+		$(
+			impl EvmData for $uint {
+				fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+					reader
+						.read::<U256>()?
+						.try_into()
+						.map_err(|_| revert(concat!("value does not fit in ", stringify!($uint))))
+				}
+
+				fn write(writer: &mut EvmDataWriter, value: Self) {
+					U256::write(writer, value.into());
+				}
+			}
+		)*
+	};
+}
+
+impl_evmdata_for_uints!(u8, u16, u32, u64, u128);
+
+impl EvmData for bool {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		match reader.read::<U256>()? {
+			v if v.is_zero() => Ok(false),
+			v if v == U256::one() => Ok(true),
+			_ => Err(revert("value is not a boolean")),
+		}
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		U256::write(writer, if value { U256::one() } else { U256::zero() });
+	}
+}
+
+impl EvmData for Bytes {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let mut inner = reader.read_pointer()?;
+		let len: usize = inner
+			.read::<U256>()?
+			.try_into()
+			.map_err(|_| revert("length is too large"))?;
+		let range = inner.move_cursor(len)?;
+		Ok(Bytes(inner.input[range].to_vec()))
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let len = value.0.len();
+		let padded = len.saturating_add(31) / 32 * 32;
+		let mut tail = vec![0u8; 32 + padded];
+		U256::from(len).to_big_endian(&mut tail[..32]);
+		tail[32..32 + len].copy_from_slice(&value.0);
+		writer.write_pointer(tail);
+	}
+
+	fn has_static_size() -> bool {
+		false
+	}
+}
+
+impl<T: EvmData> EvmData for Vec<T> {
+	fn read(reader: &mut EvmDataReader) -> EvmResult<Self> {
+		let mut inner = reader.read_pointer()?;
+		let len: usize = inner
+			.read::<U256>()?
+			.try_into()
+			.map_err(|_| revert("length is too large"))?;
+		inner.expect_arguments(len)?;
+
+		// Offsets of dynamic elements are relative to the start of the element area.
+		let mut elements = EvmDataReader::new(&inner.input[inner.cursor..]);
+		let mut values = Vec::with_capacity(len);
+		for _ in 0..len {
+			values.push(elements.read()?);
+		}
+		Ok(values)
+	}
+
+	fn write(writer: &mut EvmDataWriter, value: Self) {
+		let mut tail = vec![0u8; 32];
+		U256::from(value.len()).to_big_endian(&mut tail[..32]);
+		let mut elements = EvmDataWriter::new();
+		for element in value {
+			elements = elements.write(element);
+		}
+		tail.extend_from_slice(&elements.build());
+		writer.write_pointer(tail);
+	}
+
+	fn has_static_size() -> bool {
+		false
+	}
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! EIP-2612 `permit` signatures for the ERC-20 precompiles.
+//!
+//! The signing domain is
+//! `EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)`,
+//! with the precompile address as the verifying contract.
+
+use sp_core::{H160, H256, U256};
+use sp_io::hashing::keccak_256;
+use sp_std::vec::Vec;
+
+/// `keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")`
+pub fn domain_type_hash() -> [u8; 32] {
+	keccak_256(b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")
+}
+
+/// `keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)")`
+pub fn permit_type_hash() -> [u8; 32] {
+	keccak_256(b"Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)")
+}
+
+fn word(value: U256) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	word
+}
+
+fn address_word(address: H160) -> [u8; 32] {
+	H256::from(address).0
+}
+
+/// Domain separator of the token at `verifying_contract`.
+pub fn domain_separator(
+	name: &[u8],
+	version: &[u8],
+	chain_id: u64,
+	verifying_contract: H160,
+) -> H256 {
+	let mut encoded = Vec::with_capacity(5 * 32);
+	encoded.extend_from_slice(&domain_type_hash());
+	encoded.extend_from_slice(&keccak_256(name));
+	encoded.extend_from_slice(&keccak_256(version));
+	encoded.extend_from_slice(&word(chain_id.into()));
+	encoded.extend_from_slice(&address_word(verifying_contract));
+	H256(keccak_256(&encoded))
+}
+
+/// Digest `owner` signs to let `spender` move `value` until `deadline`.
+pub fn permit_digest(
+	domain_separator: H256,
+	owner: H160,
+	spender: H160,
+	value: U256,
+	nonce: U256,
+	deadline: U256,
+) -> H256 {
+	let mut encoded = Vec::with_capacity(6 * 32);
+	encoded.extend_from_slice(&permit_type_hash());
+	encoded.extend_from_slice(&address_word(owner));
+	encoded.extend_from_slice(&address_word(spender));
+	encoded.extend_from_slice(&word(value));
+	encoded.extend_from_slice(&word(nonce));
+	encoded.extend_from_slice(&word(deadline));
+	let struct_hash = keccak_256(&encoded);
+
+	let mut message = Vec::with_capacity(2 + 2 * 32);
+	message.extend_from_slice(b"\x19\x01");
+	message.extend_from_slice(domain_separator.as_bytes());
+	message.extend_from_slice(&struct_hash);
+	H256(keccak_256(&message))
+}
+
+/// Address whose key produced the `(v, r, s)` signature of `digest`, if any; see
+/// [`fp_account::recover_ethereum_signer`].
+pub fn recover_signer(digest: H256, v: u8, r: H256, s: H256) -> Option<H160> {
+	let mut signature = [0u8; 65];
+	signature[..32].copy_from_slice(r.as_bytes());
+	signature[32..64].copy_from_slice(s.as_bytes());
+	signature[64] = v;
+	fp_account::recover_ethereum_signer(&signature, &digest.0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{ecdsa, Pair};
+
+	#[test]
+	fn recovers_permit_signer() {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let public = libsecp256k1::PublicKey::parse_compressed(&pair.public().0).unwrap();
+		let owner = H160::from_slice(&keccak_256(&public.serialize()[1..])[12..]);
+
+		let domain = domain_separator(b"Argo", b"1", 1299, H160::repeat_byte(0x08));
+		let digest = permit_digest(
+			domain,
+			owner,
+			H160::repeat_byte(0x22),
+			U256::from(100),
+			U256::zero(),
+			U256::MAX,
+		);
+		let signature = pair.sign_prehashed(&digest.0);
+
+		let r = H256::from_slice(&signature.0[..32]);
+		let s = H256::from_slice(&signature.0[32..64]);
+		assert_eq!(recover_signer(digest, signature.0[64] + 27, r, s), Some(owner));
+		assert_ne!(
+			recover_signer(digest, signature.0[64] + 27, s, r),
+			Some(owner),
+		);
+		assert_eq!(recover_signer(digest, 29, r, s), None);
+	}
+}
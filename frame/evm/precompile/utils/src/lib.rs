@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers shared by the runtime precompiles: Solidity ABI encoding, revert reasons,
+//! EVM logs and charging gas for Substrate storage access and dispatches.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod data;
+pub mod eip2612;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use data::{Address, Bytes, EvmData, EvmDataReader, EvmDataWriter};
+
+use fp_evm::{
+	ExitError, ExitRevert, ExitSucceed, PrecompileFailure, PrecompileHandle, PrecompileOutput,
+};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{Get, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use pallet_evm::GasWeightMapping;
+use sp_core::{H160, H256, U256};
+use sp_std::{marker::PhantomData, vec, vec::Vec};
+
+/// Result of a precompile function.
+pub type EvmResult<T = ()> = Result<T, PrecompileFailure>;
+
+/// Selector of Solidity's `Error(string)`.
+const ERROR_SELECTOR: u32 = 0x08c379a0;
+
+/// Revert with `Error(message)`, which Solidity surfaces as the revert reason.
+pub fn revert(message: impl AsRef<[u8]>) -> PrecompileFailure {
+	let mut output = ERROR_SELECTOR.to_be_bytes().to_vec();
+	output.extend(EvmDataWriter::new().write(Bytes(message.as_ref().to_vec())).build());
+	PrecompileFailure::Revert {
+		exit_status: ExitRevert::Reverted,
+		output,
+	}
+}
+
+/// Successful return with ABI-encoded `output`.
+pub fn succeed(output: Vec<u8>) -> PrecompileOutput {
+	PrecompileOutput {
+		exit_status: ExitSucceed::Returned,
+		output,
+	}
+}
+
+/// Keccak-256 of a Solidity event or function signature.
+pub fn keccak256(signature: &str) -> H256 {
+	H256(sp_io::hashing::keccak_256(signature.as_bytes()))
+}
+
+/// Fail if the call carries value. Precompiles never hold native balance.
+pub fn ensure_non_payable(handle: &impl PrecompileHandle) -> EvmResult {
+	if !handle.context().apparent_value.is_zero() {
+		return Err(revert("function is not payable"));
+	}
+	Ok(())
+}
+
+/// Fail if the precompile runs in another contract's context (`DELEGATECALL` or `CALLCODE`),
+/// where `msg.sender` is not the account the call acts for.
+pub fn ensure_direct_call(handle: &impl PrecompileHandle) -> EvmResult {
+	if handle.code_address() != handle.context().address {
+		return Err(revert("cannot be called with DELEGATECALL or CALLCODE"));
+	}
+	Ok(())
+}
+
+/// Fail if the call is made from a static context (`STATICCALL`).
+pub fn ensure_not_static(handle: &impl PrecompileHandle) -> EvmResult {
+	if handle.is_static() {
+		return Err(revert("can't call non-static function in static context"));
+	}
+	Ok(())
+}
+
+/// Charge the gas equivalent of `weight`.
+pub fn record_weight<T: pallet_evm::Config>(
+	handle: &mut impl PrecompileHandle,
+	weight: Weight,
+) -> EvmResult {
+	handle.record_cost(T::GasWeightMapping::weight_to_gas(weight))?;
+	Ok(())
+}
+
+/// Charge the gas equivalent of `reads` storage reads.
+pub fn record_db_reads<T: pallet_evm::Config>(
+	handle: &mut impl PrecompileHandle,
+	reads: u64,
+) -> EvmResult {
+	record_weight::<T>(handle, T::DbWeight::get().reads(reads))
+}
+
+/// Dispatch `call` from the account mapped from the EVM caller, charging its weight as gas.
+///
+/// The call's declared weight is charged up front and the unused part is refunded from
+/// the actual weight reported after dispatch.
+pub fn try_dispatch<T>(
+	handle: &mut impl PrecompileHandle,
+	origin: <T::RuntimeCall as Dispatchable>::RuntimeOrigin,
+	call: T::RuntimeCall,
+) -> EvmResult<PostDispatchInfo>
+where
+	T: pallet_evm::Config,
+	T::RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo,
+{
+	let info = call.get_dispatch_info();
+
+	if let Some(gas) = handle.gas_limit() {
+		let max_weight = T::GasWeightMapping::gas_to_weight(gas, false);
+		if info.weight.ref_time() > max_weight.ref_time() {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::OutOfGas,
+			});
+		}
+	}
+
+	handle.record_external_cost(Some(info.weight.ref_time()), Some(info.weight.proof_size()))?;
+
+	let post_info = call.dispatch(origin).map_err(|e| {
+		revert(alloc::format!(
+			"dispatch failed: {}",
+			<&'static str>::from(e.error)
+		))
+	})?;
+
+	let actual_weight = post_info.actual_weight.unwrap_or(info.weight);
+	handle.record_cost(T::GasWeightMapping::weight_to_gas(actual_weight))?;
+	handle.refund_external_cost(
+		Some(info.weight.ref_time().saturating_sub(actual_weight.ref_time())),
+		Some(info.weight.proof_size().saturating_sub(actual_weight.proof_size())),
+	);
+
+	Ok(post_info)
+}
+
+/// An EVM log ready to be recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Log {
+	pub address: H160,
+	pub topics: Vec<H256>,
+	pub data: Vec<u8>,
+}
+
+impl Log {
+	/// Record the log, charging `375 + 375 * topics + 8 * data.len()` gas as `LOGn` does.
+	pub fn record(self, handle: &mut impl PrecompileHandle) -> EvmResult {
+		handle.record_cost(log_cost(self.topics.len(), self.data.len()))?;
+		handle.log(self.address, self.topics, self.data)?;
+		Ok(())
+	}
+}
+
+/// Gas cost of a `LOGn` opcode with `topics` topics and `data_len` bytes of data.
+pub fn log_cost(topics: usize, data_len: usize) -> u64 {
+	375u64
+		.saturating_add(375u64.saturating_mul(topics as u64))
+		.saturating_add(8u64.saturating_mul(data_len as u64))
+}
+
+/// Builds logs emitted by the precompile at `address`.
+pub struct LogsBuilder {
+	address: H160,
+}
+
+impl LogsBuilder {
+	pub fn new(address: H160) -> Self {
+		Self { address }
+	}
+
+	pub fn log1(&self, topic0: H256, data: Vec<u8>) -> Log {
+		self.log(vec![topic0], data)
+	}
+
+	pub fn log2(&self, topic0: H256, topic1: impl Into<H256>, data: Vec<u8>) -> Log {
+		self.log(vec![topic0, topic1.into()], data)
+	}
+
+	pub fn log3(
+		&self,
+		topic0: H256,
+		topic1: impl Into<H256>,
+		topic2: impl Into<H256>,
+		data: Vec<u8>,
+	) -> Log {
+		self.log(vec![topic0, topic1.into(), topic2.into()], data)
+	}
+
+	pub fn log4(
+		&self,
+		topic0: H256,
+		topic1: impl Into<H256>,
+		topic2: impl Into<H256>,
+		topic3: impl Into<H256>,
+		data: Vec<u8>,
+	) -> Log {
+		self.log(vec![topic0, topic1.into(), topic2.into(), topic3.into()], data)
+	}
+
+	fn log(&self, topics: Vec<H256>, data: Vec<u8>) -> Log {
+		Log {
+			address: self.address,
+			topics,
+			data,
+		}
+	}
+}
+
+/// Encode a `U256` as a log topic.
+pub fn u256_topic(value: U256) -> H256 {
+	let mut topic = H256::zero();
+	value.to_big_endian(topic.as_bytes_mut());
+	topic
+}
+
+/// Code stored at a precompile's address so that Solidity's `extcodesize` check passes
+/// before calling it: `PUSH1 0x00 PUSH1 0x00 REVERT`.
+pub const REVERT_CODE: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xfd];
+
+/// Stores [`REVERT_CODE`] at `address` unless it already has code, returning the weight
+/// used.
+pub fn insert_revert_code<Runtime: pallet_evm::Config>(address: H160) -> Weight {
+	if pallet_evm::AccountCodes::<Runtime>::contains_key(address) {
+		return Runtime::DbWeight::get().reads(1);
+	}
+	pallet_evm::Pallet::<Runtime>::create_account(address, REVERT_CODE.to_vec());
+	// The code, its metadata and the account sufficients.
+	Runtime::DbWeight::get().reads_writes(3, 3)
+}
+
+/// Stores [`REVERT_CODE`] at `Address` on chains where the precompile was registered
+/// before it had code. Does nothing once the code is there.
+pub struct InsertRevertCode<Runtime, Address>(PhantomData<(Runtime, Address)>);
+
+impl<Runtime, Address> OnRuntimeUpgrade for InsertRevertCode<Runtime, Address>
+where
+	Runtime: pallet_evm::Config,
+	Address: Get<H160>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		insert_revert_code::<Runtime>(Address::get())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn static_arguments_round_trip() {
+		let address = H160::repeat_byte(0x11);
+		let input = EvmDataWriter::new()
+			.write(Address(address))
+			.write(U256::from(42))
+			.write(true)
+			.build();
+
+		let mut reader = EvmDataReader::new(&input);
+		assert_eq!(reader.read::<Address>().unwrap(), Address(address));
+		assert_eq!(reader.read::<U256>().unwrap(), U256::from(42));
+		assert!(reader.read::<bool>().unwrap());
+		assert!(reader.read::<U256>().is_err());
+	}
+
+	#[test]
+	fn dynamic_arguments_round_trip() {
+		let input = EvmDataWriter::new()
+			.write(U256::from(7))
+			.write(Bytes::from("ipfs"))
+			.write(vec![Bytes::from("a"), Bytes::from("bc")])
+			.build();
+
+		let mut reader = EvmDataReader::new(&input);
+		assert_eq!(reader.read::<U256>().unwrap(), U256::from(7));
+		assert_eq!(reader.read::<Bytes>().unwrap(), Bytes::from("ipfs"));
+		assert_eq!(
+			reader.read::<Vec<Bytes>>().unwrap(),
+			vec![Bytes::from("a"), Bytes::from("bc")]
+		);
+	}
+
+	#[test]
+	fn bytes_encoding_matches_solidity() {
+		// abi.encode(bytes("ab"))
+		let encoded = EvmDataWriter::new().write(Bytes::from("ab")).build();
+		let mut expected = vec![0u8; 96];
+		expected[31] = 0x20;
+		expected[63] = 0x02;
+		expected[64] = b'a';
+		expected[65] = b'b';
+		assert_eq!(encoded, expected);
+	}
+
+	#[test]
+	fn rejects_dirty_address() {
+		let input = [0xffu8; 32];
+		assert!(EvmDataReader::new(&input).read::<Address>().is_err());
+	}
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [`PrecompileHandle`] for unit tests of precompiles, answering their subcalls with a
+//! scripted result.
+
+use crate::Log;
+use fp_evm::{Context, ExitError, ExitReason, PrecompileHandle, Transfer};
+use sp_core::{H160, H256, U256};
+use sp_std::vec::Vec;
+
+/// A subcall the precompile under test made through [`PrecompileHandle::call`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Subcall {
+	pub address: H160,
+	pub input: Vec<u8>,
+	pub caller: H160,
+	pub is_static: bool,
+}
+
+/// Handle of a call to the precompile at `code_address`, recording the gas it charges,
+/// the logs it emits and the subcalls it makes.
+pub struct MockHandle {
+	pub input: Vec<u8>,
+	pub context: Context,
+	pub code_address: H160,
+	pub is_static: bool,
+	pub gas_limit: u64,
+	pub gas_used: u64,
+	pub logs: Vec<Log>,
+	pub subcalls: Vec<Subcall>,
+	pub subcall_result: Option<(ExitReason, Vec<u8>)>,
+}
+
+impl MockHandle {
+	/// A plain `CALL` from `caller` to the precompile at `precompile`.
+	pub fn new(precompile: H160, caller: H160, input: Vec<u8>) -> Self {
+		Self {
+			input,
+			context: Context {
+				address: precompile,
+				caller,
+				apparent_value: U256::zero(),
+			},
+			code_address: precompile,
+			is_static: false,
+			gas_limit: u64::MAX,
+			gas_used: 0,
+			logs: Vec::new(),
+			subcalls: Vec::new(),
+			subcall_result: None,
+		}
+	}
+
+	/// Run the precompile's code in the context of `contract`, as `DELEGATECALL` does.
+	pub fn delegated_from(mut self, contract: H160) -> Self {
+		self.context.address = contract;
+		self
+	}
+
+	/// Make the call a `STATICCALL`.
+	pub fn static_call(mut self) -> Self {
+		self.is_static = true;
+		self
+	}
+
+	/// Attach `value` to the call.
+	pub fn with_value(mut self, value: U256) -> Self {
+		self.context.apparent_value = value;
+		self
+	}
+
+	/// Answer every subcall with `reason` and `output`. Without it, a subcall panics.
+	pub fn with_subcall_result(mut self, reason: ExitReason, output: Vec<u8>) -> Self {
+		self.subcall_result = Some((reason, output));
+		self
+	}
+}
+
+impl PrecompileHandle for MockHandle {
+	fn call(
+		&mut self,
+		address: H160,
+		_: Option<Transfer>,
+		input: Vec<u8>,
+		_: Option<u64>,
+		is_static: bool,
+		context: &Context,
+	) -> (ExitReason, Vec<u8>) {
+		self.subcalls.push(Subcall {
+			address,
+			input,
+			caller: context.caller,
+			is_static,
+		});
+		self.subcall_result
+			.clone()
+			.expect("the test scripted no subcall result")
+	}
+
+	fn record_cost(&mut self, cost: u64) -> Result<(), ExitError> {
+		self.gas_used = self.gas_used.saturating_add(cost);
+		if self.gas_used > self.gas_limit {
+			return Err(ExitError::OutOfGas);
+		}
+		Ok(())
+	}
+
+	fn record_external_cost(&mut self, _: Option<u64>, _: Option<u64>) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	fn refund_external_cost(&mut self, _: Option<u64>, _: Option<u64>) {}
+
+	fn remaining_gas(&self) -> u64 {
+		self.gas_limit.saturating_sub(self.gas_used)
+	}
+
+	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
+		self.logs.push(Log {
+			address,
+			topics,
+			data,
+		});
+		Ok(())
+	}
+
+	fn code_address(&self) -> H160 {
+		self.code_address
+	}
+
+	fn input(&self) -> &[u8] {
+		&self.input
+	}
+
+	fn context(&self) -> &Context {
+		&self.context
+	}
+
+	fn is_static(&self) -> bool {
+		self.is_static
+	}
+
+	fn gas_limit(&self) -> Option<u64> {
+		Some(self.gas_limit)
+	}
+}
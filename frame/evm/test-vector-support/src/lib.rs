@@ -20,7 +20,7 @@
 use std::fs;
 
 use evm::{Context, ExitError, ExitReason, ExitSucceed, Transfer};
-use fp_evm::{Precompile, PrecompileFailure, PrecompileHandle};
+use fp_evm::{Precompile, PrecompileFailure, PrecompileHandle, PrecompileResult, PrecompileSet};
 use sp_core::{H160, H256};
 
 #[derive(Debug, serde::Deserialize)]
@@ -46,6 +46,7 @@ pub struct MockHandle {
 	pub context: Context,
 	pub is_static: bool,
 	pub gas_used: u64,
+	pub code_address: H160,
 }
 
 impl MockHandle {
@@ -56,6 +57,7 @@ impl MockHandle {
 			context,
 			is_static: false,
 			gas_used: 0,
+			code_address: Default::default(),
 		}
 	}
 }
@@ -95,7 +97,7 @@ impl PrecompileHandle for MockHandle {
 	}
 
 	fn code_address(&self) -> H160 {
-		unimplemented!()
+		self.code_address
 	}
 
 	fn input(&self) -> &[u8] {
@@ -119,6 +121,30 @@ impl PrecompileHandle for MockHandle {
 /// The file is expected to be in JSON format and contain an array of test vectors, where each
 /// vector can be deserialized into an "EthConsensusTest".
 pub fn test_precompile_test_vectors<P: Precompile>(filepath: &str) -> Result<(), String> {
+	run_test_vectors(filepath, 0, P::execute)
+}
+
+/// Same as [`test_precompile_test_vectors`], but runs the vectors against the precompile found
+/// at `address` in `precompiles`, which charges `lookup_cost` gas on top of the precompile.
+pub fn test_precompile_set_test_vectors<S: PrecompileSet>(
+	precompiles: &S,
+	address: H160,
+	lookup_cost: u64,
+	filepath: &str,
+) -> Result<(), String> {
+	run_test_vectors(filepath, lookup_cost, |handle| {
+		handle.code_address = address;
+		precompiles
+			.execute(handle)
+			.unwrap_or_else(|| panic!("no precompile at {:?}", address))
+	})
+}
+
+fn run_test_vectors(
+	filepath: &str,
+	lookup_cost: u64,
+	mut execute: impl FnMut(&mut MockHandle) -> PrecompileResult,
+) -> Result<(), String> {
 	let data = fs::read_to_string(filepath).expect("Failed to read blake2F.json");
 
 	let tests: Vec<EthConsensusTest> = serde_json::from_str(&data).expect("expected json array");
@@ -136,7 +162,7 @@ pub fn test_precompile_test_vectors<P: Precompile>(filepath: &str) -> Result<(),
 
 		let mut handle = MockHandle::new(input, Some(cost), context);
 
-		match P::execute(&mut handle) {
+		match execute(&mut handle) {
 			Ok(result) => {
 				let as_hex: String = hex::encode(result.output);
 				assert_eq!(
@@ -153,7 +179,8 @@ pub fn test_precompile_test_vectors<P: Precompile>(filepath: &str) -> Result<(),
 				);
 				if let Some(expected_gas) = test.gas {
 					assert_eq!(
-						handle.gas_used, expected_gas,
+						handle.gas_used,
+						expected_gas + lookup_cost,
 						"test '{}' failed (different gas cost)",
 						test.name
 					);
@@ -169,6 +196,28 @@ pub fn test_precompile_test_vectors<P: Precompile>(filepath: &str) -> Result<(),
 }
 
 pub fn test_precompile_failure_test_vectors<P: Precompile>(filepath: &str) -> Result<(), String> {
+	run_failure_test_vectors(filepath, P::execute)
+}
+
+/// Same as [`test_precompile_failure_test_vectors`], but runs the vectors against the precompile
+/// found at `address` in `precompiles`.
+pub fn test_precompile_set_failure_test_vectors<S: PrecompileSet>(
+	precompiles: &S,
+	address: H160,
+	filepath: &str,
+) -> Result<(), String> {
+	run_failure_test_vectors(filepath, |handle| {
+		handle.code_address = address;
+		precompiles
+			.execute(handle)
+			.unwrap_or_else(|| panic!("no precompile at {:?}", address))
+	})
+}
+
+fn run_failure_test_vectors(
+	filepath: &str,
+	mut execute: impl FnMut(&mut MockHandle) -> PrecompileResult,
+) -> Result<(), String> {
 	let data = fs::read_to_string(filepath).expect("Failed to read json file");
 
 	let tests: Vec<EthConsensusFailureTest> =
@@ -187,7 +236,7 @@ pub fn test_precompile_failure_test_vectors<P: Precompile>(filepath: &str) -> Re
 
 		let mut handle = MockHandle::new(input, Some(cost), context);
 
-		match P::execute(&mut handle) {
+		match execute(&mut handle) {
 			Ok(..) => {
 				unreachable!("Test should be failed");
 			}
@@ -25,6 +25,7 @@ use argochain_runtime::{
 	DemocracyConfig, ElectionsConfig, ImOnlineConfig, IndicesConfig, MaxNominations,
 	NominationPoolsConfig, SessionConfig, SessionKeys, SocietyConfig, StakerStatus, StakingConfig,
 	SudoConfig, SystemConfig, TechnicalCommitteeConfig,GrandpaConfig,AuthorityDiscoveryConfig,EthereumConfig,
+	precompile_code,
 };
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use sc_chain_spec::ChainSpecExtension;
@@ -69,6 +70,19 @@ pub type ChainSpec = sc_service::GenericChainSpec<RuntimeGenesisConfig, Extensio
 // 	ChainSpec::from_json_bytes(&include_bytes!("../res/flaming-fir.json")[..])
 // }
 
+/// The accounts of the precompiles holding code, so that contracts can call them.
+fn precompile_accounts() -> impl Iterator<Item = (H160, GenesisAccount)> {
+	precompile_code().into_iter().map(|(address, code)| {
+		let account = GenesisAccount {
+			balance: U256::zero(),
+			code,
+			nonce: Default::default(),
+			storage: Default::default(),
+		};
+		(address, account)
+	})
+}
+
 fn session_keys(
 	grandpa: GrandpaId,
 	babe: BabeId,
@@ -468,11 +482,7 @@ pub fn testnet_genesis(
 		// EVM compatibility
 		// EVM compatibility
 		evm: EVMConfig {
-			accounts: {
-				let mut map = BTreeMap::new();
-				
-				map
-			},
+			accounts: precompile_accounts().collect(),
 			_marker: Default::default(),
 		},
 		ethereum: EthereumConfig {
@@ -480,6 +490,7 @@ pub fn testnet_genesis(
 		},
 		dynamic_fee: Default::default(),
 		base_fee: Default::default(),
+		precompile_registry: Default::default(),
 	}
 }
 
@@ -628,7 +639,7 @@ pub fn development_genesis(
 		// EVM compatibility
 		evm: EVMConfig {
 			accounts: {
-				let mut map = BTreeMap::new();
+				let mut map = precompile_accounts().collect::<BTreeMap<_, _>>();
 				map.insert(
 
 					H160::from_str("05E053aB0f66422d243C1F14Da2091CD56F51F73")
@@ -672,6 +683,7 @@ pub fn development_genesis(
 		},
 		dynamic_fee: Default::default(),
 		base_fee: Default::default(),
+		precompile_registry: Default::default(),
 	}
 }
 
@@ -25,6 +25,9 @@ use crate::client::{FullBackend, FullClient};
 /// Frontier DB backend type.
 pub type FrontierBackend = fc_db::Backend<Block>;
 
+/// Store of the call traces served by the `trace` namespace.
+pub type TraceStore = fc_db::kv::trace::TraceDb<Block>;
+
 pub fn db_config_dir(config: &Configuration) -> PathBuf {
 	config.base_path.config_dir(config.chain_spec.id())
 }
@@ -39,6 +42,15 @@ pub enum BackendType {
 	// Sql,
 }
 
+/// Optional Ethereum RPC namespaces.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum EthApi {
+	/// `debug_trace*` methods, replaying blocks under the EVM tracer.
+	Debug,
+	/// `trace_*` methods, served from call traces indexed in the background.
+	Trace,
+}
+
 /// The ethereum-compatibility configuration used to run a node.
 #[derive(Clone, Debug, clap::Parser)]
 pub struct EthConfiguration {
@@ -53,6 +65,20 @@ pub struct EthConfiguration {
 	#[arg(long)]
 	pub enable_dev_signer: bool,
 
+	/// Optional Ethereum RPC namespaces to enable, comma separated.
+	#[arg(long, value_delimiter = ',', value_enum, ignore_case = true)]
+	pub ethapi: Vec<EthApi>,
+
+	/// Number of finalized blocks to keep call traces of for the `trace` namespace,
+	/// all of them when unset. Without `--state-pruning archive` only blocks whose
+	/// parent state is still kept get traced.
+	#[arg(long)]
+	pub trace_retention_blocks: Option<u32>,
+
+	/// Maximum number of traces returned by `trace_filter`.
+	#[arg(long, default_value = "500")]
+	pub trace_max_count: usize,
+
 	/// The dynamic-fee pallet target gas price set by block author
 	#[arg(long, default_value = "1")]
 	pub target_gas_price: u64,
@@ -70,6 +96,10 @@ pub struct EthConfiguration {
 	#[arg(long, default_value = "50")]
 	pub eth_statuses_cache: usize,
 
+	/// Size in MB of the LRU cache for transaction receipts.
+	#[arg(long, default_value = "50")]
+	pub eth_receipts_cache: usize,
+
 	/// Sets the frontier backend type (KeyValue or Sql)
 	#[arg(long, value_enum, ignore_case = true, default_value_t = BackendType::default())]
 	pub frontier_backend_type: BackendType,
@@ -113,6 +143,7 @@ pub trait EthCompatRuntimeApiCollection:
 sp_api::ApiExt<Block>
 + fp_rpc::ConvertTransactionRuntimeApi<Block>
 + fp_rpc::EthereumRuntimeRPCApi<Block>
++ fp_evm_tracing::DebugRuntimeApi<Block>
 	where
 		<Self as sp_api::ApiExt<Block>>::StateBackend: sp_api::StateBackend<BlakeTwo256>,
 {
@@ -122,7 +153,8 @@ impl<Api> EthCompatRuntimeApiCollection for Api
 	where
 		Api: sp_api::ApiExt<Block>
 		+ fp_rpc::ConvertTransactionRuntimeApi<Block>
-		+ fp_rpc::EthereumRuntimeRPCApi<Block>,
+		+ fp_rpc::EthereumRuntimeRPCApi<Block>
+		+ fp_evm_tracing::DebugRuntimeApi<Block>,
 		<Self as sp_api::ApiExt<Block>>::StateBackend: sp_api::StateBackend<BlakeTwo256>,
 {
 }
@@ -132,6 +164,7 @@ pub fn spawn_frontier_tasks<RuntimeApi, Executor>(
 	client: Arc<FullClient<RuntimeApi, Executor>>,
 	backend: Arc<FullBackend>,
 	frontier_backend: FrontierBackend,
+	trace_store: Option<(Arc<TraceStore>, Option<u32>)>,
 	filter_pool: Option<FilterPool>,
 	overrides: Arc<OverrideHandle<Block>>,
 	fee_history_cache: FeeHistoryCache,
@@ -159,7 +192,7 @@ pub fn spawn_frontier_tasks<RuntimeApi, Executor>(
 					client.import_notification_stream(),
 					Duration::new(6, 0),
 					client.clone(),
-					backend,
+					backend.clone(),
 					overrides.clone(),
 					Arc::new(b),
 					3,
@@ -192,6 +225,26 @@ pub fn spawn_frontier_tasks<RuntimeApi, Executor>(
 		// }
 	}
 
+	// Spawn the worker filling the trace store, it replays every finalized block whose
+	// parent state is still available.
+	if let Some((trace_store, retention)) = trace_store {
+		task_manager.spawn_handle().spawn_blocking(
+			"frontier-trace-sync-worker",
+			Some("frontier"),
+			fc_mapping_sync::kv::TraceSyncWorker::run(
+				client.clone(),
+				backend,
+				trace_store,
+				client.finality_notification_stream(),
+				fc_mapping_sync::kv::TraceSyncWorkerConfig {
+					retention,
+					batch_size: 100,
+					check_interval: Duration::from_secs(30),
+				},
+			),
+		);
+	}
+
 	// Spawn Frontier EthFilterApi maintenance task.
 	if let Some(filter_pool) = filter_pool {
 		// Each filter is allowed to stay in the pool for 100 blocks.
@@ -5,6 +5,7 @@ use jsonrpsee::RpcModule;
 use sc_client_api::{
 	backend::{Backend, StorageProvider},
 	client::BlockchainEvents,
+	BlockBackend, ProofProvider,
 };
 use sc_network::NetworkService;
 use sc_network_sync::SyncingService;
@@ -20,7 +21,10 @@ use sp_runtime::traits::Block as BlockT;
 pub use fc_rpc::{EthBlockDataCacheTask, EthConfig, OverrideHandle, StorageOverride};
 pub use fc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
 pub use fc_storage::overrides_handle;
+use fp_evm_tracing::DebugRuntimeApi;
 use fp_rpc::{ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi};
+// Local
+use crate::eth::EthApi;
 
 /// Extra dependencies for Ethereum compatibility.
 pub struct EthDeps<C, P, A: ChainApi, CT, B: BlockT> {
@@ -36,12 +40,18 @@ pub struct EthDeps<C, P, A: ChainApi, CT, B: BlockT> {
 	pub is_authority: bool,
 	/// Whether to enable dev signer
 	pub enable_dev_signer: bool,
+	/// Optional RPC namespaces to enable.
+	pub ethapi: Vec<EthApi>,
 	/// Network service
 	pub network: Arc<NetworkService<B, B::Hash>>,
 	/// Chain syncing service
 	pub sync: Arc<SyncingService<B>>,
 	/// Frontier Backend.
 	pub frontier_backend: Arc<dyn fc_db::BackendReader<B> + Send + Sync>,
+	/// Store of indexed call traces, when the `trace` namespace is enabled.
+	pub trace_store: Option<Arc<fc_db::kv::trace::TraceDb<B>>>,
+	/// Maximum number of traces returned by `trace_filter`.
+	pub trace_max_count: usize,
 	/// Ethereum data access overrides.
 	pub overrides: Arc<OverrideHandle<B>>,
 	/// Cache for Ethereum block data.
@@ -70,9 +80,12 @@ impl<C, P, A: ChainApi, CT: Clone, B: BlockT> Clone for EthDeps<C, P, A, CT, B>
 			converter: self.converter.clone(),
 			is_authority: self.is_authority,
 			enable_dev_signer: self.enable_dev_signer,
+			ethapi: self.ethapi.clone(),
 			network: self.network.clone(),
 			sync: self.sync.clone(),
 			frontier_backend: self.frontier_backend.clone(),
+			trace_store: self.trace_store.clone(),
+			trace_max_count: self.trace_max_count,
 			overrides: self.overrides.clone(),
 			block_data_cache: self.block_data_cache.clone(),
 			filter_pool: self.filter_pool.clone(),
@@ -100,7 +113,8 @@ pub fn create_eth<C, BE, P, A, CT, B, EC: EthConfig<B, C>>(
 		B: BlockT<Hash = sp_core::H256>,
 		C: CallApiAt<B> + ProvideRuntimeApi<B>,
 		C::Api: BlockBuilderApi<B> + ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
-		C: BlockchainEvents<B> + 'static,
+		C::Api: DebugRuntimeApi<B>,
+		C: BlockchainEvents<B> + BlockBackend<B> + ProofProvider<B> + 'static,
 		C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError> + StorageProvider<B, BE>,
 		BE: Backend<B> + 'static,
 		P: TransactionPool<Block = B> + 'static,
@@ -108,8 +122,9 @@ pub fn create_eth<C, BE, P, A, CT, B, EC: EthConfig<B, C>>(
 		CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
 {
 	use fc_rpc::{
-		Eth, EthApiServer, EthDevSigner, EthFilter, EthFilterApiServer, EthPubSub,
-		EthPubSubApiServer, EthSigner, Net, NetApiServer, Web3, Web3ApiServer,
+		Debug, DebugApiServer, Eth, EthApiServer, EthDevSigner, EthFilter, EthFilterApiServer,
+		EthPubSub, EthPubSubApiServer, EthSigner, Net, NetApiServer, Trace, TraceApiServer, Web3,
+		Web3ApiServer,
 	};
 	#[cfg(feature = "txpool")]
 	use fc_rpc::{TxPool, TxPoolApiServer};
@@ -121,9 +136,12 @@ pub fn create_eth<C, BE, P, A, CT, B, EC: EthConfig<B, C>>(
 		converter,
 		is_authority,
 		enable_dev_signer,
+		ethapi,
 		network,
 		sync,
 		frontier_backend,
+		trace_store,
+		trace_max_count,
 		overrides,
 		block_data_cache,
 		filter_pool,
@@ -160,6 +178,22 @@ pub fn create_eth<C, BE, P, A, CT, B, EC: EthConfig<B, C>>(
 			.into_rpc(),
 	)?;
 
+	if ethapi.contains(&EthApi::Debug) {
+		io.merge(Debug::new(client.clone(), frontier_backend.clone()).into_rpc())?;
+	}
+
+	if let Some(trace_store) = trace_store {
+		io.merge(
+			Trace::new(
+				client.clone(),
+				frontier_backend.clone(),
+				trace_store,
+				trace_max_count,
+			)
+				.into_rpc(),
+		)?;
+	}
+
 	if let Some(filter_pool) = filter_pool {
 		io.merge(
 			EthFilter::new(
@@ -115,6 +115,18 @@ impl<C, BE> fc_rpc::EthConfig<Block, C> for DefaultEthConfig<C, BE>
 	type EstimateGasAdapter = ();
 	type RuntimeStorageOverride =
 	fc_rpc::frontier_backend_client::SystemAccountId20StorageOverride<Block, C, BE>;
+	type AccountLayout = AccountLayout;
+}
+
+/// Where the runtime keeps the accounts `eth_getProof` proves.
+pub struct AccountLayout;
+
+impl fp_account_proof::AccountLayout for AccountLayout {
+	const UNIFIED_ACCOUNTS: Option<&'static str> = Some("UnifiedAccounts");
+	const SYSTEM: &'static str = "System";
+	const EVM: &'static str = "EVM";
+	type Nonce = Nonce;
+	type Balance = Balance;
 }
 
 /// Instantiate all Full RPC extensions.
@@ -150,12 +162,16 @@ pub fn create_full<C, P, BE,SC, B, A, CT>(
 		C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 		C::Api: mmr_rpc::MmrRuntimeApi<Block, <Block as sp_runtime::traits::Block>::Hash, BlockNumber>,
 		C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+		C::Api: pallet_counter_rpc::PalletCounterRuntimeApi<Block, AccountId, Balance, BlockNumber>,
+		C::Api: pallet_unified_accounts_rpc::UnifiedAccountsRuntimeApi<Block, AccountId>,
 		C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
 		C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+		C::Api: fp_evm_tracing::DebugRuntimeApi<Block>,
 		C: BlockchainEvents<Block> + 'static,
 		C: HeaderBackend<Block>
 		+ HeaderMetadata<Block, Error = BlockChainError>
-		+ StorageProvider<Block, BE>,
+		+ StorageProvider<Block, BE>
+		+ sc_client_api::ProofProvider<Block>,
 		C: CallApiAt<Block> + ProvideRuntimeApi<Block>,
 		C::Api: BabeApi<Block>,
 		C::Api: BlockBuilder<Block>,
@@ -169,7 +185,9 @@ pub fn create_full<C, P, BE,SC, B, A, CT>(
 		CT: fp_rpc::ConvertTransaction<<Block as BlockT>::Extrinsic> + Send + Sync + 'static,
 {
 	use mmr_rpc::{Mmr, MmrApiServer};
+	use pallet_counter_rpc::{Counter, CounterApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use pallet_unified_accounts_rpc::{UnifiedAccounts, UnifiedAccountsApiServer};
 	use sc_consensus_babe_rpc::{Babe, BabeApiServer};
 	use sc_consensus_grandpa_rpc::{Grandpa, GrandpaApiServer};
 	use sc_rpc::{
@@ -211,6 +229,8 @@ pub fn create_full<C, P, BE,SC, B, A, CT>(
 			.into_rpc(),
 	)?;
 	io.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	io.merge(Counter::<_, _, Balance>::new(client.clone()).into_rpc())?;
+	io.merge(UnifiedAccounts::new(client.clone()).into_rpc())?;
 	io.merge(
 		Babe::new(client.clone(), babe_worker_handle.clone(), keystore, select_chain, deny_unsafe)
 			.into_rpc(),
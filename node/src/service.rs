@@ -62,8 +62,9 @@ use fc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
 use crate::{
 	client::{BaseRuntimeApiCollection, RuntimeApiCollection},
 	eth::{
-		new_frontier_partial, spawn_frontier_tasks, BackendType, EthCompatRuntimeApiCollection,
-		FrontierBackend, FrontierBlockImport, FrontierPartialComponents,
+		new_frontier_partial, spawn_frontier_tasks, BackendType, EthApi,
+		EthCompatRuntimeApiCollection, FrontierBackend, FrontierBlockImport,
+		FrontierPartialComponents, TraceStore,
 	},
 };
 pub use crate::{
@@ -349,6 +350,12 @@ pub fn new_full_base(
 		fee_history_cache_limit,
 	} = new_frontier_partial(&eth_config)?;
 
+	let trace_store = if eth_config.ethapi.contains(&EthApi::Trace) {
+		Some(Arc::new(TraceStore::open(&config.database, &db_config_dir(&config))?))
+	} else {
+		None
+	};
+
 
 	let auth_disc_publish_non_global_ips = config.network.allow_non_globals_in_dht;
 	let mut net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
@@ -424,17 +431,21 @@ pub fn new_full_base(
 		converter: Some(TransactionConverter),
 		is_authority: config.role.is_authority(),
 		enable_dev_signer: eth_config.enable_dev_signer,
+		ethapi: eth_config.ethapi.clone(),
 		network: network.clone(),
 		sync: sync_service.clone(),
 		frontier_backend: match frontier_backend.clone() {
 			fc_db::Backend::KeyValue(b) => Arc::new(b),
 		},
+		trace_store: trace_store.clone(),
+		trace_max_count: eth_config.trace_max_count,
 		overrides: overrides.clone(),
 		block_data_cache: Arc::new(fc_rpc::EthBlockDataCacheTask::new(
 			task_manager.spawn_handle(),
 			overrides.clone(),
 			eth_config.eth_log_block_cache,
 			eth_config.eth_statuses_cache,
+			eth_config.eth_receipts_cache.saturating_mul(1024 * 1024),
 			prometheus_registry.clone(),
 		)),
 		filter_pool: filter_pool.clone(),
@@ -533,6 +544,7 @@ pub fn new_full_base(
 		client.clone(),
 		backends,
 		frontier_backend,
+		trace_store.map(|store| (store, eth_config.trace_retention_blocks)),
 		filter_pool,
 		overrides,
 		fee_history_cache,
@@ -0,0 +1,263 @@
+//! RPC interface for the counter pallet.
+//!
+//! Exposes the read-only parts of [`PalletCounterApi`] under the `counter_` prefix, so
+//! wallets can query bridge balances and build signatures without paying for extrinsics.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hexdisplay::HexDisplay, H160, H256, U256};
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::traits::Block as BlockT;
+
+pub use pallet_counter_runtime_api::PalletCounterApi as PalletCounterRuntimeApi;
+use pallet_counter_runtime_api::{BridgeDirection, EvmToSubstratePayload, PalletCounterApi};
+
+/// Direction of a bridge crossing, as accepted by `counter_bridgeFee`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    SubstrateToEvm,
+    EvmToSubstrate,
+}
+
+impl From<Direction> for BridgeDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::SubstrateToEvm => BridgeDirection::SubstrateToEvm,
+            Direction::EvmToSubstrate => BridgeDirection::EvmToSubstrate,
+        }
+    }
+}
+
+/// What a wallet signs to authorize `evm_to_substrate`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmToSubstrateTypedData {
+    /// Nonce the signature is bound to.
+    pub nonce: U256,
+    /// EIP-712 digest the signature must cover.
+    pub digest: H256,
+    /// Payload for `eth_signTypedData_v4`.
+    pub typed_data: Value,
+}
+
+impl From<EvmToSubstratePayload> for EvmToSubstrateTypedData {
+    fn from(payload: EvmToSubstratePayload) -> Self {
+        let typed_data = json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                ],
+                "EvmToSubstrate": [
+                    { "name": "from", "type": "address" },
+                    { "name": "to", "type": "bytes32" },
+                    { "name": "amount", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" },
+                ],
+            },
+            "primaryType": "EvmToSubstrate",
+            "domain": {
+                "name": String::from_utf8_lossy(&payload.domain_name),
+                "version": String::from_utf8_lossy(&payload.domain_version),
+                "chainId": payload.chain_id,
+            },
+            "message": {
+                "from": format!("{:?}", payload.from),
+                "to": format!("0x{}", HexDisplay::from(&payload.to)),
+                "amount": payload.amount.to_string(),
+                "nonce": payload.nonce.to_string(),
+                "deadline": payload.deadline.to_string(),
+            },
+        });
+
+        Self { nonce: payload.nonce, digest: payload.digest, typed_data }
+    }
+}
+
+#[rpc(client, server)]
+pub trait CounterApi<BlockHash, AccountId, BlockNumber> {
+    /// Balance of `address` as seen by the EVM.
+    #[method(name = "counter_evmBalance")]
+    fn evm_balance(&self, address: H160, at: Option<BlockHash>) -> RpcResult<U256>;
+
+    /// Total amount still reserved by the locks of `who`.
+    #[method(name = "counter_lockedBalance")]
+    fn locked_balance(&self, who: AccountId, at: Option<BlockHash>) -> RpcResult<NumberOrHex>;
+
+    /// Nonce the next `evm_to_substrate` signature of `address` must use.
+    #[method(name = "counter_bridgeNonce")]
+    fn bridge_nonce(&self, address: H160, at: Option<BlockHash>) -> RpcResult<U256>;
+
+    /// Typed data `from` has to sign to move `amount` to `to` before block `deadline`.
+    #[method(name = "counter_evmToSubstrateTypedData")]
+    fn evm_to_substrate_typed_data(
+        &self,
+        from: H160,
+        to: AccountId,
+        amount: U256,
+        deadline: BlockNumber,
+        at: Option<BlockHash>,
+    ) -> RpcResult<EvmToSubstrateTypedData>;
+
+    /// Inclusion fee, without tip, of a bridge crossing in `direction`.
+    #[method(name = "counter_bridgeFee")]
+    fn bridge_fee(&self, direction: Direction, at: Option<BlockHash>) -> RpcResult<NumberOrHex>;
+}
+
+/// Provides RPC methods to query the counter pallet.
+pub struct Counter<C, Block, Balance> {
+    client: Arc<C>,
+    _marker: PhantomData<(Block, Balance)>,
+}
+
+impl<C, Block, Balance> Counter<C, Block, Balance> {
+    /// Creates a new instance of the Counter RPC helper.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client, _marker: Default::default() }
+    }
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// The call to runtime failed.
+    RuntimeError,
+    /// The destination account cannot receive bridged funds.
+    InvalidDestination,
+    /// The runtime does not implement the method.
+    UnsupportedRuntime,
+}
+
+impl From<Error> for i32 {
+    fn from(e: Error) -> i32 {
+        match e {
+            Error::RuntimeError => 1,
+            Error::InvalidDestination => 2,
+            Error::UnsupportedRuntime => 3,
+        }
+    }
+}
+
+fn runtime_error(message: &'static str, e: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(Error::RuntimeError.into(), message, Some(format!("{:?}", e)))).into()
+}
+
+/// Version of `PalletCounterApi` that introduced the methods served here.
+const QUERIES_API_VERSION: u32 = 4;
+
+impl<C, Block, Balance> Counter<C, Block, Balance>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+{
+    /// Fails unless the runtime at `at` implements the queries of this RPC.
+    fn ensure_supported<AccountId, BlockNumber>(&self, at: Block::Hash) -> RpcResult<()>
+    where
+        C::Api: PalletCounterApi<Block, AccountId, Balance, BlockNumber>,
+        AccountId: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        let version = self
+            .client
+            .runtime_api()
+            .api_version::<dyn PalletCounterApi<Block, AccountId, Balance, BlockNumber>>(at)
+            .map_err(|e| runtime_error("Unable to query the runtime api version.", e))?;
+        match version {
+            Some(version) if version >= QUERIES_API_VERSION => Ok(()),
+            _ => Err(CallError::Custom(ErrorObject::owned(
+                Error::UnsupportedRuntime.into(),
+                "The runtime does not support this query.",
+                None::<()>,
+            ))
+            .into()),
+        }
+    }
+}
+
+#[async_trait]
+impl<C, Block, AccountId, Balance, BlockNumber> CounterApiServer<<Block as BlockT>::Hash, AccountId, BlockNumber>
+    for Counter<C, Block, Balance>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: PalletCounterApi<Block, AccountId, Balance, BlockNumber>,
+    AccountId: Codec + Send + Sync + 'static,
+    Balance: Codec + Into<NumberOrHex> + Send + Sync + 'static,
+    BlockNumber: Codec + Send + Sync + 'static,
+{
+    fn evm_balance(&self, address: H160, at: Option<Block::Hash>) -> RpcResult<U256> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.ensure_supported::<AccountId, BlockNumber>(at)?;
+        self.client
+            .runtime_api()
+            .evm_balance(at, address)
+            .map_err(|e| runtime_error("Unable to query EVM balance.", e))
+    }
+
+    fn locked_balance(&self, who: AccountId, at: Option<Block::Hash>) -> RpcResult<NumberOrHex> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.ensure_supported::<AccountId, BlockNumber>(at)?;
+        self.client
+            .runtime_api()
+            .locked_balance(at, who)
+            .map(Into::into)
+            .map_err(|e| runtime_error("Unable to query locked balance.", e))
+    }
+
+    fn bridge_nonce(&self, address: H160, at: Option<Block::Hash>) -> RpcResult<U256> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.ensure_supported::<AccountId, BlockNumber>(at)?;
+        self.client
+            .runtime_api()
+            .bridge_nonce(at, address)
+            .map_err(|e| runtime_error("Unable to query bridge nonce.", e))
+    }
+
+    fn evm_to_substrate_typed_data(
+        &self,
+        from: H160,
+        to: AccountId,
+        amount: U256,
+        deadline: BlockNumber,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<EvmToSubstrateTypedData> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.ensure_supported::<AccountId, BlockNumber>(at)?;
+        let payload = self
+            .client
+            .runtime_api()
+            .evm_to_substrate_payload(at, from, to, amount, deadline)
+            .map_err(|e| runtime_error("Unable to build typed data.", e))?
+            .ok_or_else(|| {
+                CallError::Custom(ErrorObject::owned(
+                    Error::InvalidDestination.into(),
+                    "Destination is not a 32 byte account.",
+                    None::<()>,
+                ))
+            })?;
+        Ok(payload.into())
+    }
+
+    fn bridge_fee(&self, direction: Direction, at: Option<Block::Hash>) -> RpcResult<NumberOrHex> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.ensure_supported::<AccountId, BlockNumber>(at)?;
+        self.client
+            .runtime_api()
+            .bridge_fee(at, direction.into())
+            .map(Into::into)
+            .map_err(|e| runtime_error("Unable to estimate bridge fee.", e))
+    }
+}
@@ -0,0 +1,71 @@
+//! Runtime API definition for the counter pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_core::{H160, U256};
+use sp_std::vec::Vec;
+
+pub use pallet_counter::{
+    eip712::EvmToSubstratePayload,
+    locks::CounterLock,
+    memo::{MemoError, MemoPolicy},
+    BridgeDirection, IpfsRecord,
+};
+
+sp_api::decl_runtime_apis! {
+    /// Runtime api for querying the counter pallet without submitting extrinsics.
+    ///
+    /// Version history:
+    ///
+    /// 1. `ipfs_record`, `ipfs_hashes_of`
+    /// 2. `memo_policy`, `validate_memo`
+    /// 3. `locks_of`
+    /// 4. `evm_balance`, `locked_balance`, `bridge_nonce`, `evm_to_substrate_payload`,
+    ///    `bridge_fee`
+    ///
+    /// Clients should check `api_version` before calling a method the runtime may not have.
+    #[api_version(4)]
+    pub trait PalletCounterApi<AccountId, Balance, BlockNumber>
+        where
+            AccountId: Codec,
+            Balance: Codec,
+            BlockNumber: Codec,
+    {
+        /// Returns the registry entry of the textual CID `cid`, in any of its forms, if any.
+        fn ipfs_record(cid: Vec<u8>) -> Option<IpfsRecord<AccountId, BlockNumber, Vec<u8>>>;
+
+        /// Returns every CID registered by `who` together with its registry entry.
+        fn ipfs_hashes_of(who: AccountId) -> Vec<(Vec<u8>, IpfsRecord<AccountId, BlockNumber, Vec<u8>>)>;
+
+        /// Returns the memo policy `balance_transfer_new` currently enforces.
+        fn memo_policy() -> MemoPolicy<Vec<Vec<u8>>>;
+
+        /// Dry-runs the memo policy, returning the first rule `memo` breaks.
+        fn validate_memo(memo: Vec<u8>) -> Result<(), MemoError>;
+
+        /// Returns the locks of `who`, each with the amount `unlock` could release now.
+        fn locks_of(who: AccountId) -> Vec<(CounterLock<Balance, BlockNumber, Vec<u8>>, Balance)>;
+
+        /// Returns the balance of `address` as seen by the EVM.
+        fn evm_balance(address: H160) -> U256;
+
+        /// Returns the total amount still reserved by the locks of `who`.
+        fn locked_balance(who: AccountId) -> Balance;
+
+        /// Returns the nonce the next `evm_to_substrate` signature of `address` must use.
+        fn bridge_nonce(address: H160) -> U256;
+
+        /// Returns the EIP-712 typed data and digest `from` has to sign to move `amount`
+        /// to `to`, or `None` if `to` cannot be a bridge destination.
+        fn evm_to_substrate_payload(
+            from: H160,
+            to: AccountId,
+            amount: U256,
+            deadline: BlockNumber,
+        ) -> Option<EvmToSubstratePayload>;
+
+        /// Returns the inclusion fee, without tip, of a bridge crossing in `direction`.
+        fn bridge_fee(direction: BridgeDirection) -> Balance;
+    }
+}
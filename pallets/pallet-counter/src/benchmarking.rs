@@ -1,53 +1,382 @@
-#[cfg(feature = "runtime-benchmarks")]
-mod benchmarking;
-
+//! Benchmarks for pallet_counter
 
 use super::*;
-use frame_benchmarking::{benchmarks, whitelisted_caller, account};
-use frame_system::RawOrigin;
+use crate::{
+    limits::BridgeLimits,
+    locks::{LockId, LockRelease},
+    memo::{MemoCharset, MemoPolicy},
+    Pallet as PalletCounter,
+};
+use frame_benchmarking::{benchmarks, whitelisted_caller, BenchmarkError};
+use frame_support::{
+    assert_ok,
+    traits::{Currency, EnsureOrigin, Get, ReservableCurrency},
+    BoundedVec,
+};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use pallet_evm::AddressMapping;
+use sp_core::{crypto::KeyTypeId, sr25519, H160, U256};
+use sp_runtime::{traits::Saturating, SaturatedConversion};
+use sp_std::vec;
+use sp_std::vec::Vec;
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"bnch");
+const CID: &[u8] = b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+fn balance<T: Config>(multiple: u32) -> SubstrateBalanceOf<T> {
+    T::SubstrateCurrency::minimum_balance().saturating_mul(multiple.into())
+}
+
+fn funded_account<T: Config>() -> T::AccountId {
+    let who: T::AccountId = whitelisted_caller();
+    T::SubstrateCurrency::make_free_balance_be(&who, balance::<T>(1_000));
+    who
+}
+
+fn cid<T: Config>() -> CidOf<T> {
+    CID.to_vec().try_into().expect("benchmark CID fits MaxCidLength")
+}
+
+fn attestor(index: u32) -> sr25519::Public {
+    let mut raw = [0u8; 32];
+    raw[..4].copy_from_slice(&index.to_le_bytes());
+    sr25519::Public::from_raw(raw)
+}
+
+fn set_attestors<T: Config>(attestors: Vec<sr25519::Public>) {
+    <Attestors<T>>::put(BoundedVec::<_, T::MaxAttestors>::truncate_from(attestors));
+}
+
+/// Cap `direction` generously, queueing only transfers of at least `delay_threshold`.
+fn set_limits<T: Config>(direction: BridgeDirection, delay_threshold: SubstrateBalanceOf<T>) {
+    <BridgeLimitsByDirection<T>>::insert(
+        direction,
+        BridgeLimits {
+            window: 100u32.into(),
+            per_transfer_cap: balance::<T>(10_000),
+            per_account_cap: balance::<T>(10_000),
+            global_cap: balance::<T>(10_000),
+            delay_threshold,
+            delay: 10u32.into(),
+        },
+    );
+}
+
+/// Queue a `substrate_to_evm` transfer of 100 existential deposits from `who`.
+fn queue_transfer<T: Config>(who: &T::AccountId) -> PendingTransferId {
+    set_limits::<T>(BridgeDirection::SubstrateToEvm, balance::<T>(1));
+    assert_ok!(PalletCounter::<T>::substrate_to_evm(
+        RawOrigin::Signed(who.clone()).into(),
+        H160::repeat_byte(0x11),
+        balance::<T>(100),
+        true,
+    ));
+    <NextPendingTransferId<T>>::get() - 1
+}
+
+fn lock_id(index: u32) -> LockId {
+    let mut id = *b"lock    ";
+    id[4..].copy_from_slice(&index.to_le_bytes());
+    id
+}
+
+/// Give `who` `count` locks of one existential deposit each, releasable at any time.
+fn fill_locks<T: Config>(who: &T::AccountId, count: u32) {
+    for index in 0..count {
+        assert_ok!(PalletCounter::<T>::lock(
+            RawOrigin::Signed(who.clone()).into(),
+            lock_id(index),
+            balance::<T>(1),
+            LockRelease::Anytime,
+            Default::default(),
+        ));
+    }
+}
+
+/// Generate an ECDSA key in the keystore and return it with its Ethereum address.
+fn evm_key() -> (sp_core::ecdsa::Public, H160) {
+    let public = sp_io::crypto::ecdsa_generate(KEY_TYPE, None);
+    let probe = [0u8; 32];
+    let signature = sp_io::crypto::ecdsa_sign_prehashed(KEY_TYPE, &public, &probe)
+        .expect("key was just generated");
+    let address = fp_account::recover_ethereum_signer(&signature.0, &probe).expect("signature is valid");
+    (public, address)
+}
 
 benchmarks! {
+    where_clause { where T::AccountId: From<[u8; 32]> }
+
     mint {
-        let caller: T::AccountId = whitelisted_caller();
-        let amount: BalanceOf<T> = 100u32.into();
-    }: _(RawOrigin::Root, caller.clone(), amount)
+        let account: T::AccountId = whitelisted_caller();
+        let amount = balance::<T>(100);
+    }: _(RawOrigin::Root, account.clone(), amount)
     verify {
-        assert_eq!(T::Currency::free_balance(&caller), amount);
+        assert_eq!(T::SubstrateCurrency::free_balance(&account), amount);
     }
 
     burn {
-        let caller: T::AccountId = whitelisted_caller();
-        let amount: BalanceOf<T> = 100u32.into();
-        T::Currency::deposit_creating(&caller, amount);
-    }: _(RawOrigin::Root, caller.clone(), amount)
+        let account = funded_account::<T>();
+        let amount = balance::<T>(100);
+    }: _(RawOrigin::Root, account.clone(), amount)
     verify {
-        assert_eq!(T::Currency::free_balance(&caller), 0u32.into());
+        assert_eq!(T::SubstrateCurrency::free_balance(&account), balance::<T>(900));
     }
 
     lock {
-        let caller: T::AccountId = whitelisted_caller();
-        let amount: BalanceOf<T> = 100u32.into();
-        T::Currency::deposit_creating(&caller, amount);
-    }: _(RawOrigin::Signed(caller.clone()), amount)
+        let caller = funded_account::<T>();
+        fill_locks::<T>(&caller, T::MaxLocksPerAccount::get() - 1);
+        let amount = balance::<T>(100);
+        let reason = BoundedVec::truncate_from(vec![b'r'; T::MaxLockReasonLength::get() as usize]);
+        let release = LockRelease::Linear { start: 10u32.into(), end: 100u32.into() };
+    }: _(RawOrigin::Signed(caller.clone()), *b"bench   ", amount, release, reason)
     verify {
-        assert_eq!(T::Currency::reserved_balance(&caller), amount);
-        assert_eq!(LockedBalance::<T>::get(&caller), amount);
+        assert_eq!(
+            PalletCounter::<T>::locked_balance(&caller),
+            balance::<T>(T::MaxLocksPerAccount::get() - 1).saturating_add(amount)
+        );
     }
 
     unlock {
-        let caller: T::AccountId = whitelisted_caller();
-        let amount: BalanceOf<T> = 100u32.into();
-        T::Currency::reserve(&caller, amount)?;
-        <LockedBalance<T>>::insert(&caller, amount);
-    }: _(RawOrigin::Signed(caller.clone()), amount)
+        let caller = funded_account::<T>();
+        let max = T::MaxLocksPerAccount::get();
+        fill_locks::<T>(&caller, max);
+    }: _(RawOrigin::Signed(caller.clone()), lock_id(max - 1))
     verify {
-        assert_eq!(T::Currency::reserved_balance(&caller), 0u32.into());
-        assert_eq!(LockedBalance::<T>::get(&caller), 0u32.into());
+        assert_eq!(PalletCounter::<T>::locked_balance(&caller), balance::<T>(max - 1));
+    }
+
+    check_evm_balance {
+        let caller = funded_account::<T>();
+    }: _(RawOrigin::Signed(caller), H160::repeat_byte(0x11))
+
+    substrate_to_evm {
+        let caller = funded_account::<T>();
+        let evm_address = H160::repeat_byte(0x11);
+        let amount = balance::<T>(100);
+        set_limits::<T>(BridgeDirection::SubstrateToEvm, balance::<T>(10_000));
+    }: _(RawOrigin::Signed(caller), evm_address, amount, true)
+    verify {
+        let evm_account = <T as pallet_evm::Config>::AddressMapping::into_account_id(evm_address);
+        assert_eq!(T::SubstrateCurrency::free_balance(&evm_account), amount);
+    }
+
+    evm_to_substrate {
+        let caller = funded_account::<T>();
+        let dest: T::AccountId = [7u8; 32].into();
+        let (public, evm_address) = evm_key();
+        let evm_account = <T as pallet_evm::Config>::AddressMapping::into_account_id(evm_address);
+        T::SubstrateCurrency::make_free_balance_be(&evm_account, balance::<T>(1_000));
+        set_limits::<T>(BridgeDirection::EvmToSubstrate, balance::<T>(10_000));
+
+        let amount = U256::from(balance::<T>(100).saturated_into::<u128>());
+        let deadline: BlockNumberFor<T> = frame_system::Pallet::<T>::block_number() + 10u32.into();
+        let digest = PalletCounter::<T>::evm_to_substrate_digest(
+            evm_address,
+            &dest,
+            amount,
+            U256::zero(),
+            deadline,
+        )?;
+        let signature = sp_io::crypto::ecdsa_sign_prehashed(KEY_TYPE, &public, &digest)
+            .ok_or(BenchmarkError::Stop("failed to sign digest"))?;
+    }: _(RawOrigin::Signed(caller), evm_address, dest.clone(), amount, deadline, signature)
+    verify {
+        assert_eq!(T::SubstrateCurrency::free_balance(&dest), balance::<T>(100));
+        assert_eq!(PalletCounter::<T>::evm_nonce(evm_address), U256::one());
+    }
+
+    // `do_evm_to_substrate` as the bridge precompile calls it, authenticated by the EVM
+    // caller rather than a signature.
+    transfer_to_substrate {
+        let dest: T::AccountId = [7u8; 32].into();
+        let evm_address = H160::repeat_byte(0x22);
+        let evm_account = <T as pallet_evm::Config>::AddressMapping::into_account_id(evm_address);
+        T::SubstrateCurrency::make_free_balance_be(&evm_account, balance::<T>(1_000));
+        set_limits::<T>(BridgeDirection::EvmToSubstrate, balance::<T>(10_000));
+        let amount = U256::from(balance::<T>(100).saturated_into::<u128>());
+    }: {
+        PalletCounter::<T>::do_evm_to_substrate(evm_address, dest.clone(), amount)?;
+    }
+    verify {
+        assert_eq!(T::SubstrateCurrency::free_balance(&dest), balance::<T>(100));
+    }
+
+    balance_transfer_new {
+        let m in 0 .. T::MaxMemoLength::get();
+
+        let caller = funded_account::<T>();
+        // The most expensive policy governance can set: every blocked substring at full
+        // length, each matching the memo up to its last byte in every window.
+        let mut substring = vec![b'a'; T::MaxBlockedSubstringLength::get() as usize - 1];
+        substring.push(b'b');
+        let blocked = (0..T::MaxBlockedSubstrings::get())
+            .map(|_| BoundedVec::truncate_from(substring.clone()))
+            .collect::<Vec<_>>();
+        <ActiveMemoPolicy<T>>::put(MemoPolicy {
+            max_length: T::MaxMemoLength::get(),
+            charset: MemoCharset::Utf8,
+            blocked_substrings: BoundedVec::truncate_from(blocked),
+            link_detection: true,
+        });
+        let to: T::AccountId = [7u8; 32].into();
+        let amount = balance::<T>(100);
+        let message = vec![b'a'; m as usize];
+    }: _(RawOrigin::Signed(caller), to.clone(), amount, message)
+    verify {
+        assert_eq!(T::SubstrateCurrency::free_balance(&to), amount);
     }
-}
 
-impl_benchmark_test_suite!(
-    Pallet,
-    crate::mock::new_test_ext(),
-    crate::mock::Test,
-);
+    include_ipfs_hash {
+        let a in 1 .. T::MaxAttestors::get();
+
+        let caller = funded_account::<T>();
+        let ipfs_hash = cid::<T>();
+        let attestors: Vec<_> = (0..a).map(|_| sp_io::crypto::sr25519_generate(KEY_TYPE, None)).collect();
+        set_attestors::<T>(attestors.clone());
+        <AttestationThreshold<T>>::put(a);
+
+        let payload = PalletCounter::<T>::ipfs_attestation_payload(&caller, 0, &ipfs_hash);
+        let attestations = attestors
+            .into_iter()
+            .map(|public| {
+                let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &public, &payload)
+                    .expect("key was just generated");
+                (public, signature)
+            })
+            .collect::<Vec<_>>();
+        let attestations: BoundedVec<_, T::MaxAttestors> = BoundedVec::truncate_from(attestations);
+    }: _(RawOrigin::Signed(caller.clone()), ipfs_hash.clone(), None, attestations)
+    verify {
+        assert_eq!(PalletCounter::<T>::ipfs_record(&ipfs_hash).map(|r| r.owner), Some(caller));
+    }
+
+    add_attestor {
+        let a in 0 .. T::MaxAttestors::get() - 1;
+
+        set_attestors::<T>((0..a).map(attestor).collect());
+        let new = attestor(a);
+        let origin = T::AttestorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, new)
+    verify {
+        assert!(PalletCounter::<T>::attestors().contains(&new));
+    }
+
+    remove_attestor {
+        let a in 2 .. T::MaxAttestors::get();
+
+        set_attestors::<T>((0..a).map(attestor).collect());
+        let removed = attestor(a - 1);
+        let origin = T::AttestorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, removed)
+    verify {
+        assert!(!PalletCounter::<T>::attestors().contains(&removed));
+    }
+
+    rotate_attestor {
+        let a in 1 .. T::MaxAttestors::get();
+
+        set_attestors::<T>((0..a).map(attestor).collect());
+        let old = attestor(a - 1);
+        let new = attestor(a);
+        let origin = T::AttestorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, old, new)
+    verify {
+        assert!(PalletCounter::<T>::attestors().contains(&new));
+    }
+
+    set_attestation_threshold {
+        let max = T::MaxAttestors::get();
+        set_attestors::<T>((0..max).map(attestor).collect());
+        let origin = T::AttestorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, max)
+    verify {
+        assert_eq!(PalletCounter::<T>::attestation_threshold(), max);
+    }
+
+    deregister_ipfs_hash {
+        let caller = funded_account::<T>();
+        let ipfs_hash = cid::<T>();
+        let key: CidOf<T> = crate::cid::decode(&ipfs_hash)
+            .expect("benchmark CID is valid")
+            .1
+            .try_into()
+            .expect("binary CID is shorter than its text");
+        <IpfsRegistry<T>>::insert(
+            &key,
+            IpfsRecord {
+                owner: caller.clone(),
+                registered_at: frame_system::Pallet::<T>::block_number(),
+                version: 0,
+                codec: 0x70,
+                tag: None,
+            },
+        );
+        <IpfsHashesByOwner<T>>::insert(&caller, &key, &ipfs_hash);
+    }: _(RawOrigin::Signed(caller), ipfs_hash.clone())
+    verify {
+        assert!(PalletCounter::<T>::ipfs_record(&ipfs_hash).is_none());
+    }
+
+    set_memo_policy {
+        let s in 0 .. T::MaxBlockedSubstrings::get();
+
+        let substring = vec![b'x'; T::MaxBlockedSubstringLength::get() as usize];
+        let blocked = (0..s).map(|_| BoundedVec::truncate_from(substring.clone())).collect::<Vec<_>>();
+        let policy = MemoPolicy {
+            max_length: T::MaxMemoLength::get(),
+            charset: MemoCharset::Utf8,
+            blocked_substrings: BoundedVec::truncate_from(blocked),
+            link_detection: true,
+        };
+        let origin = T::MemoPolicyOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, policy.clone())
+    verify {
+        assert_eq!(PalletCounter::<T>::memo_policy(), policy);
+    }
+
+    set_bridge_limits {
+        let limits = BridgeLimits {
+            window: 100u32.into(),
+            per_transfer_cap: balance::<T>(10),
+            per_account_cap: balance::<T>(10),
+            global_cap: balance::<T>(100),
+            delay_threshold: balance::<T>(5),
+            delay: 10u32.into(),
+        };
+        let origin = T::BridgeGuardOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, BridgeDirection::EvmToSubstrate, Some(limits))
+    verify {
+        assert_eq!(PalletCounter::<T>::bridge_limits(BridgeDirection::EvmToSubstrate), Some(limits));
+    }
+
+    set_bridge_paused {
+        let origin = T::BridgeGuardOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, BridgeDirection::EvmToSubstrate, true)
+    verify {
+        assert!(PalletCounter::<T>::bridge_paused(BridgeDirection::EvmToSubstrate));
+    }
+
+    execute_pending_transfer {
+        let caller = funded_account::<T>();
+        let id = queue_transfer::<T>(&caller);
+        let execute_at = PalletCounter::<T>::pending_transfer(id).map(|p| p.execute_at).unwrap_or_default();
+        frame_system::Pallet::<T>::set_block_number(execute_at);
+    }: _(RawOrigin::Signed(caller), id)
+    verify {
+        assert!(PalletCounter::<T>::pending_transfer(id).is_none());
+        let evm_account = <T as pallet_evm::Config>::AddressMapping::into_account_id(H160::repeat_byte(0x11));
+        assert_eq!(T::SubstrateCurrency::free_balance(&evm_account), balance::<T>(100));
+    }
+
+    cancel_pending_transfer {
+        let caller = funded_account::<T>();
+        let id = queue_transfer::<T>(&caller);
+        let origin = T::BridgeGuardOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, id)
+    verify {
+        assert!(PalletCounter::<T>::pending_transfer(id).is_none());
+        assert_eq!(T::SubstrateCurrency::reserved_balance(&caller), 0u32.into());
+    }
+
+    impl_benchmark_test_suite!(PalletCounter, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,218 @@
+//! Minimal, `no_std` parser for IPFS content identifiers.
+//!
+//! Supports the two textual forms wallets and pinning services hand out:
+//!
+//! * CIDv0: 46 base58btc characters starting with `Qm`, an sha2-256 multihash.
+//! * CIDv1: a multibase string, either base32 lower-case (`b…`, e.g. `bafy…`) or
+//!   base58btc (`z…`), encoding `<version><codec><multihash>` as unsigned varints.
+
+use sp_std::vec::Vec;
+
+/// Multihash code of sha2-256, the only hash allowed in a CIDv0.
+pub const SHA2_256: u64 = 0x12;
+/// Multicodec of dag-protobuf, the implicit codec of a CIDv0.
+pub const DAG_PB: u64 = 0x70;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Decoded header of a valid CID.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cid {
+    /// CID version, 0 or 1.
+    pub version: u8,
+    /// Multicodec of the addressed content.
+    pub codec: u64,
+    /// Multihash function code.
+    pub hash_code: u64,
+    /// Length of the hash digest in bytes.
+    pub digest_len: u64,
+}
+
+/// Reasons a byte string is not a valid CID.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CidError {
+    /// Not valid UTF-8 / ASCII text in the expected alphabet.
+    InvalidEncoding,
+    /// Multibase prefix is not supported.
+    UnsupportedMultibase,
+    /// Version is neither 0 nor 1.
+    UnsupportedVersion,
+    /// Multihash is truncated or its length does not match the digest.
+    InvalidMultihash,
+}
+
+/// Parses and validates a textual CID.
+pub fn parse(text: &[u8]) -> Result<Cid, CidError> {
+    decode(text).map(|(cid, _)| cid)
+}
+
+/// Parses a textual CID and returns it along with its binary CIDv1 form.
+///
+/// A CIDv0 is converted to the dag-pb CIDv1 it is equivalent to and every varint is
+/// re-encoded minimally, so all textual forms of one CID yield the same bytes.
+pub fn decode(text: &[u8]) -> Result<(Cid, Vec<u8>), CidError> {
+    if text.len() == 46 && text.starts_with(b"Qm") {
+        let bytes = decode_base58(text)?;
+        let (hash_code, digest) = parse_multihash(&bytes)?;
+        if hash_code != SHA2_256 || digest.len() != 32 {
+            return Err(CidError::InvalidMultihash);
+        }
+        let cid = Cid { version: 0, codec: DAG_PB, hash_code, digest_len: 32 };
+        return Ok((cid, encode_v1(DAG_PB, hash_code, digest)));
+    }
+
+    let (prefix, rest) = text.split_first().ok_or(CidError::InvalidEncoding)?;
+    let bytes = match prefix {
+        b'b' => decode_base32(rest)?,
+        b'z' => decode_base58(rest)?,
+        _ => return Err(CidError::UnsupportedMultibase),
+    };
+
+    let mut cursor = &bytes[..];
+    let version = read_varint(&mut cursor).ok_or(CidError::InvalidEncoding)?;
+    if version != 1 {
+        return Err(CidError::UnsupportedVersion);
+    }
+    let codec = read_varint(&mut cursor).ok_or(CidError::InvalidEncoding)?;
+    let (hash_code, digest) = parse_multihash(cursor)?;
+
+    let cid = Cid { version: 1, codec, hash_code, digest_len: digest.len() as u64 };
+    Ok((cid, encode_v1(codec, hash_code, digest)))
+}
+
+/// Returns `(hash_code, digest)` of a multihash that spans all of `bytes`.
+fn parse_multihash(mut bytes: &[u8]) -> Result<(u64, &[u8]), CidError> {
+    let hash_code = read_varint(&mut bytes).ok_or(CidError::InvalidMultihash)?;
+    let digest_len = read_varint(&mut bytes).ok_or(CidError::InvalidMultihash)?;
+    if digest_len == 0 || bytes.len() as u64 != digest_len {
+        return Err(CidError::InvalidMultihash);
+    }
+    Ok((hash_code, bytes))
+}
+
+/// `<1><codec><hash_code><digest length><digest>`, every number as a minimal varint.
+fn encode_v1(codec: u64, hash_code: u64, digest: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(digest.len() + 16);
+    write_varint(&mut bytes, 1);
+    write_varint(&mut bytes, codec);
+    write_varint(&mut bytes, hash_code);
+    write_varint(&mut bytes, digest.len() as u64);
+    bytes.extend_from_slice(digest);
+    bytes
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads an unsigned LEB128 varint of at most 9 bytes, as used by multiformats.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value: u64 = 0;
+    for i in 0..9 {
+        let (byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn decode_base58(text: &[u8]) -> Result<Vec<u8>, CidError> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len());
+    for &c in text {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(CidError::InvalidEncoding)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+    for _ in text.iter().take_while(|&&c| c == b'1') {
+        bytes.push(0);
+    }
+    bytes.reverse();
+    Ok(bytes)
+}
+
+fn decode_base32(text: &[u8]) -> Result<Vec<u8>, CidError> {
+    let mut bytes = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0u32;
+    for &c in text {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(CidError::InvalidEncoding)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // Unpadded RFC 4648: leftover bits must be zero padding.
+    if bits >= 5 || buffer != 0 {
+        return Err(CidError::InvalidEncoding);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cid_v0() {
+        let cid = parse(b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG").unwrap();
+        assert_eq!(cid, Cid { version: 0, codec: DAG_PB, hash_code: SHA2_256, digest_len: 32 });
+    }
+
+    #[test]
+    fn parses_cid_v1_base32() {
+        let cid = parse(b"bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+        assert_eq!(cid, Cid { version: 1, codec: DAG_PB, hash_code: SHA2_256, digest_len: 32 });
+
+        let raw = parse(b"bafkreigh2akiscaildcqabsyg3dfr6chu3fgpregiymsck7e7aqa4s52zy").unwrap();
+        assert_eq!(raw.codec, 0x55);
+    }
+
+    #[test]
+    fn every_form_of_a_cid_has_one_binary_form() {
+        let v0 = decode(b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG").unwrap().1;
+        let v1_base32 = decode(b"bafybeie5nqv6kd3qnfjupgvz34woh3oksc3iau6abmyajn7qvtf6d2ho34").unwrap().1;
+        let v1_base58 = decode(b"zdj7Wg2Qkk4mYgAkVU1kppfQ2sMGz5zPwERVpeWmxCQLDxVoC").unwrap().1;
+
+        assert_eq!(&v0[..4], &[0x01, 0x70, 0x12, 0x20]);
+        assert_eq!(v0, v1_base32);
+        assert_eq!(v0, v1_base58);
+    }
+
+    #[test]
+    fn rejects_malformed_cids() {
+        assert_eq!(parse(b""), Err(CidError::InvalidEncoding));
+        assert_eq!(parse(b"1.2.3.4"), Err(CidError::UnsupportedMultibase));
+        assert_eq!(
+            parse(b"bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzd"),
+            Err(CidError::InvalidEncoding)
+        );
+        assert_eq!(
+            parse(b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPb0G"),
+            Err(CidError::InvalidEncoding)
+        );
+    }
+}
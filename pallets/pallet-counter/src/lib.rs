@@ -1,29 +1,101 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
 pub use pallet::*;
 
+/// Direction of a bridge crossing.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum BridgeDirection {
+    /// From a Substrate account to the account mapped from an H160.
+    SubstrateToEvm,
+    /// From the account mapped from an H160 to a Substrate account.
+    EvmToSubstrate,
+}
+
+/// Total issuance observed around a bridge crossing.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct SupplyAudit<Balance> {
+    pub direction: BridgeDirection,
+    pub amount: Balance,
+    pub issuance_before: Balance,
+    pub issuance_after: Balance,
+}
+
+pub mod cid;
+pub mod eip712;
+pub mod limits;
+pub mod locks;
+pub mod memo;
+pub mod migrations;
+pub mod weights;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub use weights::WeightInfo;
+
+/// A CID registered through `include_ipfs_hash`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct IpfsRecord<AccountId, BlockNumber, Tag> {
+    /// Account that registered the CID and may de-register it.
+    pub owner: AccountId,
+    /// Block in which the CID was registered.
+    pub registered_at: BlockNumber,
+    /// CID version, 0 or 1.
+    pub version: u8,
+    /// Multicodec of the addressed content.
+    pub codec: u64,
+    /// Optional free-form tag supplied by the owner.
+    pub tag: Option<Tag>,
+}
+
+impl<AccountId, BlockNumber, Tag> IpfsRecord<AccountId, BlockNumber, Tag> {
+    /// Converts the tag, e.g. from a bounded on-chain vector into a plain one.
+    pub fn map_tag<NewTag>(self, f: impl FnOnce(Tag) -> NewTag) -> IpfsRecord<AccountId, BlockNumber, NewTag> {
+        IpfsRecord {
+            owner: self.owner,
+            registered_at: self.registered_at,
+            version: self.version,
+            codec: self.codec,
+            tag: self.tag.map(f),
+        }
+    }
+}
+
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::{
         dispatch::DispatchResult,
         pallet_prelude::*,
-        traits::{Currency, ReservableCurrency},
+        traits::{
+            fungible,
+            tokens::Preservation,
+            Currency, ReservableCurrency,
+        },
     };
     use frame_system::pallet_prelude::*;
-    use pallet_evm::Pallet as EvmPallet;
-    use sp_core::{H160, U256, ecdsa};
-    use sp_runtime::traits::SaturatedConversion;
-    #[allow(unused_imports)]
-    use sp_io::crypto::secp256k1_ecdsa_recover_compressed;
-    use sp_io::hashing::keccak_256;
-    use scale_info::prelude::format;
+    use pallet_evm::{AddressMapping, Pallet as EvmPallet};
+    use sp_core::{H160, H256, U256, ecdsa, sr25519};
+    use sp_runtime::traits::{SaturatedConversion, Saturating, Zero};
     use sp_std::vec::Vec;
     use hex_literal::hex;
-    use pallet_evm:: PrecompileSet;
-    use sp_io::crypto::secp256k1_ecdsa_recover;
     use frame_support::traits::ExistenceRequirement;
-    use sp_runtime::AccountId32;
+    use crate::{
+        cid, eip712,
+        limits::{BridgeLimits, PendingTransfer, WindowUsage},
+        locks::{CounterLock, LockId, LockRelease},
+        memo::{MemoCharset, MemoError, MemoPolicy},
+        BridgeDirection, IpfsRecord, SupplyAudit, WeightInfo,
+    };
 
 
     // Define the authorized backend account (common account for safety)
@@ -31,36 +103,238 @@ pub mod pallet {
     //     "64882b6b92eefc93a7e9c929681a7facc12eb8c5ee505c610aa207a5e7c46206"
     // ));
 
-    type SubstrateBalanceOf<T> = <<T as Config>::SubstrateCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type SubstrateBalanceOf<T> = <<T as Config>::SubstrateCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type CidOf<T> = BoundedVec<u8, <T as Config>::MaxCidLength>;
+    pub type IpfsTagOf<T> = BoundedVec<u8, <T as Config>::MaxIpfsTagLength>;
+    pub type IpfsRecordOf<T> = IpfsRecord<<T as frame_system::Config>::AccountId, BlockNumberFor<T>, IpfsTagOf<T>>;
+    pub type LockReasonOf<T> = BoundedVec<u8, <T as Config>::MaxLockReasonLength>;
+    pub type CounterLockOf<T> = CounterLock<SubstrateBalanceOf<T>, BlockNumberFor<T>, LockReasonOf<T>>;
+    pub type BridgeLimitsOf<T> = BridgeLimits<SubstrateBalanceOf<T>, BlockNumberFor<T>>;
+    pub type WindowUsageOf<T> = WindowUsage<SubstrateBalanceOf<T>, BlockNumberFor<T>>;
+    pub type PendingTransferOf<T> =
+        PendingTransfer<<T as frame_system::Config>::AccountId, SubstrateBalanceOf<T>, BlockNumberFor<T>>;
+    pub type PendingTransferId = u64;
+    pub type BlockedSubstringsOf<T> = BoundedVec<
+        BoundedVec<u8, <T as Config>::MaxBlockedSubstringLength>,
+        <T as Config>::MaxBlockedSubstrings,
+    >;
+    pub type MemoPolicyOf<T> = MemoPolicy<BlockedSubstringsOf<T>>;
+
     #[allow(dead_code)]
     type EvmBalanceOf<T> = <<T as Config>::EvmCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+    /// Legacy backend key that signed IPFS hashes before the attestor registry existed.
+    pub const LEGACY_BACKEND_ATTESTOR: [u8; 32] =
+        hex!("7c650b5b9f657ddcc7a6ddbf9147d33f3b6ffda5009658b1ee6b7e3665a99701");
+
+    /// Domain tag prepended to every IPFS attestation payload.
+    pub const IPFS_ATTESTATION_CONTEXT: &[u8] = b"argochain/ipfs-attestation";
+
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
     pub trait Config: frame_system::Config + pallet_evm::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
-        type SubstrateCurrency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+        /// Native currency. It must be the currency backing `pallet_evm` accounts, since the
+        /// bridge moves funds to and from `AddressMapping::into_account_id(h160)` with it.
+        type SubstrateCurrency: Currency<Self::AccountId>
+            + ReservableCurrency<Self::AccountId>
+            + fungible::Mutate<Self::AccountId>;
         type EvmCurrency: Currency<Self::AccountId>;
+        /// Origin allowed to manage the IPFS attestor registry.
+        type AttestorOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Maximum number of registered attestors.
+        #[pallet::constant]
+        type MaxAttestors: Get<u32>;
+        /// Maximum length in bytes of a textual CID.
+        #[pallet::constant]
+        type MaxCidLength: Get<u32>;
+        /// Maximum length in bytes of the tag attached to a CID.
+        #[pallet::constant]
+        type MaxIpfsTagLength: Get<u32>;
+        /// Origin allowed to set bridge limits, pause the bridge and cancel queued transfers.
+        type BridgeGuardOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Maximum number of simultaneous locks per account.
+        #[pallet::constant]
+        type MaxLocksPerAccount: Get<u32>;
+        /// Maximum length in bytes of the reason attached to a lock.
+        #[pallet::constant]
+        type MaxLockReasonLength: Get<u32>;
+        /// Origin allowed to change the memo policy of `balance_transfer_new`.
+        type MemoPolicyOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Upper bound on the memo length a policy may allow.
+        #[pallet::constant]
+        type MaxMemoLength: Get<u32>;
+        /// Maximum number of blocked substrings in the memo policy.
+        #[pallet::constant]
+        type MaxBlockedSubstrings: Get<u32>;
+        /// Maximum length in bytes of a blocked substring.
+        #[pallet::constant]
+        type MaxBlockedSubstringLength: Get<u32>;
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Locks held by each account. Every lock reserves its remaining amount.
+    #[pallet::storage]
+    #[pallet::getter(fn locks)]
+    pub type Locks<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<CounterLockOf<T>, T::MaxLocksPerAccount>, ValueQuery>;
+
+    /// Sum of what the pallet keeps reserved: the remaining amount of every lock and the amount
+    /// of every queued crossing.
+    #[pallet::storage]
+    #[pallet::getter(fn total_reserved)]
+    pub type TotalReserved<T: Config> = StorageValue<_, SubstrateBalanceOf<T>, ValueQuery>;
+
+    /// Issuance observed around the most recent bridge crossing.
+    #[pallet::storage]
+    #[pallet::getter(fn last_supply_audit)]
+    pub type LastSupplyAudit<T: Config> = StorageValue<_, SupplyAudit<SubstrateBalanceOf<T>>, OptionQuery>;
+
+    /// Next EIP-712 nonce expected from each EVM account by `evm_to_substrate`.
+    #[pallet::storage]
+    #[pallet::getter(fn evm_nonce)]
+    pub type EvmNonces<T: Config> = StorageMap<_, Blake2_128Concat, H160, U256, ValueQuery>;
+
+    /// sr25519 keys allowed to attest IPFS hashes.
+    #[pallet::storage]
+    #[pallet::getter(fn attestors)]
+    pub type Attestors<T: Config> = StorageValue<_, BoundedVec<sr25519::Public, T::MaxAttestors>, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultAttestationThreshold() -> u32 {
+        1
+    }
+
+    /// Number of distinct attestor signatures `include_ipfs_hash` requires.
+    #[pallet::storage]
+    #[pallet::getter(fn attestation_threshold)]
+    pub type AttestationThreshold<T: Config> =
+        StorageValue<_, u32, ValueQuery, DefaultAttestationThreshold>;
+
+    /// Next attestation nonce expected for each submitting account.
+    #[pallet::storage]
+    #[pallet::getter(fn attestation_nonce)]
+    pub type AttestationNonces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+
+    /// Registered CIDs, keyed by their binary CIDv1 form (see [`cid::decode`]) so that the
+    /// CIDv0 and every multibase encoding of one CID share an entry.
+    #[pallet::storage]
+    pub type IpfsRegistry<T: Config> = StorageMap<_, Blake2_128Concat, CidOf<T>, IpfsRecordOf<T>, OptionQuery>;
+
+    /// Index of registered CIDs by owner: binary CID to the textual CID that was submitted.
+    #[pallet::storage]
+    pub type IpfsHashesByOwner<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, CidOf<T>, CidOf<T>, OptionQuery>;
+
+    /// The rules `balance_transfer_new` enforced before the memo policy became editable.
+    #[pallet::type_value]
+    pub fn DefaultMemoPolicy<T: Config>() -> MemoPolicyOf<T> {
+        let blocked = ["scam", "fraud", "hack", "illegal", "phishing"]
+            .iter()
+            .map(|word| BoundedVec::truncate_from(word.as_bytes().to_vec()))
+            .collect::<Vec<_>>();
+        MemoPolicy {
+            max_length: 64u32.min(T::MaxMemoLength::get()),
+            charset: MemoCharset::PrintableAscii,
+            blocked_substrings: BoundedVec::truncate_from(blocked),
+            link_detection: true,
+        }
     }
 
+    /// Rules the memo of `balance_transfer_new` must satisfy.
+    #[pallet::storage]
+    #[pallet::getter(fn memo_policy)]
+    pub type ActiveMemoPolicy<T: Config> = StorageValue<_, MemoPolicyOf<T>, ValueQuery, DefaultMemoPolicy<T>>;
+
+    /// Rate limits of each bridge direction. Directions without limits are uncapped.
+    #[pallet::storage]
+    #[pallet::getter(fn bridge_limits)]
+    pub type BridgeLimitsByDirection<T: Config> =
+        StorageMap<_, Twox64Concat, BridgeDirection, BridgeLimitsOf<T>, OptionQuery>;
+
+    /// Directions in which crossings are currently refused.
+    #[pallet::storage]
+    #[pallet::getter(fn bridge_paused)]
+    pub type BridgePaused<T: Config> = StorageMap<_, Twox64Concat, BridgeDirection, bool, ValueQuery>;
+
+    /// Value each source account moved per direction, see [`WindowUsage`].
+    #[pallet::storage]
+    #[pallet::getter(fn account_bridge_usage)]
+    pub type AccountBridgeUsage<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BridgeDirection,
+        Blake2_128Concat,
+        T::AccountId,
+        WindowUsageOf<T>,
+        ValueQuery,
+    >;
+
+    /// Value all accounts moved per direction, see [`WindowUsage`].
+    #[pallet::storage]
+    #[pallet::getter(fn global_bridge_usage)]
+    pub type GlobalBridgeUsage<T: Config> =
+        StorageMap<_, Twox64Concat, BridgeDirection, WindowUsageOf<T>, ValueQuery>;
+
+    /// Crossings waiting for their delay to pass. The amount is reserved on `from`.
     #[pallet::storage]
-    #[pallet::getter(fn locked_balance)]
-    pub type LockedBalance<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, SubstrateBalanceOf<T>, ValueQuery>;
+    #[pallet::getter(fn pending_transfer)]
+    pub type PendingTransfers<T: Config> =
+        StorageMap<_, Twox64Concat, PendingTransferId, PendingTransferOf<T>, OptionQuery>;
+
+    /// Id of the next queued crossing.
+    #[pallet::storage]
+    pub type NextPendingTransferId<T: Config> = StorageValue<_, PendingTransferId, ValueQuery>;
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         Minted { who: T::AccountId, amount: SubstrateBalanceOf<T> },
         Burned { who: T::AccountId, amount: SubstrateBalanceOf<T> },
-        Locked { who: T::AccountId, amount: SubstrateBalanceOf<T> },
-        Unlocked { who: T::AccountId, amount: SubstrateBalanceOf<T> },
+        Locked { who: T::AccountId, id: LockId, amount: SubstrateBalanceOf<T> },
+        Unlocked { who: T::AccountId, id: LockId, amount: SubstrateBalanceOf<T> },
         EvmBalanceChecked(H160, U256),
         EvmBalanceMutated(H160, U256, bool),
         EvmToSubstrateTransfer(H160, T::AccountId, u128),
         TransferOfBalanceNew{ from: T::AccountId, to: T::AccountId, amount: SubstrateBalanceOf<T>, message: Vec<u8> },
         IPFSHashIncluded(T::AccountId, Vec<u8>),
+        IPFSHashDeregistered(T::AccountId, Vec<u8>),
+        BridgeSupplyAudited {
+            direction: BridgeDirection,
+            amount: SubstrateBalanceOf<T>,
+            issuance_before: SubstrateBalanceOf<T>,
+            issuance_after: SubstrateBalanceOf<T>,
+        },
+        AttestorAdded { attestor: sr25519::Public },
+        AttestorRemoved { attestor: sr25519::Public },
+        AttestorRotated { old: sr25519::Public, new: sr25519::Public },
+        AttestationThresholdSet { threshold: u32 },
+        MemoPolicySet { policy: MemoPolicyOf<T> },
+        BridgeLimitsSet { direction: BridgeDirection, limits: Option<BridgeLimitsOf<T>> },
+        BridgePauseSet { direction: BridgeDirection, paused: bool },
+        /// Usage of the rolling window after a crossing, including that crossing.
+        BridgeUsageUpdated {
+            direction: BridgeDirection,
+            who: T::AccountId,
+            account_used: SubstrateBalanceOf<T>,
+            global_used: SubstrateBalanceOf<T>,
+        },
+        TransferQueued {
+            id: PendingTransferId,
+            direction: BridgeDirection,
+            from: T::AccountId,
+            to: T::AccountId,
+            amount: SubstrateBalanceOf<T>,
+            execute_at: BlockNumberFor<T>,
+        },
+        PendingTransferExecuted { id: PendingTransferId },
+        PendingTransferCancelled { id: PendingTransferId },
 
 
     }
@@ -80,12 +354,54 @@ pub mod pallet {
         InvalidIPFSHash,
         UnauthorizedBackend,
         UnauthorizedUser,
+        SignatureExpired,
+        InvalidDestination,
+        AttestorAlreadyExists,
+        AttestorNotFound,
+        TooManyAttestors,
+        InvalidThreshold,
+        InsufficientAttestations,
+        DuplicateAttestation,
+        IPFSHashAlreadyRegistered,
+        IPFSHashNotFound,
+        NotIPFSHashOwner,
+        SupplyMismatch,
+        InvalidMemoPolicy,
+        LockAlreadyExists,
+        TooManyLocks,
+        InvalidLockSchedule,
+        BridgePaused,
+        TransferBridgeLimitExceeded,
+        AccountBridgeLimitExceeded,
+        GlobalBridgeLimitExceeded,
+        InvalidBridgeLimits,
+        PendingTransferNotFound,
+        PendingTransferNotDue,
+        ZeroLockAmount,
+    }
+
+    impl<T> From<MemoError> for Error<T> {
+        fn from(error: MemoError) -> Self {
+            match error {
+                MemoError::TooLong => Error::<T>::MessageTooLong,
+                MemoError::InvalidUtf8 | MemoError::DisallowedCharacter(_) => Error::<T>::InvalidMessageContent,
+                MemoError::BlockedSubstring(_) | MemoError::ContainsLink => Error::<T>::SuspiciousContent,
+            }
+        }
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        #[cfg(feature = "try-runtime")]
+        fn try_state(_n: BlockNumberFor<T>) -> Result<(), sp_runtime::TryRuntimeError> {
+            Self::do_try_state()
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         
-        #[pallet::weight(10_000)]
+        #[pallet::weight(<T as Config>::WeightInfo::mint())]
         #[pallet::call_index(0)]
         pub fn mint(origin: OriginFor<T>, account: T::AccountId, amount: SubstrateBalanceOf<T>) -> DispatchResult {
             ensure_root(origin)?;
@@ -95,7 +411,7 @@ pub mod pallet {
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
+        #[pallet::weight(<T as Config>::WeightInfo::burn())]
         #[pallet::call_index(1)]
         pub fn burn(origin: OriginFor<T>, account: T::AccountId, amount: SubstrateBalanceOf<T>) -> DispatchResult {
             ensure_root(origin)?;
@@ -110,45 +426,86 @@ pub mod pallet {
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
+        /// Reserve `amount` of the caller's balance under a new lock `id`.
+        ///
+        /// `release` decides when `unlock` may return the funds: at any time, from a
+        /// given block, or linearly between two blocks.
+        #[pallet::weight(<T as Config>::WeightInfo::lock())]
         #[pallet::call_index(2)]
-        pub fn lock(origin: OriginFor<T>, amount: SubstrateBalanceOf<T>) -> DispatchResult {
+        pub fn lock(
+            origin: OriginFor<T>,
+            id: LockId,
+            amount: SubstrateBalanceOf<T>,
+            release: LockRelease<BlockNumberFor<T>>,
+            reason: LockReasonOf<T>,
+        ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            T::SubstrateCurrency::reserve(&who, amount)?;
-            <LockedBalance<T>>::insert(&who, amount);
-            Self::deposit_event(Event::Locked { who: who.clone(), amount });
+            ensure!(!amount.is_zero(), Error::<T>::ZeroLockAmount);
+            ensure!(release.is_valid(), Error::<T>::InvalidLockSchedule);
+
+            <Locks<T>>::try_mutate(&who, |locks| -> DispatchResult {
+                ensure!(!locks.iter().any(|lock| lock.id == id), Error::<T>::LockAlreadyExists);
+                locks
+                    .try_push(CounterLock { id, amount, released: Zero::zero(), release, reason })
+                    .map_err(|_| Error::<T>::TooManyLocks)?;
+                T::SubstrateCurrency::reserve(&who, amount)
+            })?;
+            Self::note_reserved(amount);
+
+            Self::deposit_event(Event::Locked { who, id, amount });
             Ok(())
         }
-        
 
-        #[pallet::weight(10_000)]
+        /// Release whatever lock `id` allows at the current block. The lock is removed
+        /// once all of its funds are released.
+        #[pallet::weight(<T as Config>::WeightInfo::unlock())]
         #[pallet::call_index(3)]
-        pub fn unlock(origin: OriginFor<T>, amount: SubstrateBalanceOf<T>) -> DispatchResult {
+        pub fn unlock(origin: OriginFor<T>, id: LockId) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            let now = frame_system::Pallet::<T>::block_number();
+
+            let amount = <Locks<T>>::try_mutate(&who, |locks| -> Result<_, DispatchError> {
+                let lock = locks.iter_mut().find(|lock| lock.id == id).ok_or(Error::<T>::LockNotFound)?;
 
-            let locked_amount = <LockedBalance<T>>::get(&who);
-            ensure!(locked_amount >= amount, Error::<T>::UnlockNotPossible);
+                let amount = lock.releasable(now);
+                ensure!(!amount.is_zero(), Error::<T>::UnlockNotPossible);
+                lock.released = lock.released.saturating_add(amount);
+                if lock.remaining().is_zero() {
+                    locks.retain(|lock| lock.id != id);
+                }
+                Ok(amount)
+            })?;
 
             T::SubstrateCurrency::unreserve(&who, amount);
-            <LockedBalance<T>>::mutate(&who, |balance| *balance -= amount);
-            Self::deposit_event(Event::Unlocked { who: who.clone(), amount });
+            Self::note_unreserved(amount);
+            Self::deposit_event(Event::Unlocked { who, id, amount });
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
+        /// Emit the EVM balance of `evm_address` as an event.
+        ///
+        /// Deprecated since runtime spec version 3: query `PalletCounterApi::evm_balance` or
+        /// the `counter_evmBalance` RPC instead, which are free.
+        ///
+        /// The call is deleted once wallets no longer submit it. Its call index, 4, is never
+        /// reused, so a transaction signed for it cannot decode as another call. FRAME does
+        /// not support `#[deprecated]` on dispatchables yet, hence only this note.
+        #[pallet::weight(<T as Config>::WeightInfo::check_evm_balance())]
         #[pallet::call_index(4)]
         pub fn check_evm_balance(origin: OriginFor<T>, evm_address: H160) -> DispatchResult {
             let _who = ensure_signed(origin)?;
 
-            let (account, _) = EvmPallet::<T>::account_basic(&evm_address);
-
-            Self::deposit_event(Event::EvmBalanceChecked(evm_address, account.balance));
+            Self::deposit_event(Event::EvmBalanceChecked(evm_address, Self::evm_balance(evm_address)));
 
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
+        /// Move `amount` from the caller to the account backing `evm_address`.
+        ///
+        /// The funds are transferred, not burnt and re-minted, so total issuance is unchanged.
+        /// Subject to the bridge limits, see [`Pallet::cross`].
+        #[pallet::weight(<T as Config>::WeightInfo::substrate_to_evm())]
         #[pallet::call_index(5)]
         pub fn substrate_to_evm(
             origin: OriginFor<T>,
@@ -159,96 +516,56 @@ pub mod pallet {
             ensure!(add, Error::<T>::OperationNotAllowed);
 
             let substrate_account = ensure_signed(origin)?;
+            let evm_account = <T as pallet_evm::Config>::AddressMapping::into_account_id(evm_address);
 
-            let transferable_balance = T::SubstrateCurrency::free_balance(&substrate_account);
-            ensure!(transferable_balance >= amount, Error::<T>::InsufficientBalance);
-
-            T::SubstrateCurrency::withdraw(
-                &substrate_account,
-                amount,
-                frame_support::traits::WithdrawReasons::TRANSFER,
-                frame_support::traits::ExistenceRequirement::KeepAlive,
-            )?;
-
-            let amount_u256 = U256::from(amount.saturated_into::<u128>());
+            let queued = Self::cross(&substrate_account, &evm_account, amount, BridgeDirection::SubstrateToEvm)?;
 
-            EvmPallet::<T>::mutate_balance(evm_address, amount_u256, add);
-
-            Self::deposit_event(Event::EvmBalanceMutated(evm_address, amount_u256, add));
+            if queued.is_none() {
+                let amount_u256 = U256::from(amount.saturated_into::<u128>());
+                Self::deposit_event(Event::EvmBalanceMutated(evm_address, amount_u256, add));
+            }
 
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
+        /// Move `amount` from the EVM balance of `evm_address` to `dest`.
+        ///
+        /// `signature` is an EIP-712 typed-data signature by `evm_address` over
+        /// `EvmToSubstrate(from, to, amount, nonce, deadline)` (see [`eip712`]). The
+        /// nonce must equal [`Pallet::evm_nonce`] of `evm_address` and is bumped on
+        /// success, so every signature can be used exactly once. Any signed origin
+        /// may relay the call since the destination is part of the signed payload.
+        #[pallet::weight(<T as Config>::WeightInfo::evm_to_substrate())]
         #[pallet::call_index(6)]
         pub fn evm_to_substrate(
             origin: OriginFor<T>,
             evm_address: H160,
+            dest: T::AccountId,
             amount: U256,
-            subtract: bool,
-            signature: ecdsa::Signature, 
+            deadline: BlockNumberFor<T>,
+            signature: ecdsa::Signature,
         ) -> DispatchResult {
-            ensure!(!subtract, Error::<T>::Unauthorized);
-            // let _who = ensure_signed(origin)?;
-            let substrate_account = ensure_signed(origin)?;
-            frame_support::log::info!("amount:{}", amount);
-            frame_support::log::info!("signature:{:?}", signature);
-        
-            let amount_u128: u128 = amount.try_into().map_err(|_| Error::<T>::AmountConversionFailed)?;
-            let message = format!("Transfer {} AGC from 0x{:x} to Substrate", amount_u128, evm_address);
-            frame_support::log::info!("amount in u128:{}", amount_u128);
-            frame_support::log::info!("message:{}", message);
-            frame_support::log::info!("message length:{}", message.len());
-        
-            let prefix = "\x19Ethereum Signed Message:\n";
-            let message_len = format!("{}", message.len());
-            let message_to_sign = format!("{}{}{}", prefix, message_len, message);
-        
-            let message_hash = keccak_256(message_to_sign.as_bytes());
-            frame_support::log::info!("message_to_sign:{}", message_to_sign);
-            frame_support::log::info!("message_hash:{:?}", message_hash);
-        
-            let r = &signature.0[..32];
-            let s = &signature.0[32..64];
-            let v = &signature.0[64..65];
-            
-            let mut sig_array = [0u8; 65];
-            sig_array[..32].copy_from_slice(r);
-            sig_array[32..64].copy_from_slice(s);
-            sig_array[64..65].copy_from_slice(v);
-        
-            let recovered_pubkey = secp256k1_ecdsa_recover(&sig_array, &message_hash)
-                .map_err(|_| Error::<T>::InvalidSignature)?;
-        
-            frame_support::log::info!("recovered_pubkey: {:?}", recovered_pubkey);
-        
-            let recovered_key_hash = keccak_256(&recovered_pubkey);
-            let recovered_address = H160::from_slice(&recovered_key_hash[12..]);
-        
-            frame_support::log::info!("Recovered Ethereum Address: {:?}", recovered_address);
-        
+            let _relayer = ensure_signed(origin)?;
+
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= deadline,
+                Error::<T>::SignatureExpired
+            );
+
+            let nonce = Self::evm_nonce(evm_address);
+
+            let digest = Self::evm_to_substrate_digest(evm_address, &dest, amount, nonce, deadline)?;
+            let recovered_address = fp_account::recover_ethereum_signer(&signature.0, &digest)
+                .ok_or(Error::<T>::InvalidSignature)?;
             ensure!(recovered_address == evm_address, Error::<T>::Unauthorized);
-        
-            let (account, _) = EvmPallet::<T>::account_basic(&evm_address);
-            ensure!(account.balance >= amount, Error::<T>::InsufficientBalance);
-        
-            EvmPallet::<T>::mutate_balance(evm_address, amount, false);
-        
-            let substrate_amount = SubstrateBalanceOf::<T>::saturated_from(amount_u128);
-        
-            T::SubstrateCurrency::deposit_creating(&substrate_account, substrate_amount);
-        
-            Self::deposit_event(Event::Minted {
-                who: substrate_account.clone(),
-                amount: substrate_amount,
-            });
-            Self::deposit_event(Event::EvmToSubstrateTransfer(evm_address, substrate_account, amount_u128));
-        
-            Ok(())
+
+            <EvmNonces<T>>::insert(evm_address, nonce.saturating_add(U256::one()));
+
+            Self::do_evm_to_substrate(evm_address, dest, amount).map(|_| ())
         }
 
 
-        #[pallet::weight(5_000)]
+        #[pallet::weight(<T as Config>::WeightInfo::balance_transfer_new(message.len() as u32))]
         #[pallet::call_index(7)]
         pub fn balance_transfer_new(
             origin: OriginFor<T>,
@@ -257,30 +574,14 @@ pub mod pallet {
             message: Vec<u8>,               
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            ensure!(core::str::from_utf8(&message).is_ok(), Error::<T>::InvalidMessageContent);
-            ensure!(message.len() <= 64, Error::<T>::MessageTooLong);
-            let message_str = core::str::from_utf8(&message).unwrap_or("");
-
-            ensure!(
-                message.iter().all(|&byte| (byte >= 32 && byte <= 126)), 
-                Error::<T>::InvalidMessageContent
-            );
-            let url_patterns = ["http://", "https://", "www.", ".com", ".net", ".org", ".xyz", ".io", ".gov", ".edu", ".mil", ".info"];
-            ensure!(
-                !url_patterns.iter().any(|&pattern| message_str.contains(pattern)),
-                Error::<T>::SuspiciousContent
-            );
-            let blacklisted_words = ["scam", "fraud", "hack", "illegal", "phishing"];
-            ensure!(
-                !blacklisted_words.iter().any(|&word| message_str.to_lowercase().contains(word)),
-                Error::<T>::SuspiciousContent
-            );
-            ensure!(
-                !Self::contains_ip_address(message_str),
-                Error::<T>::SuspiciousContent
-            );
+            Self::validate_memo(&message).map_err(Error::<T>::from)?;
 
-            T::SubstrateCurrency::transfer(&who, &to, amount, ExistenceRequirement::KeepAlive)?;
+            <T::SubstrateCurrency as Currency<T::AccountId>>::transfer(
+                &who,
+                &to,
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
 
             Self::deposit_event(Event::TransferOfBalanceNew {
                 from: who.clone(),
@@ -292,88 +593,571 @@ pub mod pallet {
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
+        /// Register `ipfs_hash` (a CIDv0 or CIDv1 string) to the caller, with an optional tag.
+        ///
+        /// `attestations` must carry at least [`Pallet::attestation_threshold`] signatures
+        /// by distinct registered attestors over
+        /// `(IPFS_ATTESTATION_CONTEXT, caller, nonce, ipfs_hash).encode()`, where `nonce` is
+        /// [`Pallet::attestation_nonce`] of the caller. Binding the caller and nonce means an
+        /// attestation cannot be replayed or submitted by another account.
+        #[pallet::weight(<T as Config>::WeightInfo::include_ipfs_hash(attestations.len() as u32))]
         #[pallet::call_index(8)]
         pub fn include_ipfs_hash(
             origin: OriginFor<T>,
-            ipfs_hash: Vec<u8>,
-            backend_signature: Vec<u8>,  
+            ipfs_hash: CidOf<T>,
+            tag: Option<IpfsTagOf<T>>,
+            attestations: BoundedVec<(sr25519::Public, sr25519::Signature), T::MaxAttestors>,
         ) -> DispatchResult {
             let user = ensure_signed(origin)?;
 
-            let authorized_backend_account: AccountId32 = AccountId32::new(hex!(
-                "7c650b5b9f657ddcc7a6ddbf9147d33f3b6ffda5009658b1ee6b7e3665a99701"
-            ));
+            Self::do_include_ipfs_hash(user, ipfs_hash, tag, &attestations)
+        }
 
-            let backend_bytes: [u8; 32] = *authorized_backend_account.as_ref();
+        #[pallet::weight(<T as Config>::WeightInfo::add_attestor(T::MaxAttestors::get()))]
+        #[pallet::call_index(9)]
+        pub fn add_attestor(origin: OriginFor<T>, attestor: sr25519::Public) -> DispatchResult {
+            T::AttestorOrigin::ensure_origin(origin)?;
 
-            let backend_public_key = sp_core::sr25519::Public::from_raw(backend_bytes);
+            <Attestors<T>>::try_mutate(|attestors| -> DispatchResult {
+                ensure!(!attestors.contains(&attestor), Error::<T>::AttestorAlreadyExists);
+                attestors.try_push(attestor).map_err(|_| Error::<T>::TooManyAttestors)?;
+                Ok(())
+            })?;
 
-            let backend_sig = sp_core::sr25519::Signature::try_from(backend_signature.as_slice())
-                .map_err(|_| Error::<T>::InvalidSignature)?;
+            Self::deposit_event(Event::AttestorAdded { attestor });
+            Ok(())
+        }
+
+        #[pallet::weight(<T as Config>::WeightInfo::remove_attestor(T::MaxAttestors::get()))]
+        #[pallet::call_index(10)]
+        pub fn remove_attestor(origin: OriginFor<T>, attestor: sr25519::Public) -> DispatchResult {
+            T::AttestorOrigin::ensure_origin(origin)?;
+
+            <Attestors<T>>::try_mutate(|attestors| -> DispatchResult {
+                let index = attestors
+                    .iter()
+                    .position(|a| a == &attestor)
+                    .ok_or(Error::<T>::AttestorNotFound)?;
+                attestors.remove(index);
+                ensure!(
+                    attestors.len() as u32 >= Self::attestation_threshold(),
+                    Error::<T>::InvalidThreshold
+                );
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::AttestorRemoved { attestor });
+            Ok(())
+        }
+
+        #[pallet::weight(<T as Config>::WeightInfo::rotate_attestor(T::MaxAttestors::get()))]
+        #[pallet::call_index(11)]
+        pub fn rotate_attestor(
+            origin: OriginFor<T>,
+            old: sr25519::Public,
+            new: sr25519::Public,
+        ) -> DispatchResult {
+            T::AttestorOrigin::ensure_origin(origin)?;
+
+            <Attestors<T>>::try_mutate(|attestors| -> DispatchResult {
+                ensure!(!attestors.contains(&new), Error::<T>::AttestorAlreadyExists);
+                let slot = attestors
+                    .iter_mut()
+                    .find(|a| **a == old)
+                    .ok_or(Error::<T>::AttestorNotFound)?;
+                *slot = new;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::AttestorRotated { old, new });
+            Ok(())
+        }
 
-            let message = ipfs_hash.clone();  
+        #[pallet::weight(<T as Config>::WeightInfo::set_attestation_threshold())]
+        #[pallet::call_index(12)]
+        pub fn set_attestation_threshold(origin: OriginFor<T>, threshold: u32) -> DispatchResult {
+            T::AttestorOrigin::ensure_origin(origin)?;
 
             ensure!(
-                sp_io::crypto::sr25519_verify(
-                    &backend_sig, 
-                    &message, 
-                    &backend_public_key
-                ),
-                Error::<T>::InvalidSignature
+                threshold >= 1 && threshold <= Self::attestors().len() as u32,
+                Error::<T>::InvalidThreshold
             );
+            <AttestationThreshold<T>>::put(threshold);
 
-            ensure!(ipfs_hash.len() == 46, Error::<T>::InvalidIPFSHash);
+            Self::deposit_event(Event::AttestationThresholdSet { threshold });
+            Ok(())
+        }
+
+        /// Remove a CID previously registered by the caller.
+        #[pallet::weight(<T as Config>::WeightInfo::deregister_ipfs_hash())]
+        #[pallet::call_index(13)]
+        pub fn deregister_ipfs_hash(origin: OriginFor<T>, ipfs_hash: CidOf<T>) -> DispatchResult {
+            let user = ensure_signed(origin)?;
+
+            let (_, key) = Self::cid_key(&ipfs_hash)?;
+            let record = <IpfsRegistry<T>>::get(&key).ok_or(Error::<T>::IPFSHashNotFound)?;
+            ensure!(record.owner == user, Error::<T>::NotIPFSHashOwner);
+
+            <IpfsRegistry<T>>::remove(&key);
+            <IpfsHashesByOwner<T>>::remove(&user, &key);
+
+            Self::deposit_event(Event::IPFSHashDeregistered(user, ipfs_hash.into_inner()));
+            Ok(())
+        }
 
-            Self::deposit_event(Event::IPFSHashIncluded(user.clone(), ipfs_hash.clone()));
+        /// Replace the memo policy enforced by `balance_transfer_new`.
+        #[pallet::weight(<T as Config>::WeightInfo::set_memo_policy(policy.blocked_substrings.len() as u32))]
+        #[pallet::call_index(14)]
+        pub fn set_memo_policy(origin: OriginFor<T>, policy: MemoPolicyOf<T>) -> DispatchResult {
+            T::MemoPolicyOrigin::ensure_origin(origin)?;
 
+            ensure!(policy.max_length <= T::MaxMemoLength::get(), Error::<T>::InvalidMemoPolicy);
+            ensure!(
+                policy.blocked_substrings.iter().all(|blocked| !blocked.is_empty()),
+                Error::<T>::InvalidMemoPolicy
+            );
+            <ActiveMemoPolicy<T>>::put(&policy);
+
+            Self::deposit_event(Event::MemoPolicySet { policy });
             Ok(())
         }
 
-  
+        /// Set or, with `None`, remove the rate limits of `direction`.
+        #[pallet::weight(<T as Config>::WeightInfo::set_bridge_limits())]
+        #[pallet::call_index(15)]
+        pub fn set_bridge_limits(
+            origin: OriginFor<T>,
+            direction: BridgeDirection,
+            limits: Option<BridgeLimitsOf<T>>,
+        ) -> DispatchResult {
+            T::BridgeGuardOrigin::ensure_origin(origin)?;
+
+            match limits {
+                Some(limits) => {
+                    ensure!(limits.is_valid(), Error::<T>::InvalidBridgeLimits);
+                    <BridgeLimitsByDirection<T>>::insert(direction, limits);
+                },
+                None => <BridgeLimitsByDirection<T>>::remove(direction),
+            }
+
+            Self::deposit_event(Event::BridgeLimitsSet { direction, limits });
+            Ok(())
+        }
+
+        /// Refuse or allow crossings in `direction`, including queued ones.
+        #[pallet::weight(<T as Config>::WeightInfo::set_bridge_paused())]
+        #[pallet::call_index(16)]
+        pub fn set_bridge_paused(origin: OriginFor<T>, direction: BridgeDirection, paused: bool) -> DispatchResult {
+            T::BridgeGuardOrigin::ensure_origin(origin)?;
+
+            <BridgePaused<T>>::insert(direction, paused);
+
+            Self::deposit_event(Event::BridgePauseSet { direction, paused });
+            Ok(())
+        }
+
+        /// Execute a queued crossing whose delay has passed. Any signed origin may call this.
+        #[pallet::weight(<T as Config>::WeightInfo::execute_pending_transfer())]
+        #[pallet::call_index(17)]
+        pub fn execute_pending_transfer(origin: OriginFor<T>, id: PendingTransferId) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let pending = <PendingTransfers<T>>::get(id).ok_or(Error::<T>::PendingTransferNotFound)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= pending.execute_at,
+                Error::<T>::PendingTransferNotDue
+            );
+            ensure!(!Self::bridge_paused(pending.direction), Error::<T>::BridgePaused);
+
+            <PendingTransfers<T>>::remove(id);
+            T::SubstrateCurrency::unreserve(&pending.from, pending.amount);
+            Self::note_unreserved(pending.amount);
+            Self::bridge_transfer(
+                &pending.from,
+                &pending.to,
+                pending.amount,
+                pending.direction,
+                Self::preservation(pending.direction),
+            )?;
+
+            Self::deposit_event(Event::PendingTransferExecuted { id });
+            Ok(())
+        }
+
+        /// Drop a queued crossing and release its funds back to the source account. The
+        /// amount stays counted against the rate limits of the current window.
+        #[pallet::weight(<T as Config>::WeightInfo::cancel_pending_transfer())]
+        #[pallet::call_index(18)]
+        pub fn cancel_pending_transfer(origin: OriginFor<T>, id: PendingTransferId) -> DispatchResult {
+            T::BridgeGuardOrigin::ensure_origin(origin)?;
+
+            let pending = <PendingTransfers<T>>::take(id).ok_or(Error::<T>::PendingTransferNotFound)?;
+            T::SubstrateCurrency::unreserve(&pending.from, pending.amount);
+            Self::note_unreserved(pending.amount);
+
+            Self::deposit_event(Event::PendingTransferCancelled { id });
+            Ok(())
+        }
     }
     impl<T: Config> Pallet<T> {
-        pub fn contains_ip_address(message: &str) -> bool {
-            if Self::contains_ipv4_address(message) {
-                return true;
-            }    
-            if Self::contains_ipv6_address(message) {
-                return true;
+        /// EIP-712 domain separator of this pallet on this chain.
+        pub fn domain_separator() -> H256 {
+            let name = <Self as PalletInfoAccess>::name();
+            eip712::domain_separator(name.as_bytes(), <T as pallet_evm::Config>::ChainId::get())
+        }
+
+        /// The typed data `evm_address` has to sign to move `amount` to `dest` with its
+        /// current nonce.
+        pub fn evm_to_substrate_payload(
+            evm_address: H160,
+            dest: &T::AccountId,
+            amount: U256,
+            deadline: BlockNumberFor<T>,
+        ) -> Result<eip712::EvmToSubstratePayload, DispatchError> {
+            let nonce = Self::evm_nonce(evm_address);
+            let digest = Self::evm_to_substrate_digest(evm_address, dest, amount, nonce, deadline)?;
+            let to: [u8; 32] = dest.encode().try_into().map_err(|_| Error::<T>::InvalidDestination)?;
+
+            Ok(eip712::EvmToSubstratePayload {
+                domain_name: <Self as PalletInfoAccess>::name().as_bytes().to_vec(),
+                domain_version: eip712::DOMAIN_VERSION.to_vec(),
+                chain_id: <T as pallet_evm::Config>::ChainId::get(),
+                from: evm_address,
+                to,
+                amount,
+                nonce,
+                deadline: U256::from(deadline.saturated_into::<u128>()),
+                digest: H256(digest),
+            })
+        }
+
+        /// The digest `evm_address` has to sign to move `amount` to `dest`.
+        pub fn evm_to_substrate_digest(
+            evm_address: H160,
+            dest: &T::AccountId,
+            amount: U256,
+            nonce: U256,
+            deadline: BlockNumberFor<T>,
+        ) -> Result<[u8; 32], DispatchError> {
+            let dest: [u8; 32] = dest
+                .encode()
+                .try_into()
+                .map_err(|_| Error::<T>::InvalidDestination)?;
+            let deadline = U256::from(deadline.saturated_into::<u128>());
+            let struct_hash = eip712::evm_to_substrate_struct_hash(evm_address, &dest, amount, nonce, deadline);
+            Ok(eip712::typed_data_digest(Self::domain_separator(), struct_hash))
+        }
+
+        /// The payload attestors sign for `who` to include `ipfs_hash`.
+        pub fn ipfs_attestation_payload(who: &T::AccountId, nonce: u64, ipfs_hash: &[u8]) -> Vec<u8> {
+            (IPFS_ATTESTATION_CONTEXT, who, nonce, ipfs_hash).encode()
+        }
+
+        /// Checks that enough distinct registered attestors signed `message`.
+        fn ensure_attested(
+            message: &[u8],
+            attestations: &[(sr25519::Public, sr25519::Signature)],
+        ) -> DispatchResult {
+            let attestors = Self::attestors();
+            let mut seen: Vec<&sr25519::Public> = Vec::with_capacity(attestations.len());
+
+            for (attestor, signature) in attestations {
+                ensure!(attestors.contains(attestor), Error::<T>::UnauthorizedBackend);
+                ensure!(!seen.contains(&attestor), Error::<T>::DuplicateAttestation);
+                ensure!(
+                    sp_io::crypto::sr25519_verify(signature, message, attestor),
+                    Error::<T>::InvalidSignature
+                );
+                seen.push(attestor);
             }
-            false
+
+            ensure!(
+                seen.len() as u32 >= Self::attestation_threshold(),
+                Error::<T>::InsufficientAttestations
+            );
+            Ok(())
         }
-    
-        pub fn contains_ipv4_address(message: &str) -> bool {
-            let parts: Vec<&str> = message.split('.').collect();
-            if parts.len() == 4 {
-                for part in parts {
-                    if let Ok(num) = part.parse::<u8>() {
-                        if !(0..=255).contains(&num) {
-                            return false;
-                        }
-                    } else {
-                        return false;
-                    }
+
+        /// Move `amount` from the account backing `evm_address` to `dest`.
+        ///
+        /// The caller is responsible for authenticating `evm_address`, either through an
+        /// EIP-712 signature or because it is the `msg.sender` of a precompile call. Returns
+        /// the id of the queued transfer if the amount reached the delay threshold.
+        pub fn do_evm_to_substrate(
+            evm_address: H160,
+            dest: T::AccountId,
+            amount: U256,
+        ) -> Result<Option<PendingTransferId>, DispatchError> {
+            let amount_u128: u128 = amount.try_into().map_err(|_| Error::<T>::AmountConversionFailed)?;
+
+            let (account, _) = EvmPallet::<T>::account_basic(&evm_address);
+            ensure!(account.balance >= amount, Error::<T>::InsufficientBalance);
+
+            let evm_account = <T as pallet_evm::Config>::AddressMapping::into_account_id(evm_address);
+            let substrate_amount = SubstrateBalanceOf::<T>::saturated_from(amount_u128);
+
+            let queued = Self::cross(&evm_account, &dest, substrate_amount, BridgeDirection::EvmToSubstrate)?;
+
+            if queued.is_none() {
+                Self::deposit_event(Event::EvmToSubstrateTransfer(evm_address, dest, amount_u128));
+            }
+            Ok(queued)
+        }
+
+        /// Register `ipfs_hash` to `user` once it carries enough valid attestations.
+        pub fn do_include_ipfs_hash(
+            user: T::AccountId,
+            ipfs_hash: CidOf<T>,
+            tag: Option<IpfsTagOf<T>>,
+            attestations: &[(sr25519::Public, sr25519::Signature)],
+        ) -> DispatchResult {
+            let nonce = Self::attestation_nonce(&user);
+            let message = Self::ipfs_attestation_payload(&user, nonce, &ipfs_hash);
+            Self::ensure_attested(&message, attestations)?;
+
+            let (parsed, key) = Self::cid_key(&ipfs_hash)?;
+            ensure!(!<IpfsRegistry<T>>::contains_key(&key), Error::<T>::IPFSHashAlreadyRegistered);
+
+            <AttestationNonces<T>>::insert(&user, nonce.saturating_add(1));
+            <IpfsRegistry<T>>::insert(
+                &key,
+                IpfsRecord {
+                    owner: user.clone(),
+                    registered_at: frame_system::Pallet::<T>::block_number(),
+                    version: parsed.version,
+                    codec: parsed.codec,
+                    tag,
+                },
+            );
+            <IpfsHashesByOwner<T>>::insert(&user, &key, &ipfs_hash);
+
+            Self::deposit_event(Event::IPFSHashIncluded(user, ipfs_hash.into_inner()));
+            Ok(())
+        }
+
+        /// Applies the pause switch and rate limits of `direction` to a crossing, then either
+        /// executes it or, if `amount` reaches the delay threshold, reserves it on `from` and
+        /// queues it. Returns the id of the queued transfer.
+        pub fn cross(
+            from: &T::AccountId,
+            to: &T::AccountId,
+            amount: SubstrateBalanceOf<T>,
+            direction: BridgeDirection,
+        ) -> Result<Option<PendingTransferId>, DispatchError> {
+            ensure!(!Self::bridge_paused(direction), Error::<T>::BridgePaused);
+
+            if let Some(limits) = Self::bridge_limits(direction) {
+                Self::record_bridge_usage(from, amount, direction, &limits)?;
+
+                if amount >= limits.delay_threshold {
+                    T::SubstrateCurrency::reserve(from, amount)?;
+                    Self::note_reserved(amount);
+
+                    let id = <NextPendingTransferId<T>>::mutate(|next| {
+                        let id = *next;
+                        *next = next.saturating_add(1);
+                        id
+                    });
+                    let execute_at = frame_system::Pallet::<T>::block_number().saturating_add(limits.delay);
+                    <PendingTransfers<T>>::insert(
+                        id,
+                        PendingTransfer { direction, from: from.clone(), to: to.clone(), amount, execute_at },
+                    );
+
+                    Self::deposit_event(Event::TransferQueued {
+                        id,
+                        direction,
+                        from: from.clone(),
+                        to: to.clone(),
+                        amount,
+                        execute_at,
+                    });
+                    return Ok(Some(id));
                 }
-                return true;
             }
-            false
+
+            Self::bridge_transfer(from, to, amount, direction, Self::preservation(direction))?;
+            Ok(None)
         }
-    
-        pub fn contains_ipv6_address(message: &str) -> bool {
-            let parts: Vec<&str> = message.split(':').collect();
-            if parts.len() > 1 && parts.len() <= 8 {
-                for part in parts {
-                    if part.is_empty() {
-                        continue;
-                    }
-                    if part.len() > 4 || part.chars().any(|c| !c.is_digit(16)) {
-                        return false;
-                    }
+
+        /// Adds `amount` to the rolling window usage of `who` and of everyone, failing if
+        /// `amount` or either usage would exceed its cap.
+        fn record_bridge_usage(
+            who: &T::AccountId,
+            amount: SubstrateBalanceOf<T>,
+            direction: BridgeDirection,
+            limits: &BridgeLimitsOf<T>,
+        ) -> DispatchResult {
+            ensure!(amount <= limits.per_transfer_cap, Error::<T>::TransferBridgeLimitExceeded);
+            let now = frame_system::Pallet::<T>::block_number();
+
+            let mut account = Self::account_bridge_usage(direction, who);
+            account.roll(now, limits.window);
+            let account_used = account.used(now, limits.window).saturating_add(amount);
+            ensure!(account_used <= limits.per_account_cap, Error::<T>::AccountBridgeLimitExceeded);
+
+            let mut global = Self::global_bridge_usage(direction);
+            global.roll(now, limits.window);
+            let global_used = global.used(now, limits.window).saturating_add(amount);
+            ensure!(global_used <= limits.global_cap, Error::<T>::GlobalBridgeLimitExceeded);
+
+            account.current = account.current.saturating_add(amount);
+            global.current = global.current.saturating_add(amount);
+            <AccountBridgeUsage<T>>::insert(direction, who, account);
+            <GlobalBridgeUsage<T>>::insert(direction, global);
+
+            Self::deposit_event(Event::BridgeUsageUpdated { direction, who: who.clone(), account_used, global_used });
+            Ok(())
+        }
+
+        /// Value `who` and everyone moved in `direction` over the current rolling window.
+        pub fn bridge_usage(
+            direction: BridgeDirection,
+            who: &T::AccountId,
+        ) -> (SubstrateBalanceOf<T>, SubstrateBalanceOf<T>) {
+            let Some(limits) = Self::bridge_limits(direction) else {
+                return (Zero::zero(), Zero::zero());
+            };
+            let now = frame_system::Pallet::<T>::block_number();
+            let mut account = Self::account_bridge_usage(direction, who);
+            account.roll(now, limits.window);
+            let mut global = Self::global_bridge_usage(direction);
+            global.roll(now, limits.window);
+            (account.used(now, limits.window), global.used(now, limits.window))
+        }
+
+        /// Whether a crossing may empty the source account.
+        fn preservation(direction: BridgeDirection) -> Preservation {
+            match direction {
+                BridgeDirection::SubstrateToEvm => Preservation::Preserve,
+                BridgeDirection::EvmToSubstrate => Preservation::Expendable,
+            }
+        }
+
+        /// Transfers `amount` between the two sides of the bridge and records the issuance
+        /// before and after, failing if the crossing changed it.
+        fn bridge_transfer(
+            from: &T::AccountId,
+            to: &T::AccountId,
+            amount: SubstrateBalanceOf<T>,
+            direction: BridgeDirection,
+            preservation: Preservation,
+        ) -> DispatchResult {
+            let issuance_before = <T::SubstrateCurrency as Currency<T::AccountId>>::total_issuance();
+
+            <T::SubstrateCurrency as fungible::Mutate<T::AccountId>>::transfer(
+                from,
+                to,
+                amount.saturated_into::<u128>().saturated_into(),
+                preservation,
+            )?;
+
+            let issuance_after = <T::SubstrateCurrency as Currency<T::AccountId>>::total_issuance();
+            ensure!(issuance_before == issuance_after, Error::<T>::SupplyMismatch);
+
+            <LastSupplyAudit<T>>::put(SupplyAudit { direction, amount, issuance_before, issuance_after });
+            Self::deposit_event(Event::BridgeSupplyAudited { direction, amount, issuance_before, issuance_after });
+            Ok(())
+        }
+
+        /// Adds `amount` reserved by a lock or a queued crossing to [`TotalReserved`].
+        pub(crate) fn note_reserved(amount: SubstrateBalanceOf<T>) {
+            <TotalReserved<T>>::mutate(|total| *total = total.saturating_add(amount));
+        }
+
+        /// Removes `amount` released by a lock or a queued crossing from [`TotalReserved`].
+        fn note_unreserved(amount: SubstrateBalanceOf<T>) {
+            <TotalReserved<T>>::mutate(|total| *total = total.saturating_sub(amount));
+        }
+
+        #[cfg(any(feature = "try-runtime", test))]
+        pub fn do_try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+            // Every lock is in the current layout: the legacy records are gone once the v2
+            // migration ran, and each stored lock list decodes and is well formed.
+            if Self::on_chain_storage_version() >= 2 {
+                ensure!(
+                    crate::migrations::v2::LockedBalance::<T>::iter_keys().next().is_none(),
+                    "pallet_counter: legacy locks left after the v2 migration"
+                );
+            }
+
+            // Locks and queued crossings are both backed by reserves of their account.
+            let mut held = sp_std::collections::btree_map::BTreeMap::<T::AccountId, SubstrateBalanceOf<T>>::new();
+            for who in <Locks<T>>::iter_keys() {
+                let locks = <Locks<T>>::try_get(&who).map_err(|_| "pallet_counter: undecodable locks")?;
+                let mut locked: SubstrateBalanceOf<T> = Zero::zero();
+                for (index, lock) in locks.iter().enumerate() {
+                    ensure!(
+                        lock.release.is_valid() && lock.released < lock.amount,
+                        "pallet_counter: malformed or fully released lock"
+                    );
+                    ensure!(
+                        !locks[..index].iter().any(|other| other.id == lock.id),
+                        "pallet_counter: duplicate lock id"
+                    );
+                    locked = locked.saturating_add(lock.remaining());
                 }
-                return true;
+                held.insert(who, locked);
+            }
+            for (_, pending) in <PendingTransfers<T>>::iter() {
+                let total = held.entry(pending.from).or_insert_with(Zero::zero);
+                *total = total.saturating_add(pending.amount);
+            }
+
+            let mut total: SubstrateBalanceOf<T> = Zero::zero();
+            for (who, amount) in held {
+                ensure!(
+                    amount <= T::SubstrateCurrency::reserved_balance(&who),
+                    "pallet_counter: locks and queued crossings exceed the reserved balance"
+                );
+                total = total.saturating_add(amount);
             }
-            false
+            ensure!(
+                total == Self::total_reserved(),
+                "pallet_counter: locks and queued crossings do not add up to the reserved total"
+            );
+            ensure!(
+                total <= <T::SubstrateCurrency as Currency<T::AccountId>>::total_issuance(),
+                "pallet_counter: the reserved total exceeds the total issuance"
+            );
+            Ok(())
+        }
+
+        /// Total amount still reserved by the locks of `who`.
+        pub fn locked_balance(who: &T::AccountId) -> SubstrateBalanceOf<T> {
+            Self::locks(who)
+                .iter()
+                .fold(Zero::zero(), |total: SubstrateBalanceOf<T>, lock| total.saturating_add(lock.remaining()))
+        }
+
+        /// Parses a textual CID into its header and its registry key.
+        fn cid_key(text: &[u8]) -> Result<(cid::Cid, CidOf<T>), DispatchError> {
+            let (parsed, binary) = cid::decode(text).map_err(|_| Error::<T>::InvalidIPFSHash)?;
+            let key = CidOf::<T>::try_from(binary).map_err(|_| Error::<T>::InvalidIPFSHash)?;
+            Ok((parsed, key))
+        }
+
+        /// The registry entry of the CID `text`, in any of its textual forms.
+        pub fn ipfs_record(text: &[u8]) -> Option<IpfsRecordOf<T>> {
+            let (_, key) = Self::cid_key(text).ok()?;
+            <IpfsRegistry<T>>::get(&key)
+        }
+
+        /// All CIDs registered by `who`, as submitted, with their records.
+        pub fn ipfs_hashes_of(who: &T::AccountId) -> Vec<(CidOf<T>, IpfsRecordOf<T>)> {
+            <IpfsHashesByOwner<T>>::iter_prefix(who)
+                .filter_map(|(key, text)| <IpfsRegistry<T>>::get(&key).map(|record| (text, record)))
+                .collect()
+        }
+
+        /// Balance of `evm_address` as seen by the EVM.
+        pub fn evm_balance(evm_address: H160) -> U256 {
+            EvmPallet::<T>::account_basic(&evm_address).0.balance
+        }
+
+        /// Checks `memo` against the active memo policy without submitting a transfer.
+        pub fn validate_memo(memo: &[u8]) -> Result<(), MemoError> {
+            Self::memo_policy().validate(memo)
         }
     }
     
@@ -0,0 +1,147 @@
+//! Rate limits applied to bridge crossings.
+
+use crate::BridgeDirection;
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::{
+    helpers_128bit::multiply_by_rational_with_rounding,
+    traits::{AtLeast32BitUnsigned, One, Saturating, Zero},
+    Rounding, RuntimeDebug, SaturatedConversion,
+};
+
+/// Caps on the value crossing the bridge in one direction.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct BridgeLimits<Balance, BlockNumber> {
+    /// Length in blocks of the rolling window the caps apply to.
+    pub window: BlockNumber,
+    /// Most a single crossing may move.
+    pub per_transfer_cap: Balance,
+    /// Most a single source account may move within `window`.
+    pub per_account_cap: Balance,
+    /// Most all accounts together may move within `window`.
+    pub global_cap: Balance,
+    /// Transfers of at least this amount are queued instead of executed.
+    pub delay_threshold: Balance,
+    /// Blocks a queued transfer waits before it can be executed.
+    pub delay: BlockNumber,
+}
+
+impl<Balance: PartialOrd + Zero, BlockNumber: Zero> BridgeLimits<Balance, BlockNumber> {
+    /// Whether the limits are usable: a non-empty window, caps that admit something and a
+    /// delay threshold between one and the per-transfer cap, so that neither every crossing
+    /// nor none of them is delayed.
+    pub fn is_valid(&self) -> bool {
+        !self.window.is_zero() &&
+            !self.per_transfer_cap.is_zero() &&
+            self.per_transfer_cap <= self.per_account_cap &&
+            self.per_account_cap <= self.global_cap &&
+            !self.delay_threshold.is_zero() &&
+            self.delay_threshold <= self.per_transfer_cap
+    }
+}
+
+/// Value moved in the current and the previous window.
+///
+/// Usage over the rolling window is approximated by weighting the previous window by the
+/// part of it that still overlaps the rolling window ending now.
+#[derive(Clone, Copy, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct WindowUsage<Balance, BlockNumber> {
+    /// First block of the current window.
+    pub window_start: BlockNumber,
+    /// Value moved since `window_start`.
+    pub current: Balance,
+    /// Value moved in the window before `window_start`.
+    pub previous: Balance,
+}
+
+impl<Balance, BlockNumber> WindowUsage<Balance, BlockNumber>
+where
+    Balance: AtLeast32BitUnsigned + Copy,
+    BlockNumber: AtLeast32BitUnsigned + Copy,
+{
+    /// Moves the usage forward so that `now` falls in the current window.
+    pub fn roll(&mut self, now: BlockNumber, window: BlockNumber) {
+        if window.is_zero() || now < self.window_start.saturating_add(window) {
+            return;
+        }
+        let elapsed_windows = (now - self.window_start) / window;
+        self.previous = if elapsed_windows == BlockNumber::one() { self.current } else { Zero::zero() };
+        self.current = Zero::zero();
+        self.window_start = self.window_start.saturating_add(elapsed_windows.saturating_mul(window));
+    }
+
+    /// Estimated value moved in the `window` blocks ending at `now`.
+    ///
+    /// Expects [`Self::roll`] to have been called with the same `now` and `window`.
+    pub fn used(&self, now: BlockNumber, window: BlockNumber) -> Balance {
+        if window.is_zero() {
+            return self.current;
+        }
+        let into_window = now.saturating_sub(self.window_start).min(window);
+        let overlap = (window - into_window).saturated_into::<u128>();
+        let previous = multiply_by_rational_with_rounding(
+            self.previous.saturated_into::<u128>(),
+            overlap,
+            window.saturated_into::<u128>(),
+            Rounding::Up,
+        )
+        .map(|previous| previous.saturated_into())
+        .unwrap_or(self.previous);
+        self.current.saturating_add(previous)
+    }
+}
+
+/// A crossing held back because its amount reached the delay threshold.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PendingTransfer<AccountId, Balance, BlockNumber> {
+    pub direction: BridgeDirection,
+    /// Account the amount is reserved on until execution.
+    pub from: AccountId,
+    pub to: AccountId,
+    pub amount: Balance,
+    /// First block in which the transfer can be executed.
+    pub execute_at: BlockNumber,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_rolls_over_windows() {
+        let mut usage = WindowUsage::<u64, u32> { window_start: 0, current: 0, previous: 0 };
+        usage.current = 100;
+        usage.roll(5, 10);
+        assert_eq!(usage.used(5, 10), 100);
+
+        // Half of the previous window still overlaps the rolling window.
+        usage.roll(15, 10);
+        assert_eq!(usage, WindowUsage { window_start: 10, current: 0, previous: 100 });
+        assert_eq!(usage.used(15, 10), 50);
+        assert_eq!(usage.used(19, 10), 10);
+
+        // Two windows later nothing is left.
+        usage.roll(31, 10);
+        assert_eq!(usage, WindowUsage { window_start: 30, current: 0, previous: 0 });
+    }
+
+    #[test]
+    fn validates_limits() {
+        let limits = BridgeLimits::<u64, u32> {
+            window: 10,
+            per_transfer_cap: 5,
+            per_account_cap: 10,
+            global_cap: 100,
+            delay_threshold: 5,
+            delay: 3,
+        };
+        assert!(limits.is_valid());
+        assert!(!BridgeLimits { window: 0, ..limits }.is_valid());
+        assert!(!BridgeLimits { per_transfer_cap: 11, ..limits }.is_valid());
+        assert!(!BridgeLimits { global_cap: 9, ..limits }.is_valid());
+        // Delaying every crossing, or none of them, is rejected.
+        assert!(!BridgeLimits { delay_threshold: 0, ..limits }.is_valid());
+        assert!(BridgeLimits { delay_threshold: 1, ..limits }.is_valid());
+        assert!(!BridgeLimits { delay_threshold: 6, ..limits }.is_valid());
+    }
+}
@@ -0,0 +1,129 @@
+//! Named locks created through `lock` and released through `unlock`.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::{
+    helpers_128bit::multiply_by_rational_with_rounding,
+    traits::{AtLeast32BitUnsigned, Saturating, Zero},
+    Rounding, RuntimeDebug, SaturatedConversion,
+};
+
+/// Identifier of a lock, unique per account.
+pub type LockId = [u8; 8];
+
+/// Id given to balances locked before locks were named.
+pub const LEGACY_LOCK_ID: LockId = *b"legacy  ";
+
+/// When the funds of a lock become releasable.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum LockRelease<BlockNumber> {
+    /// Releasable at any time.
+    Anytime,
+    /// Fully releasable from this block on.
+    At(BlockNumber),
+    /// Released linearly, nothing at `start` and everything at `end`.
+    Linear { start: BlockNumber, end: BlockNumber },
+}
+
+impl<BlockNumber: PartialOrd> LockRelease<BlockNumber> {
+    /// Whether the schedule is well formed.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            LockRelease::Linear { start, end } => start < end,
+            _ => true,
+        }
+    }
+}
+
+/// Funds reserved by `lock`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct CounterLock<Balance, BlockNumber, Reason> {
+    pub id: LockId,
+    /// Amount reserved when the lock was created.
+    pub amount: Balance,
+    /// Part of `amount` already unlocked.
+    pub released: Balance,
+    pub release: LockRelease<BlockNumber>,
+    /// Free-form reason supplied by the owner.
+    pub reason: Reason,
+}
+
+impl<Balance, BlockNumber, Reason> CounterLock<Balance, BlockNumber, Reason>
+where
+    Balance: AtLeast32BitUnsigned + Copy,
+    BlockNumber: AtLeast32BitUnsigned + Copy,
+{
+    /// Amount still reserved.
+    pub fn remaining(&self) -> Balance {
+        self.amount.saturating_sub(self.released)
+    }
+
+    /// Amount `unlock` may release at block `now`.
+    pub fn releasable(&self, now: BlockNumber) -> Balance {
+        let unlocked = match self.release {
+            LockRelease::Anytime => self.amount,
+            LockRelease::At(at) if now >= at => self.amount,
+            LockRelease::At(_) => Zero::zero(),
+            LockRelease::Linear { end, .. } if now >= end => self.amount,
+            LockRelease::Linear { start, .. } if now <= start => Zero::zero(),
+            LockRelease::Linear { start, end } => {
+                let elapsed = (now - start).saturated_into::<u128>();
+                let duration = (end - start).saturated_into::<u128>();
+                multiply_by_rational_with_rounding(
+                    self.amount.saturated_into::<u128>(),
+                    elapsed,
+                    duration,
+                    Rounding::Down,
+                )
+                .map(|unlocked| unlocked.saturated_into())
+                .unwrap_or(self.amount)
+            },
+        };
+        unlocked.saturating_sub(self.released)
+    }
+
+    /// Converts the reason, e.g. from a bounded on-chain vector into a plain one.
+    pub fn map_reason<New>(self, f: impl FnOnce(Reason) -> New) -> CounterLock<Balance, BlockNumber, New> {
+        CounterLock {
+            id: self.id,
+            amount: self.amount,
+            released: self.released,
+            release: self.release,
+            reason: f(self.reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(release: LockRelease<u32>) -> CounterLock<u64, u32, ()> {
+        CounterLock { id: *b"test    ", amount: 1_000, released: 0, release, reason: () }
+    }
+
+    #[test]
+    fn fixed_release() {
+        assert_eq!(lock(LockRelease::Anytime).releasable(0), 1_000);
+        assert_eq!(lock(LockRelease::At(10)).releasable(9), 0);
+        assert_eq!(lock(LockRelease::At(10)).releasable(10), 1_000);
+    }
+
+    #[test]
+    fn linear_release() {
+        let mut linear = lock(LockRelease::Linear { start: 10, end: 20 });
+        assert_eq!(linear.releasable(10), 0);
+        assert_eq!(linear.releasable(13), 300);
+        linear.released = 300;
+        assert_eq!(linear.releasable(13), 0);
+        assert_eq!(linear.releasable(15), 200);
+        assert_eq!(linear.releasable(25), 700);
+        assert_eq!(linear.remaining(), 700);
+    }
+
+    #[test]
+    fn rejects_empty_schedule() {
+        assert!(!LockRelease::Linear { start: 5u32, end: 5 }.is_valid());
+        assert!(LockRelease::Linear { start: 5u32, end: 6 }.is_valid());
+    }
+}
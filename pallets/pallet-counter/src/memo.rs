@@ -0,0 +1,199 @@
+//! Governance-editable rules for the memo attached to `balance_transfer_new`.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// Substrings that mark a memo as a link when link detection is on.
+pub const LINK_PATTERNS: [&str; 12] = [
+    "http://", "https://", "www.", ".com", ".net", ".org", ".xyz", ".io", ".gov", ".edu", ".mil", ".info",
+];
+
+/// Characters a memo may contain.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum MemoCharset {
+    /// Any UTF-8 text without control characters.
+    Utf8,
+    /// ASCII `0x20..=0x7e`.
+    PrintableAscii,
+    /// ASCII letters, digits and spaces.
+    Alphanumeric,
+}
+
+impl MemoCharset {
+    fn allows(&self, c: char) -> bool {
+        match self {
+            MemoCharset::Utf8 => !c.is_control(),
+            MemoCharset::PrintableAscii => matches!(c, ' '..='~'),
+            MemoCharset::Alphanumeric => c.is_ascii_alphanumeric() || c == ' ',
+        }
+    }
+}
+
+/// Rules a memo must satisfy.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct MemoPolicy<Substrings> {
+    /// Maximum memo length in bytes.
+    pub max_length: u32,
+    /// Characters the memo may contain.
+    pub charset: MemoCharset,
+    /// Substrings the memo may not contain, matched ASCII case-insensitively.
+    pub blocked_substrings: Substrings,
+    /// Reject memos containing URLs or IP addresses.
+    pub link_detection: bool,
+}
+
+/// Why a memo was rejected.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum MemoError {
+    /// The memo is longer than `max_length`.
+    TooLong,
+    /// The memo is not valid UTF-8.
+    InvalidUtf8,
+    /// The memo contains a character outside the charset, at this byte offset.
+    DisallowedCharacter(u32),
+    /// The memo contains the blocked substring at this index of `blocked_substrings`.
+    BlockedSubstring(u32),
+    /// The memo contains a URL or IP address.
+    ContainsLink,
+}
+
+impl<Substrings> MemoPolicy<Substrings> {
+    /// Converts the blocked substrings, e.g. from bounded on-chain vectors into plain ones.
+    pub fn map_substrings<New>(self, f: impl FnOnce(Substrings) -> New) -> MemoPolicy<New> {
+        MemoPolicy {
+            max_length: self.max_length,
+            charset: self.charset,
+            blocked_substrings: f(self.blocked_substrings),
+            link_detection: self.link_detection,
+        }
+    }
+
+    /// Checks `memo` against the policy, reporting the first rule it breaks.
+    pub fn validate<'a, S>(&'a self, memo: &[u8]) -> Result<(), MemoError>
+    where
+        &'a Substrings: IntoIterator<Item = &'a S>,
+        S: AsRef<[u8]> + 'a,
+    {
+        if memo.len() > self.max_length as usize {
+            return Err(MemoError::TooLong);
+        }
+        let text = core::str::from_utf8(memo).map_err(|_| MemoError::InvalidUtf8)?;
+
+        if let Some((offset, _)) = text.char_indices().find(|(_, c)| !self.charset.allows(*c)) {
+            return Err(MemoError::DisallowedCharacter(offset as u32));
+        }
+
+        for (index, blocked) in (&self.blocked_substrings).into_iter().enumerate() {
+            if contains_ignore_ascii_case(memo, blocked.as_ref()) {
+                return Err(MemoError::BlockedSubstring(index as u32));
+            }
+        }
+
+        if self.link_detection && contains_link(text) {
+            return Err(MemoError::ContainsLink);
+        }
+
+        Ok(())
+    }
+}
+
+fn contains_ignore_ascii_case(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// Whether `text` contains a URL or an IP address.
+///
+/// A bare dotted quad such as a version number is not a link; an IPv4 address counts
+/// only together with a port or path. IPv6 addresses need `::` or all eight groups so
+/// that times like `12:30:00` are not flagged.
+pub fn contains_link(text: &str) -> bool {
+    if LINK_PATTERNS.iter().any(|pattern| contains_ignore_ascii_case(text.as_bytes(), pattern.as_bytes())) {
+        return true;
+    }
+    text.split_whitespace().any(|token| {
+        let token = token.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | '"' | '\''));
+        is_ipv4_endpoint(token) || is_ipv6_address(token)
+    })
+}
+
+fn is_ipv4(text: &str) -> bool {
+    let mut octets = 0;
+    for part in text.split('.') {
+        if part.is_empty() || part.len() > 3 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return false;
+        }
+        if part.parse::<u8>().is_err() {
+            return false;
+        }
+        octets += 1;
+    }
+    octets == 4
+}
+
+fn is_ipv4_endpoint(token: &str) -> bool {
+    match token.find(|c| c == ':' || c == '/') {
+        Some(split) => is_ipv4(&token[..split]) && token.len() > split + 1,
+        None => false,
+    }
+}
+
+fn is_ipv6_address(token: &str) -> bool {
+    let token = token.trim_start_matches('[');
+    let token = token.split(']').next().unwrap_or(token);
+    let mut groups = 0;
+    let mut non_empty = 0;
+    for group in token.split(':') {
+        if group.len() > 4 || !group.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return false;
+        }
+        groups += 1;
+        if !group.is_empty() {
+            non_empty += 1;
+        }
+    }
+    groups <= 8 && non_empty > 0 && (token.contains("::") || groups == 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(blocked: &[&str]) -> MemoPolicy<Vec<Vec<u8>>> {
+        MemoPolicy {
+            max_length: 64,
+            charset: MemoCharset::PrintableAscii,
+            blocked_substrings: blocked.iter().map(|s| s.as_bytes().to_vec()).collect(),
+            link_detection: true,
+        }
+    }
+
+    #[test]
+    fn accepts_plain_memos() {
+        let policy = policy(&["scam"]);
+        assert_eq!(policy.validate(b"rent for March"), Ok(()));
+        assert_eq!(policy.validate(b"upgrade to 1.2.3.4"), Ok(()));
+        assert_eq!(policy.validate(b"meet at 12:30:00"), Ok(()));
+        assert_eq!(policy.validate(b""), Ok(()));
+    }
+
+    #[test]
+    fn reports_the_broken_rule() {
+        let policy = policy(&["fraud", "scam"]);
+        assert_eq!(policy.validate(&[b'a'; 65]), Err(MemoError::TooLong));
+        assert_eq!(policy.validate(&[0xff]), Err(MemoError::InvalidUtf8));
+        assert_eq!(policy.validate(b"ok\nnot ok"), Err(MemoError::DisallowedCharacter(2)));
+        assert_eq!(policy.validate(b"Not a SCAM"), Err(MemoError::BlockedSubstring(1)));
+        assert_eq!(policy.validate(b"see https://x"), Err(MemoError::ContainsLink));
+        assert_eq!(policy.validate(b"node 10.0.0.1:30333"), Err(MemoError::ContainsLink));
+        assert_eq!(policy.validate(b"ping fe80::1"), Err(MemoError::ContainsLink));
+    }
+
+    #[test]
+    fn link_detection_can_be_disabled() {
+        let mut policy = policy(&[]);
+        policy.link_detection = false;
+        policy.charset = MemoCharset::Utf8;
+        assert_eq!(policy.validate("café at www.example.com".as_bytes()), Ok(()));
+    }
+}
@@ -0,0 +1,213 @@
+//! Storage migrations for the counter pallet.
+
+use super::*;
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Get, OnRuntimeUpgrade},
+    weights::Weight,
+};
+
+pub mod v1 {
+    use super::*;
+
+    /// Seeds the attestor registry with the backend key that used to be hard-coded in
+    /// `include_ipfs_hash`, so that backend keeps its authority after the upgrade.
+    ///
+    /// Attestations it produced before the upgrade are not accepted anymore: it has to sign
+    /// the new `(IPFS_ATTESTATION_CONTEXT, who, nonce, ipfs_hash)` payload, see
+    /// [`Pallet::ipfs_attestation_payload`].
+    pub struct SeedLegacyAttestor<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for SeedLegacyAttestor<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let onchain = Pallet::<T>::on_chain_storage_version();
+            if onchain >= 1 {
+                frame_support::log::info!("pallet_counter: v1 migration already applied, skipping");
+                return T::DbWeight::get().reads(1);
+            }
+
+            let legacy = sp_core::sr25519::Public::from_raw(LEGACY_BACKEND_ATTESTOR);
+            Attestors::<T>::mutate(|attestors| {
+                if !attestors.contains(&legacy) {
+                    let _ = attestors.try_push(legacy);
+                }
+            });
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            frame_support::log::info!("pallet_counter: seeded legacy IPFS attestor");
+            T::DbWeight::get().reads_writes(2, 2)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(_state: sp_std::vec::Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 1,
+                "pallet_counter: storage version not bumped"
+            );
+            ensure!(!Attestors::<T>::get().is_empty(), "pallet_counter: no attestors after migration");
+            Ok(())
+        }
+    }
+}
+
+pub mod v2 {
+    use super::*;
+    use crate::locks::{CounterLock, LockRelease, LEGACY_LOCK_ID};
+    use frame_support::{storage_alias, traits::ReservableCurrency, BoundedVec};
+    use sp_runtime::traits::{Saturating, Zero};
+    use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+    /// The single, overwritable lock per account used before v2.
+    #[storage_alias]
+    pub type LockedBalance<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        SubstrateBalanceOf<T>,
+        ValueQuery,
+    >;
+
+    /// Moves every entry of `LockedBalance` to `Locks` and bumps the storage version to 2.
+    ///
+    /// `Reserves` lists the total each account reserved through the pre-v2 `lock` minus what
+    /// it got back through `unlock`, as summed from the `Locked` and `Unlocked` events of the
+    /// pre-v2 pallet: `lock` overwrote the record but reserved again every time. Each record
+    /// becomes a lock with id [`LEGACY_LOCK_ID`] that can be released at any time, and
+    /// whatever the account reserved beyond it is unreserved. An account missing from
+    /// `Reserves` is assumed to have reserved exactly its record, so its excess, if any, stays
+    /// reserved.
+    pub struct MigrateLockedBalance<T, Reserves>(PhantomData<(T, Reserves)>);
+
+    impl<T: Config, Reserves> MigrateLockedBalance<T, Reserves>
+    where
+        Reserves: Get<Vec<(T::AccountId, SubstrateBalanceOf<T>)>>,
+    {
+        /// Every account with a legacy lock or legacy reserve, with its record and the amount
+        /// it reserved through the pre-v2 `lock`.
+        fn legacy_accounts() -> Vec<(T::AccountId, SubstrateBalanceOf<T>, SubstrateBalanceOf<T>)> {
+            let mut reserves: BTreeMap<_, _> = Reserves::get().into_iter().collect();
+            let mut accounts: Vec<_> = LockedBalance::<T>::iter()
+                .map(|(who, recorded)| {
+                    let reserved = reserves.remove(&who).unwrap_or(recorded);
+                    (who, recorded, reserved)
+                })
+                .collect();
+            accounts.extend(reserves.into_iter().map(|(who, reserved)| (who, Zero::zero(), reserved)));
+            accounts
+        }
+    }
+
+    impl<T: Config, Reserves> OnRuntimeUpgrade for MigrateLockedBalance<T, Reserves>
+    where
+        Reserves: Get<Vec<(T::AccountId, SubstrateBalanceOf<T>)>>,
+    {
+        fn on_runtime_upgrade() -> Weight {
+            let onchain = Pallet::<T>::on_chain_storage_version();
+            if onchain >= 2 {
+                frame_support::log::info!("pallet_counter: v2 migration already applied, skipping");
+                return T::DbWeight::get().reads(1);
+            }
+
+            let accounts = Self::legacy_accounts();
+            let count = accounts.len() as u64;
+            for (who, recorded, reserved) in accounts {
+                LockedBalance::<T>::remove(&who);
+                migrate_account::<T>(&who, recorded, reserved);
+            }
+            StorageVersion::new(2).put::<Pallet<T>>();
+
+            frame_support::log::info!("pallet_counter: migrated {} legacy locks", count);
+            T::DbWeight::get()
+                .reads_writes(1, 1)
+                .saturating_add(migrate_account_weight::<T>().saturating_mul(count))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            use codec::Encode;
+
+            // The lock each legacy account should end up with.
+            let expected: Vec<(T::AccountId, SubstrateBalanceOf<T>)> = Self::legacy_accounts()
+                .into_iter()
+                .map(|(who, recorded, reserved)| {
+                    let (locked, _) = legacy_lock::<T>(&who, recorded, reserved);
+                    (who, locked)
+                })
+                .collect();
+            Ok(expected.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            use codec::Decode;
+
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 2,
+                "pallet_counter: storage version not bumped"
+            );
+            ensure!(
+                LockedBalance::<T>::iter_keys().next().is_none(),
+                "pallet_counter: legacy locks left behind"
+            );
+            let expected = Vec::<(T::AccountId, SubstrateBalanceOf<T>)>::decode(&mut &state[..])
+                .map_err(|_| "pallet_counter: cannot decode the pre-upgrade state")?;
+            for (who, locked) in expected {
+                let migrated = Locks::<T>::get(&who)
+                    .iter()
+                    .find(|lock| lock.id == LEGACY_LOCK_ID)
+                    .map(|lock| lock.amount)
+                    .unwrap_or_else(Zero::zero);
+                ensure!(migrated == locked, "pallet_counter: a legacy lock was not moved");
+            }
+            Ok(())
+        }
+    }
+
+    /// Weight of [`migrate_account`].
+    pub fn migrate_account_weight<T: Config>() -> Weight {
+        // The legacy record, the account, its locks and the reserved total.
+        T::DbWeight::get().reads_writes(4, 4)
+    }
+
+    /// Amount of the legacy lock of `who` and the part of what it reserved through the pre-v2
+    /// `lock` that no lock backs anymore.
+    fn legacy_lock<T: Config>(
+        who: &T::AccountId,
+        recorded: SubstrateBalanceOf<T>,
+        reserved_by_locks: SubstrateBalanceOf<T>,
+    ) -> (SubstrateBalanceOf<T>, SubstrateBalanceOf<T>) {
+        let reserved_by_locks = reserved_by_locks.min(T::SubstrateCurrency::reserved_balance(who));
+        let locked = recorded.min(reserved_by_locks);
+        (locked, reserved_by_locks.saturating_sub(locked))
+    }
+
+    /// Moves the legacy lock of `who` into `Locks` and unreserves what the account reserved
+    /// through the pre-v2 `lock` beyond it.
+    fn migrate_account<T: Config>(
+        who: &T::AccountId,
+        recorded: SubstrateBalanceOf<T>,
+        reserved_by_locks: SubstrateBalanceOf<T>,
+    ) {
+        let (locked, excess) = legacy_lock::<T>(who, recorded, reserved_by_locks);
+        if !excess.is_zero() {
+            T::SubstrateCurrency::unreserve(who, excess);
+        }
+        if locked.is_zero() {
+            return;
+        }
+
+        let lock = CounterLock {
+            id: LEGACY_LOCK_ID,
+            amount: locked,
+            released: Zero::zero(),
+            release: LockRelease::Anytime,
+            reason: BoundedVec::default(),
+        };
+        if Locks::<T>::mutate(who, |locks| locks.try_insert(0, lock)).is_err() {
+            // Only reachable with `MaxLocksPerAccount` of zero: nothing could hold the funds.
+            T::SubstrateCurrency::unreserve(who, locked);
+            return;
+        }
+        Pallet::<T>::note_reserved(locked);
+    }
+}
@@ -0,0 +1,141 @@
+//! Test mock for unit tests and benchmarking
+
+use crate as pallet_counter;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, ConstU64},
+    weights::Weight,
+};
+use frame_system::EnsureRoot;
+use pallet_evm::{EnsureAddressNever, EnsureAddressRoot, HashedAddressMapping};
+use sp_core::{H256, U256};
+use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    AccountId32, BuildStorage,
+};
+use sp_std::sync::Arc;
+
+pub type AccountId = AccountId32;
+pub type Balance = u128;
+
+frame_support::construct_runtime! {
+    pub enum Test {
+        System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        Timestamp: pallet_timestamp::{Pallet, Call, Storage},
+        EVM: pallet_evm::{Pallet, Call, Storage, Config<T>, Event<T>},
+        PalletCounter: pallet_counter::{Pallet, Call, Storage, Event<T>},
+    }
+}
+
+impl frame_system::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = frame_system::mocking::MockBlock<Self>;
+    type BlockHashCount = ConstU64<250>;
+    type DbWeight = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type Balance = Balance;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type ReserveIdentifier = [u8; 8];
+    type RuntimeHoldReason = ();
+    type FreezeIdentifier = ();
+    type MaxLocks = ();
+    type MaxReserves = ConstU32<50>;
+    type MaxHolds = ();
+    type MaxFreezes = ();
+}
+
+impl pallet_timestamp::Config for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = ConstU64<1000>;
+    type WeightInfo = ();
+}
+
+const BLOCK_GAS_LIMIT: u64 = 150_000_000;
+const MAX_POV_SIZE: u64 = 5 * 1024 * 1024;
+
+parameter_types! {
+    pub BlockGasLimit: U256 = U256::from(BLOCK_GAS_LIMIT);
+    pub const GasLimitPovSizeRatio: u64 = BLOCK_GAS_LIMIT.saturating_div(MAX_POV_SIZE);
+    pub WeightPerGas: Weight = Weight::from_parts(20_000, 0);
+}
+
+impl pallet_evm::Config for Test {
+    type FeeCalculator = ();
+    type GasWeightMapping = pallet_evm::FixedGasWeightMapping<Self>;
+    type WeightPerGas = WeightPerGas;
+    type BlockHashMapping = pallet_evm::SubstrateBlockHashMapping<Self>;
+    type CallOrigin = EnsureAddressRoot<Self::AccountId>;
+    type WithdrawOrigin = EnsureAddressNever<Self::AccountId>;
+    type AddressMapping = HashedAddressMapping<BlakeTwo256>;
+    type Currency = Balances;
+    type RuntimeEvent = RuntimeEvent;
+    type PrecompilesType = ();
+    type PrecompilesValue = ();
+    type ChainId = ConstU64<1299>;
+    type BlockGasLimit = BlockGasLimit;
+    type Runner = pallet_evm::runner::stack::Runner<Self>;
+    type OnChargeTransaction = ();
+    type OnCreate = ();
+    type FindAuthor = ();
+    type GasLimitPovSizeRatio = GasLimitPovSizeRatio;
+    type Timestamp = Timestamp;
+    type WeightInfo = ();
+}
+
+impl pallet_counter::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type SubstrateCurrency = Balances;
+    type EvmCurrency = Balances;
+    type AttestorOrigin = EnsureRoot<AccountId>;
+    type MaxAttestors = ConstU32<16>;
+    type MaxCidLength = ConstU32<128>;
+    type MaxIpfsTagLength = ConstU32<64>;
+    type BridgeGuardOrigin = EnsureRoot<AccountId>;
+    type MaxLocksPerAccount = ConstU32<16>;
+    type MaxLockReasonLength = ConstU32<64>;
+    type MemoPolicyOrigin = EnsureRoot<AccountId>;
+    type MaxMemoLength = ConstU32<256>;
+    type MaxBlockedSubstrings = ConstU32<32>;
+    type MaxBlockedSubstringLength = ConstU32<32>;
+    type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.register_extension(KeystoreExt(Arc::new(MemoryKeystore::new())));
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,814 @@
+//! Tests for the counter pallet's extrinsics.
+
+use crate::{mock::*, Error, Event};
+use frame_support::{assert_noop, assert_ok, traits::Currency};
+use pallet_evm::AddressMapping;
+use sp_core::{ecdsa, Pair, H160, U256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::{AccountId32, TokenError};
+
+const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
+const BOB: AccountId32 = AccountId32::new([2u8; 32]);
+const CHARLIE: AccountId32 = AccountId32::new([3u8; 32]);
+
+fn evm_address_of(pair: &ecdsa::Pair) -> H160 {
+    let signature = pair.sign_prehashed(&[0u8; 32]);
+    let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&signature.0, &[0u8; 32]).unwrap();
+    H160::from_slice(&keccak_256(&pubkey)[12..])
+}
+
+fn evm_account(address: H160) -> AccountId {
+    <Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+/// An EVM key whose mapped account holds `balance`.
+fn funded_evm_key(seed: u8, balance: Balance) -> (ecdsa::Pair, H160) {
+    let pair = ecdsa::Pair::from_seed(&[seed; 32]);
+    let address = evm_address_of(&pair);
+    Balances::make_free_balance_be(&evm_account(address), balance);
+    (pair, address)
+}
+
+/// Signs the EIP-712 `EvmToSubstrate` message for the current nonce of `from`.
+fn sign_evm_to_substrate(pair: &ecdsa::Pair, dest: &AccountId, amount: u128, deadline: u64) -> ecdsa::Signature {
+    let from = evm_address_of(pair);
+    let payload = PalletCounter::evm_to_substrate_payload(from, dest, U256::from(amount), deadline).unwrap();
+    pair.sign_prehashed(&payload.digest.0)
+}
+
+mod substrate_to_evm {
+    use super::*;
+
+    #[test]
+    fn reports_why_the_transfer_failed() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 100);
+            let to = H160::repeat_byte(0x11);
+
+            assert_noop!(
+                PalletCounter::substrate_to_evm(RuntimeOrigin::signed(ALICE), to, 500, true),
+                TokenError::FundsUnavailable
+            );
+            // The source account is kept alive.
+            assert_noop!(
+                PalletCounter::substrate_to_evm(RuntimeOrigin::signed(ALICE), to, 100, true),
+                TokenError::NotExpendable
+            );
+        });
+    }
+}
+
+mod try_state {
+    use super::*;
+    use crate::{
+        limits::{BridgeLimits, PendingTransfer},
+        locks::{CounterLock, LockRelease},
+        migrations::v2::LockedBalance,
+        BridgeDirection, CounterLockOf, Locks, PendingTransfers, TotalReserved,
+    };
+    use frame_support::traits::{ReservableCurrency, StorageVersion};
+
+    fn lock(id: &[u8; 8], amount: Balance, released: Balance) -> CounterLockOf<Test> {
+        CounterLock { id: *id, amount, released, release: LockRelease::Anytime, reason: Default::default() }
+    }
+
+    #[test]
+    fn holds_across_mints_burns_locks_and_crossings() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(PalletCounter::mint(RuntimeOrigin::root(), ALICE, 1_000));
+            assert_ok!(PalletCounter::substrate_to_evm(RuntimeOrigin::signed(ALICE), H160::repeat_byte(0x11), 300, true));
+            assert_ok!(PalletCounter::burn(RuntimeOrigin::root(), ALICE, 200));
+            assert_ok!(PalletCounter::lock(
+                RuntimeOrigin::signed(ALICE),
+                *b"lock0000",
+                100,
+                LockRelease::Anytime,
+                Default::default(),
+            ));
+            // A crossing above the delay threshold is queued and reserved.
+            assert_ok!(PalletCounter::set_bridge_limits(
+                RuntimeOrigin::root(),
+                BridgeDirection::SubstrateToEvm,
+                Some(BridgeLimits {
+                    per_transfer_cap: 1_000,
+                    per_account_cap: 1_000,
+                    global_cap: 1_000,
+                    window: 10,
+                    delay_threshold: 150,
+                    delay: 5,
+                }),
+            ));
+            assert_ok!(PalletCounter::substrate_to_evm(RuntimeOrigin::signed(ALICE), H160::repeat_byte(0x11), 200, true));
+            assert_eq!(PalletCounter::total_reserved(), 300);
+            assert_ok!(PalletCounter::do_try_state());
+
+            assert_ok!(PalletCounter::unlock(RuntimeOrigin::signed(ALICE), *b"lock0000"));
+            assert_ok!(PalletCounter::cancel_pending_transfer(RuntimeOrigin::root(), 0));
+            assert_eq!(PalletCounter::total_reserved(), 0);
+            assert_ok!(PalletCounter::do_try_state());
+        });
+    }
+
+    #[test]
+    fn records_the_issuance_around_a_crossing() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(PalletCounter::mint(RuntimeOrigin::root(), ALICE, 1_000));
+            assert_ok!(PalletCounter::substrate_to_evm(RuntimeOrigin::signed(ALICE), H160::repeat_byte(0x11), 300, true));
+
+            let audit = PalletCounter::last_supply_audit().unwrap();
+            assert_eq!((audit.amount, audit.issuance_before, audit.issuance_after), (300, 1_000, 1_000));
+        });
+    }
+
+    #[test]
+    fn detects_a_queued_crossing_without_its_reserve() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            PendingTransfers::<Test>::insert(
+                0,
+                PendingTransfer {
+                    direction: BridgeDirection::SubstrateToEvm,
+                    from: ALICE,
+                    to: BOB,
+                    amount: 100,
+                    execute_at: 5,
+                },
+            );
+            TotalReserved::<Test>::put(100);
+
+            assert!(PalletCounter::do_try_state().is_err());
+        });
+    }
+
+    #[test]
+    fn detects_a_lock_missing_from_the_reserved_total() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            assert_ok!(Balances::reserve(&ALICE, 100));
+            Locks::<Test>::insert(&ALICE, sp_runtime::BoundedVec::truncate_from(vec![lock(b"lock0000", 100, 0)]));
+
+            assert!(PalletCounter::do_try_state().is_err());
+            TotalReserved::<Test>::put(100);
+            assert_ok!(PalletCounter::do_try_state());
+        });
+    }
+
+    #[test]
+    fn detects_a_malformed_lock() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            Locks::<Test>::insert(&ALICE, sp_runtime::BoundedVec::truncate_from(vec![lock(b"lock0000", 100, 100)]));
+
+            assert!(PalletCounter::do_try_state().is_err());
+        });
+    }
+
+    #[test]
+    fn detects_a_legacy_lock_after_the_migration() {
+        new_test_ext().execute_with(|| {
+            LockedBalance::<Test>::insert(&ALICE, 300);
+            StorageVersion::new(1).put::<PalletCounter>();
+            assert_ok!(PalletCounter::do_try_state());
+
+            StorageVersion::new(2).put::<PalletCounter>();
+            assert!(PalletCounter::do_try_state().is_err());
+        });
+    }
+}
+
+mod bridge_limits {
+    use super::*;
+    use crate::{limits::BridgeLimits, BridgeDirection, PendingTransfers};
+    use frame_support::traits::ReservableCurrency;
+    use sp_runtime::DispatchError;
+
+    const TO: H160 = H160::repeat_byte(0x11);
+
+    /// Caps of 300 per crossing, 500 per account and 800 overall per 10 blocks. Crossings
+    /// of at least `delay_threshold` wait 5 blocks.
+    fn limit(direction: BridgeDirection, delay_threshold: Balance) {
+        assert_ok!(PalletCounter::set_bridge_limits(
+            RuntimeOrigin::root(),
+            direction,
+            Some(BridgeLimits {
+                window: 10,
+                per_transfer_cap: 300,
+                per_account_cap: 500,
+                global_cap: 800,
+                delay_threshold,
+                delay: 5,
+            }),
+        ));
+    }
+
+    fn to_evm(who: AccountId, amount: Balance) -> sp_runtime::DispatchResult {
+        PalletCounter::substrate_to_evm(RuntimeOrigin::signed(who), TO, amount, true)
+    }
+
+    #[test]
+    fn pausing_one_direction_leaves_the_other_open() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            let (pair, from) = funded_evm_key(7, 1_000);
+            let to_substrate = |amount: u128| {
+                let signature = sign_evm_to_substrate(&pair, &BOB, amount, 10);
+                PalletCounter::evm_to_substrate(
+                    RuntimeOrigin::signed(ALICE),
+                    from,
+                    BOB,
+                    U256::from(amount),
+                    10,
+                    signature,
+                )
+            };
+
+            assert_noop!(
+                PalletCounter::set_bridge_paused(RuntimeOrigin::signed(ALICE), BridgeDirection::SubstrateToEvm, true),
+                DispatchError::BadOrigin
+            );
+
+            assert_ok!(PalletCounter::set_bridge_paused(RuntimeOrigin::root(), BridgeDirection::SubstrateToEvm, true));
+            assert!(PalletCounter::bridge_paused(BridgeDirection::SubstrateToEvm));
+            System::assert_has_event(
+                Event::<Test>::BridgePauseSet { direction: BridgeDirection::SubstrateToEvm, paused: true }.into(),
+            );
+            assert_noop!(to_evm(ALICE, 100), Error::<Test>::BridgePaused);
+            assert_ok!(to_substrate(100));
+
+            assert_ok!(PalletCounter::set_bridge_paused(RuntimeOrigin::root(), BridgeDirection::SubstrateToEvm, false));
+            assert_ok!(PalletCounter::set_bridge_paused(RuntimeOrigin::root(), BridgeDirection::EvmToSubstrate, true));
+            assert_noop!(to_substrate(100), Error::<Test>::BridgePaused);
+            assert_ok!(to_evm(ALICE, 100));
+
+            assert_eq!(Balances::free_balance(&BOB), 100);
+            assert_eq!(Balances::free_balance(&evm_account(TO)), 100);
+        });
+    }
+
+    #[test]
+    fn rejects_a_delay_threshold_outside_the_transfer_cap() {
+        new_test_ext().execute_with(|| {
+            let limits = BridgeLimits {
+                window: 10,
+                per_transfer_cap: 300,
+                per_account_cap: 500,
+                global_cap: 800,
+                delay_threshold: 0,
+                delay: 5,
+            };
+            let set = |delay_threshold| {
+                PalletCounter::set_bridge_limits(
+                    RuntimeOrigin::root(),
+                    BridgeDirection::SubstrateToEvm,
+                    Some(BridgeLimits { delay_threshold, ..limits }),
+                )
+            };
+
+            assert_noop!(set(0), Error::<Test>::InvalidBridgeLimits);
+            assert_noop!(set(301), Error::<Test>::InvalidBridgeLimits);
+            assert_ok!(set(1));
+            assert_ok!(set(300));
+        });
+    }
+
+    #[test]
+    fn enforces_the_transfer_account_and_global_caps() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 10_000);
+            Balances::make_free_balance_be(&BOB, 10_000);
+            limit(BridgeDirection::SubstrateToEvm, 300);
+
+            assert_noop!(to_evm(ALICE, 301), Error::<Test>::TransferBridgeLimitExceeded);
+
+            assert_ok!(to_evm(ALICE, 300));
+            assert_ok!(to_evm(ALICE, 200));
+            assert_noop!(to_evm(ALICE, 1), Error::<Test>::AccountBridgeLimitExceeded);
+
+            assert_ok!(to_evm(BOB, 300));
+            assert_noop!(to_evm(BOB, 1), Error::<Test>::GlobalBridgeLimitExceeded);
+
+            assert_eq!(PalletCounter::bridge_usage(BridgeDirection::SubstrateToEvm, &ALICE), (500, 800));
+            System::assert_has_event(
+                Event::<Test>::BridgeUsageUpdated {
+                    direction: BridgeDirection::SubstrateToEvm,
+                    who: BOB,
+                    account_used: 300,
+                    global_used: 800,
+                }
+                .into(),
+            );
+            // The other direction has no limits.
+            assert_eq!(PalletCounter::bridge_usage(BridgeDirection::EvmToSubstrate, &ALICE), (0, 0));
+        });
+    }
+
+    #[test]
+    fn the_window_rolls_over() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 10_000);
+            limit(BridgeDirection::SubstrateToEvm, 300);
+            assert_ok!(to_evm(ALICE, 300));
+            assert_ok!(to_evm(ALICE, 200));
+
+            // Half of the previous window still counts.
+            System::set_block_number(15);
+            assert_eq!(PalletCounter::bridge_usage(BridgeDirection::SubstrateToEvm, &ALICE), (250, 250));
+            assert_noop!(to_evm(ALICE, 251), Error::<Test>::AccountBridgeLimitExceeded);
+            assert_ok!(to_evm(ALICE, 250));
+
+            // Two windows later nothing does.
+            System::set_block_number(30);
+            assert_eq!(PalletCounter::bridge_usage(BridgeDirection::SubstrateToEvm, &ALICE), (0, 0));
+            assert_ok!(to_evm(ALICE, 300));
+            assert_ok!(to_evm(ALICE, 200));
+        });
+    }
+
+    #[test]
+    fn queues_crossings_at_the_delay_threshold() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            limit(BridgeDirection::SubstrateToEvm, 100);
+
+            assert_ok!(to_evm(ALICE, 99));
+            assert_eq!(Balances::free_balance(&evm_account(TO)), 99);
+
+            assert_ok!(to_evm(ALICE, 100));
+            assert_eq!(Balances::free_balance(&evm_account(TO)), 99);
+            assert_eq!(Balances::reserved_balance(&ALICE), 100);
+            assert_eq!(Balances::free_balance(&ALICE), 801);
+            let pending = PalletCounter::pending_transfer(0).unwrap();
+            assert_eq!((pending.from, pending.amount, pending.execute_at), (ALICE, 100, 6));
+            System::assert_has_event(
+                Event::<Test>::TransferQueued {
+                    id: 0,
+                    direction: BridgeDirection::SubstrateToEvm,
+                    from: ALICE,
+                    to: evm_account(TO),
+                    amount: 100,
+                    execute_at: 6,
+                }
+                .into(),
+            );
+            // Queued crossings count against the caps right away.
+            assert_eq!(PalletCounter::bridge_usage(BridgeDirection::SubstrateToEvm, &ALICE), (199, 199));
+        });
+    }
+
+    #[test]
+    fn executes_a_queued_crossing_once_it_is_due() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            limit(BridgeDirection::SubstrateToEvm, 100);
+            assert_ok!(to_evm(ALICE, 100));
+
+            System::set_block_number(5);
+            assert_noop!(
+                PalletCounter::execute_pending_transfer(RuntimeOrigin::signed(BOB), 0),
+                Error::<Test>::PendingTransferNotDue
+            );
+
+            System::set_block_number(6);
+            assert_ok!(PalletCounter::execute_pending_transfer(RuntimeOrigin::signed(BOB), 0));
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 0);
+            assert_eq!(Balances::free_balance(&ALICE), 900);
+            assert_eq!(Balances::free_balance(&evm_account(TO)), 100);
+            assert!(!PendingTransfers::<Test>::contains_key(0));
+            System::assert_has_event(Event::<Test>::PendingTransferExecuted { id: 0 }.into());
+            assert_noop!(
+                PalletCounter::execute_pending_transfer(RuntimeOrigin::signed(BOB), 0),
+                Error::<Test>::PendingTransferNotFound
+            );
+        });
+    }
+
+    #[test]
+    fn cancelling_returns_the_reserve() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            limit(BridgeDirection::SubstrateToEvm, 100);
+            assert_ok!(to_evm(ALICE, 100));
+
+            assert_noop!(
+                PalletCounter::cancel_pending_transfer(RuntimeOrigin::signed(ALICE), 0),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(PalletCounter::cancel_pending_transfer(RuntimeOrigin::root(), 0));
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 0);
+            assert_eq!(Balances::free_balance(&ALICE), 1_000);
+            assert_eq!(Balances::free_balance(&evm_account(TO)), 0);
+            assert!(!PendingTransfers::<Test>::contains_key(0));
+            System::assert_has_event(Event::<Test>::PendingTransferCancelled { id: 0 }.into());
+
+            System::set_block_number(6);
+            assert_noop!(
+                PalletCounter::execute_pending_transfer(RuntimeOrigin::signed(BOB), 0),
+                Error::<Test>::PendingTransferNotFound
+            );
+        });
+    }
+}
+
+mod evm_to_substrate {
+    use super::*;
+
+    #[test]
+    fn moves_funds_and_bumps_the_nonce() {
+        new_test_ext().execute_with(|| {
+            let (pair, from) = funded_evm_key(7, 1_000);
+            let signature = sign_evm_to_substrate(&pair, &BOB, 400, 10);
+
+            assert_ok!(PalletCounter::evm_to_substrate(
+                RuntimeOrigin::signed(ALICE),
+                from,
+                BOB,
+                U256::from(400),
+                10,
+                signature,
+            ));
+
+            assert_eq!(Balances::free_balance(&BOB), 400);
+            assert_eq!(Balances::free_balance(&evm_account(from)), 600);
+            assert_eq!(PalletCounter::evm_nonce(from), U256::one());
+            System::assert_has_event(Event::<Test>::EvmToSubstrateTransfer(from, BOB, 400).into());
+        });
+    }
+
+    #[test]
+    fn rejects_a_replayed_signature() {
+        new_test_ext().execute_with(|| {
+            let (pair, from) = funded_evm_key(7, 1_000);
+            let signature = sign_evm_to_substrate(&pair, &BOB, 100, 10);
+            let submit = || {
+                PalletCounter::evm_to_substrate(
+                    RuntimeOrigin::signed(ALICE),
+                    from,
+                    BOB,
+                    U256::from(100),
+                    10,
+                    signature.clone(),
+                )
+            };
+
+            assert_ok!(submit());
+            assert_noop!(submit(), Error::<Test>::Unauthorized);
+            assert_eq!(Balances::free_balance(&BOB), 100);
+        });
+    }
+
+    #[test]
+    fn rejects_an_expired_deadline() {
+        new_test_ext().execute_with(|| {
+            let (pair, from) = funded_evm_key(7, 1_000);
+            let signature = sign_evm_to_substrate(&pair, &BOB, 100, 10);
+            System::set_block_number(11);
+
+            assert_noop!(
+                PalletCounter::evm_to_substrate(
+                    RuntimeOrigin::signed(ALICE),
+                    from,
+                    BOB,
+                    U256::from(100),
+                    10,
+                    signature,
+                ),
+                Error::<Test>::SignatureExpired
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_a_signature_for_another_destination() {
+        new_test_ext().execute_with(|| {
+            let (pair, from) = funded_evm_key(7, 1_000);
+            let signature = sign_evm_to_substrate(&pair, &BOB, 100, 10);
+
+            assert_noop!(
+                PalletCounter::evm_to_substrate(
+                    RuntimeOrigin::signed(ALICE),
+                    from,
+                    CHARLIE,
+                    U256::from(100),
+                    10,
+                    signature,
+                ),
+                Error::<Test>::Unauthorized
+            );
+            assert_eq!(PalletCounter::evm_nonce(from), U256::zero());
+        });
+    }
+}
+
+mod memo_policy {
+    use super::*;
+    use crate::{memo::{MemoCharset, MemoError, MemoPolicy}, MemoPolicyOf};
+    use frame_support::BoundedVec;
+    use sp_runtime::DispatchError;
+
+    /// Alphanumeric memos of up to 16 bytes, no "rent", links allowed.
+    fn strict_policy() -> MemoPolicyOf<Test> {
+        MemoPolicy {
+            max_length: 16,
+            charset: MemoCharset::Alphanumeric,
+            blocked_substrings: BoundedVec::truncate_from(vec![BoundedVec::truncate_from(b"rent".to_vec())]),
+            link_detection: false,
+        }
+    }
+
+    #[test]
+    fn only_the_memo_policy_origin_may_set_it() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PalletCounter::set_memo_policy(RuntimeOrigin::signed(ALICE), strict_policy()),
+                DispatchError::BadOrigin
+            );
+
+            assert_ok!(PalletCounter::set_memo_policy(RuntimeOrigin::root(), strict_policy()));
+            assert_eq!(PalletCounter::memo_policy(), strict_policy());
+            System::assert_has_event(Event::<Test>::MemoPolicySet { policy: strict_policy() }.into());
+        });
+    }
+
+    #[test]
+    fn rejects_a_policy_beyond_the_configured_bounds() {
+        new_test_ext().execute_with(|| {
+            let too_long = MemoPolicy { max_length: 257, ..strict_policy() };
+            let empty_substring = MemoPolicy {
+                blocked_substrings: BoundedVec::truncate_from(vec![BoundedVec::new()]),
+                ..strict_policy()
+            };
+
+            for policy in [too_long, empty_substring] {
+                assert_noop!(
+                    PalletCounter::set_memo_policy(RuntimeOrigin::root(), policy),
+                    Error::<Test>::InvalidMemoPolicy
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn transfers_and_dry_runs_follow_the_new_policy() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            let transfer = |memo: &[u8]| {
+                PalletCounter::balance_transfer_new(RuntimeOrigin::signed(ALICE), BOB, 10, memo.to_vec())
+            };
+
+            // The default policy refuses links and accepts punctuation.
+            assert_noop!(transfer(b"see www.example"), Error::<Test>::SuspiciousContent);
+            assert_eq!(PalletCounter::validate_memo(b"rent, March"), Ok(()));
+
+            assert_ok!(PalletCounter::set_memo_policy(RuntimeOrigin::root(), strict_policy()));
+
+            assert_eq!(PalletCounter::validate_memo(b"see www"), Ok(()));
+            assert_ok!(transfer(b"see www"));
+            assert_eq!(Balances::free_balance(&BOB), 10);
+
+            assert_eq!(PalletCounter::validate_memo(b"rent, March"), Err(MemoError::DisallowedCharacter(4)));
+            assert_noop!(transfer(b"rent, March"), Error::<Test>::InvalidMessageContent);
+            assert_eq!(PalletCounter::validate_memo(b"Rent for March"), Err(MemoError::BlockedSubstring(0)));
+            assert_noop!(transfer(b"Rent for March"), Error::<Test>::SuspiciousContent);
+            assert_eq!(PalletCounter::validate_memo(b"a very long memo text"), Err(MemoError::TooLong));
+            assert_noop!(transfer(b"a very long memo text"), Error::<Test>::MessageTooLong);
+        });
+    }
+}
+
+mod include_ipfs_hash {
+    use super::*;
+    use crate::CidOf;
+    use codec::Encode;
+    use frame_support::BoundedVec;
+    use sp_core::sr25519;
+
+    const CID: &[u8] = b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+    fn cid(text: &[u8]) -> CidOf<Test> {
+        text.to_vec().try_into().unwrap()
+    }
+
+    fn registered_attestor() -> sr25519::Pair {
+        let pair = sr25519::Pair::from_seed(&[9u8; 32]);
+        assert_ok!(PalletCounter::add_attestor(RuntimeOrigin::root(), pair.public()));
+        pair
+    }
+
+    fn attest(pair: &sr25519::Pair, payload: &[u8]) -> BoundedVec<(sr25519::Public, sr25519::Signature), MaxAttestors> {
+        BoundedVec::truncate_from(vec![(pair.public(), pair.sign(payload))])
+    }
+
+    type MaxAttestors = <Test as crate::Config>::MaxAttestors;
+
+    #[test]
+    fn registers_the_cid_and_bumps_the_nonce() {
+        new_test_ext().execute_with(|| {
+            let attestor = registered_attestor();
+            let payload = PalletCounter::ipfs_attestation_payload(&ALICE, 0, CID);
+
+            assert_ok!(PalletCounter::include_ipfs_hash(
+                RuntimeOrigin::signed(ALICE),
+                cid(CID),
+                None,
+                attest(&attestor, &payload),
+            ));
+
+            assert_eq!(PalletCounter::attestation_nonce(&ALICE), 1);
+            assert_eq!(PalletCounter::ipfs_hashes_of(&ALICE).len(), 1);
+            System::assert_has_event(Event::<Test>::IPFSHashIncluded(ALICE, CID.to_vec()).into());
+        });
+    }
+
+    #[test]
+    fn treats_every_encoding_of_a_cid_as_the_same_entry() {
+        new_test_ext().execute_with(|| {
+            const V1: &[u8] = b"bafybeie5nqv6kd3qnfjupgvz34woh3oksc3iau6abmyajn7qvtf6d2ho34";
+            let attestor = registered_attestor();
+            let payload = PalletCounter::ipfs_attestation_payload(&ALICE, 0, CID);
+            assert_ok!(PalletCounter::include_ipfs_hash(
+                RuntimeOrigin::signed(ALICE),
+                cid(CID),
+                None,
+                attest(&attestor, &payload),
+            ));
+
+            let payload = PalletCounter::ipfs_attestation_payload(&BOB, 0, V1);
+            assert_noop!(
+                PalletCounter::include_ipfs_hash(RuntimeOrigin::signed(BOB), cid(V1), None, attest(&attestor, &payload)),
+                Error::<Test>::IPFSHashAlreadyRegistered
+            );
+            assert!(PalletCounter::ipfs_record(V1).is_some());
+
+            assert_ok!(PalletCounter::deregister_ipfs_hash(RuntimeOrigin::signed(ALICE), cid(V1)));
+            assert!(PalletCounter::ipfs_record(CID).is_none());
+            assert!(PalletCounter::ipfs_hashes_of(&ALICE).is_empty());
+        });
+    }
+
+    #[test]
+    fn rejects_an_attestation_for_a_spent_nonce() {
+        new_test_ext().execute_with(|| {
+            let attestor = registered_attestor();
+            let payload = PalletCounter::ipfs_attestation_payload(&ALICE, 0, CID);
+            assert_ok!(PalletCounter::include_ipfs_hash(
+                RuntimeOrigin::signed(ALICE),
+                cid(CID),
+                None,
+                attest(&attestor, &payload),
+            ));
+            assert_ok!(PalletCounter::deregister_ipfs_hash(RuntimeOrigin::signed(ALICE), cid(CID)));
+
+            assert_noop!(
+                PalletCounter::include_ipfs_hash(
+                    RuntimeOrigin::signed(ALICE),
+                    cid(CID),
+                    None,
+                    attest(&attestor, &payload),
+                ),
+                Error::<Test>::InvalidSignature
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_an_attestation_for_another_context_or_account() {
+        new_test_ext().execute_with(|| {
+            let attestor = registered_attestor();
+            let other_context = (b"argochain/other".as_slice(), &ALICE, 0u64, CID).encode();
+            let other_account = PalletCounter::ipfs_attestation_payload(&BOB, 0, CID);
+            // Attestors signed the bare CID before the registry existed.
+            let legacy = CID.to_vec();
+
+            for payload in [other_context, other_account, legacy] {
+                assert_noop!(
+                    PalletCounter::include_ipfs_hash(
+                        RuntimeOrigin::signed(ALICE),
+                        cid(CID),
+                        None,
+                        attest(&attestor, &payload),
+                    ),
+                    Error::<Test>::InvalidSignature
+                );
+            }
+        });
+    }
+}
+
+mod locks {
+    use super::*;
+    use crate::{
+        locks::{LockRelease, LEGACY_LOCK_ID},
+        migrations::v2::{LockedBalance, MigrateLockedBalance},
+    };
+    use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, ReservableCurrency, StorageVersion};
+
+    frame_support::parameter_types! {
+        pub LegacyReserves: Vec<(AccountId, Balance)> = vec![(ALICE, 400)];
+        pub NoLegacyReserves: Vec<(AccountId, Balance)> = vec![];
+        pub UnrecordedReserves: Vec<(AccountId, Balance)> = vec![(ALICE, 100)];
+    }
+
+    #[test]
+    fn rejects_a_zero_amount() {
+        new_test_ext().execute_with(|| {
+            Balances::make_free_balance_be(&ALICE, 1_000);
+
+            assert_noop!(
+                PalletCounter::lock(
+                    RuntimeOrigin::signed(ALICE),
+                    *b"lock0000",
+                    0,
+                    LockRelease::Anytime,
+                    Default::default(),
+                ),
+                Error::<Test>::ZeroLockAmount
+            );
+        });
+    }
+
+    /// ALICE called the pre-v2 `lock` with 100, then 300: 400 reserved, the record says 300.
+    fn legacy_lock_of_alice() {
+        StorageVersion::new(1).put::<PalletCounter>();
+        Balances::make_free_balance_be(&ALICE, 1_000);
+        assert_ok!(Balances::reserve(&ALICE, 400));
+        LockedBalance::<Test>::insert(&ALICE, 300);
+    }
+
+    fn legacy_lock_amount(who: &AccountId) -> Option<Balance> {
+        PalletCounter::locks(who).iter().find(|lock| lock.id == LEGACY_LOCK_ID).map(|lock| lock.amount)
+    }
+
+    #[test]
+    fn upgrading_unreserves_what_the_record_lost() {
+        new_test_ext().execute_with(|| {
+            legacy_lock_of_alice();
+
+            MigrateLockedBalance::<Test, LegacyReserves>::on_runtime_upgrade();
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 300);
+            assert_eq!(PalletCounter::locks(&ALICE).len(), 1);
+            assert_eq!(legacy_lock_amount(&ALICE), Some(300));
+            assert!(!LockedBalance::<Test>::contains_key(&ALICE));
+            assert_eq!(PalletCounter::on_chain_storage_version(), 2);
+            assert_ok!(PalletCounter::do_try_state());
+        });
+    }
+
+    #[test]
+    fn upgrading_keeps_reserves_it_cannot_attribute() {
+        new_test_ext().execute_with(|| {
+            legacy_lock_of_alice();
+
+            MigrateLockedBalance::<Test, NoLegacyReserves>::on_runtime_upgrade();
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 400);
+            assert_eq!(legacy_lock_amount(&ALICE), Some(300));
+            assert!(!LockedBalance::<Test>::contains_key(&ALICE));
+        });
+    }
+
+    #[test]
+    fn upgrading_an_emptied_record_returns_the_leftover_reserve() {
+        new_test_ext().execute_with(|| {
+            // Everything recorded was unlocked, the earlier lock of 100 was not.
+            StorageVersion::new(1).put::<PalletCounter>();
+            Balances::make_free_balance_be(&ALICE, 1_000);
+            assert_ok!(Balances::reserve(&ALICE, 100));
+
+            MigrateLockedBalance::<Test, UnrecordedReserves>::on_runtime_upgrade();
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 0);
+            assert!(PalletCounter::locks(&ALICE).is_empty());
+        });
+    }
+
+    #[test]
+    fn upgrading_twice_changes_nothing() {
+        new_test_ext().execute_with(|| {
+            legacy_lock_of_alice();
+            MigrateLockedBalance::<Test, LegacyReserves>::on_runtime_upgrade();
+
+            // A record written after the upgrade is not picked up again.
+            LockedBalance::<Test>::insert(&ALICE, 50);
+            MigrateLockedBalance::<Test, LegacyReserves>::on_runtime_upgrade();
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 300);
+            assert_eq!(legacy_lock_amount(&ALICE), Some(300));
+        });
+    }
+
+    #[test]
+    fn a_migrated_lock_can_be_released() {
+        new_test_ext().execute_with(|| {
+            legacy_lock_of_alice();
+            MigrateLockedBalance::<Test, LegacyReserves>::on_runtime_upgrade();
+
+            assert_ok!(PalletCounter::unlock(RuntimeOrigin::signed(ALICE), LEGACY_LOCK_ID));
+
+            assert_eq!(Balances::reserved_balance(&ALICE), 0);
+            assert!(PalletCounter::locks(&ALICE).is_empty());
+        });
+    }
+}
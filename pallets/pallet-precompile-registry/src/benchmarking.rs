@@ -0,0 +1,50 @@
+//! Benchmarks for pallet_precompile_registry
+
+use super::*;
+use crate::Pallet as PrecompileRegistry;
+use frame_benchmarking::{benchmarks, BenchmarkError};
+use frame_support::traits::{EnsureOrigin, Get};
+use sp_core::H160;
+
+/// A default precompile, whose address is one the registry may hold.
+fn default_precompile<T: Config>() -> Result<(H160, T::PrecompileId), BenchmarkError> {
+    T::DefaultPrecompiles::get()
+        .first()
+        .copied()
+        .ok_or(BenchmarkError::Stop("no default precompile to register"))
+}
+
+fn registered<T: Config>() -> Result<H160, BenchmarkError> {
+    let (address, id) = default_precompile::<T>()?;
+    Precompiles::<T>::insert(address, PrecompileEntry { id, status: PrecompileStatus::Active });
+    Ok(address)
+}
+
+benchmarks! {
+    set_precompile {
+        let (address, id) = default_precompile::<T>()?;
+        Precompiles::<T>::remove(address);
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, address, id, PrecompileStatus::Active)
+    verify {
+        assert_eq!(PrecompileRegistry::<T>::precompile(address), Some(PrecompileEntry { id, status: PrecompileStatus::Active }));
+    }
+
+    set_status {
+        let address = registered::<T>()?;
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, address, PrecompileStatus::Disabled)
+    verify {
+        assert_eq!(PrecompileRegistry::<T>::precompile(address).map(|entry| entry.status), Some(PrecompileStatus::Disabled));
+    }
+
+    remove_precompile {
+        let address = registered::<T>()?;
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, address)
+    verify {
+        assert!(PrecompileRegistry::<T>::precompile(address).is_none());
+    }
+
+    impl_benchmark_test_suite!(PrecompileRegistry, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,182 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Registry of the addresses the runtime's precompile set answers for.
+//!
+//! Every entry maps an address to the id of a precompile and a status, so governance can
+//! add, move or switch off a precompile without a runtime upgrade. The runtime's
+//! `PrecompileSet` is expected to consult [`Pallet::precompile`] in both `execute` and
+//! `is_precompile`, for the addresses in [`Config::PrecompileAddresses`] only, and to charge
+//! the storage read to the caller.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+pub use pallet::*;
+
+pub mod migrations;
+pub mod weights;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub use weights::WeightInfo;
+
+/// How calls to a registered address are handled.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum PrecompileStatus {
+    /// The precompile runs.
+    Active,
+    /// The address is not a precompile; calls reach whatever account lives there.
+    Disabled,
+    /// The address stays reserved, but calls fail without running the precompile and
+    /// consume the gas they were given.
+    ChargeOnly,
+}
+
+/// A registered precompile.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PrecompileEntry<Id> {
+    pub id: Id,
+    pub status: PrecompileStatus,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{dispatch::DispatchResult, pallet_prelude::*, traits::Contains};
+    use frame_system::pallet_prelude::*;
+    use sp_core::H160;
+    use sp_std::vec::Vec;
+    use crate::{PrecompileEntry, PrecompileStatus, WeightInfo};
+
+    pub type PrecompileEntryOf<T> = PrecompileEntry<<T as Config>::PrecompileId>;
+
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+    #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        /// Identifies the precompile implementations the runtime can execute.
+        type PrecompileId: Parameter + MaxEncodedLen + Copy;
+        /// Origin allowed to change the registry.
+        type RegistryOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Addresses a precompile may be registered at. Keeping them to a small range lets the
+        /// runtime skip the registry lookup for every other address.
+        type PrecompileAddresses: Contains<H160>;
+        /// Active precompiles written at genesis and by [`crate::migrations::v1::SeedDefaults`].
+        type DefaultPrecompiles: Get<Vec<(H160, Self::PrecompileId)>>;
+        type WeightInfo: WeightInfo;
+    }
+
+    #[pallet::storage]
+    #[pallet::getter(fn precompile)]
+    pub type Precompiles<T: Config> = StorageMap<_, Blake2_128Concat, H160, PrecompileEntryOf<T>, OptionQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T> {
+        #[serde(skip)]
+        pub _marker: PhantomData<T>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            Pallet::<T>::seed_defaults();
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// `address` now runs `id` with `status`.
+        PrecompileSet { address: H160, id: T::PrecompileId, status: PrecompileStatus },
+        /// The status of the precompile at `address` changed.
+        PrecompileStatusSet { address: H160, status: PrecompileStatus },
+        /// `address` is no longer registered.
+        PrecompileRemoved { address: H160 },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// No precompile is registered at the address.
+        NotRegistered,
+        /// The address is outside of `PrecompileAddresses`.
+        AddressNotAllowed,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Register `id` at `address`, replacing any precompile registered there.
+        #[pallet::weight(<T as Config>::WeightInfo::set_precompile())]
+        #[pallet::call_index(0)]
+        pub fn set_precompile(
+            origin: OriginFor<T>,
+            address: H160,
+            id: T::PrecompileId,
+            status: PrecompileStatus,
+        ) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            ensure!(T::PrecompileAddresses::contains(&address), Error::<T>::AddressNotAllowed);
+
+            Precompiles::<T>::insert(address, PrecompileEntry { id, status });
+
+            Self::deposit_event(Event::PrecompileSet { address, id, status });
+            Ok(())
+        }
+
+        /// Change the status of the precompile registered at `address`.
+        #[pallet::weight(<T as Config>::WeightInfo::set_status())]
+        #[pallet::call_index(1)]
+        pub fn set_status(origin: OriginFor<T>, address: H160, status: PrecompileStatus) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+
+            Precompiles::<T>::try_mutate(address, |entry| -> DispatchResult {
+                let entry = entry.as_mut().ok_or(Error::<T>::NotRegistered)?;
+                entry.status = status;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::PrecompileStatusSet { address, status });
+            Ok(())
+        }
+
+        /// Remove the precompile registered at `address`.
+        #[pallet::weight(<T as Config>::WeightInfo::remove_precompile())]
+        #[pallet::call_index(2)]
+        pub fn remove_precompile(origin: OriginFor<T>, address: H160) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+
+            ensure!(Precompiles::<T>::contains_key(address), Error::<T>::NotRegistered);
+            Precompiles::<T>::remove(address);
+
+            Self::deposit_event(Event::PrecompileRemoved { address });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Registers every default precompile as active, keeping existing entries.
+        ///
+        /// Returns the number of entries written.
+        pub(crate) fn seed_defaults() -> u32 {
+            let mut written = 0;
+            for (address, id) in T::DefaultPrecompiles::get() {
+                if !Precompiles::<T>::contains_key(address) {
+                    Precompiles::<T>::insert(address, PrecompileEntry { id, status: PrecompileStatus::Active });
+                    written += 1;
+                }
+            }
+            written
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Storage migrations for the precompile registry.
+
+use super::*;
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Get, OnRuntimeUpgrade},
+    weights::Weight,
+};
+
+pub mod v1 {
+    use super::*;
+
+    /// Registers the default precompiles on chains that ran before the registry existed,
+    /// so every precompile stays reachable after the upgrade.
+    pub struct SeedDefaults<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for SeedDefaults<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let onchain = Pallet::<T>::on_chain_storage_version();
+            if onchain >= 1 {
+                frame_support::log::info!("pallet_precompile_registry: v1 migration already applied, skipping");
+                return T::DbWeight::get().reads(1);
+            }
+
+            let defaults = T::DefaultPrecompiles::get().len() as u64;
+            let written = Pallet::<T>::seed_defaults() as u64;
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            frame_support::log::info!("pallet_precompile_registry: registered {} default precompiles", written);
+            T::DbWeight::get().reads_writes(defaults.saturating_add(1), written.saturating_add(1))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(_state: sp_std::vec::Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 1,
+                "pallet_precompile_registry: storage version not bumped"
+            );
+            for (address, _) in T::DefaultPrecompiles::get() {
+                ensure!(
+                    Precompiles::<T>::contains_key(address),
+                    "pallet_precompile_registry: default precompile missing after migration"
+                );
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Test mock for unit tests and benchmarking
+
+use crate as pallet_precompile_registry;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, ConstU64, Contains},
+};
+use frame_system::EnsureRoot;
+use sp_core::{H160, H256};
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    BuildStorage,
+};
+
+frame_support::construct_runtime! {
+    pub enum Test {
+        System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
+        PrecompileRegistry: pallet_precompile_registry::{Pallet, Call, Storage, Config<T>, Event<T>},
+    }
+}
+
+impl frame_system::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = frame_system::mocking::MockBlock<Self>;
+    type BlockHashCount = ConstU64<250>;
+    type DbWeight = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+    pub DefaultPrecompiles: Vec<(H160, u8)> = vec![
+        (H160::from_low_u64_be(1), 1),
+        (H160::from_low_u64_be(2), 2),
+    ];
+}
+
+/// The first 256 addresses.
+pub struct LowAddresses;
+impl Contains<H160> for LowAddresses {
+    fn contains(address: &H160) -> bool {
+        address.as_bytes()[..19].iter().all(|byte| *byte == 0)
+    }
+}
+
+impl pallet_precompile_registry::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type PrecompileId = u8;
+    type RegistryOrigin = EnsureRoot<u64>;
+    type PrecompileAddresses = LowAddresses;
+    type DefaultPrecompiles = DefaultPrecompiles;
+    type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = RuntimeGenesisConfig::default().build_storage().unwrap();
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,156 @@
+//! Tests for the precompile registry's extrinsics.
+
+use crate::{mock::*, Error, Event, PrecompileEntry, PrecompileStatus};
+use frame_support::{assert_noop, assert_ok};
+use sp_core::H160;
+use sp_runtime::DispatchError;
+
+fn address(index: u64) -> H160 {
+    H160::from_low_u64_be(index)
+}
+
+#[test]
+fn genesis_registers_the_defaults() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            PrecompileRegistry::precompile(address(1)),
+            Some(PrecompileEntry { id: 1, status: PrecompileStatus::Active })
+        );
+        assert_eq!(
+            PrecompileRegistry::precompile(address(2)),
+            Some(PrecompileEntry { id: 2, status: PrecompileStatus::Active })
+        );
+    });
+}
+
+mod set_precompile {
+    use super::*;
+
+    #[test]
+    fn registers_and_replaces() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(PrecompileRegistry::set_precompile(
+                RuntimeOrigin::root(),
+                address(9),
+                1,
+                PrecompileStatus::ChargeOnly
+            ));
+            assert_eq!(
+                PrecompileRegistry::precompile(address(9)),
+                Some(PrecompileEntry { id: 1, status: PrecompileStatus::ChargeOnly })
+            );
+
+            assert_ok!(PrecompileRegistry::set_precompile(
+                RuntimeOrigin::root(),
+                address(9),
+                2,
+                PrecompileStatus::Active
+            ));
+            assert_eq!(
+                PrecompileRegistry::precompile(address(9)),
+                Some(PrecompileEntry { id: 2, status: PrecompileStatus::Active })
+            );
+            System::assert_last_event(
+                Event::<Test>::PrecompileSet { address: address(9), id: 2, status: PrecompileStatus::Active }.into(),
+            );
+        });
+    }
+
+    #[test]
+    fn requires_the_registry_origin() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PrecompileRegistry::set_precompile(RuntimeOrigin::signed(1), address(9), 1, PrecompileStatus::Active),
+                DispatchError::BadOrigin
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_addresses_outside_the_allowed_range() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PrecompileRegistry::set_precompile(
+                    RuntimeOrigin::root(),
+                    H160::repeat_byte(0xee),
+                    1,
+                    PrecompileStatus::Active
+                ),
+                Error::<Test>::AddressNotAllowed
+            );
+        });
+    }
+}
+
+mod set_status {
+    use super::*;
+
+    #[test]
+    fn changes_only_the_status() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(PrecompileRegistry::set_status(RuntimeOrigin::root(), address(1), PrecompileStatus::Disabled));
+
+            assert_eq!(
+                PrecompileRegistry::precompile(address(1)),
+                Some(PrecompileEntry { id: 1, status: PrecompileStatus::Disabled })
+            );
+            System::assert_last_event(
+                Event::<Test>::PrecompileStatusSet { address: address(1), status: PrecompileStatus::Disabled }.into(),
+            );
+        });
+    }
+
+    #[test]
+    fn fails_for_an_unregistered_address() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PrecompileRegistry::set_status(RuntimeOrigin::root(), address(9), PrecompileStatus::Disabled),
+                Error::<Test>::NotRegistered
+            );
+        });
+    }
+
+    #[test]
+    fn requires_the_registry_origin() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PrecompileRegistry::set_status(RuntimeOrigin::signed(1), address(1), PrecompileStatus::Disabled),
+                DispatchError::BadOrigin
+            );
+        });
+    }
+}
+
+mod remove_precompile {
+    use super::*;
+
+    #[test]
+    fn unregisters_the_address() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(PrecompileRegistry::remove_precompile(RuntimeOrigin::root(), address(2)));
+
+            assert_eq!(PrecompileRegistry::precompile(address(2)), None);
+            System::assert_last_event(Event::<Test>::PrecompileRemoved { address: address(2) }.into());
+        });
+    }
+
+    #[test]
+    fn fails_for_an_unregistered_address() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PrecompileRegistry::remove_precompile(RuntimeOrigin::root(), address(9)),
+                Error::<Test>::NotRegistered
+            );
+        });
+    }
+
+    #[test]
+    fn requires_the_registry_origin() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                PrecompileRegistry::remove_precompile(RuntimeOrigin::signed(1), address(2)),
+                DispatchError::BadOrigin
+            );
+        });
+    }
+}
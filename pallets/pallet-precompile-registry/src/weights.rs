@@ -0,0 +1,69 @@
+//! Weights for pallet_precompile_registry
+//!
+//! The values below are conservative estimates for single map writes. Regenerate them on
+//! reference hardware before relying on them for fee calculation:
+//!
+//! ./target/release/argochain benchmark pallet
+//! --chain=dev
+//! --steps=50
+//! --repeat=20
+//! --pallet=pallet_precompile_registry
+//! --extrinsic=*
+//! --execution=wasm
+//! --wasm-execution=compiled
+//! --output=pallets/pallet-precompile-registry/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_precompile_registry.
+pub trait WeightInfo {
+	fn set_precompile() -> Weight;
+	fn set_status() -> Weight;
+	fn remove_precompile() -> Weight;
+}
+
+/// Weights for pallet_precompile_registry using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: PrecompileRegistry Precompiles (r:0 w:1)
+	fn set_precompile() -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: PrecompileRegistry Precompiles (r:1 w:1)
+	fn set_status() -> Weight {
+		Weight::from_parts(12_000_000, 3522)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: PrecompileRegistry Precompiles (r:1 w:1)
+	fn remove_precompile() -> Weight {
+		Weight::from_parts(12_000_000, 3522)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn set_precompile() -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn set_status() -> Weight {
+		Weight::from_parts(12_000_000, 3522)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn remove_precompile() -> Weight {
+		Weight::from_parts(12_000_000, 3522)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+}
@@ -0,0 +1,112 @@
+//! RPC interface for the unified accounts pallet.
+//!
+//! Resolves an H160 to the account it spends from and an account to its linked H160,
+//! under the `unifiedAccounts_` prefix.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::H160;
+use sp_runtime::traits::Block as BlockT;
+
+pub use pallet_unified_accounts_runtime_api::UnifiedAccountsApi as UnifiedAccountsRuntimeApi;
+use pallet_unified_accounts_runtime_api::UnifiedAccountsApi;
+
+#[rpc(client, server)]
+pub trait UnifiedAccountsApi<BlockHash, AccountId> {
+    /// Account `address` spends from: the linked account, or its hashed account.
+    #[method(name = "unifiedAccounts_nativeAccount")]
+    fn native_account(&self, address: H160, at: Option<BlockHash>) -> RpcResult<AccountId>;
+
+    /// H160 linked to `account`, if any.
+    #[method(name = "unifiedAccounts_evmAddress")]
+    fn evm_address(&self, account: AccountId, at: Option<BlockHash>) -> RpcResult<Option<H160>>;
+
+    /// Text `account` and `address` must both sign to be linked.
+    #[method(name = "unifiedAccounts_linkMessage")]
+    fn link_message(&self, account: AccountId, address: H160, at: Option<BlockHash>) -> RpcResult<String>;
+}
+
+/// Provides RPC methods to query the unified accounts pallet.
+pub struct UnifiedAccounts<C, Block> {
+    client: Arc<C>,
+    _marker: PhantomData<Block>,
+}
+
+impl<C, Block> UnifiedAccounts<C, Block> {
+    /// Creates a new instance of the UnifiedAccounts RPC helper.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client, _marker: Default::default() }
+    }
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// The call to runtime failed.
+    RuntimeError,
+    /// The account is not a 32 byte account id.
+    InvalidAccount,
+}
+
+impl From<Error> for i32 {
+    fn from(e: Error) -> i32 {
+        match e {
+            Error::RuntimeError => 1,
+            Error::InvalidAccount => 2,
+        }
+    }
+}
+
+fn runtime_error(message: &'static str, e: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(Error::RuntimeError.into(), message, Some(format!("{:?}", e)))).into()
+}
+
+#[async_trait]
+impl<C, Block, AccountId> UnifiedAccountsApiServer<<Block as BlockT>::Hash, AccountId> for UnifiedAccounts<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: UnifiedAccountsApi<Block, AccountId>,
+    AccountId: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn native_account(&self, address: H160, at: Option<Block::Hash>) -> RpcResult<AccountId> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.client
+            .runtime_api()
+            .native_account(at, address)
+            .map_err(|e| runtime_error("Unable to resolve native account.", e))
+    }
+
+    fn evm_address(&self, account: AccountId, at: Option<Block::Hash>) -> RpcResult<Option<H160>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.client
+            .runtime_api()
+            .evm_address(at, account)
+            .map_err(|e| runtime_error("Unable to resolve EVM address.", e))
+    }
+
+    fn link_message(&self, account: AccountId, address: H160, at: Option<Block::Hash>) -> RpcResult<String> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let message = self
+            .client
+            .runtime_api()
+            .link_message(at, account, address)
+            .map_err(|e| runtime_error("Unable to build link message.", e))?
+            .ok_or_else(|| {
+                CallError::Custom(ErrorObject::owned(
+                    Error::InvalidAccount.into(),
+                    "Account is not a 32 byte account id.",
+                    None::<()>,
+                ))
+            })?;
+        Ok(String::from_utf8_lossy(&message).into_owned())
+    }
+}
@@ -0,0 +1,25 @@
+//! Runtime API definition for the unified accounts pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_core::H160;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    /// Runtime api for resolving unified accounts.
+    pub trait UnifiedAccountsApi<AccountId>
+        where
+            AccountId: Codec,
+    {
+        /// Returns the account `address` maps to, linked or hashed.
+        fn native_account(address: H160) -> AccountId;
+
+        /// Returns the H160 linked to `account`, if any.
+        fn evm_address(account: AccountId) -> Option<H160>;
+
+        /// Returns the message `account` and `address` must both sign to be linked, or
+        /// `None` if `account` is not a 32 byte account id.
+        fn link_message(account: AccountId, address: H160) -> Option<Vec<u8>>;
+    }
+}
@@ -0,0 +1,119 @@
+//! Benchmarks for pallet_unified_accounts
+
+use super::*;
+use crate::Pallet as UnifiedAccounts;
+use frame_benchmarking::{benchmarks, whitelisted_caller, BenchmarkError};
+use frame_support::traits::fungible::{Inspect, Mutate};
+use frame_system::RawOrigin;
+use pallet_evm::AddressMapping;
+use sp_core::{crypto::KeyTypeId, ecdsa};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    SaturatedConversion,
+};
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"bnch");
+
+fn balance<T: Config>(multiple: u32) -> BalanceOf<T> {
+    <T as Config>::Currency::minimum_balance().saturating_mul(multiple.into())
+}
+
+/// Generate an ECDSA key in the keystore and return it with its Ethereum address.
+fn evm_key() -> (ecdsa::Public, H160) {
+    let public = sp_io::crypto::ecdsa_generate(KEY_TYPE, None);
+    let probe = [0u8; 32];
+    let signature = sp_io::crypto::ecdsa_sign_prehashed(KEY_TYPE, &public, &probe)
+        .expect("key was just generated");
+    let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&signature.0, &probe).expect("signature is valid");
+    (public, H160::from_slice(&sp_io::hashing::keccak_256(&pubkey)[12..]))
+}
+
+/// Link a fresh account to a fresh H160 and return both.
+fn linked<T: Config>() -> (T::AccountId, H160)
+where
+    T::AccountId: From<[u8; 32]>,
+{
+    let account: T::AccountId = [1u8; 32].into();
+    let evm_address = H160::repeat_byte(0xee);
+    EvmToNative::<T>::insert(evm_address, &account);
+    NativeToEvm::<T>::insert(&account, evm_address);
+    (account, evm_address)
+}
+
+benchmarks! {
+    where_clause { where T::AccountId: From<[u8; 32]> }
+
+    link {
+        let caller: T::AccountId = whitelisted_caller();
+        let public = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+        let account: T::AccountId = public.0.into();
+        let (evm_public, evm_address) = evm_key();
+
+        let hashed = T::DefaultMapping::into_account_id(evm_address);
+        <T as Config>::Currency::set_balance(&hashed, balance::<T>(100));
+        <T as Config>::Currency::set_balance(&account, balance::<T>(1));
+
+        let message = UnifiedAccounts::<T>::link_message(&account, evm_address)
+            .ok_or(BenchmarkError::Stop("account is not 32 bytes"))?;
+        let evm_signature = sp_io::crypto::ecdsa_sign_prehashed(
+            KEY_TYPE,
+            &evm_public,
+            &signing::personal_sign_digest(&message),
+        )
+        .ok_or(BenchmarkError::Stop("failed to sign with the EVM key"))?;
+        let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &public, &message)
+            .ok_or(BenchmarkError::Stop("failed to sign with the account key"))?;
+    }: _(RawOrigin::Signed(caller), account.clone(), evm_address, evm_signature, signature)
+    verify {
+        assert_eq!(UnifiedAccounts::<T>::linked_account(evm_address), Some(account.clone()));
+        assert_eq!(<T as Config>::Currency::balance(&account), balance::<T>(101));
+    }
+
+    unlink {
+        let (account, evm_address) = linked::<T>();
+        frame_system::Pallet::<T>::inc_account_nonce(&account);
+    }: _(RawOrigin::Signed(account.clone()))
+    verify {
+        assert!(UnifiedAccounts::<T>::linked_evm_address(&account).is_none());
+        assert!(UnifiedAccounts::<T>::linked_account(evm_address).is_none());
+        let hashed = T::DefaultMapping::into_account_id(evm_address);
+        assert_eq!(
+            frame_system::Pallet::<T>::account_nonce(&hashed),
+            frame_system::Pallet::<T>::account_nonce(&account)
+        );
+    }
+
+    migrate_hashed_balance {
+        let (account, evm_address) = linked::<T>();
+        let hashed = T::DefaultMapping::into_account_id(evm_address);
+        <T as Config>::Currency::set_balance(&hashed, balance::<T>(100));
+        <T as Config>::Currency::set_balance(&account, balance::<T>(1));
+    }: _(RawOrigin::Signed(account.clone()))
+    verify {
+        assert_eq!(<T as Config>::Currency::balance(&account), balance::<T>(101));
+        assert!(<T as Config>::Currency::balance(&hashed).is_zero());
+    }
+
+    withdraw_with_proof {
+        let caller: T::AccountId = whitelisted_caller();
+        let (evm_public, evm_address) = evm_key();
+        let hashed = T::DefaultMapping::into_account_id(evm_address);
+        <T as Config>::Currency::set_balance(&hashed, balance::<T>(100));
+        <T as Config>::Currency::set_balance(&caller, balance::<T>(1));
+
+        let value: EvmBalanceOf<T> = balance::<T>(10).saturated_into::<u128>().saturated_into();
+        let message = UnifiedAccounts::<T>::withdraw_message(&caller, evm_address, value);
+        let signature = sp_io::crypto::ecdsa_sign_prehashed(
+            KEY_TYPE,
+            &evm_public,
+            &signing::personal_sign_digest(&message),
+        )
+        .ok_or(BenchmarkError::Stop("failed to sign with the EVM key"))?;
+    }: _(RawOrigin::Signed(caller.clone()), evm_address, value, signature)
+    verify {
+        assert_eq!(<T as Config>::Currency::balance(&caller), balance::<T>(11));
+        assert_eq!(UnifiedAccounts::<T>::withdraw_nonce(evm_address), 1);
+    }
+
+    impl_benchmark_test_suite!(UnifiedAccounts, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,339 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Unified accounts: one account and one balance behind an H160 and an AccountId32.
+//!
+//! Without this pallet every H160 is backed by the account `pallet_evm`'s address mapping
+//! hashes it to, so a user holding both an Ethereum key and a Substrate key sees two
+//! unrelated balances. [`Pallet::link`] takes a signature by each key over the same
+//! [`signing::link_message`] and records the pair; from then on [`UnifiedAddressMapping`]
+//! routes the H160 to the linked AccountId32, so the EVM and Substrate sides spend the
+//! same free balance and nonce.
+//!
+//! Linking sweeps whatever the hashed account still holds into the linked account, and
+//! [`Pallet::migrate_hashed_balance`] repeats the sweep for funds sent to the hashed
+//! account later. [`Pallet::unlink`] removes the pair; the H160 falls back to the hashed
+//! account and the balance stays with the AccountId32.
+//!
+//! [`withdraw::EnsureWithdrawOrigin`] is the `pallet_evm` withdraw origin that goes with
+//! this: [`Pallet::withdraw_with_proof`] lets the holder of an unlinked H160 pull funds out
+//! of its hashed account with a single ECDSA signature.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_core::H160;
+use sp_runtime::RuntimeDebug;
+use sp_std::marker::PhantomData;
+
+pub use pallet::*;
+
+pub mod signing;
+pub mod weights;
+pub mod withdraw;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub use weights::WeightInfo;
+
+/// Origin of calls dispatched by this pallet.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum RawOrigin<AccountId> {
+    /// `account` proved control of `evm_address` with an ECDSA signature.
+    EvmAddress { account: AccountId, evm_address: H160 },
+}
+
+/// Maps a linked H160 to its AccountId32 and every other H160 through `Config::DefaultMapping`.
+///
+/// Use it as `pallet_evm::Config::AddressMapping` (and anywhere else the runtime maps
+/// H160s to accounts) so the EVM sees the linked account.
+pub struct UnifiedAddressMapping<T>(PhantomData<T>);
+
+impl<T: Config> pallet_evm::AddressMapping<T::AccountId> for UnifiedAddressMapping<T> {
+    fn into_account_id(address: H160) -> T::AccountId {
+        Pallet::<T>::native_account(address)
+    }
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use codec::Encode;
+    use frame_support::{
+        dispatch::{DispatchResult, GetDispatchInfo, PostDispatchInfo},
+        pallet_prelude::*,
+        traits::{
+            fungible::{Inspect, Mutate},
+            tokens::{Fortitude, Preservation},
+        },
+    };
+    use frame_system::pallet_prelude::*;
+    use pallet_evm::{AddressMapping, WeightInfo as _};
+    use sp_core::{ecdsa, sr25519, H160};
+    use sp_runtime::traits::{Dispatchable, SaturatedConversion, Zero};
+    use sp_std::vec::Vec;
+    use crate::{signing, WeightInfo};
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type EvmBalanceOf<T> = pallet_evm::BalanceOf<T>;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_evm::Config {
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        /// The runtime origin, able to carry [`crate::RawOrigin`].
+        type RuntimeOrigin: From<crate::RawOrigin<Self::AccountId>>;
+        /// The runtime call, used to dispatch `pallet_evm::Call::withdraw`.
+        type RuntimeCall: Parameter
+            + Dispatchable<RuntimeOrigin = <Self as Config>::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo
+            + From<pallet_evm::Call<Self>>;
+        /// Currency backing EVM balances, used to sweep hashed accounts.
+        type Currency: Mutate<Self::AccountId>;
+        /// Mapping of H160s that are not linked. It must not be [`crate::UnifiedAddressMapping`].
+        type DefaultMapping: AddressMapping<Self::AccountId>;
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    #[pallet::origin]
+    pub type Origin<T> = crate::RawOrigin<<T as frame_system::Config>::AccountId>;
+
+    /// AccountId32 linked to each H160.
+    #[pallet::storage]
+    #[pallet::getter(fn linked_account)]
+    pub type EvmToNative<T: Config> = StorageMap<_, Blake2_128Concat, H160, T::AccountId, OptionQuery>;
+
+    /// H160 linked to each AccountId32.
+    #[pallet::storage]
+    #[pallet::getter(fn linked_evm_address)]
+    pub type NativeToEvm<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, H160, OptionQuery>;
+
+    /// Nonce the next link message of each account must carry.
+    #[pallet::storage]
+    #[pallet::getter(fn link_nonce)]
+    pub type LinkNonces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Nonce the next withdraw message of each H160 must carry.
+    #[pallet::storage]
+    #[pallet::getter(fn withdraw_nonce)]
+    pub type WithdrawNonces<T: Config> = StorageMap<_, Blake2_128Concat, H160, u32, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// `evm_address` now maps to `account`.
+        AccountsLinked { account: T::AccountId, evm_address: H160 },
+        /// `evm_address` maps to its hashed account again.
+        AccountsUnlinked { account: T::AccountId, evm_address: H160 },
+        /// `amount` moved from the hashed account `from` to the linked `account`.
+        HashedBalanceMigrated { account: T::AccountId, from: T::AccountId, amount: BalanceOf<T> },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The account is already linked to an H160.
+        AccountAlreadyLinked,
+        /// The H160 is already linked to an account.
+        EvmAddressAlreadyLinked,
+        /// The account is not linked to an H160.
+        NotLinked,
+        /// The account id is not a 32 byte sr25519 public key.
+        InvalidAccount,
+        /// The ECDSA signature was not made by the H160 over the link message.
+        InvalidEvmSignature,
+        /// The sr25519 signature was not made by the account over the link message.
+        InvalidAccountSignature,
+        /// The ECDSA signature was not made by the H160 over the withdraw message.
+        InvalidWithdrawSignature,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Link `account` and `evm_address` and sweep the hashed account of `evm_address`
+        /// into `account`.
+        ///
+        /// `evm_signature` is a `personal_sign` signature and `signature` an sr25519
+        /// signature, both over [`Pallet::link_message`] of the pair. Any signed origin may
+        /// relay the call, so an account whose funds all sit behind the H160 can still link.
+        #[pallet::weight(<T as Config>::WeightInfo::link())]
+        #[pallet::call_index(0)]
+        pub fn link(
+            origin: OriginFor<T>,
+            account: T::AccountId,
+            evm_address: H160,
+            evm_signature: ecdsa::Signature,
+            signature: sr25519::Signature,
+        ) -> DispatchResult {
+            let _relayer = ensure_signed(origin)?;
+
+            ensure!(!NativeToEvm::<T>::contains_key(&account), Error::<T>::AccountAlreadyLinked);
+            ensure!(!EvmToNative::<T>::contains_key(evm_address), Error::<T>::EvmAddressAlreadyLinked);
+
+            let public = Self::account_public(&account).ok_or(Error::<T>::InvalidAccount)?;
+            let nonce = Self::link_nonce(&account);
+            let message = signing::link_message(Self::chain_id(), &public.0, evm_address, nonce);
+
+            ensure!(
+                signing::recover_evm_signer(&evm_signature, &message) == Some(evm_address),
+                Error::<T>::InvalidEvmSignature
+            );
+            ensure!(
+                signing::verify_sr25519(&signature, &message, &public),
+                Error::<T>::InvalidAccountSignature
+            );
+
+            LinkNonces::<T>::insert(&account, nonce.wrapping_add(1));
+            EvmToNative::<T>::insert(evm_address, &account);
+            NativeToEvm::<T>::insert(&account, evm_address);
+
+            Self::sweep_hashed_account(&account, evm_address)?;
+
+            Self::deposit_event(Event::AccountsLinked { account, evm_address });
+            Ok(())
+        }
+
+        /// Remove the link of the caller. The H160 maps to its hashed account again; the
+        /// balance stays with the caller.
+        ///
+        /// The hashed account takes over the caller's nonce if it is behind, so Ethereum
+        /// transactions the H160 signed while linked cannot be replayed against it.
+        #[pallet::weight(<T as Config>::WeightInfo::unlink())]
+        #[pallet::call_index(1)]
+        pub fn unlink(origin: OriginFor<T>) -> DispatchResult {
+            let account = ensure_signed(origin)?;
+
+            let evm_address = NativeToEvm::<T>::take(&account).ok_or(Error::<T>::NotLinked)?;
+            EvmToNative::<T>::remove(evm_address);
+
+            let hashed = T::DefaultMapping::into_account_id(evm_address);
+            if hashed != account {
+                Self::raise_nonce(&hashed, frame_system::Pallet::<T>::account_nonce(&account));
+            }
+
+            Self::deposit_event(Event::AccountsUnlinked { account, evm_address });
+            Ok(())
+        }
+
+        /// Sweep funds that reached the hashed account of the caller's H160 after linking.
+        #[pallet::weight(<T as Config>::WeightInfo::migrate_hashed_balance())]
+        #[pallet::call_index(2)]
+        pub fn migrate_hashed_balance(origin: OriginFor<T>) -> DispatchResult {
+            let account = ensure_signed(origin)?;
+
+            let evm_address = Self::linked_evm_address(&account).ok_or(Error::<T>::NotLinked)?;
+            Self::sweep_hashed_account(&account, evm_address)
+        }
+
+        /// Withdraw `value` from the EVM balance of `evm_address` to the caller through
+        /// `pallet_evm::Call::withdraw`.
+        ///
+        /// `signature` is a `personal_sign` signature by `evm_address` over
+        /// [`Pallet::withdraw_message`]. It names the caller, the value and the
+        /// [`Pallet::withdraw_nonce`] of `evm_address`, which is bumped on success, so it can
+        /// be used once and only by the caller. The runtime must use
+        /// [`crate::withdraw::EnsureWithdrawOrigin`] as `pallet_evm`'s withdraw origin.
+        #[pallet::weight(
+            <T as Config>::WeightInfo::withdraw_with_proof()
+                .saturating_add(<T as pallet_evm::Config>::WeightInfo::withdraw())
+        )]
+        #[pallet::call_index(3)]
+        pub fn withdraw_with_proof(
+            origin: OriginFor<T>,
+            evm_address: H160,
+            value: EvmBalanceOf<T>,
+            signature: ecdsa::Signature,
+        ) -> DispatchResult {
+            let account = ensure_signed(origin)?;
+
+            let nonce = Self::withdraw_nonce(evm_address);
+            let message = Self::withdraw_message(&account, evm_address, value);
+            ensure!(
+                signing::recover_evm_signer(&signature, &message) == Some(evm_address),
+                Error::<T>::InvalidWithdrawSignature
+            );
+            WithdrawNonces::<T>::insert(evm_address, nonce.wrapping_add(1));
+
+            let call: <T as Config>::RuntimeCall = pallet_evm::Call::<T>::withdraw { address: evm_address, value }.into();
+            let origin = crate::RawOrigin::EvmAddress { account, evm_address };
+            call.dispatch(origin.into()).map(|_| ()).map_err(|e| e.error)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The account `address` maps to: the linked account, or the default mapping.
+        pub fn native_account(address: H160) -> T::AccountId {
+            Self::linked_account(address).unwrap_or_else(|| T::DefaultMapping::into_account_id(address))
+        }
+
+        /// The message `account` and `evm_address` must both sign to be linked now, or
+        /// `None` if `account` is not a 32 byte account id.
+        pub fn link_message(account: &T::AccountId, evm_address: H160) -> Option<Vec<u8>> {
+            let public = Self::account_public(account)?;
+            Some(signing::link_message(Self::chain_id(), &public.0, evm_address, Self::link_nonce(account)))
+        }
+
+        /// The message `evm_address` must sign for `account` to withdraw `value` from it now.
+        pub fn withdraw_message(account: &T::AccountId, evm_address: H160, value: EvmBalanceOf<T>) -> Vec<u8> {
+            signing::withdraw_message(
+                Self::chain_id(),
+                evm_address,
+                &account.encode(),
+                value.saturated_into(),
+                Self::withdraw_nonce(evm_address),
+            )
+        }
+
+        fn chain_id() -> u64 {
+            <T as pallet_evm::Config>::ChainId::get()
+        }
+
+        fn account_public(account: &T::AccountId) -> Option<sr25519::Public> {
+            let raw: [u8; 32] = account.encode().try_into().ok()?;
+            Some(sr25519::Public(raw))
+        }
+
+        /// Moves the spendable balance of the hashed account of `evm_address` to `account`,
+        /// and carries its nonce over so Ethereum transactions signed before the link cannot
+        /// be replayed against `account`.
+        ///
+        /// Frozen and held funds stay behind, and so does a spendable balance below the
+        /// existential deposit while `account` holds nothing; [`Pallet::migrate_hashed_balance`]
+        /// picks it up once `account` is funded.
+        fn sweep_hashed_account(account: &T::AccountId, evm_address: H160) -> DispatchResult {
+            let hashed = T::DefaultMapping::into_account_id(evm_address);
+            if hashed == *account {
+                return Ok(())
+            }
+
+            Self::raise_nonce(account, frame_system::Pallet::<T>::account_nonce(&hashed));
+
+            let amount = <T as Config>::Currency::reducible_balance(&hashed, Preservation::Expendable, Fortitude::Polite);
+            let below_minimum = amount < <T as Config>::Currency::minimum_balance()
+                && <T as Config>::Currency::total_balance(account).is_zero();
+            if !amount.is_zero() && !below_minimum {
+                <T as Config>::Currency::transfer(&hashed, account, amount, Preservation::Expendable)?;
+                Self::deposit_event(Event::HashedBalanceMigrated {
+                    account: account.clone(),
+                    from: hashed,
+                    amount,
+                });
+            }
+            Ok(())
+        }
+
+        /// Raises the nonce of `who` to `nonce` if it is lower. The nonce is kept even if
+        /// `who` does not exist yet, as `pallet_evm` does for the senders it bumps.
+        fn raise_nonce(who: &T::AccountId, nonce: T::Nonce) {
+            if frame_system::Pallet::<T>::account_nonce(who) < nonce {
+                frame_system::Account::<T>::mutate(who, |info| info.nonce = nonce);
+            }
+        }
+    }
+}
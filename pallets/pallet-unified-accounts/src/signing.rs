@@ -0,0 +1,194 @@
+//! Messages and signature checks behind `link` and `withdraw_with_proof`.
+//!
+//! To link, both keys sign the same human-readable text,
+//!
+//! ```text
+//! Link Argochain accounts
+//! chain: <chain id>
+//! account: 0x<32 byte account id>
+//! evm: 0x<20 byte address>
+//! nonce: <link nonce of the account>
+//! ```
+//!
+//! The EVM key signs it with `personal_sign` (EIP-191) and the sr25519 key with a raw
+//! signature, as produced by `signRaw` in polkadot.js, which wraps the payload in
+//! `<Bytes>…</Bytes>` before signing. Binding the chain id and the nonce means a pair of
+//! signatures links one pair of accounts on one chain exactly once.
+//!
+//! To withdraw from its hashed account, the EVM key `personal_sign`s
+//!
+//! ```text
+//! Withdraw from Argochain EVM address
+//! chain: <chain id>
+//! evm: 0x<20 byte address>
+//! to: 0x<SCALE encoded account id>
+//! value: <value in the smallest unit>
+//! nonce: <withdraw nonce of the address>
+//! ```
+
+use sp_core::{ecdsa, sr25519, H160};
+use sp_io::hashing::keccak_256;
+use sp_std::vec::Vec;
+
+/// First line of every link message.
+pub const LINK_MESSAGE_HEADER: &[u8] = b"Link Argochain accounts";
+
+/// First line of every withdraw message.
+pub const WITHDRAW_MESSAGE_HEADER: &[u8] = b"Withdraw from Argochain EVM address";
+
+/// Builds the message both keys sign to link `account` and `evm_address`.
+pub fn link_message(chain_id: u64, account: &[u8; 32], evm_address: H160, nonce: u32) -> Vec<u8> {
+    let mut message = Vec::with_capacity(LINK_MESSAGE_HEADER.len() + 160);
+    message.extend_from_slice(LINK_MESSAGE_HEADER);
+    message.extend_from_slice(b"\nchain: ");
+    push_decimal(&mut message, chain_id.into());
+    message.extend_from_slice(b"\naccount: 0x");
+    message.extend_from_slice(hex::encode(account).as_bytes());
+    message.extend_from_slice(b"\nevm: 0x");
+    message.extend_from_slice(hex::encode(evm_address.as_bytes()).as_bytes());
+    message.extend_from_slice(b"\nnonce: ");
+    push_decimal(&mut message, nonce.into());
+    message
+}
+
+/// Builds the message `evm_address` signs to let `account` withdraw `value` from it.
+pub fn withdraw_message(chain_id: u64, evm_address: H160, account: &[u8], value: u128, nonce: u32) -> Vec<u8> {
+    let mut message = Vec::with_capacity(WITHDRAW_MESSAGE_HEADER.len() + 200);
+    message.extend_from_slice(WITHDRAW_MESSAGE_HEADER);
+    message.extend_from_slice(b"\nchain: ");
+    push_decimal(&mut message, chain_id.into());
+    message.extend_from_slice(b"\nevm: 0x");
+    message.extend_from_slice(hex::encode(evm_address.as_bytes()).as_bytes());
+    message.extend_from_slice(b"\nto: 0x");
+    message.extend_from_slice(hex::encode(account).as_bytes());
+    message.extend_from_slice(b"\nvalue: ");
+    push_decimal(&mut message, value);
+    message.extend_from_slice(b"\nnonce: ");
+    push_decimal(&mut message, nonce.into());
+    message
+}
+
+/// `keccak256("\x19Ethereum Signed Message:\n" ‖ len(message) ‖ message)`
+pub fn personal_sign_digest(message: &[u8]) -> [u8; 32] {
+    let mut prefixed = Vec::with_capacity(32 + message.len());
+    prefixed.extend_from_slice(b"\x19Ethereum Signed Message:\n");
+    push_decimal(&mut prefixed, message.len() as u128);
+    prefixed.extend_from_slice(message);
+    keccak_256(&prefixed)
+}
+
+/// Recovers the H160 that `personal_sign`ed `message`; see
+/// [`fp_account::recover_ethereum_signer`].
+pub fn recover_evm_signer(signature: &ecdsa::Signature, message: &[u8]) -> Option<H160> {
+    fp_account::recover_ethereum_signer(&signature.0, &personal_sign_digest(message))
+}
+
+/// Checks an sr25519 signature of `signer` over `message`, either raw or wrapped in
+/// `<Bytes>…</Bytes>`.
+pub fn verify_sr25519(signature: &sr25519::Signature, message: &[u8], signer: &sr25519::Public) -> bool {
+    if sp_io::crypto::sr25519_verify(signature, message, signer) {
+        return true
+    }
+
+    let mut wrapped = Vec::with_capacity(message.len() + 15);
+    wrapped.extend_from_slice(b"<Bytes>");
+    wrapped.extend_from_slice(message);
+    wrapped.extend_from_slice(b"</Bytes>");
+    sp_io::crypto::sr25519_verify(signature, &wrapped, signer)
+}
+
+fn push_decimal(out: &mut Vec<u8>, value: u128) {
+    let mut digits = [0u8; 39];
+    let mut len = 0;
+    let mut rest = value;
+    loop {
+        digits[len] = b'0' + (rest % 10) as u8;
+        len += 1;
+        rest /= 10;
+        if rest == 0 {
+            break
+        }
+    }
+    out.extend(digits[..len].iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::Pair;
+
+    fn evm_address_of(pair: &ecdsa::Pair) -> H160 {
+        let signature = pair.sign_prehashed(&[0u8; 32]);
+        let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&signature.0, &[0u8; 32]).unwrap();
+        H160::from_slice(&keccak_256(&pubkey)[12..])
+    }
+
+    #[test]
+    fn link_message_is_readable() {
+        let message = link_message(1299, &[0xab; 32], H160::repeat_byte(0x12), 7);
+        let expected = [
+            "Link Argochain accounts",
+            "chain: 1299",
+            &format!("account: 0x{}", "ab".repeat(32)),
+            &format!("evm: 0x{}", "12".repeat(20)),
+            "nonce: 7",
+        ]
+        .join("\n");
+        assert_eq!(message, expected.into_bytes());
+    }
+
+    #[test]
+    fn withdraw_message_is_readable() {
+        let message = withdraw_message(1299, H160::repeat_byte(0x12), &[0xab; 32], u128::MAX, 0);
+        let expected = [
+            "Withdraw from Argochain EVM address",
+            "chain: 1299",
+            &format!("evm: 0x{}", "12".repeat(20)),
+            &format!("to: 0x{}", "ab".repeat(32)),
+            "value: 340282366920938463463374607431768211455",
+            "nonce: 0",
+        ]
+        .join("\n");
+        assert_eq!(message, expected.into_bytes());
+    }
+
+    #[test]
+    fn personal_sign_digest_matches_eip191() {
+        // `web3.eth.accounts.hashMessage("hello")`
+        assert_eq!(
+            personal_sign_digest(b"hello"),
+            bytes32("50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750"),
+        );
+    }
+
+    #[test]
+    fn recovers_personal_sign_signer() {
+        let pair = ecdsa::Pair::from_seed(&[1u8; 32]);
+        let message = link_message(1299, &[2u8; 32], evm_address_of(&pair), 0);
+        let signature = pair.sign_prehashed(&personal_sign_digest(&message));
+
+        assert_eq!(recover_evm_signer(&signature, &message), Some(evm_address_of(&pair)));
+
+        let other = link_message(1299, &[2u8; 32], evm_address_of(&pair), 1);
+        assert_ne!(recover_evm_signer(&signature, &other), Some(evm_address_of(&pair)));
+    }
+
+    #[test]
+    fn accepts_raw_and_wrapped_sr25519_signatures() {
+        let pair = sr25519::Pair::from_seed(&[3u8; 32]);
+        let message = link_message(1299, &pair.public().0, H160::repeat_byte(1), 0);
+
+        let raw = pair.sign(&message);
+        assert!(verify_sr25519(&raw, &message, &pair.public()));
+
+        let wrapped = pair.sign(&[b"<Bytes>".as_slice(), &message, b"</Bytes>"].concat());
+        assert!(verify_sr25519(&wrapped, &message, &pair.public()));
+
+        let other = link_message(1299, &pair.public().0, H160::repeat_byte(2), 0);
+        assert!(!verify_sr25519(&raw, &other, &pair.public()));
+    }
+
+    fn bytes32(value: &str) -> [u8; 32] {
+        hex::decode(value).unwrap().try_into().unwrap()
+    }
+}
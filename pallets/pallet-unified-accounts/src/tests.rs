@@ -0,0 +1,287 @@
+//! Tests for the unified accounts extrinsics.
+
+use crate::{mock::*, signing, Error, Event};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{Currency, ReservableCurrency},
+};
+use pallet_evm::{AddressMapping, HashedAddressMapping};
+use sp_core::{ecdsa, sr25519, Pair, H160};
+use sp_runtime::traits::BlakeTwo256;
+
+const RELAYER: AccountId = AccountId::new([9u8; 32]);
+
+fn evm_key(seed: u8) -> (ecdsa::Pair, H160) {
+    let pair = ecdsa::Pair::from_seed(&[seed; 32]);
+    let probe = pair.sign_prehashed(&[0u8; 32]);
+    let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&probe.0, &[0u8; 32]).unwrap();
+    (pair, H160::from_slice(&sp_io::hashing::keccak_256(&pubkey)[12..]))
+}
+
+fn account_key(seed: u8) -> (sr25519::Pair, AccountId) {
+    let pair = sr25519::Pair::from_seed(&[seed; 32]);
+    let account = AccountId::new(pair.public().0);
+    (pair, account)
+}
+
+fn hashed(address: H160) -> AccountId {
+    HashedAddressMapping::<BlakeTwo256>::into_account_id(address)
+}
+
+fn mapped(address: H160) -> AccountId {
+    <Test as pallet_evm::Config>::AddressMapping::into_account_id(address)
+}
+
+fn set_nonce(who: &AccountId, nonce: u64) {
+    frame_system::Account::<Test>::mutate(who, |info| info.nonce = nonce);
+}
+
+/// Both signatures over the current link message of `account` and `evm_address`.
+fn sign_link(
+    account_pair: &sr25519::Pair,
+    evm_pair: &ecdsa::Pair,
+    evm_address: H160,
+) -> (ecdsa::Signature, sr25519::Signature) {
+    let account = AccountId::new(account_pair.public().0);
+    let message = UnifiedAccounts::link_message(&account, evm_address).unwrap();
+    (evm_pair.sign_prehashed(&signing::personal_sign_digest(&message)), account_pair.sign(&message))
+}
+
+/// Links account key `1` and EVM key `7`, relayed by `RELAYER`.
+fn link() -> (AccountId, H160) {
+    let (account_pair, account) = account_key(1);
+    let (evm_pair, evm_address) = evm_key(7);
+    let (evm_signature, signature) = sign_link(&account_pair, &evm_pair, evm_address);
+    assert_ok!(UnifiedAccounts::link(
+        RuntimeOrigin::signed(RELAYER),
+        account.clone(),
+        evm_address,
+        evm_signature,
+        signature
+    ));
+    (account, evm_address)
+}
+
+mod link {
+    use super::*;
+
+    #[test]
+    fn links_and_sweeps_the_hashed_account() {
+        new_test_ext().execute_with(|| {
+            let (_, account) = account_key(1);
+            let (_, evm_address) = evm_key(7);
+            Balances::make_free_balance_be(&hashed(evm_address), 1_000);
+            Balances::make_free_balance_be(&account, 10);
+            set_nonce(&hashed(evm_address), 5);
+
+            link();
+
+            assert_eq!(UnifiedAccounts::linked_account(evm_address), Some(account.clone()));
+            assert_eq!(UnifiedAccounts::linked_evm_address(&account), Some(evm_address));
+            assert_eq!(mapped(evm_address), account);
+            assert_eq!(UnifiedAccounts::link_nonce(&account), 1);
+            assert_eq!(Balances::free_balance(&account), 1_010);
+            assert_eq!(Balances::free_balance(hashed(evm_address)), 0);
+            assert_eq!(System::account_nonce(&account), 5);
+            System::assert_has_event(
+                Event::<Test>::HashedBalanceMigrated {
+                    account: account.clone(),
+                    from: hashed(evm_address),
+                    amount: 1_000,
+                }
+                .into(),
+            );
+            System::assert_last_event(Event::<Test>::AccountsLinked { account, evm_address }.into());
+        });
+    }
+
+    #[test]
+    fn never_lowers_the_account_nonce() {
+        new_test_ext().execute_with(|| {
+            let (_, account) = account_key(1);
+            let (_, evm_address) = evm_key(7);
+            set_nonce(&account, 9);
+            set_nonce(&hashed(evm_address), 5);
+
+            link();
+
+            assert_eq!(System::account_nonce(&account), 9);
+        });
+    }
+
+    #[test]
+    fn leaves_dust_behind_for_an_account_that_does_not_exist() {
+        new_test_ext().execute_with(|| {
+            ExistentialDeposit::set(10);
+            let (_, account) = account_key(1);
+            let (_, evm_address) = evm_key(7);
+            // 5 of the 15 free are spendable, less than the account could be created with.
+            Balances::make_free_balance_be(&hashed(evm_address), 100);
+            assert_ok!(Balances::reserve(&hashed(evm_address), 85));
+            set_nonce(&hashed(evm_address), 3);
+
+            link();
+
+            assert_eq!(UnifiedAccounts::linked_account(evm_address), Some(account.clone()));
+            assert_eq!(Balances::total_balance(&account), 0);
+            assert_eq!(Balances::free_balance(hashed(evm_address)), 15);
+            assert_eq!(System::account_nonce(&account), 3);
+
+            Balances::make_free_balance_be(&account, 100);
+            assert_ok!(UnifiedAccounts::migrate_hashed_balance(RuntimeOrigin::signed(account.clone())));
+            assert_eq!(Balances::free_balance(&account), 105);
+            assert_eq!(Balances::free_balance(hashed(evm_address)), 10);
+        });
+    }
+
+    #[test]
+    fn rejects_signatures_by_other_keys() {
+        new_test_ext().execute_with(|| {
+            let (account_pair, account) = account_key(1);
+            let (other_account_pair, _) = account_key(2);
+            let (evm_pair, evm_address) = evm_key(7);
+            let (other_evm_pair, _) = evm_key(8);
+
+            let (_, signature) = sign_link(&account_pair, &evm_pair, evm_address);
+            let (forged, _) = sign_link(&account_pair, &other_evm_pair, evm_address);
+            assert_noop!(
+                UnifiedAccounts::link(RuntimeOrigin::signed(RELAYER), account.clone(), evm_address, forged, signature),
+                Error::<Test>::InvalidEvmSignature
+            );
+
+            let (evm_signature, _) = sign_link(&account_pair, &evm_pair, evm_address);
+            let message = UnifiedAccounts::link_message(&account, evm_address).unwrap();
+            let forged = other_account_pair.sign(&message);
+            assert_noop!(
+                UnifiedAccounts::link(RuntimeOrigin::signed(RELAYER), account, evm_address, evm_signature, forged),
+                Error::<Test>::InvalidAccountSignature
+            );
+        });
+    }
+
+    #[test]
+    fn signatures_cannot_be_replayed_after_unlinking() {
+        new_test_ext().execute_with(|| {
+            let (account_pair, account) = account_key(1);
+            let (evm_pair, evm_address) = evm_key(7);
+            let (evm_signature, signature) = sign_link(&account_pair, &evm_pair, evm_address);
+            assert_ok!(UnifiedAccounts::link(
+                RuntimeOrigin::signed(RELAYER),
+                account.clone(),
+                evm_address,
+                evm_signature.clone(),
+                signature.clone()
+            ));
+            assert_ok!(UnifiedAccounts::unlink(RuntimeOrigin::signed(account.clone())));
+
+            assert_noop!(
+                UnifiedAccounts::link(RuntimeOrigin::signed(RELAYER), account, evm_address, evm_signature, signature),
+                Error::<Test>::InvalidEvmSignature
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_linked_accounts_and_addresses() {
+        new_test_ext().execute_with(|| {
+            let (account, evm_address) = link();
+
+            let (account_pair, _) = account_key(1);
+            let (other_evm_pair, other_address) = evm_key(8);
+            let (evm_signature, signature) = sign_link(&account_pair, &other_evm_pair, other_address);
+            assert_noop!(
+                UnifiedAccounts::link(RuntimeOrigin::signed(RELAYER), account, other_address, evm_signature, signature),
+                Error::<Test>::AccountAlreadyLinked
+            );
+
+            let (other_account_pair, other_account) = account_key(2);
+            let (evm_pair, _) = evm_key(7);
+            let (evm_signature, signature) = sign_link(&other_account_pair, &evm_pair, evm_address);
+            assert_noop!(
+                UnifiedAccounts::link(
+                    RuntimeOrigin::signed(RELAYER),
+                    other_account,
+                    evm_address,
+                    evm_signature,
+                    signature
+                ),
+                Error::<Test>::EvmAddressAlreadyLinked
+            );
+        });
+    }
+}
+
+mod unlink {
+    use super::*;
+
+    #[test]
+    fn unlinks_and_raises_the_hashed_nonce() {
+        new_test_ext().execute_with(|| {
+            let (account, evm_address) = link();
+            Balances::make_free_balance_be(&account, 1_000);
+            set_nonce(&account, 7);
+
+            assert_ok!(UnifiedAccounts::unlink(RuntimeOrigin::signed(account.clone())));
+
+            assert_eq!(UnifiedAccounts::linked_account(evm_address), None);
+            assert_eq!(UnifiedAccounts::linked_evm_address(&account), None);
+            assert_eq!(mapped(evm_address), hashed(evm_address));
+            assert_eq!(System::account_nonce(hashed(evm_address)), 7);
+            assert_eq!(Balances::free_balance(&account), 1_000);
+            System::assert_last_event(Event::<Test>::AccountsUnlinked { account, evm_address }.into());
+        });
+    }
+
+    #[test]
+    fn never_lowers_the_hashed_nonce() {
+        new_test_ext().execute_with(|| {
+            let (account, evm_address) = link();
+            set_nonce(&account, 7);
+            set_nonce(&hashed(evm_address), 9);
+
+            assert_ok!(UnifiedAccounts::unlink(RuntimeOrigin::signed(account)));
+
+            assert_eq!(System::account_nonce(hashed(evm_address)), 9);
+        });
+    }
+
+    #[test]
+    fn fails_for_an_unlinked_account() {
+        new_test_ext().execute_with(|| {
+            let (_, account) = account_key(1);
+            assert_noop!(UnifiedAccounts::unlink(RuntimeOrigin::signed(account)), Error::<Test>::NotLinked);
+        });
+    }
+}
+
+mod migrate_hashed_balance {
+    use super::*;
+
+    #[test]
+    fn sweeps_funds_sent_after_linking() {
+        new_test_ext().execute_with(|| {
+            let (account, evm_address) = link();
+            Balances::make_free_balance_be(&account, 10);
+            Balances::make_free_balance_be(&hashed(evm_address), 500);
+
+            assert_ok!(UnifiedAccounts::migrate_hashed_balance(RuntimeOrigin::signed(account.clone())));
+
+            assert_eq!(Balances::free_balance(&account), 510);
+            assert_eq!(Balances::free_balance(hashed(evm_address)), 0);
+            System::assert_last_event(
+                Event::<Test>::HashedBalanceMigrated { account, from: hashed(evm_address), amount: 500 }.into(),
+            );
+        });
+    }
+
+    #[test]
+    fn fails_for_an_unlinked_account() {
+        new_test_ext().execute_with(|| {
+            let (_, account) = account_key(1);
+            assert_noop!(
+                UnifiedAccounts::migrate_hashed_balance(RuntimeOrigin::signed(account)),
+                Error::<Test>::NotLinked
+            );
+        });
+    }
+}
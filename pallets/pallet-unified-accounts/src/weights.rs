@@ -0,0 +1,91 @@
+//! Weights for pallet_unified_accounts
+//!
+//! The values below are conservative estimates. Regenerate them on reference hardware
+//! before relying on them for fee calculation:
+//!
+//! ./target/release/argochain benchmark pallet
+//! --chain=dev
+//! --steps=50
+//! --repeat=20
+//! --pallet=pallet_unified_accounts
+//! --extrinsic=*
+//! --execution=wasm
+//! --wasm-execution=compiled
+//! --output=pallets/pallet-unified-accounts/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_unified_accounts.
+pub trait WeightInfo {
+	fn link() -> Weight;
+	fn unlink() -> Weight;
+	fn migrate_hashed_balance() -> Weight;
+	fn withdraw_with_proof() -> Weight;
+}
+
+/// Weights for pallet_unified_accounts using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: UnifiedAccounts NativeToEvm (r:1 w:1)
+	/// Storage: UnifiedAccounts EvmToNative (r:1 w:1)
+	/// Storage: UnifiedAccounts LinkNonces (r:1 w:1)
+	/// Storage: System Account (r:2 w:2)
+	fn link() -> Weight {
+		Weight::from_parts(120_000_000, 6196)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: UnifiedAccounts NativeToEvm (r:1 w:1)
+	/// Storage: UnifiedAccounts EvmToNative (r:0 w:1)
+	/// Storage: System Account (r:2 w:1)
+	fn unlink() -> Weight {
+		Weight::from_parts(20_000_000, 6196)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: UnifiedAccounts NativeToEvm (r:1 w:0)
+	/// Storage: System Account (r:2 w:2)
+	fn migrate_hashed_balance() -> Weight {
+		Weight::from_parts(50_000_000, 6196)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: UnifiedAccounts WithdrawNonces (r:1 w:1)
+	/// Storage: UnifiedAccounts EvmToNative (r:1 w:0)
+	/// The withdraw itself is charged with `pallet_evm`'s weight on top.
+	fn withdraw_with_proof() -> Weight {
+		Weight::from_parts(60_000_000, 3529)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn link() -> Weight {
+		Weight::from_parts(120_000_000, 6196)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	fn unlink() -> Weight {
+		Weight::from_parts(20_000_000, 6196)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn migrate_hashed_balance() -> Weight {
+		Weight::from_parts(50_000_000, 6196)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn withdraw_with_proof() -> Weight {
+		Weight::from_parts(60_000_000, 3529)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+}
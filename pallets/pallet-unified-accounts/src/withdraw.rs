@@ -0,0 +1,162 @@
+//! Origin check for `pallet_evm::Call::withdraw`.
+//!
+//! `pallet_evm` withdraws from `AddressMapping::into_account_id(address)` to whatever
+//! account its `WithdrawOrigin` returns. [`EnsureWithdrawOrigin`] returns
+//!
+//! - the signer of [`crate::Pallet::withdraw_with_proof`], which dispatches the withdraw with
+//!   a [`RawOrigin::EvmAddress`] origin once the ECDSA signature of `address` checks out, or
+//! - the signed account linked to `address`.
+//!
+//! Any other origin is rejected, so a plain signed origin can no longer drain an H160.
+
+use frame_system::RawOrigin as SystemOrigin;
+use pallet_evm::EnsureAddressOrigin;
+use sp_core::H160;
+use sp_std::marker::PhantomData;
+
+use crate::{Config, Pallet, RawOrigin};
+
+/// `pallet_evm::Config::WithdrawOrigin` accepting an ECDSA proof or the linked account.
+pub struct EnsureWithdrawOrigin<T>(PhantomData<T>);
+
+impl<T, OuterOrigin> EnsureAddressOrigin<OuterOrigin> for EnsureWithdrawOrigin<T>
+where
+    T: Config,
+    OuterOrigin: Into<Result<RawOrigin<T::AccountId>, OuterOrigin>>
+        + From<RawOrigin<T::AccountId>>
+        + Into<Result<SystemOrigin<T::AccountId>, OuterOrigin>>
+        + From<SystemOrigin<T::AccountId>>,
+{
+    type Success = T::AccountId;
+
+    fn try_address_origin(address: &H160, origin: OuterOrigin) -> Result<T::AccountId, OuterOrigin> {
+        let origin = match Into::<Result<RawOrigin<T::AccountId>, OuterOrigin>>::into(origin) {
+            Ok(RawOrigin::EvmAddress { account, evm_address }) if &evm_address == address => return Ok(account),
+            Ok(other) => return Err(OuterOrigin::from(other)),
+            Err(origin) => origin,
+        };
+
+        Into::<Result<SystemOrigin<T::AccountId>, OuterOrigin>>::into(origin).and_then(|o| match o {
+            SystemOrigin::Signed(who) if Pallet::<T>::linked_evm_address(&who).as_ref() == Some(address) => Ok(who),
+            r => Err(OuterOrigin::from(r)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{new_test_ext, AccountId, Balances, RuntimeOrigin, Test, UnifiedAccounts, EVM},
+        signing, Error, EvmToNative, NativeToEvm,
+    };
+    use frame_support::{assert_noop, assert_ok, traits::Currency};
+    use pallet_evm::{AddressMapping, HashedAddressMapping};
+    use sp_core::{ecdsa, Pair};
+    use sp_runtime::{traits::BlakeTwo256, DispatchError};
+
+    const ALICE: AccountId = AccountId::new([1u8; 32]);
+    const BOB: AccountId = AccountId::new([2u8; 32]);
+
+    fn evm_key(seed: u8) -> (ecdsa::Pair, H160) {
+        let pair = ecdsa::Pair::from_seed(&[seed; 32]);
+        let probe = pair.sign_prehashed(&[0u8; 32]);
+        let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&probe.0, &[0u8; 32]).unwrap();
+        (pair, H160::from_slice(&sp_io::hashing::keccak_256(&pubkey)[12..]))
+    }
+
+    fn sign_withdraw(pair: &ecdsa::Pair, account: &AccountId, address: H160, value: u128) -> ecdsa::Signature {
+        let message = UnifiedAccounts::withdraw_message(account, address, value);
+        pair.sign_prehashed(&signing::personal_sign_digest(&message))
+    }
+
+    fn hashed(address: H160) -> AccountId {
+        HashedAddressMapping::<BlakeTwo256>::into_account_id(address)
+    }
+
+    #[test]
+    fn unlinked_address_withdraws_from_hashed_account() {
+        new_test_ext().execute_with(|| {
+            let (pair, address) = evm_key(7);
+            Balances::make_free_balance_be(&hashed(address), 1_000);
+            assert_eq!(<Test as pallet_evm::Config>::AddressMapping::into_account_id(address), hashed(address));
+
+            let signature = sign_withdraw(&pair, &ALICE, address, 400);
+            assert_ok!(UnifiedAccounts::withdraw_with_proof(RuntimeOrigin::signed(ALICE), address, 400, signature));
+
+            assert_eq!(Balances::free_balance(hashed(address)), 600);
+            assert_eq!(Balances::free_balance(ALICE), 400);
+            assert_eq!(UnifiedAccounts::withdraw_nonce(address), 1);
+        });
+    }
+
+    #[test]
+    fn withdraw_signature_cannot_be_replayed() {
+        new_test_ext().execute_with(|| {
+            let (pair, address) = evm_key(7);
+            Balances::make_free_balance_be(&hashed(address), 1_000);
+
+            let signature = sign_withdraw(&pair, &ALICE, address, 400);
+            assert_ok!(UnifiedAccounts::withdraw_with_proof(
+                RuntimeOrigin::signed(ALICE),
+                address,
+                400,
+                signature.clone()
+            ));
+            assert_noop!(
+                UnifiedAccounts::withdraw_with_proof(RuntimeOrigin::signed(ALICE), address, 400, signature),
+                Error::<Test>::InvalidWithdrawSignature
+            );
+        });
+    }
+
+    #[test]
+    fn withdraw_signature_is_bound_to_caller_value_and_key() {
+        new_test_ext().execute_with(|| {
+            let (pair, address) = evm_key(7);
+            let (other, _) = evm_key(8);
+            Balances::make_free_balance_be(&hashed(address), 1_000);
+
+            let signature = sign_withdraw(&pair, &ALICE, address, 400);
+            assert_noop!(
+                UnifiedAccounts::withdraw_with_proof(RuntimeOrigin::signed(BOB), address, 400, signature.clone()),
+                Error::<Test>::InvalidWithdrawSignature
+            );
+            assert_noop!(
+                UnifiedAccounts::withdraw_with_proof(RuntimeOrigin::signed(ALICE), address, 1_000, signature),
+                Error::<Test>::InvalidWithdrawSignature
+            );
+
+            let forged = sign_withdraw(&other, &ALICE, address, 400);
+            assert_noop!(
+                UnifiedAccounts::withdraw_with_proof(RuntimeOrigin::signed(ALICE), address, 400, forged),
+                Error::<Test>::InvalidWithdrawSignature
+            );
+        });
+    }
+
+    #[test]
+    fn evm_withdraw_rejects_unproven_origins() {
+        new_test_ext().execute_with(|| {
+            let (_, address) = evm_key(7);
+            Balances::make_free_balance_be(&hashed(address), 1_000);
+
+            assert_noop!(EVM::withdraw(RuntimeOrigin::signed(ALICE), address, 400), DispatchError::BadOrigin);
+            assert_noop!(EVM::withdraw(RuntimeOrigin::root(), address, 400), DispatchError::BadOrigin);
+        });
+    }
+
+    #[test]
+    fn linked_account_passes_withdraw_origin() {
+        new_test_ext().execute_with(|| {
+            let (_, address) = evm_key(7);
+            EvmToNative::<Test>::insert(address, ALICE);
+            NativeToEvm::<Test>::insert(ALICE, address);
+
+            let ensure = |address, who| EnsureWithdrawOrigin::<Test>::try_address_origin(&address, RuntimeOrigin::signed(who));
+            assert_eq!(ensure(address, ALICE).ok(), Some(ALICE));
+            assert!(ensure(address, BOB).is_err());
+            assert!(ensure(H160::repeat_byte(1), ALICE).is_err());
+        });
+    }
+}
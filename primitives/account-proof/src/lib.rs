@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage keys and verifier of the account proofs returned by `eth_getProof`.
+//!
+//! Substrate keeps every account in the single state trie of the block instead of
+//! Ethereum's account and per-account storage tries, so `eth_getProof` returns Substrate
+//! trie proofs against the block's `state_root`, which it also reports as `storageHash`:
+//!
+//! - `accountProof` proves the `EvmToNative` entry of the address in the unified accounts
+//!   pallet, present or not, the `Account` entry in `frame_system` of the account the
+//!   address maps to and the `AccountCodes` entry of the address in `pallet_evm`.
+//! - `storageProof[i].proof` proves the `AccountStorages` entry of the address and slot.
+//!
+//! Where those entries live depends on the runtime, which describes it with an
+//! [`AccountLayout`]. Each proof is an unordered set of trie nodes. An absent entry is
+//! proven by the nodes leading to where it would be.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(unused_crate_dependencies)]
+
+use scale_codec::Decode;
+// Substrate
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::BlakeTwo256;
+use sp_std::vec::Vec;
+use sp_trie::{LayoutV1, StorageProof};
+
+/// How a runtime stores the entries making up the EVM view of an account.
+pub trait AccountLayout {
+	/// `construct_runtime!` name of `pallet_unified_accounts`, `None` if the runtime has no
+	/// links and always maps addresses to their hashed account.
+	const UNIFIED_ACCOUNTS: Option<&'static str>;
+	/// `construct_runtime!` name of `frame_system`.
+	const SYSTEM: &'static str;
+	/// `construct_runtime!` name of `pallet_evm`.
+	const EVM: &'static str;
+	/// `frame_system::Config::Nonce` of the runtime.
+	type Nonce: Decode + Into<U256>;
+	/// Balance of the `pallet_balances::AccountData` the runtime keeps in `frame_system`.
+	type Balance: Decode + Into<U256>;
+}
+
+/// A runtime without unified accounts, with `u32` nonces and `u128` balances.
+impl AccountLayout for () {
+	const UNIFIED_ACCOUNTS: Option<&'static str> = None;
+	const SYSTEM: &'static str = "System";
+	const EVM: &'static str = "EVM";
+	type Nonce = u32;
+	type Balance = u128;
+}
+
+/// Storage keys of the entries making up the EVM view of an account.
+pub mod keys {
+	use sp_core::{
+		hashing::{blake2_128, blake2_256, twox_128},
+		H160, H256,
+	};
+	use sp_std::vec::Vec;
+
+	use super::AccountLayout;
+
+	fn storage_prefix(pallet: &str, storage: &[u8]) -> Vec<u8> {
+		[twox_128(pallet.as_bytes()), twox_128(storage)].concat()
+	}
+
+	fn blake2_128_concat(data: &[u8]) -> Vec<u8> {
+		[&blake2_128(data)[..], data].concat()
+	}
+
+	/// `EvmToNative` entry of the address, the account linked to it. `None` if the runtime
+	/// has no unified accounts.
+	pub fn evm_to_native<L: AccountLayout>(address: H160) -> Option<Vec<u8>> {
+		L::UNIFIED_ACCOUNTS.map(|pallet| {
+			[
+				storage_prefix(pallet, b"EvmToNative"),
+				blake2_128_concat(address.as_bytes()),
+			]
+			.concat()
+		})
+	}
+
+	/// `frame_system` `Account` entry of a 32 byte account id.
+	pub fn system_account<L: AccountLayout>(account_id: &[u8; 32]) -> Vec<u8> {
+		[
+			storage_prefix(L::SYSTEM, b"Account"),
+			blake2_128_concat(account_id),
+		]
+		.concat()
+	}
+
+	/// `pallet_evm` `AccountCodes` entry of the address.
+	pub fn account_code<L: AccountLayout>(address: H160) -> Vec<u8> {
+		[
+			storage_prefix(L::EVM, b"AccountCodes"),
+			blake2_128_concat(address.as_bytes()),
+		]
+		.concat()
+	}
+
+	/// `pallet_evm` `AccountStorages` entry of the address and slot.
+	pub fn account_storage<L: AccountLayout>(address: H160, index: H256) -> Vec<u8> {
+		[
+			storage_prefix(L::EVM, b"AccountStorages"),
+			blake2_128_concat(address.as_bytes()),
+			blake2_128_concat(index.as_bytes()),
+		]
+		.concat()
+	}
+
+	/// Account an address maps to when it is not linked, as `HashedAddressMapping<BlakeTwo256>`.
+	pub fn hashed_account_id(address: H160) -> [u8; 32] {
+		let mut data = [0u8; 24];
+		data[0..4].copy_from_slice(b"evm:");
+		data[4..24].copy_from_slice(address.as_bytes());
+		blake2_256(&data)
+	}
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProofError {
+	/// The proof does not contain the nodes needed to read an entry under the state root.
+	InvalidProof,
+	/// A proven entry could not be decoded.
+	InvalidValue,
+}
+
+/// EVM view of an account, as proven by an `accountProof`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProvenAccount {
+	/// Account the address maps to.
+	pub account_id: [u8; 32],
+	/// Whether the address is linked to `account_id` rather than hashed into it.
+	pub linked: bool,
+	pub nonce: U256,
+	pub free_balance: U256,
+	pub reserved_balance: U256,
+	pub code: Vec<u8>,
+}
+
+/// Layout of `frame_system::AccountInfo<Nonce, pallet_balances::AccountData<Balance>>`.
+#[derive(Decode)]
+pub struct AccountInfo<Nonce, Balance> {
+	pub nonce: Nonce,
+	_consumers: u32,
+	_providers: u32,
+	_sufficients: u32,
+	pub free: Balance,
+	pub reserved: Balance,
+	_frozen: Balance,
+	_flags: u128,
+}
+
+/// Decodes a `frame_system` `Account` entry of a runtime with the layout `L`.
+pub fn decode_account_info<L: AccountLayout>(
+	raw: &[u8],
+) -> Result<AccountInfo<L::Nonce, L::Balance>, ProofError> {
+	Decode::decode(&mut &raw[..]).map_err(|_| ProofError::InvalidValue)
+}
+
+/// Reads the value of `key` from a proof against `state_root`, `None` if proven absent.
+pub fn read_value(
+	state_root: H256,
+	proof: &[Vec<u8>],
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, ProofError> {
+	let db = StorageProof::new(proof.iter().cloned()).into_memory_db::<BlakeTwo256>();
+	sp_trie::read_trie_value::<LayoutV1<BlakeTwo256>, _>(&db, &state_root, key, None, None)
+		.map_err(|_| ProofError::InvalidProof)
+}
+
+fn decode<T: Decode>(raw: Vec<u8>) -> Result<T, ProofError> {
+	T::decode(&mut &raw[..]).map_err(|_| ProofError::InvalidValue)
+}
+
+/// Checks an `accountProof` of a runtime with the layout `L` against `state_root` and
+/// returns the proven account.
+pub fn verify_account<L: AccountLayout>(
+	state_root: H256,
+	address: H160,
+	proof: &[Vec<u8>],
+) -> Result<ProvenAccount, ProofError> {
+	let linked = match keys::evm_to_native::<L>(address) {
+		Some(key) => read_value(state_root, proof, &key)?
+			.map(decode::<[u8; 32]>)
+			.transpose()?,
+		None => None,
+	};
+	let account_id = linked.unwrap_or_else(|| keys::hashed_account_id(address));
+
+	let mut account = ProvenAccount {
+		account_id,
+		linked: linked.is_some(),
+		..Default::default()
+	};
+	if let Some(raw) = read_value(state_root, proof, &keys::system_account::<L>(&account_id))? {
+		let info = decode_account_info::<L>(&raw)?;
+		account.nonce = info.nonce.into();
+		account.free_balance = info.free.into();
+		account.reserved_balance = info.reserved.into();
+	}
+	if let Some(raw) = read_value(state_root, proof, &keys::account_code::<L>(address))? {
+		account.code = decode(raw)?;
+	}
+
+	Ok(account)
+}
+
+/// Checks a `storageProof` entry of a runtime with the layout `L` against `state_root` and
+/// returns the proven slot value.
+pub fn verify_storage<L: AccountLayout>(
+	state_root: H256,
+	address: H160,
+	index: H256,
+	proof: &[Vec<u8>],
+) -> Result<H256, ProofError> {
+	Ok(read_value(
+		state_root,
+		proof,
+		&keys::account_storage::<L>(address, index),
+	)?
+	.map(decode)
+	.transpose()?
+	.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use scale_codec::Encode;
+	use sp_trie::{MemoryDB, TrieDBMutBuilder, TrieMut};
+
+	/// A runtime with unified accounts and `u64` nonces.
+	struct Layout;
+	impl AccountLayout for Layout {
+		const UNIFIED_ACCOUNTS: Option<&'static str> = Some("UnifiedAccounts");
+		const SYSTEM: &'static str = "System";
+		const EVM: &'static str = "EVM";
+		type Nonce = u64;
+		type Balance = u128;
+	}
+
+	/// Builds a state with the given entries and returns its root and every node of it.
+	fn state(entries: &[(Vec<u8>, Vec<u8>)]) -> (H256, Vec<Vec<u8>>) {
+		let mut db = MemoryDB::<BlakeTwo256>::default();
+		let mut root = H256::zero();
+		{
+			let mut trie =
+				TrieDBMutBuilder::<LayoutV1<BlakeTwo256>>::new(&mut db, &mut root).build();
+			for (key, value) in entries {
+				trie.insert(key, value).unwrap();
+			}
+		}
+		let nodes = db.drain().into_values().map(|(node, _)| node).collect();
+		(root, nodes)
+	}
+
+	fn account_info(nonce: u64, free: u128) -> Vec<u8> {
+		(nonce, 0u32, 1u32, 0u32, free, 0u128, 0u128, 0u128).encode()
+	}
+
+	#[test]
+	fn verify_hashed_account_and_storage() {
+		let address = H160::repeat_byte(1);
+		let account_id = keys::hashed_account_id(address);
+		let (root, proof) = state(&[
+			(
+				keys::system_account::<Layout>(&account_id),
+				account_info(3, 1_000),
+			),
+			(
+				keys::account_code::<Layout>(address),
+				vec![0x60u8, 0x00].encode(),
+			),
+			(
+				keys::account_storage::<Layout>(address, H256::from_low_u64_be(1)),
+				H256::repeat_byte(7).encode(),
+			),
+		]);
+
+		let account = verify_account::<Layout>(root, address, &proof).unwrap();
+		assert_eq!(account.account_id, account_id);
+		assert!(!account.linked);
+		assert_eq!(
+			(account.nonce, account.free_balance),
+			(U256::from(3), U256::from(1_000))
+		);
+		assert_eq!(account.code, vec![0x60, 0x00]);
+
+		assert_eq!(
+			verify_storage::<Layout>(root, address, H256::from_low_u64_be(1), &proof),
+			Ok(H256::repeat_byte(7))
+		);
+		assert_eq!(
+			verify_storage::<Layout>(root, address, H256::from_low_u64_be(2), &proof),
+			Ok(H256::zero())
+		);
+	}
+
+	#[test]
+	fn verify_linked_account() {
+		let address = H160::repeat_byte(1);
+		let account_id = [9u8; 32];
+		let (root, proof) = state(&[
+			(
+				keys::evm_to_native::<Layout>(address).unwrap(),
+				account_id.encode(),
+			),
+			(
+				keys::system_account::<Layout>(&account_id),
+				account_info(0, 42),
+			),
+		]);
+
+		let account = verify_account::<Layout>(root, address, &proof).unwrap();
+		assert_eq!(account.account_id, account_id);
+		assert!(account.linked);
+		assert_eq!(account.free_balance, U256::from(42));
+		assert!(account.code.is_empty());
+	}
+
+	#[test]
+	fn layout_without_unified_accounts_uses_the_hashed_account() {
+		let address = H160::repeat_byte(1);
+		let account_id = keys::hashed_account_id(address);
+		let (root, proof) = state(&[
+			(
+				keys::evm_to_native::<Layout>(address).unwrap(),
+				[9u8; 32].encode(),
+			),
+			(
+				keys::system_account::<()>(&account_id),
+				(5u32, 0u32, 1u32, 0u32, 7u128, 0u128, 0u128, 0u128).encode(),
+			),
+		]);
+
+		assert_eq!(keys::evm_to_native::<()>(address), None);
+		let account = verify_account::<()>(root, address, &proof).unwrap();
+		assert_eq!(account.account_id, account_id);
+		assert!(!account.linked);
+		assert_eq!(
+			(account.nonce, account.free_balance),
+			(U256::from(5), U256::from(7))
+		);
+	}
+
+	#[test]
+	fn reject_proof_of_another_state() {
+		let address = H160::repeat_byte(1);
+		let (_, proof) = state(&[(keys::account_code::<Layout>(address), vec![0u8].encode())]);
+		let (other_root, _) = state(&[(keys::account_code::<Layout>(address), vec![1u8].encode())]);
+
+		assert_eq!(
+			verify_account::<Layout>(other_root, address, &proof),
+			Err(ProofError::InvalidProof)
+		);
+	}
+}
@@ -182,10 +182,34 @@ impl From<ecdsa::Public> for EthereumSigner {
 	}
 }
 
+/// Half the order of the secp256k1 curve. A signature with a larger `s` is the malleated twin
+/// of the one with `n - s`, and is rejected as in EIP-2.
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+	0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// Address of the key that produced the `r ‖ s ‖ v` `signature` of `digest`.
+///
+/// `v` may be either 27/28 or the raw recovery id. Signatures with a high `s` are rejected.
+pub fn recover_ethereum_signer(signature: &[u8; 65], digest: &[u8; 32]) -> Option<H160> {
+	if signature[32..64] > SECP256K1_HALF_ORDER[..] {
+		return None;
+	}
+	let mut signature = *signature;
+	signature[64] = match signature[64] {
+		v @ (27 | 28) => v - 27,
+		v @ (0 | 1) => v,
+		_ => return None,
+	};
+	let public = sp_io::crypto::secp256k1_ecdsa_recover(&signature, digest).ok()?;
+	Some(H160::from_slice(&keccak_256(&public)[12..]))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use sp_core::{ecdsa, Pair, H256};
+	use sp_core::{ecdsa, Pair, H256, U256};
 	use sp_runtime::traits::IdentifyAccount;
 
 	#[test]
@@ -213,6 +237,44 @@ mod tests {
 		assert_eq!(new, old);
 	}
 
+	#[test]
+	fn recovers_ethereum_signer() {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let signer: EthereumSigner = pair.public().into();
+		let address = H160::from(signer.into_account());
+		let digest = keccak_256(b"digest");
+		let signature = pair.sign_prehashed(&digest).0;
+
+		assert_eq!(recover_ethereum_signer(&signature, &digest), Some(address));
+
+		let mut legacy_v = signature;
+		legacy_v[64] += 27;
+		assert_eq!(recover_ethereum_signer(&legacy_v, &digest), Some(address));
+
+		let mut bad_v = signature;
+		bad_v[64] = 29;
+		assert_eq!(recover_ethereum_signer(&bad_v, &digest), None);
+	}
+
+	#[test]
+	fn rejects_high_s_signatures() {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let digest = keccak_256(b"digest");
+		let signature = pair.sign_prehashed(&digest).0;
+
+		// (r, n - s, v ^ 1) is an equally valid signature by the same key.
+		let order = U256::from_big_endian(
+			&hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+				.unwrap(),
+		);
+		let high = order - U256::from_big_endian(&signature[32..64]);
+		let mut malleated = signature;
+		high.to_big_endian(&mut malleated[32..64]);
+		malleated[64] ^= 1;
+
+		assert_eq!(recover_ethereum_signer(&malleated, &digest), None);
+	}
+
 	#[test]
 	fn test_account_display() {
 		let pk = ecdsa::Pair::from_string("//Alice", None)
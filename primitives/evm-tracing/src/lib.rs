@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Primitives for re-executing Ethereum transactions under an EVM event listener.
+//!
+//! The runtime drives the `tracer::Tracer` while it replays a block and hands the
+//! SCALE-encoded [`TraceResult`] back to the client, which renders it in the geth format.
+//! The tracer is only built with the `tracing` feature; a runtime without it answers
+//! [`TraceError::Unsupported`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::too_many_arguments)]
+#![deny(unused_crate_dependencies)]
+
+mod opcodes;
+#[cfg(any(feature = "tracing", test))]
+pub mod tracer;
+
+use scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+// Substrate
+use sp_core::{H160, H256, U256};
+use sp_runtime::{transaction_validity::TransactionValidityError, DispatchError, RuntimeDebug};
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+pub use evm::{ExitError, ExitReason};
+
+pub use self::opcodes::opcode_name;
+
+/// Tracer selected by the caller of the debug RPC.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum TracerConfig {
+	/// Geth's default opcode-level logger.
+	StructLog {
+		disable_storage: bool,
+		disable_memory: bool,
+		disable_stack: bool,
+	},
+	/// Call tree of the transaction, as geth's `callTracer`.
+	CallTracer { only_top_call: bool },
+	/// Pre-transaction state of every account touched, as geth's `prestateTracer`.
+	PrestateTracer,
+}
+
+impl Default for TracerConfig {
+	fn default() -> Self {
+		Self::StructLog {
+			disable_storage: false,
+			disable_memory: true,
+			disable_stack: false,
+		}
+	}
+}
+
+/// A single executed opcode.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct StructLog {
+	pub pc: u64,
+	pub op: u8,
+	/// Gas left before the opcode executed.
+	pub gas: u64,
+	pub gas_cost: u64,
+	/// Call depth, starting at 1 for the transaction itself.
+	pub depth: u32,
+	pub stack: Option<Vec<H256>>,
+	pub memory: Option<Vec<u8>>,
+	/// Storage of the executing contract seen so far, set on `SLOAD` and `SSTORE`.
+	pub storage: Option<BTreeMap<H256, H256>>,
+	pub error: Option<ExitReason>,
+}
+
+/// Output of the struct logger.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct StructLogTrace {
+	pub gas: u64,
+	pub failed: bool,
+	pub return_value: Vec<u8>,
+	pub struct_logs: Vec<StructLog>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum CallType {
+	Call,
+	StaticCall,
+	DelegateCall,
+	CallCode,
+	Create,
+	Create2,
+	SelfDestruct,
+}
+
+/// A call frame of the call tracer.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct CallFrame {
+	pub call_type: CallType,
+	pub from: H160,
+	pub to: H160,
+	pub value: Option<U256>,
+	pub gas: u64,
+	pub gas_used: u64,
+	pub input: Vec<u8>,
+	pub output: Vec<u8>,
+	pub error: Option<ExitReason>,
+	pub calls: Vec<CallFrame>,
+}
+
+/// State of an account before the traced transaction.
+#[derive(Clone, Default, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct PrestateAccount {
+	pub balance: U256,
+	pub nonce: U256,
+	pub code: Vec<u8>,
+	pub storage: BTreeMap<H256, H256>,
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum TraceResult {
+	StructLog(StructLogTrace),
+	CallTracer(CallFrame),
+	PrestateTracer(BTreeMap<H160, PrestateAccount>),
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum TraceError {
+	/// The transaction is not part of the given extrinsics.
+	TransactionNotFound,
+	/// An extrinsic of the replayed block failed validity checks.
+	InvalidTransaction(TransactionValidityError),
+	/// The traced call could not be executed.
+	Execution(DispatchError),
+	/// The runtime was built without the EVM tracer.
+	Unsupported,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Re-execution of Ethereum transactions under an EVM tracer.
+	///
+	/// The block-level methods are meant to be called at the parent of the block whose
+	/// extrinsics are passed in.
+	pub trait DebugRuntimeApi {
+		/// Replay `extrinsics` up to `transaction` and trace it.
+		fn trace_transaction(
+			extrinsics: Vec<Block::Extrinsic>,
+			header: &Block::Header,
+			transaction: H256,
+			tracer: TracerConfig,
+		) -> Result<TraceResult, TraceError>;
+
+		/// Replay `extrinsics`, tracing every Ethereum transaction.
+		fn trace_block(
+			extrinsics: Vec<Block::Extrinsic>,
+			header: &Block::Header,
+			tracer: TracerConfig,
+		) -> Result<Vec<(H256, TraceResult)>, TraceError>;
+
+		/// Trace a call or create (when `to` is `None`) on top of the current state.
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+			tracer: TracerConfig,
+		) -> Result<TraceResult, TraceError>;
+
+		/// Whether `address` is a precompile, which EIP-2929 treats as warm from the start.
+		fn is_precompile(address: H160) -> bool;
+	}
+}
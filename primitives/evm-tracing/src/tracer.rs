@@ -0,0 +1,1132 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! EVM event listener building the trace of a single transaction.
+//!
+//! The executor, the interpreter and the gasometer each report through their own
+//! `environmental` listener; [`Tracer::trace`] installs one proxy per source, all feeding the
+//! same state. Call frames are opened on `Call`/`Create` and closed on `Exit`. Frames that
+//! end without an `Exit` event (precompiles, calls failing before entering their substate)
+//! are closed when the parent resumes, or when the parent gasometer refunds their gas.
+
+use evm::{
+	tracing::{Event as EvmEvent, EventListener as EvmListener},
+	CreateScheme, ExitError, ExitReason,
+};
+use evm_gasometer::tracing::{
+	Event as GasometerEvent, EventListener as GasometerListener, Snapshot,
+};
+use evm_runtime::{
+	tracing::{Event as RuntimeEvent, EventListener as RuntimeListener},
+	Capture,
+};
+// Substrate
+use sp_core::{H160, H256, U256};
+use sp_std::{
+	cell::RefCell,
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	rc::Rc,
+	vec::Vec,
+};
+
+use crate::{
+	CallFrame, CallType, PrestateAccount, StructLog, StructLogTrace, TraceResult, TracerConfig,
+};
+
+/// Gas added to the forwarded gas of a value-bearing `CALL` or `CALLCODE`.
+const CALL_STIPEND: u64 = 2300;
+
+const OP_BALANCE: u8 = 0x31;
+const OP_EXTCODESIZE: u8 = 0x3b;
+const OP_EXTCODECOPY: u8 = 0x3c;
+const OP_EXTCODEHASH: u8 = 0x3f;
+const OP_SLOAD: u8 = 0x54;
+const OP_SSTORE: u8 = 0x55;
+
+struct Frame {
+	call: CallFrame,
+	/// Address whose storage the frame executes against.
+	context: H160,
+	/// The parent gasometer has not yet charged the gas forwarded to this frame.
+	awaiting_gas: bool,
+}
+
+/// Tracing state of one transaction.
+#[derive(Default)]
+pub struct Tracer {
+	config: TracerConfig,
+	frames: Vec<Frame>,
+	root: Option<CallFrame>,
+	/// A `Transact*` event opened the root frame, its `Call`/`Create` event is still due.
+	root_pending: bool,
+	/// The last child of the current frame exited and its unused gas is still due.
+	awaiting_stipend: bool,
+	logs: Vec<StructLog>,
+	/// Opcode reported by the interpreter, waiting for its cost from the gasometer.
+	step: Option<StructLog>,
+	storage: BTreeMap<H160, BTreeMap<H256, H256>>,
+	touched: BTreeMap<H160, BTreeSet<H256>>,
+}
+
+impl Tracer {
+	pub fn new(config: TracerConfig) -> Self {
+		Self {
+			config,
+			..Default::default()
+		}
+	}
+
+	/// Run `f` with the tracer listening to every EVM event it raises.
+	pub fn trace<R, F: FnOnce() -> R>(&mut self, f: F) -> R {
+		let shared = Rc::new(RefCell::new(sp_std::mem::take(self)));
+		let result = {
+			let mut evm_listener = Listener(shared.clone());
+			let mut runtime_listener = Listener(shared.clone());
+			let mut gasometer_listener = Listener(shared.clone());
+			evm::tracing::using(&mut evm_listener, || {
+				evm_runtime::tracing::using(&mut runtime_listener, || {
+					evm_gasometer::tracing::using(&mut gasometer_listener, f)
+				})
+			})
+		};
+		*self = shared.take();
+		result
+	}
+
+	/// Build the trace once the transaction has been executed.
+	///
+	/// `gas_used` and `failed` are the outcome recorded in the receipt. `prestate` reads the
+	/// state of a touched account and the given storage slots; it is only called by the
+	/// prestate tracer.
+	pub fn into_result<F>(mut self, gas_used: u64, failed: bool, mut prestate: F) -> TraceResult
+	where
+		F: FnMut(H160, &BTreeSet<H256>) -> PrestateAccount,
+	{
+		self.flush_step(0, None);
+		while !self.frames.is_empty() {
+			self.exit_frame(None, Vec::new());
+		}
+
+		match self.config {
+			TracerConfig::StructLog { .. } => TraceResult::StructLog(StructLogTrace {
+				gas: gas_used,
+				failed,
+				return_value: self.root.map(|root| root.output).unwrap_or_default(),
+				struct_logs: self.logs,
+			}),
+			TracerConfig::CallTracer { only_top_call } => {
+				let mut root = self.root.unwrap_or(CallFrame {
+					call_type: CallType::Call,
+					from: H160::default(),
+					to: H160::default(),
+					value: None,
+					gas: 0,
+					gas_used: 0,
+					input: Vec::new(),
+					output: Vec::new(),
+					error: None,
+					calls: Vec::new(),
+				});
+				root.gas_used = gas_used;
+				if only_top_call {
+					root.calls.clear();
+				}
+				TraceResult::CallTracer(root)
+			}
+			TracerConfig::PrestateTracer => TraceResult::PrestateTracer(
+				self.touched
+					.iter()
+					.map(|(address, slots)| (*address, prestate(*address, slots)))
+					.collect(),
+			),
+		}
+	}
+
+	fn touch(&mut self, address: H160) {
+		if self.config == TracerConfig::PrestateTracer {
+			self.touched.entry(address).or_default();
+		}
+	}
+
+	fn touch_slot(&mut self, address: H160, index: H256) {
+		if self.config == TracerConfig::PrestateTracer {
+			self.touched.entry(address).or_default().insert(index);
+		}
+	}
+
+	fn current_log(&mut self) -> Option<&mut StructLog> {
+		match self.step {
+			Some(ref mut step) => Some(step),
+			None => self.logs.last_mut(),
+		}
+	}
+
+	fn flush_step(&mut self, gas_cost: u64, snapshot: Option<Snapshot>) {
+		if let Some(mut log) = self.step.take() {
+			log.gas = snapshot
+				.map(|s| {
+					s.gas_limit
+						.saturating_sub(s.used_gas)
+						.saturating_sub(s.memory_gas)
+				})
+				.unwrap_or_default();
+			log.gas_cost = gas_cost;
+			self.logs.push(log);
+		}
+	}
+
+	fn enter_frame(&mut self, call: CallFrame, context: H160) {
+		self.awaiting_stipend = false;
+		if self.root_pending {
+			self.root_pending = false;
+			return;
+		}
+		self.close_unentered_frame();
+		self.frames.push(Frame {
+			call,
+			context,
+			awaiting_gas: true,
+		});
+	}
+
+	/// Close the current frame if its gas was never charged, i.e. it failed before entering.
+	fn close_unentered_frame(&mut self) {
+		if self.frames.last().map_or(false, |frame| frame.awaiting_gas) {
+			self.exit_frame(
+				Some(ExitReason::Error(ExitError::Other(
+					"call failed before execution".into(),
+				))),
+				Vec::new(),
+			);
+		}
+	}
+
+	fn exit_frame(&mut self, reason: Option<ExitReason>, output: Vec<u8>) {
+		let Some(frame) = self.frames.pop() else {
+			return;
+		};
+		let mut call = frame.call;
+		call.error = reason.filter(|reason| !reason.is_succeed());
+		call.output = output;
+		call.gas_used = if frame.awaiting_gas { 0 } else { call.gas };
+
+		match self.frames.last_mut() {
+			Some(parent) => {
+				parent.call.calls.push(call);
+				self.awaiting_stipend = true;
+			}
+			None => self.root = Some(call),
+		}
+	}
+
+	fn on_evm_event(&mut self, event: EvmEvent) {
+		match event {
+			EvmEvent::TransactCall {
+				caller,
+				address,
+				value,
+				data,
+				gas_limit,
+			} => {
+				self.touch(caller);
+				self.touch(address);
+				self.open_root(CallType::Call, caller, address, value, data, gas_limit);
+			}
+			EvmEvent::TransactCreate {
+				caller,
+				value,
+				init_code,
+				gas_limit,
+				address,
+			} => {
+				self.touch(caller);
+				self.touch(address);
+				self.open_root(
+					CallType::Create,
+					caller,
+					address,
+					value,
+					init_code,
+					gas_limit,
+				);
+			}
+			EvmEvent::TransactCreate2 {
+				caller,
+				value,
+				init_code,
+				gas_limit,
+				address,
+				..
+			} => {
+				self.touch(caller);
+				self.touch(address);
+				self.open_root(
+					CallType::Create2,
+					caller,
+					address,
+					value,
+					init_code,
+					gas_limit,
+				);
+			}
+			EvmEvent::Call {
+				code_address,
+				transfer,
+				input,
+				target_gas,
+				is_static,
+				context,
+			}
+			| EvmEvent::PrecompileSubcall {
+				code_address,
+				transfer,
+				input,
+				target_gas,
+				is_static,
+				context,
+			} => {
+				self.touch(context.caller);
+				self.touch(context.address);
+				self.touch(code_address);
+				let value = transfer.as_ref().map(|transfer| transfer.value);
+				let (call_type, from, value) = if is_static {
+					(CallType::StaticCall, context.caller, None)
+				} else if context.address != code_address {
+					match value {
+						Some(value) => (CallType::CallCode, context.address, Some(value)),
+						None => (CallType::DelegateCall, context.address, None),
+					}
+				} else {
+					(CallType::Call, context.caller, value)
+				};
+				self.enter_frame(
+					CallFrame {
+						call_type,
+						from,
+						to: code_address,
+						value,
+						gas: target_gas.unwrap_or_default(),
+						gas_used: 0,
+						input: input.to_vec(),
+						output: Vec::new(),
+						error: None,
+						calls: Vec::new(),
+					},
+					context.address,
+				);
+			}
+			EvmEvent::Create {
+				caller,
+				address,
+				scheme,
+				value,
+				init_code,
+				target_gas,
+			} => {
+				self.touch(caller);
+				self.touch(address);
+				let call_type = match scheme {
+					CreateScheme::Create2 { .. } => CallType::Create2,
+					_ => CallType::Create,
+				};
+				self.enter_frame(
+					CallFrame {
+						call_type,
+						from: caller,
+						to: address,
+						value: Some(value),
+						gas: target_gas.unwrap_or_default(),
+						gas_used: 0,
+						input: init_code.to_vec(),
+						output: Vec::new(),
+						error: None,
+						calls: Vec::new(),
+					},
+					address,
+				);
+			}
+			EvmEvent::Suicide {
+				address,
+				target,
+				balance,
+			} => {
+				self.touch(address);
+				self.touch(target);
+				if let Some(frame) = self.frames.last_mut() {
+					frame.call.calls.push(CallFrame {
+						call_type: CallType::SelfDestruct,
+						from: address,
+						to: target,
+						value: Some(balance),
+						gas: 0,
+						gas_used: 0,
+						input: Vec::new(),
+						output: Vec::new(),
+						error: None,
+						calls: Vec::new(),
+					});
+				}
+			}
+			EvmEvent::Exit {
+				reason,
+				return_value,
+			} => {
+				self.awaiting_stipend = false;
+				self.close_unentered_frame();
+				if !reason.is_succeed() {
+					let depth = self.frames.len() as u32;
+					if let Some(log) = self.current_log() {
+						if log.depth == depth && log.error.is_none() {
+							log.error = Some(reason.clone());
+						}
+					}
+				}
+				self.exit_frame(Some(reason.clone()), return_value.to_vec());
+			}
+		}
+	}
+
+	fn open_root(
+		&mut self,
+		call_type: CallType,
+		from: H160,
+		to: H160,
+		value: U256,
+		input: &[u8],
+		gas_limit: U256,
+	) {
+		self.root_pending = true;
+		self.frames.push(Frame {
+			call: CallFrame {
+				call_type,
+				from,
+				to,
+				value: Some(value),
+				gas: gas_limit.min(U256::from(u64::MAX)).low_u64(),
+				gas_used: 0,
+				input: input.to_vec(),
+				output: Vec::new(),
+				error: None,
+				calls: Vec::new(),
+			},
+			context: to,
+			awaiting_gas: false,
+		});
+	}
+
+	fn on_runtime_event(&mut self, event: RuntimeEvent) {
+		match event {
+			RuntimeEvent::Step {
+				context,
+				opcode,
+				position,
+				stack,
+				memory,
+			} => {
+				self.awaiting_stipend = false;
+				// The interpreter only steps in the current frame, anything above it has ended.
+				while self.frames.len() > 1
+					&& self.frames.last().map_or(false, |frame| {
+						frame.awaiting_gas || frame.context != context.address
+					}) {
+					if self.frames.last().map_or(false, |frame| frame.awaiting_gas) {
+						self.close_unentered_frame();
+					} else {
+						self.exit_frame(None, Vec::new());
+					}
+				}
+
+				// Accounts read by address without being called.
+				if matches!(
+					opcode.0,
+					OP_BALANCE | OP_EXTCODESIZE | OP_EXTCODECOPY | OP_EXTCODEHASH
+				) {
+					if let Some(address) = stack.data().last() {
+						self.touch(H160::from(*address));
+					}
+				}
+
+				if let TracerConfig::StructLog {
+					disable_memory,
+					disable_stack,
+					..
+				} = self.config
+				{
+					self.flush_step(0, None);
+					self.step = Some(StructLog {
+						pc: position.as_ref().map(|pc| *pc as u64).unwrap_or_default(),
+						op: opcode.0,
+						gas: 0,
+						gas_cost: 0,
+						depth: self.frames.len() as u32,
+						stack: (!disable_stack).then(|| stack.data().clone()),
+						memory: (!disable_memory).then(|| memory.data().clone()),
+						storage: None,
+						error: None,
+					});
+				}
+			}
+			RuntimeEvent::StepResult {
+				result: Err(Capture::Exit(reason)),
+				..
+			} if !reason.is_succeed() => {
+				if let Some(log) = self.current_log() {
+					log.error = Some(reason.clone());
+				}
+			}
+			RuntimeEvent::SLoad {
+				address,
+				index,
+				value,
+			}
+			| RuntimeEvent::SStore {
+				address,
+				index,
+				value,
+			} => {
+				self.touch_slot(address, index);
+				if let TracerConfig::StructLog {
+					disable_storage: false,
+					..
+				} = self.config
+				{
+					let storage = self.storage.entry(address).or_default();
+					storage.insert(index, value);
+					let storage = storage.clone();
+					if let Some(log) = self.current_log() {
+						if matches!(log.op, OP_SLOAD | OP_SSTORE) {
+							log.storage = Some(storage);
+						}
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn on_gasometer_event(&mut self, event: GasometerEvent) {
+		match event {
+			GasometerEvent::RecordCost { cost, snapshot } => {
+				if let Some(frame) = self.frames.last_mut().filter(|frame| frame.awaiting_gas) {
+					// The parent charging the gas it forwards to the new frame.
+					frame.awaiting_gas = false;
+					frame.call.gas = cost;
+					let with_stipend =
+						matches!(frame.call.call_type, CallType::Call | CallType::CallCode)
+							&& frame.call.value.map_or(false, |value| !value.is_zero());
+					if with_stipend {
+						frame.call.gas = cost.saturating_add(CALL_STIPEND);
+					}
+				} else {
+					self.flush_step(cost, snapshot);
+				}
+			}
+			GasometerEvent::RecordDynamicCost {
+				gas_cost,
+				memory_gas,
+				snapshot,
+				..
+			} => {
+				let memory_cost =
+					memory_gas.saturating_sub(snapshot.map(|s| s.memory_gas).unwrap_or_default());
+				self.flush_step(gas_cost.saturating_add(memory_cost), snapshot);
+			}
+			GasometerEvent::RecordStipend { stipend, .. } => {
+				if !self.awaiting_stipend && self.frames.len() > 1 {
+					// A frame without `Exit` event handing its unused gas back.
+					self.exit_frame(None, Vec::new());
+				}
+				if self.awaiting_stipend {
+					self.awaiting_stipend = false;
+					if let Some(child) = self
+						.frames
+						.last_mut()
+						.and_then(|frame| frame.call.calls.last_mut())
+					{
+						child.gas_used = child.gas.saturating_sub(stipend);
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Forwards the events of one EVM component to the shared [`Tracer`].
+struct Listener(Rc<RefCell<Tracer>>);
+
+impl EvmListener for Listener {
+	fn event(&mut self, event: EvmEvent) {
+		self.0.borrow_mut().on_evm_event(event);
+	}
+}
+
+impl RuntimeListener for Listener {
+	fn event(&mut self, event: RuntimeEvent) {
+		self.0.borrow_mut().on_runtime_event(event);
+	}
+}
+
+impl GasometerListener for Listener {
+	fn event(&mut self, event: GasometerEvent) {
+		self.0.borrow_mut().on_gasometer_event(event);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use evm::{ExitRevert, ExitSucceed, Transfer};
+	use evm_runtime::{Context, Memory, Opcode, Stack};
+
+	const ALICE: H160 = H160::repeat_byte(0xaa);
+	const CONTRACT: H160 = H160::repeat_byte(0xc1);
+	const CHILD: H160 = H160::repeat_byte(0xc2);
+	const OTHER: H160 = H160::repeat_byte(0xc3);
+	const PRECOMPILE: H160 = H160::repeat_byte(0x01);
+
+	const OP_STOP: u8 = 0x00;
+	const OP_POP: u8 = 0x50;
+	const OP_MSTORE: u8 = 0x52;
+	const OP_PUSH1: u8 = 0x60;
+	const OP_CREATE2: u8 = 0xf5;
+	const OP_CALL: u8 = 0xf1;
+	const OP_REVERT: u8 = 0xfd;
+	const OP_SELFDESTRUCT: u8 = 0xff;
+
+	/// Gas limit of the root frame's gasometer in the snapshots below.
+	const ROOT_GAS: u64 = 79_000;
+
+	fn snapshot(gas_limit: u64, used_gas: u64, memory_gas: u64) -> Option<Snapshot> {
+		Some(Snapshot {
+			gas_limit,
+			memory_gas,
+			used_gas,
+			refunded_gas: 0,
+		})
+	}
+
+	fn call_frame(
+		call_type: CallType,
+		from: H160,
+		to: H160,
+		value: Option<U256>,
+		gas: u64,
+		gas_used: u64,
+	) -> CallFrame {
+		CallFrame {
+			call_type,
+			from,
+			to,
+			value,
+			gas,
+			gas_used,
+			input: Vec::new(),
+			output: Vec::new(),
+			error: None,
+			calls: Vec::new(),
+		}
+	}
+
+	/// Feeds the tracer the events the executor, interpreter and gasometer raise.
+	struct Replay(Tracer);
+
+	impl Replay {
+		fn new(config: TracerConfig) -> Self {
+			Self(Tracer::new(config))
+		}
+
+		fn call_tracer() -> Self {
+			Self::new(TracerConfig::CallTracer {
+				only_top_call: false,
+			})
+		}
+
+		/// A transaction from `ALICE` calling `CONTRACT` with `input`, up to its first step.
+		fn transact(&mut self, input: &[u8], gas_limit: u64) {
+			self.0.on_evm_event(EvmEvent::TransactCall {
+				caller: ALICE,
+				address: CONTRACT,
+				value: U256::zero(),
+				data: input,
+				gas_limit: gas_limit.into(),
+			});
+			self.call(ALICE, CONTRACT, None, input, ROOT_GAS);
+			self.forward(ROOT_GAS);
+		}
+
+		fn call(&mut self, caller: H160, to: H160, value: Option<U256>, input: &[u8], gas: u64) {
+			let transfer = value.map(|value| Transfer {
+				source: caller,
+				target: to,
+				value,
+			});
+			self.0.on_evm_event(EvmEvent::Call {
+				code_address: to,
+				transfer: &transfer,
+				input,
+				target_gas: Some(gas),
+				is_static: false,
+				context: &Context {
+					address: to,
+					caller,
+					apparent_value: value.unwrap_or_default(),
+				},
+			});
+		}
+
+		/// A call made by the precompile `caller` through its handle.
+		fn precompile_subcall(&mut self, caller: H160, to: H160, input: &[u8], gas: u64) {
+			self.0.on_evm_event(EvmEvent::PrecompileSubcall {
+				code_address: to,
+				transfer: &None,
+				input,
+				target_gas: Some(gas),
+				is_static: false,
+				context: &Context {
+					address: to,
+					caller,
+					apparent_value: U256::zero(),
+				},
+			});
+		}
+
+		fn create2(&mut self, caller: H160, address: H160, value: U256, init_code: &[u8]) {
+			self.0.on_evm_event(EvmEvent::Create {
+				caller,
+				address,
+				scheme: CreateScheme::Create2 {
+					caller,
+					code_hash: H256::zero(),
+					salt: H256::repeat_byte(1),
+				},
+				value,
+				init_code,
+				target_gas: None,
+			});
+		}
+
+		/// The caller's gasometer charging the gas it forwards to a new frame.
+		fn forward(&mut self, gas: u64) {
+			self.0.on_gasometer_event(GasometerEvent::RecordCost {
+				cost: gas,
+				snapshot: None,
+			});
+		}
+
+		fn step(&mut self, address: H160, pc: usize, op: u8, stack: &[H256]) {
+			let mut data = Stack::new(1024);
+			for item in stack {
+				data.push(*item).unwrap();
+			}
+			self.0.on_runtime_event(RuntimeEvent::Step {
+				context: &Context {
+					address,
+					caller: ALICE,
+					apparent_value: U256::zero(),
+				},
+				opcode: Opcode(op),
+				position: &Ok(pc),
+				stack: &data,
+				memory: &Memory::new(1024),
+			});
+		}
+
+		fn charge(&mut self, cost: u64, snapshot: Option<Snapshot>) {
+			self.0
+				.on_gasometer_event(GasometerEvent::RecordCost { cost, snapshot });
+		}
+
+		fn charge_dynamic(&mut self, gas_cost: u64, memory_gas: u64, snapshot: Option<Snapshot>) {
+			self.0
+				.on_gasometer_event(GasometerEvent::RecordDynamicCost {
+					gas_cost,
+					memory_gas,
+					gas_refund: 0,
+					snapshot,
+				});
+		}
+
+		fn sload(&mut self, address: H160, index: H256, value: H256) {
+			self.0.on_runtime_event(RuntimeEvent::SLoad {
+				address,
+				index,
+				value,
+			});
+		}
+
+		fn sstore(&mut self, address: H160, index: H256, value: H256) {
+			self.0.on_runtime_event(RuntimeEvent::SStore {
+				address,
+				index,
+				value,
+			});
+		}
+
+		fn suicide(&mut self, address: H160, target: H160, balance: U256) {
+			self.0.on_evm_event(EvmEvent::Suicide {
+				address,
+				target,
+				balance,
+			});
+		}
+
+		fn exit(&mut self, reason: ExitReason, output: &[u8]) {
+			self.0.on_evm_event(EvmEvent::Exit {
+				reason: &reason,
+				return_value: output,
+			});
+		}
+
+		/// The caller's gasometer taking back the gas a frame left unused.
+		fn stipend(&mut self, stipend: u64) {
+			self.0.on_gasometer_event(GasometerEvent::RecordStipend {
+				stipend,
+				snapshot: None,
+			});
+		}
+
+		fn finish(self, gas_used: u64, failed: bool) -> TraceResult {
+			self.0
+				.into_result(gas_used, failed, |address, slots| PrestateAccount {
+					balance: U256::from(address.to_low_u64_be()),
+					storage: slots.iter().map(|slot| (*slot, H256::zero())).collect(),
+					..Default::default()
+				})
+		}
+
+		fn call_trace(self, gas_used: u64) -> CallFrame {
+			match self.finish(gas_used, false) {
+				TraceResult::CallTracer(root) => root,
+				result => panic!("not a call trace: {:?}", result),
+			}
+		}
+	}
+
+	#[test]
+	fn nested_call_with_value_gets_the_stipend() {
+		let mut replay = Replay::call_tracer();
+		replay.transact(&[1, 2], 100_000);
+		replay.step(CONTRACT, 0, OP_CALL, &[]);
+		replay.call(CONTRACT, CHILD, Some(5.into()), &[3], 40_000);
+		replay.forward(30_000);
+		replay.step(CHILD, 0, OP_STOP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Returned), &[4]);
+		// 32_300 forwarded with the stipend, 12_300 handed back.
+		replay.stipend(12_300);
+		replay.step(CONTRACT, 1, OP_STOP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Returned), &[9]);
+
+		let mut child = call_frame(
+			CallType::Call,
+			CONTRACT,
+			CHILD,
+			Some(5.into()),
+			32_300,
+			20_000,
+		);
+		child.input = vec![3];
+		child.output = vec![4];
+		let mut root = call_frame(
+			CallType::Call,
+			ALICE,
+			CONTRACT,
+			Some(0.into()),
+			100_000,
+			60_000,
+		);
+		root.input = vec![1, 2];
+		root.output = vec![9];
+		root.calls = vec![child];
+		assert_eq!(replay.call_trace(60_000), root);
+	}
+
+	#[test]
+	fn only_top_call_drops_the_children() {
+		let mut replay = Replay::new(TracerConfig::CallTracer {
+			only_top_call: true,
+		});
+		replay.transact(&[], 100_000);
+		replay.step(CONTRACT, 0, OP_CALL, &[]);
+		replay.call(CONTRACT, CHILD, None, &[], 30_000);
+		replay.forward(30_000);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+		replay.stipend(30_000);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		assert!(replay.call_trace(21_000).calls.is_empty());
+	}
+
+	#[test]
+	fn reverting_child_keeps_its_output() {
+		let mut replay = Replay::call_tracer();
+		replay.transact(&[], 100_000);
+		replay.step(CONTRACT, 0, OP_CALL, &[]);
+		replay.call(CONTRACT, CHILD, None, &[], 30_000);
+		replay.forward(30_000);
+		replay.step(CHILD, 0, OP_REVERT, &[]);
+		replay.exit(ExitReason::Revert(ExitRevert::Reverted), &[0xde, 0xad]);
+		replay.stipend(25_000);
+		replay.step(CONTRACT, 1, OP_POP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		let root = replay.call_trace(30_000);
+		assert_eq!(root.error, None);
+		let mut child = call_frame(CallType::Call, CONTRACT, CHILD, None, 30_000, 5_000);
+		child.output = vec![0xde, 0xad];
+		child.error = Some(ExitReason::Revert(ExitRevert::Reverted));
+		assert_eq!(root.calls, vec![child]);
+	}
+
+	#[test]
+	fn precompile_frames_close_on_their_stipend() {
+		let mut replay = Replay::call_tracer();
+		replay.transact(&[], 100_000);
+		replay.step(CONTRACT, 0, OP_CALL, &[]);
+		replay.call(CONTRACT, PRECOMPILE, None, &[7], 10_000);
+		replay.forward(10_000);
+		// The precompile calls back into a contract, which exits normally.
+		replay.precompile_subcall(PRECOMPILE, CHILD, &[8], 4_000);
+		replay.forward(4_000);
+		replay.step(CHILD, 0, OP_STOP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Returned), &[1]);
+		replay.stipend(1_000);
+		// The precompile itself raises no `Exit`, only hands its gas back.
+		replay.stipend(2_000);
+		replay.step(CONTRACT, 1, OP_POP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		let mut subcall = call_frame(CallType::Call, PRECOMPILE, CHILD, None, 4_000, 3_000);
+		subcall.input = vec![8];
+		subcall.output = vec![1];
+		let mut precompile = call_frame(CallType::Call, CONTRACT, PRECOMPILE, None, 10_000, 8_000);
+		precompile.input = vec![7];
+		precompile.calls = vec![subcall];
+		assert_eq!(replay.call_trace(40_000).calls, vec![precompile]);
+	}
+
+	#[test]
+	fn frames_without_exit_close_when_the_parent_resumes() {
+		let mut replay = Replay::call_tracer();
+		replay.transact(&[], 100_000);
+		// Fails before its gas is charged, e.g. too deep.
+		replay.step(CONTRACT, 0, OP_CALL, &[]);
+		replay.call(CONTRACT, CHILD, None, &[], 50_000);
+		replay.step(CONTRACT, 1, OP_POP, &[]);
+		// Charged, then ends without `Exit` nor stipend, e.g. a precompile out of gas.
+		replay.step(CONTRACT, 2, OP_CALL, &[]);
+		replay.call(CONTRACT, PRECOMPILE, None, &[], 10_000);
+		replay.forward(10_000);
+		replay.step(CONTRACT, 3, OP_POP, &[]);
+		// Fails before its gas is charged, right before the root exits.
+		replay.step(CONTRACT, 4, OP_CALL, &[]);
+		replay.call(CONTRACT, OTHER, None, &[], 5_000);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		let unentered = Some(ExitReason::Error(ExitError::Other(
+			"call failed before execution".into(),
+		)));
+		let mut too_deep = call_frame(CallType::Call, CONTRACT, CHILD, None, 50_000, 0);
+		too_deep.error = unentered.clone();
+		let out_of_gas = call_frame(CallType::Call, CONTRACT, PRECOMPILE, None, 10_000, 10_000);
+		let mut last = call_frame(CallType::Call, CONTRACT, OTHER, None, 5_000, 0);
+		last.error = unentered;
+		let root = replay.call_trace(70_000);
+		assert_eq!(root.calls, vec![too_deep, out_of_gas, last]);
+		assert_eq!(root.error, None);
+	}
+
+	#[test]
+	fn create2_and_selfdestruct_frames() {
+		let mut replay = Replay::call_tracer();
+		replay.transact(&[], 200_000);
+		replay.step(CONTRACT, 0, OP_CREATE2, &[]);
+		replay.create2(CONTRACT, OTHER, 7.into(), &[0x60, 0x00]);
+		replay.forward(50_000);
+		replay.step(OTHER, 0, OP_STOP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Returned), &[0xfe]);
+		replay.stipend(40_000);
+		replay.step(CONTRACT, 1, OP_CALL, &[]);
+		replay.call(CONTRACT, CHILD, None, &[], 20_000);
+		replay.forward(20_000);
+		replay.step(CHILD, 0, OP_SELFDESTRUCT, &[]);
+		replay.suicide(CHILD, ALICE, 3.into());
+		replay.exit(ExitReason::Succeed(ExitSucceed::Suicided), &[]);
+		replay.stipend(15_000);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		let mut created = call_frame(
+			CallType::Create2,
+			CONTRACT,
+			OTHER,
+			Some(7.into()),
+			50_000,
+			10_000,
+		);
+		created.input = vec![0x60, 0x00];
+		created.output = vec![0xfe];
+		let mut destroyed = call_frame(CallType::Call, CONTRACT, CHILD, None, 20_000, 5_000);
+		destroyed.calls = vec![call_frame(
+			CallType::SelfDestruct,
+			CHILD,
+			ALICE,
+			Some(3.into()),
+			0,
+			0,
+		)];
+		assert_eq!(replay.call_trace(90_000).calls, vec![created, destroyed]);
+	}
+
+	#[test]
+	fn create2_transaction_opens_the_root() {
+		let mut replay = Replay::call_tracer();
+		replay.0.on_evm_event(EvmEvent::TransactCreate2 {
+			caller: ALICE,
+			value: U256::zero(),
+			init_code: &[0x60, 0x00],
+			salt: H256::repeat_byte(1),
+			gas_limit: 100_000.into(),
+			address: OTHER,
+		});
+		// The executor's own `Create` event belongs to the root frame.
+		replay.create2(ALICE, OTHER, U256::zero(), &[0x60, 0x00]);
+		replay.forward(ROOT_GAS);
+		replay.step(OTHER, 0, OP_STOP, &[]);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Returned), &[0xfe]);
+
+		let mut root = call_frame(
+			CallType::Create2,
+			ALICE,
+			OTHER,
+			Some(0.into()),
+			100_000,
+			60_000,
+		);
+		root.input = vec![0x60, 0x00];
+		root.output = vec![0xfe];
+		assert_eq!(replay.call_trace(60_000), root);
+	}
+
+	#[test]
+	fn struct_logs_take_gas_from_the_snapshot_before_the_cost() {
+		let mut replay = Replay::new(TracerConfig::StructLog {
+			disable_storage: false,
+			disable_memory: true,
+			disable_stack: false,
+		});
+		let slot = H256::repeat_byte(0x51);
+		let value = H256::repeat_byte(0x52);
+		replay.transact(&[], 100_000);
+		replay.step(CONTRACT, 0, OP_PUSH1, &[]);
+		replay.charge(3, snapshot(ROOT_GAS, 0, 0));
+		replay.step(CONTRACT, 2, OP_SLOAD, &[slot]);
+		replay.charge_dynamic(2_100, 0, snapshot(ROOT_GAS, 3, 0));
+		replay.sload(CONTRACT, slot, value);
+		// Expands memory by 6 gas on top of the opcode's 3.
+		replay.step(CONTRACT, 3, OP_MSTORE, &[]);
+		replay.charge_dynamic(3, 9, snapshot(ROOT_GAS, 2_103, 3));
+		// The gas forwarded to the child is not part of the `CALL`'s cost.
+		replay.step(CONTRACT, 4, OP_CALL, &[]);
+		replay.charge_dynamic(100, 9, snapshot(ROOT_GAS, 2_112, 9));
+		replay.call(CONTRACT, CHILD, None, &[], 30_000);
+		replay.forward(30_000);
+		replay.step(CHILD, 0, OP_REVERT, &[]);
+		replay.charge_dynamic(0, 0, snapshot(30_000, 0, 0));
+		replay.exit(ExitReason::Revert(ExitRevert::Reverted), &[]);
+		replay.stipend(29_000);
+		replay.step(CONTRACT, 5, OP_STOP, &[]);
+		replay.charge(0, snapshot(ROOT_GAS, 3_212, 9));
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		let TraceResult::StructLog(trace) = replay.finish(24_212, false) else {
+			panic!("not a struct log trace");
+		};
+		assert_eq!(trace.gas, 24_212);
+		assert!(!trace.failed);
+		assert_eq!(
+			trace
+				.struct_logs
+				.iter()
+				.map(|log| (log.pc, log.op, log.depth, log.gas, log.gas_cost))
+				.collect::<Vec<_>>(),
+			vec![
+				(0, OP_PUSH1, 1, 79_000, 3),
+				(2, OP_SLOAD, 1, 78_997, 2_100),
+				(3, OP_MSTORE, 1, 76_894, 9),
+				(4, OP_CALL, 1, 76_879, 100),
+				(0, OP_REVERT, 2, 30_000, 0),
+				(5, OP_STOP, 1, 75_779, 0),
+			]
+		);
+		let sload = &trace.struct_logs[1];
+		assert_eq!(sload.stack, Some(vec![slot]));
+		assert_eq!(sload.memory, None);
+		assert_eq!(sload.storage, Some([(slot, value)].into_iter().collect()));
+		assert_eq!(
+			trace.struct_logs[4].error,
+			Some(ExitReason::Revert(ExitRevert::Reverted))
+		);
+		assert!(trace
+			.struct_logs
+			.iter()
+			.enumerate()
+			.all(|(i, log)| i == 4 || log.error.is_none()));
+	}
+
+	#[test]
+	fn prestate_covers_every_touched_account_and_slot() {
+		let mut replay = Replay::new(TracerConfig::PrestateTracer);
+		let slots = [1u8, 2, 3].map(H256::repeat_byte);
+		replay.transact(&[], 100_000);
+		// Read by address without being called.
+		replay.step(CONTRACT, 0, OP_BALANCE, &[H256::from(OTHER)]);
+		replay.sload(CONTRACT, slots[0], H256::zero());
+		replay.sstore(CONTRACT, slots[1], H256::repeat_byte(9));
+		replay.step(CONTRACT, 1, OP_CALL, &[]);
+		replay.call(CONTRACT, CHILD, None, &[], 30_000);
+		replay.forward(30_000);
+		replay.sload(CHILD, slots[2], H256::zero());
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+		replay.stipend(20_000);
+		replay.exit(ExitReason::Succeed(ExitSucceed::Stopped), &[]);
+
+		let TraceResult::PrestateTracer(accounts) = replay.finish(40_000, false) else {
+			panic!("not a prestate trace");
+		};
+		let account = |address: H160, slots: &[H256]| {
+			(
+				address,
+				PrestateAccount {
+					balance: U256::from(address.to_low_u64_be()),
+					storage: slots.iter().map(|slot| (*slot, H256::zero())).collect(),
+					..Default::default()
+				},
+			)
+		};
+		assert_eq!(
+			accounts,
+			[
+				account(ALICE, &[]),
+				account(CONTRACT, &slots[..2]),
+				account(CHILD, &slots[2..]),
+				account(OTHER, &[]),
+			]
+			.into_iter()
+			.collect()
+		);
+	}
+}
@@ -0,0 +1,172 @@
+//! Replay of Ethereum transactions under the EVM tracer, backing `fp_evm_tracing::DebugRuntimeApi`.
+//!
+//! Only built with the `evm-tracing` feature, which makes the EVM report every step it
+//! executes. Nodes serving the debug RPC run such a build of the runtime through
+//! `--wasm-runtime-overrides`; the on-chain runtime answers `TraceError::Unsupported`.
+
+use fp_evm_tracing::{tracer::Tracer, PrestateAccount, TraceError, TraceResult, TracerConfig};
+use frame_support::traits::Get;
+use pallet_ethereum::{Call::transact, Receipt};
+use pallet_evm::Runner;
+use sp_core::{H160, H256, U256};
+use sp_runtime::{traits::Block as BlockT, DispatchError};
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+use crate::{Block, Executive, Runtime, RuntimeCall, BLOCK_GAS_LIMIT};
+
+type Extrinsic = <Block as BlockT>::Extrinsic;
+
+/// Hash of the Ethereum transaction carried by `xt`, if any.
+fn ethereum_transaction(xt: &Extrinsic) -> Option<H256> {
+    match &xt.0.function {
+        RuntimeCall::Ethereum(transact { transaction }) => Some(transaction.hash()),
+        _ => None,
+    }
+}
+
+/// Status code and cumulative gas of the last receipt of the block being replayed.
+fn last_receipt() -> Option<(u8, U256)> {
+    pallet_ethereum::Pallet::<Runtime>::last_pending().map(|(_, receipt)| match receipt {
+        Receipt::Legacy(d) | Receipt::EIP2930(d) | Receipt::EIP1559(d) => (d.status_code, d.used_gas),
+    })
+}
+
+fn prestate(address: H160, slots: &BTreeSet<H256>) -> PrestateAccount {
+    let (account, _) = pallet_evm::Pallet::<Runtime>::account_basic(&address);
+    PrestateAccount {
+        balance: account.balance,
+        nonce: account.nonce,
+        code: pallet_evm::AccountCodes::<Runtime>::get(address),
+        storage: slots
+            .iter()
+            .map(|slot| (*slot, pallet_evm::AccountStorages::<Runtime>::get(address, slot)))
+            .collect(),
+    }
+}
+
+/// Apply `xt` under the tracer, leaving the state as a plain `apply_extrinsic` would.
+fn trace_extrinsic(xt: Extrinsic, config: TracerConfig) -> Result<TraceResult, TraceError> {
+    let mut tracer = Tracer::new(config);
+
+    if config == TracerConfig::PrestateTracer {
+        // Collect the touched accounts and slots, then read them as they were before.
+        sp_io::storage::start_transaction();
+        let _ = tracer.trace(|| Executive::apply_extrinsic(xt.clone()));
+        sp_io::storage::rollback_transaction();
+        let result = tracer.into_result(0, false, prestate);
+        Executive::apply_extrinsic(xt).map_err(TraceError::InvalidTransaction)?;
+        return Ok(result);
+    }
+
+    let gas_before = last_receipt().map(|(_, gas)| gas).unwrap_or_default();
+    tracer
+        .trace(|| Executive::apply_extrinsic(xt))
+        .map_err(TraceError::InvalidTransaction)?;
+    let (failed, gas_used) = match last_receipt() {
+        Some((status_code, gas)) => (status_code == 0, gas.saturating_sub(gas_before)),
+        None => (true, U256::zero()),
+    };
+
+    Ok(tracer.into_result(gas_used.low_u64(), failed, |_, _| PrestateAccount::default()))
+}
+
+pub fn trace_transaction(
+    extrinsics: Vec<Extrinsic>,
+    header: &<Block as BlockT>::Header,
+    transaction: H256,
+    config: TracerConfig,
+) -> Result<TraceResult, TraceError> {
+    Executive::initialize_block(header);
+
+    for xt in extrinsics {
+        if ethereum_transaction(&xt) == Some(transaction) {
+            return trace_extrinsic(xt, config);
+        }
+        let _ = Executive::apply_extrinsic(xt);
+    }
+
+    Err(TraceError::TransactionNotFound)
+}
+
+pub fn trace_block(
+    extrinsics: Vec<Extrinsic>,
+    header: &<Block as BlockT>::Header,
+    config: TracerConfig,
+) -> Result<Vec<(H256, TraceResult)>, TraceError> {
+    Executive::initialize_block(header);
+
+    let mut traces = Vec::new();
+    for xt in extrinsics {
+        match ethereum_transaction(&xt) {
+            Some(hash) => traces.push((hash, trace_extrinsic(xt, config)?)),
+            None => {
+                let _ = Executive::apply_extrinsic(xt);
+            }
+        }
+    }
+
+    Ok(traces)
+}
+
+pub fn trace_call(
+    from: H160,
+    to: Option<H160>,
+    data: Vec<u8>,
+    value: U256,
+    gas_limit: U256,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    nonce: Option<U256>,
+    access_list: Option<Vec<(H160, Vec<H256>)>>,
+    config: TracerConfig,
+) -> Result<TraceResult, TraceError> {
+    let gas_limit = gas_limit.min(U256::from(BLOCK_GAS_LIMIT)).low_u64();
+    let evm_config = <Runtime as pallet_evm::Config>::config();
+    let access_list = access_list.unwrap_or_default();
+    let mut tracer = Tracer::new(config);
+
+    // The call runs on a throwaway overlay, roll it back so the prestate tracer reads the
+    // state it started from.
+    sp_io::storage::start_transaction();
+    let outcome: Result<_, DispatchError> = tracer.trace(|| match to {
+        Some(to) => <Runtime as pallet_evm::Config>::Runner::call(
+            from,
+            to,
+            data,
+            value,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            nonce,
+            access_list,
+            false,
+            true,
+            None,
+            None,
+            evm_config,
+        )
+        .map(|info| (info.exit_reason, info.used_gas.effective))
+        .map_err(|err| err.error.into()),
+        None => <Runtime as pallet_evm::Config>::Runner::create(
+            from,
+            data,
+            value,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            nonce,
+            access_list,
+            false,
+            true,
+            None,
+            None,
+            evm_config,
+        )
+        .map(|info| (info.exit_reason, info.used_gas.effective))
+        .map_err(|err| err.error.into()),
+    });
+    sp_io::storage::rollback_transaction();
+
+    let (exit_reason, used_gas) = outcome.map_err(TraceError::Execution)?;
+    Ok(tracer.into_result(used_gas.low_u64(), !exit_reason.is_succeed(), prestate))
+}
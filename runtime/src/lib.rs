@@ -87,13 +87,16 @@ use fp_self_contained;
 //
 use pallet_ethereum::{Call::transact, PostLogContent, Transaction as EthereumTransaction};
 use pallet_evm::{
-    Account as EVMAccount, EnsureAccountId20, EnsureAddressNever, EnsureAddressRoot, FeeCalculator,
+    Account as EVMAccount, EnsureAccountId20, EnsureAddressRoot, FeeCalculator,
     GasWeightMapping, HashedAddressMapping, IdentityAddressMapping, Runner,
 };
 // use account::AccountId20;
 use pallet_base_fee;
 use pallet_dynamic_fee;
+#[cfg(feature = "evm-tracing")]
+mod evm_tracing;
 mod precompiles;
+pub use precompiles::precompile_code;
 use precompiles::FrontierPrecompiles;
 
 ///
@@ -186,10 +189,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     // and set impl_version to 0. If only runtime
     // implementation changes and behavior does not, then leave spec_version as
     // is and increment impl_version.
-    spec_version: 2,
+    spec_version: 4,
     impl_version: 0,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 2,
+    transaction_version: 3,
     state_version: 1,
 };
 
@@ -1670,7 +1673,8 @@ impl pallet_assets::Config<Instance1> for Runtime {
     type StringLimit = StringLimit;
     type Freezer = ();
     type Extra = ();
-    type CallbackHandle = ();
+    type CallbackHandle =
+        pallet_evm_precompile_assets_erc20::Erc20AssetsCallback<Runtime, precompiles::AssetsErc20Prefix>;
     type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
     type RemoveItemsLimit = ConstU32<1000>;
     #[cfg(feature = "runtime-benchmarks")]
@@ -1699,7 +1703,8 @@ impl pallet_assets::Config<Instance2> for Runtime {
     type Extra = ();
     type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
     type RemoveItemsLimit = ConstU32<1000>;
-    type CallbackHandle = ();
+    type CallbackHandle =
+        pallet_evm_precompile_assets_erc20::Erc20AssetsCallback<Runtime, precompiles::PoolAssetsErc20Prefix>;
     #[cfg(feature = "runtime-benchmarks")]
     type BenchmarkHelper = ();
 }
@@ -1898,7 +1903,11 @@ impl pallet_nfts::Config for Runtime {
     type WeightInfo = pallet_nfts::weights::SubstrateWeight<Runtime>;
     #[cfg(feature = "runtime-benchmarks")]
     type Helper = ();
-    type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+    type CreateOrigin = pallet_evm_precompile_nfts_erc721::InsertCodeOnCreate<
+        AsEnsureOriginWithArg<EnsureSigned<AccountId>>,
+        Runtime,
+        precompiles::NftsErc721Prefix,
+    >;
     type Locker = ();
 }
 
@@ -2086,7 +2095,7 @@ impl pallet_base_fee::Config for Runtime {
     type DefaultElasticity = DefaultElasticity;
 }
 impl pallet_hotfix_sufficients::Config for Runtime {
-    type AddressMapping = HashedAddressMapping<BlakeTwo256>;
+    type AddressMapping = pallet_unified_accounts::UnifiedAddressMapping<Runtime>;
     type WeightInfo = pallet_hotfix_sufficients::weights::SubstrateWeight<Self>;
 }
 use pallet_evm::EVMCurrencyAdapter;
@@ -2097,8 +2106,8 @@ impl pallet_evm::Config for Runtime {
     type WeightPerGas = WeightPerGas;
     type BlockHashMapping = pallet_ethereum::EthereumBlockHashMapping<Self>;
     type CallOrigin = EnsureAddressRoot<AccountId>;
-    type WithdrawOrigin = EnsureAddressNever<AccountId>;
-    type AddressMapping = HashedAddressMapping<BlakeTwo256>;
+    type WithdrawOrigin = pallet_unified_accounts::withdraw::EnsureWithdrawOrigin<Runtime>;
+    type AddressMapping = pallet_unified_accounts::UnifiedAddressMapping<Runtime>;
     type Currency = Balances;
     type RuntimeEvent = RuntimeEvent;
     type PrecompilesType = FrontierPrecompiles<Self>;
@@ -2204,6 +2213,8 @@ construct_runtime!(
         DynamicFee: pallet_dynamic_fee,
         BaseFee: pallet_base_fee,
         PalletCounter: pallet_counter::{Pallet, Call, Storage, Event<T>},
+        PrecompileRegistry: pallet_precompile_registry,
+        UnifiedAccounts: pallet_unified_accounts,
 
 
 
@@ -2213,10 +2224,68 @@ construct_runtime!(
 // use pallet_counter::{self, WeightInfo, DefaultWeightInfo};
 pub use pallet_counter;
 
+parameter_types! {
+    pub const MaxIpfsAttestors: u32 = 16;
+    pub const MaxIpfsCidLength: u32 = 128;
+    pub const MaxIpfsTagLength: u32 = 64;
+    pub const MaxMemoLength: u32 = 256;
+    pub const MaxMemoBlockedSubstrings: u32 = 32;
+    pub const MaxMemoBlockedSubstringLength: u32 = 32;
+    pub const MaxCounterLocks: u32 = 16;
+    pub const MaxCounterLockReasonLength: u32 = 64;
+}
+
+/// Total each account reserved through the pre-v2 `PalletCounter::lock` and did not get back
+/// through `unlock`, summed from the pallet's `Locked` and `Unlocked` events up to the upgrade
+/// block. Only accounts whose `LockedBalance` record differs from that total are listed, see
+/// `pallet_counter::migrations::v2::MigrateLockedBalance`.
+const LEGACY_COUNTER_RESERVES: &[([u8; 32], Balance)] = &[];
+
+pub struct LegacyCounterReserves;
+
+impl Get<Vec<(AccountId, Balance)>> for LegacyCounterReserves {
+    fn get() -> Vec<(AccountId, Balance)> {
+        LEGACY_COUNTER_RESERVES
+            .iter()
+            .map(|(who, reserved)| (AccountId::from(*who), *reserved))
+            .collect()
+    }
+}
+
 impl pallet_counter::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type SubstrateCurrency = Balances; 
     type EvmCurrency = Balances; 
+    type AttestorOrigin = EnsureRootOrHalfCouncil;
+    type MaxAttestors = MaxIpfsAttestors;
+    type MaxCidLength = MaxIpfsCidLength;
+    type MaxIpfsTagLength = MaxIpfsTagLength;
+    type BridgeGuardOrigin = EnsureRootOrHalfCouncil;
+    type MaxLocksPerAccount = MaxCounterLocks;
+    type MaxLockReasonLength = MaxCounterLockReasonLength;
+    type MemoPolicyOrigin = EnsureRootOrHalfCouncil;
+    type MaxMemoLength = MaxMemoLength;
+    type MaxBlockedSubstrings = MaxMemoBlockedSubstrings;
+    type MaxBlockedSubstringLength = MaxMemoBlockedSubstringLength;
+    type WeightInfo = pallet_counter::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_precompile_registry::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type PrecompileId = precompiles::PrecompileId;
+    type RegistryOrigin = EnsureRootOrHalfCouncil;
+    type PrecompileAddresses = precompiles::PrecompileAddresses;
+    type DefaultPrecompiles = precompiles::DefaultPrecompiles;
+    type WeightInfo = pallet_precompile_registry::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_unified_accounts::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Currency = Balances;
+    type DefaultMapping = HashedAddressMapping<BlakeTwo256>;
+    type WeightInfo = pallet_unified_accounts::weights::SubstrateWeight<Runtime>;
 }
 
 
@@ -2226,6 +2295,17 @@ type Migrations = (
     pallet_nomination_pools::migration::v2::MigrateToV2<Runtime>,
     pallet_alliance::migration::Migration<Runtime>,
     pallet_contracts::Migration<Runtime>,
+    pallet_counter::migrations::v1::SeedLegacyAttestor<Runtime>,
+    pallet_counter::migrations::v2::MigrateLockedBalance<Runtime, LegacyCounterReserves>,
+    pallet_precompile_registry::migrations::v1::SeedDefaults<Runtime>,
+    pallet_evm_precompile_assets_erc20::InsertAssetCode<Runtime, Instance1, precompiles::AssetsErc20Prefix>,
+    pallet_evm_precompile_assets_erc20::InsertAssetCode<Runtime, Instance2, precompiles::PoolAssetsErc20Prefix>,
+    pallet_evm_precompile_nfts_erc721::InsertCollectionCode<Runtime, precompiles::NftsErc721Prefix>,
+    precompile_utils::InsertRevertCode<Runtime, precompiles::CounterBridgeAddress>,
+    precompile_utils::InsertRevertCode<Runtime, precompiles::DispatchAddress>,
+    precompile_utils::InsertRevertCode<Runtime, precompiles::NativeErc20Address>,
+    precompile_utils::InsertRevertCode<Runtime, precompiles::StakingAddress>,
+    precompile_utils::InsertRevertCode<Runtime, precompiles::GovernanceAddress>,
 );
 
 type EventRecord = frame_system::EventRecord<
@@ -2277,6 +2357,9 @@ mod benches {
         [pallet_multisig, Multisig]
         [pallet_nomination_pools, NominationPoolsBench::<Runtime>]
         [pallet_offences, OffencesBench::<Runtime>]
+        [pallet_counter, PalletCounter]
+        [pallet_precompile_registry, PrecompileRegistry]
+        [pallet_unified_accounts, UnifiedAccounts]
         [pallet_preimage, Preimage]
         [pallet_proxy, Proxy]
         [pallet_ranked_collective, RankedCollective]
@@ -2435,6 +2518,123 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_counter_runtime_api::PalletCounterApi<Block, AccountId, Balance, BlockNumber> for Runtime {
+        fn ipfs_record(
+            cid: Vec<u8>,
+        ) -> Option<pallet_counter_runtime_api::IpfsRecord<AccountId, BlockNumber, Vec<u8>>> {
+            PalletCounter::ipfs_record(&cid).map(|record| record.map_tag(|tag| tag.into_inner()))
+        }
+
+        fn ipfs_hashes_of(
+            who: AccountId,
+        ) -> Vec<(Vec<u8>, pallet_counter_runtime_api::IpfsRecord<AccountId, BlockNumber, Vec<u8>>)> {
+            PalletCounter::ipfs_hashes_of(&who)
+                .into_iter()
+                .map(|(cid, record)| (cid.into_inner(), record.map_tag(|tag| tag.into_inner())))
+                .collect()
+        }
+
+        fn memo_policy() -> pallet_counter_runtime_api::MemoPolicy<Vec<Vec<u8>>> {
+            PalletCounter::memo_policy().map_substrings(|blocked| {
+                blocked.into_iter().map(|substring| substring.into_inner()).collect()
+            })
+        }
+
+        fn validate_memo(memo: Vec<u8>) -> Result<(), pallet_counter_runtime_api::MemoError> {
+            PalletCounter::validate_memo(&memo)
+        }
+
+        fn locks_of(
+            who: AccountId,
+        ) -> Vec<(pallet_counter_runtime_api::CounterLock<Balance, BlockNumber, Vec<u8>>, Balance)> {
+            let now = System::block_number();
+            PalletCounter::locks(&who)
+                .into_iter()
+                .map(|lock| {
+                    let releasable = lock.releasable(now);
+                    (lock.map_reason(|reason| reason.into_inner()), releasable)
+                })
+                .collect()
+        }
+
+        fn evm_balance(address: H160) -> U256 {
+            PalletCounter::evm_balance(address)
+        }
+
+        fn locked_balance(who: AccountId) -> Balance {
+            PalletCounter::locked_balance(&who)
+        }
+
+        fn bridge_nonce(address: H160) -> U256 {
+            PalletCounter::evm_nonce(address)
+        }
+
+        fn evm_to_substrate_payload(
+            from: H160,
+            to: AccountId,
+            amount: U256,
+            deadline: BlockNumber,
+        ) -> Option<pallet_counter_runtime_api::EvmToSubstratePayload> {
+            PalletCounter::evm_to_substrate_payload(from, &to, amount, deadline).ok()
+        }
+
+        fn bridge_fee(direction: pallet_counter_runtime_api::BridgeDirection) -> Balance {
+            use pallet_counter_runtime_api::BridgeDirection;
+
+            let call = match direction {
+                BridgeDirection::SubstrateToEvm => {
+                    RuntimeCall::PalletCounter(pallet_counter::Call::substrate_to_evm {
+                        evm_address: H160::zero(),
+                        amount: 0,
+                        add: true,
+                    })
+                },
+                BridgeDirection::EvmToSubstrate => {
+                    RuntimeCall::PalletCounter(pallet_counter::Call::evm_to_substrate {
+                        evm_address: H160::zero(),
+                        dest: AccountId::new([0u8; 32]),
+                        amount: U256::zero(),
+                        deadline: 0,
+                        signature: sp_core::ecdsa::Signature::from_raw([0u8; 65]),
+                    })
+                },
+            };
+            // Priced as wallets submit it: signed by an sr25519 account, mortal, no tip.
+            let extra: SignedExtra = (
+                frame_system::CheckNonZeroSender::<Runtime>::new(),
+                frame_system::CheckSpecVersion::<Runtime>::new(),
+                frame_system::CheckTxVersion::<Runtime>::new(),
+                frame_system::CheckGenesis::<Runtime>::new(),
+                frame_system::CheckEra::<Runtime>::from(Era::mortal(BlockHashCount::get().into(), 0)),
+                frame_system::CheckNonce::<Runtime>::from(0),
+                frame_system::CheckWeight::<Runtime>::new(),
+                pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            );
+            let uxt = UncheckedExtrinsic::new_signed(
+                call,
+                Address::Id(AccountId::new([0u8; 32])),
+                sp_runtime::MultiSignature::Sr25519(sp_core::sr25519::Signature::from_raw([0u8; 64])),
+                extra,
+            );
+            let len = uxt.encoded_size() as u32;
+            TransactionPayment::query_info(uxt, len).partial_fee
+        }
+    }
+
+    impl pallet_unified_accounts_runtime_api::UnifiedAccountsApi<Block, AccountId> for Runtime {
+        fn native_account(address: H160) -> AccountId {
+            UnifiedAccounts::native_account(address)
+        }
+
+        fn evm_address(account: AccountId) -> Option<H160> {
+            UnifiedAccounts::linked_evm_address(account)
+        }
+
+        fn link_message(account: AccountId, address: H160) -> Option<Vec<u8>> {
+            UnifiedAccounts::link_message(&account, address)
+        }
+    }
+
     impl sp_consensus_babe::BabeApi<Block> for Runtime {
         fn configuration() -> sp_consensus_babe::BabeConfiguration {
             let epoch_config = Babe::epoch_config().unwrap_or(BABE_GENESIS_EPOCH_CONFIG);
@@ -2763,6 +2963,90 @@ impl_runtime_apis! {
         }
     }
 
+    impl fp_evm_tracing::DebugRuntimeApi<Block> for Runtime {
+        fn trace_transaction(
+            extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+            header: &<Block as BlockT>::Header,
+            transaction: H256,
+            tracer: fp_evm_tracing::TracerConfig,
+        ) -> Result<fp_evm_tracing::TraceResult, fp_evm_tracing::TraceError> {
+            #[cfg(feature = "evm-tracing")]
+            {
+                evm_tracing::trace_transaction(extrinsics, header, transaction, tracer)
+            }
+            #[cfg(not(feature = "evm-tracing"))]
+            {
+                let _ = (extrinsics, header, transaction, tracer);
+                Err(fp_evm_tracing::TraceError::Unsupported)
+            }
+        }
+
+        fn trace_block(
+            extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+            header: &<Block as BlockT>::Header,
+            tracer: fp_evm_tracing::TracerConfig,
+        ) -> Result<Vec<(H256, fp_evm_tracing::TraceResult)>, fp_evm_tracing::TraceError> {
+            #[cfg(feature = "evm-tracing")]
+            {
+                evm_tracing::trace_block(extrinsics, header, tracer)
+            }
+            #[cfg(not(feature = "evm-tracing"))]
+            {
+                let _ = (extrinsics, header, tracer);
+                Err(fp_evm_tracing::TraceError::Unsupported)
+            }
+        }
+
+        fn trace_call(
+            from: H160,
+            to: Option<H160>,
+            data: Vec<u8>,
+            value: U256,
+            gas_limit: U256,
+            max_fee_per_gas: Option<U256>,
+            max_priority_fee_per_gas: Option<U256>,
+            nonce: Option<U256>,
+            access_list: Option<Vec<(H160, Vec<H256>)>>,
+            tracer: fp_evm_tracing::TracerConfig,
+        ) -> Result<fp_evm_tracing::TraceResult, fp_evm_tracing::TraceError> {
+            #[cfg(feature = "evm-tracing")]
+            {
+                evm_tracing::trace_call(
+                    from,
+                    to,
+                    data,
+                    value,
+                    gas_limit,
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                    nonce,
+                    access_list,
+                    tracer,
+                )
+            }
+            #[cfg(not(feature = "evm-tracing"))]
+            {
+                let _ = (
+                    from,
+                    to,
+                    data,
+                    value,
+                    gas_limit,
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                    nonce,
+                    access_list,
+                    tracer,
+                );
+                Err(fp_evm_tracing::TraceError::Unsupported)
+            }
+        }
+
+        fn is_precompile(address: H160) -> bool {
+            precompiles::is_precompile(address)
+        }
+    }
+
     impl assets_api::AssetsApi<
         Block,
         AccountId,
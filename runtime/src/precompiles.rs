@@ -1,61 +1,795 @@
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+    dispatch::{DispatchClass, GetDispatchInfo, Pays},
+    instances::{Instance1, Instance2},
+    parameter_types,
+    traits::{Contains, Get},
+};
 use pallet_evm::{
-    IsPrecompileResult, Precompile, PrecompileHandle, PrecompileResult, PrecompileSet,
+    ExitError, GasWeightMapping, IsPrecompileResult, Precompile, PrecompileFailure,
+    PrecompileHandle, PrecompileResult, PrecompileSet,
 };
+use pallet_precompile_registry::PrecompileStatus;
+use scale_info::TypeInfo;
 use sp_core::H160;
-use sp_std::marker::PhantomData;
+use sp_runtime::{traits::Convert, RuntimeDebug};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+use crate::{AccountId, OriginCaller, RuntimeCall};
 
+use pallet_evm_precompile_assets_erc20::Erc20AssetsPrecompileSet;
+use pallet_evm_precompile_balances_erc20::{Erc20BalancesPrecompile, Erc20Metadata};
+use pallet_evm_precompile_blake2::Blake2F;
+use pallet_evm_precompile_bls12377::{
+    Bls12377G1Add, Bls12377G1Mul, Bls12377G1MultiExp, Bls12377G2Add, Bls12377G2Mul,
+    Bls12377G2MultiExp, Bls12377Pairing,
+};
+use pallet_evm_precompile_bn128::{Bn128Add, Bn128Mul, Bn128Pairing};
+use pallet_evm_precompile_bw6761::{
+    Bw6761G1Add, Bw6761G1Mul, Bw6761G1MultiExp, Bw6761G2Add, Bw6761G2Mul, Bw6761G2MultiExp,
+    Bw6761Pairing,
+};
+use pallet_evm_precompile_counter_bridge::CounterBridgePrecompile;
+use pallet_evm_precompile_curve25519::{Curve25519Add, Curve25519ScalarMul};
+use pallet_evm_precompile_dispatch::{Dispatch, DispatchValidateT};
+use pallet_evm_precompile_ed25519::Ed25519Verify;
+use pallet_evm_precompile_governance::GovernancePrecompile;
 use pallet_evm_precompile_modexp::Modexp;
+use pallet_evm_precompile_nfts_erc721::{AccountAddress, Erc721NftsPrecompileSet};
 use pallet_evm_precompile_sha3fips::Sha3FIPS256;
 use pallet_evm_precompile_simple::{ECRecover, ECRecoverPublicKey, Identity, Ripemd160, Sha256};
+use pallet_evm_precompile_staking::StakingPrecompile;
+
+/// Every precompile the runtime knows about.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum PrecompileId {
+    ECRecover,
+    Sha256,
+    Ripemd160,
+    Identity,
+    Modexp,
+    Bn128Add,
+    Bn128Mul,
+    Bn128Pairing,
+    Blake2F,
+    Sha3FIPS256,
+    ECRecoverPublicKey,
+    Ed25519Verify,
+    Curve25519Add,
+    Curve25519ScalarMul,
+    Bls12377G1Add,
+    Bls12377G1Mul,
+    Bls12377G1MultiExp,
+    Bls12377G2Add,
+    Bls12377G2Mul,
+    Bls12377G2MultiExp,
+    Bls12377Pairing,
+    Bw6761G1Add,
+    Bw6761G1Mul,
+    Bw6761G1MultiExp,
+    Bw6761G2Add,
+    Bw6761G2Mul,
+    Bw6761G2MultiExp,
+    Bw6761Pairing,
+    CounterBridge,
+    Dispatch,
+    NativeErc20,
+    Staking,
+    Governance,
+}
+
+/// Default address of every precompile, registered in `PrecompileRegistry` at genesis.
+pub const PRECOMPILES: [(u64, PrecompileId); 33] = [
+    // Ethereum precompiles :
+    (1, PrecompileId::ECRecover),
+    (2, PrecompileId::Sha256),
+    (3, PrecompileId::Ripemd160),
+    (4, PrecompileId::Identity),
+    (5, PrecompileId::Modexp),
+    (6, PrecompileId::Bn128Add),
+    (7, PrecompileId::Bn128Mul),
+    (8, PrecompileId::Bn128Pairing),
+    (9, PrecompileId::Blake2F),
+    // Non-Frontier specific nor Ethereum precompiles :
+    (1024, PrecompileId::Sha3FIPS256),
+    (1025, PrecompileId::ECRecoverPublicKey),
+    (1026, PrecompileId::Ed25519Verify),
+    (1027, PrecompileId::Curve25519Add),
+    (1028, PrecompileId::Curve25519ScalarMul),
+    (1029, PrecompileId::Bls12377G1Add),
+    (1030, PrecompileId::Bls12377G1Mul),
+    (1031, PrecompileId::Bls12377G1MultiExp),
+    (1032, PrecompileId::Bls12377G2Add),
+    (1033, PrecompileId::Bls12377G2Mul),
+    (1034, PrecompileId::Bls12377G2MultiExp),
+    (1035, PrecompileId::Bls12377Pairing),
+    (1036, PrecompileId::Bw6761G1Add),
+    (1037, PrecompileId::Bw6761G1Mul),
+    (1038, PrecompileId::Bw6761G1MultiExp),
+    (1039, PrecompileId::Bw6761G2Add),
+    (1040, PrecompileId::Bw6761G2Mul),
+    (1041, PrecompileId::Bw6761G2MultiExp),
+    (1042, PrecompileId::Bw6761Pairing),
+    (1043, PrecompileId::Dispatch),
+    // Argochain specific precompiles :
+    (2048, PrecompileId::CounterBridge),
+    (2049, PrecompileId::NativeErc20),
+    (2050, PrecompileId::Staking),
+    (2051, PrecompileId::Governance),
+];
 
+/// Addresses `PrecompileRegistry` may hold: the lowest 2^16, where [`PRECOMPILES`] live.
+pub struct PrecompileAddresses;
+impl Contains<H160> for PrecompileAddresses {
+    fn contains(address: &H160) -> bool {
+        address.as_bytes()[..18].iter().all(|byte| *byte == 0)
+    }
+}
+
+/// [`PRECOMPILES`], as seeded into `PrecompileRegistry`.
+pub struct DefaultPrecompiles;
+impl Get<Vec<(H160, PrecompileId)>> for DefaultPrecompiles {
+    fn get() -> Vec<(H160, PrecompileId)> {
+        PRECOMPILES.iter().map(|(address, id)| (hash(*address), *id)).collect()
+    }
+}
+
+parameter_types! {
+    /// Address prefix of the ERC-20 precompiles of `Assets`.
+    pub const AssetsErc20Prefix: u32 = 0xFFFF_FFFF;
+    /// Address prefix of the ERC-20 precompiles of `PoolAssets`.
+    pub const PoolAssetsErc20Prefix: u32 = 0xFFFF_FFFE;
+    /// Address prefix of the ERC-721 precompiles of `Nfts` collections.
+    pub const NftsErc721Prefix: u32 = 0xFFFF_FFFD;
+    /// Default address of the `pallet_counter` bridge precompile.
+    pub CounterBridgeAddress: H160 = hash(2048);
+    /// Default address of the dispatch precompile.
+    pub DispatchAddress: H160 = hash(1043);
+    /// Default address of the native ERC-20 precompile.
+    pub NativeErc20Address: H160 = hash(2049);
+    /// Default address of the staking precompile.
+    pub StakingAddress: H160 = hash(2050);
+    /// Default address of the governance precompile.
+    pub GovernanceAddress: H160 = hash(2051);
+}
+
+/// Code of the precompiles contracts call through typed interfaces, whose `extcodesize`
+/// check reverts on addresses without code. Stored at genesis, and on existing chains by
+/// the `InsertRevertCode` migrations.
+pub fn precompile_code() -> Vec<(H160, Vec<u8>)> {
+    sp_std::vec![
+        (CounterBridgeAddress::get(), precompile_utils::REVERT_CODE.to_vec()),
+        (DispatchAddress::get(), precompile_utils::REVERT_CODE.to_vec()),
+        (NativeErc20Address::get(), precompile_utils::REVERT_CODE.to_vec()),
+        (StakingAddress::get(), precompile_utils::REVERT_CODE.to_vec()),
+        (GovernanceAddress::get(), precompile_utils::REVERT_CODE.to_vec()),
+    ]
+}
+
+/// Whether the precompile set of the runtime answers for `address`.
+pub fn is_precompile(address: H160) -> bool {
+    matches!(
+        crate::PrecompilesValue::get().is_precompile(address, u64::MAX),
+        IsPrecompileResult::Answer { is_precompile: true, .. }
+    )
+}
+
+/// ERC-20 precompiles of the `Assets` instance.
+pub type AssetsErc20<R> = Erc20AssetsPrecompileSet<R, Instance1, AssetsErc20Prefix>;
+/// ERC-20 precompiles of the `PoolAssets` instance.
+pub type PoolAssetsErc20<R> = Erc20AssetsPrecompileSet<R, Instance2, PoolAssetsErc20Prefix>;
+/// ERC-721 precompiles of the `Nfts` collections.
+pub type NftsErc721<R> = Erc721NftsPrecompileSet<R, NftsErc721Prefix, LinkedEvmAddresses>;
+
+/// Precompiles registered in `PrecompileRegistry`; both `execute` and `is_precompile` are
+/// answered from it for [`PrecompileAddresses`], charging the registry read. Other addresses
+/// fall through to the asset ERC-20 and collection ERC-721 sets.
 pub struct FrontierPrecompiles<R>(PhantomData<R>);
 
 impl<R> FrontierPrecompiles<R>
 where
-    R: pallet_evm::Config,
+    R: pallet_evm::Config
+        + pallet_counter::Config
+        + pallet_precompile_registry::Config<PrecompileId = PrecompileId>,
+    R::AccountId: From<[u8; 32]>,
 {
     pub fn new() -> Self {
         Self(Default::default())
     }
-    pub fn used_addresses() -> [H160; 7] {
-        [
-            hash(1),
-            hash(2),
-            hash(3),
-            hash(4),
-            hash(5),
-            hash(1024),
-            hash(1025),
-        ]
+    /// Precompile registered at `address`, unless it is disabled.
+    ///
+    /// Only reads the registry for [`PrecompileAddresses`], see [`Self::registry_read_cost`].
+    pub fn precompile_at(address: H160) -> Option<(PrecompileId, PrecompileStatus)> {
+        if !PrecompileAddresses::contains(&address) {
+            return None;
+        }
+        pallet_precompile_registry::Pallet::<R>::precompile(address)
+            .filter(|entry| entry.status != PrecompileStatus::Disabled)
+            .map(|entry| (entry.id, entry.status))
+    }
+
+    /// Gas charged for looking `address` up in the registry. Addresses outside of
+    /// [`PrecompileAddresses`] are not looked up, so calls to contracts and accounts, which
+    /// may carry no more than the 2300 gas stipend, do not pay for it.
+    fn registry_read_cost(address: H160) -> u64 {
+        if PrecompileAddresses::contains(&address) {
+            R::GasWeightMapping::weight_to_gas(R::DbWeight::get().reads(1))
+        } else {
+            0
+        }
     }
 }
 impl<R> PrecompileSet for FrontierPrecompiles<R>
 where
-    R: pallet_evm::Config,
+    R: pallet_evm::Config
+        + pallet_counter::Config
+        + pallet_precompile_registry::Config<PrecompileId = PrecompileId>,
+    R::AccountId: From<[u8; 32]>,
+    DirectDispatch<R>: Precompile,
+    StakingPrecompile<R>: Precompile,
+    GovernancePrecompile<R, GovernanceTrackOrigins>: Precompile,
+    AssetsErc20<R>: PrecompileSet,
+    PoolAssetsErc20<R>: PrecompileSet,
+    NftsErc721<R>: PrecompileSet,
 {
     fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
-        match handle.code_address() {
-            // Ethereum precompiles :
-            a if a == hash(1) => Some(ECRecover::execute(handle)),
-            a if a == hash(2) => Some(Sha256::execute(handle)),
-            a if a == hash(3) => Some(Ripemd160::execute(handle)),
-            a if a == hash(4) => Some(Identity::execute(handle)),
-            a if a == hash(5) => Some(Modexp::execute(handle)),
-            // Non-Frontier specific nor Ethereum precompiles :
-            a if a == hash(1024) => Some(Sha3FIPS256::execute(handle)),
-            a if a == hash(1025) => Some(ECRecoverPublicKey::execute(handle)),
+        let address = handle.code_address();
+        if let Err(error) = handle.record_cost(Self::registry_read_cost(address)) {
+            return Some(Err(error.into()));
+        }
+        let id = match Self::precompile_at(address) {
+            Some((id, PrecompileStatus::Active)) => id,
+            Some(_) => {
+                return Some(Err(PrecompileFailure::Error {
+                    exit_status: ExitError::Other("precompile disabled".into()),
+                }))
+            }
+            None => {
+                return AssetsErc20::<R>::new()
+                    .execute(handle)
+                    .or_else(|| PoolAssetsErc20::<R>::new().execute(handle))
+                    .or_else(|| NftsErc721::<R>::new().execute(handle))
+            }
+        };
+        let result = match id {
+            PrecompileId::ECRecover => ECRecover::execute(handle),
+            PrecompileId::Sha256 => Sha256::execute(handle),
+            PrecompileId::Ripemd160 => Ripemd160::execute(handle),
+            PrecompileId::Identity => Identity::execute(handle),
+            PrecompileId::Modexp => Modexp::execute(handle),
+            PrecompileId::Bn128Add => Bn128Add::execute(handle),
+            PrecompileId::Bn128Mul => Bn128Mul::execute(handle),
+            PrecompileId::Bn128Pairing => Bn128Pairing::execute(handle),
+            PrecompileId::Blake2F => Blake2F::execute(handle),
+            PrecompileId::Sha3FIPS256 => Sha3FIPS256::execute(handle),
+            PrecompileId::ECRecoverPublicKey => ECRecoverPublicKey::execute(handle),
+            PrecompileId::Ed25519Verify => Ed25519Verify::execute(handle),
+            PrecompileId::Curve25519Add => Curve25519Add::execute(handle),
+            PrecompileId::Curve25519ScalarMul => Curve25519ScalarMul::execute(handle),
+            PrecompileId::Bls12377G1Add => Bls12377G1Add::execute(handle),
+            PrecompileId::Bls12377G1Mul => Bls12377G1Mul::execute(handle),
+            PrecompileId::Bls12377G1MultiExp => Bls12377G1MultiExp::execute(handle),
+            PrecompileId::Bls12377G2Add => Bls12377G2Add::execute(handle),
+            PrecompileId::Bls12377G2Mul => Bls12377G2Mul::execute(handle),
+            PrecompileId::Bls12377G2MultiExp => Bls12377G2MultiExp::execute(handle),
+            PrecompileId::Bls12377Pairing => Bls12377Pairing::execute(handle),
+            PrecompileId::Bw6761G1Add => Bw6761G1Add::execute(handle),
+            PrecompileId::Bw6761G1Mul => Bw6761G1Mul::execute(handle),
+            PrecompileId::Bw6761G1MultiExp => Bw6761G1MultiExp::execute(handle),
+            PrecompileId::Bw6761G2Add => Bw6761G2Add::execute(handle),
+            PrecompileId::Bw6761G2Mul => Bw6761G2Mul::execute(handle),
+            PrecompileId::Bw6761G2MultiExp => Bw6761G2MultiExp::execute(handle),
+            PrecompileId::Bw6761Pairing => Bw6761Pairing::execute(handle),
+            PrecompileId::CounterBridge => CounterBridgePrecompile::<R>::execute(handle),
+            PrecompileId::Dispatch => DirectDispatch::<R>::execute(handle),
+            PrecompileId::NativeErc20 => {
+                Erc20BalancesPrecompile::<R, NativeErc20Metadata>::execute(handle)
+            }
+            PrecompileId::Staking => StakingPrecompile::<R>::execute(handle),
+            PrecompileId::Governance => {
+                GovernancePrecompile::<R, GovernanceTrackOrigins>::execute(handle)
+            }
+        };
+        Some(result)
+    }
+
+    fn is_precompile(&self, address: H160, gas: u64) -> IsPrecompileResult {
+        if PrecompileAddresses::contains(&address) {
+            return IsPrecompileResult::Answer {
+                is_precompile: Self::precompile_at(address).is_some(),
+                extra_cost: Self::registry_read_cost(address),
+            };
+        }
+        // Only addresses under an asset or collection prefix pay for their lookup.
+        let answer = AssetsErc20::<R>::new().is_precompile(address, gas);
+        let answer = or_else(answer, || PoolAssetsErc20::<R>::new().is_precompile(address, gas));
+        or_else(answer, || NftsErc721::<R>::new().is_precompile(address, gas))
+    }
+}
+
+/// `first`, unless it rejects the address, in which case `next` is asked as well.
+fn or_else(
+    first: IsPrecompileResult,
+    next: impl FnOnce() -> IsPrecompileResult,
+) -> IsPrecompileResult {
+    match first {
+        IsPrecompileResult::Answer { is_precompile: false, extra_cost } => match next() {
+            IsPrecompileResult::Answer { is_precompile, extra_cost: next_cost } => {
+                IsPrecompileResult::Answer {
+                    is_precompile,
+                    extra_cost: extra_cost.saturating_add(next_cost),
+                }
+            }
+            other => other,
+        },
+        answer => answer,
+    }
+}
+
+/// EVM addresses of accounts linked through `pallet_unified_accounts`.
+pub struct LinkedEvmAddresses;
+
+impl AccountAddress<AccountId> for LinkedEvmAddresses {
+    fn address_of(account: &AccountId) -> Option<H160> {
+        pallet_unified_accounts::NativeToEvm::<crate::Runtime>::get(account)
+    }
+}
+
+/// Metadata of the native token as seen through its ERC-20 precompile.
+pub struct NativeErc20Metadata;
+
+impl Erc20Metadata for NativeErc20Metadata {
+    fn name() -> &'static str {
+        "Argochain"
+    }
+    fn symbol() -> &'static str {
+        "AGC"
+    }
+    fn decimals() -> u8 {
+        18
+    }
+}
+
+/// Origin that proposals submitted through the governance precompile on a `Referenda`
+/// track run with; the inverse of `TracksInfo::track_for`.
+pub struct GovernanceTrackOrigins;
+
+impl Convert<u16, Option<OriginCaller>> for GovernanceTrackOrigins {
+    fn convert(track: u16) -> Option<OriginCaller> {
+        match track {
+            0 => Some(frame_system::RawOrigin::Root.into()),
             _ => None,
         }
     }
+}
+
+/// The dispatch precompile, refusing `DELEGATECALL` and `CALLCODE`: through those the call
+/// would be dispatched for whoever called the delegating contract.
+pub struct DirectDispatch<R>(PhantomData<R>);
+
+impl<R> Precompile for DirectDispatch<R>
+where
+    Dispatch<R, DispatchCallFilter>: Precompile,
+{
+    fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+        precompile_utils::ensure_direct_call(handle)?;
+        Dispatch::<R, DispatchCallFilter>::execute(handle)
+    }
+}
+
+/// Calls the dispatch precompile may run on behalf of an EVM caller.
+///
+/// Only calls paying a normal-class fee are admitted, so their weight is charged as gas, and
+/// only calls acting on the caller's own staking, pool membership, votes or identity. Batches
+/// are admitted when every inner call is.
+pub struct DispatchCallFilter;
+
+impl DispatchCallFilter {
+    fn is_allowed(call: &RuntimeCall) -> bool {
+        use pallet_conviction_voting::Call as ConvictionVotingCall;
+        use pallet_identity::Call as IdentityCall;
+        use pallet_nomination_pools::Call as NominationPoolsCall;
+        use pallet_staking::Call as StakingCall;
+
+        match call {
+            RuntimeCall::Utility(pallet_utility::Call::batch { calls })
+            | RuntimeCall::Utility(pallet_utility::Call::batch_all { calls })
+            | RuntimeCall::Utility(pallet_utility::Call::force_batch { calls }) => {
+                calls.iter().all(Self::is_allowed)
+            }
+            RuntimeCall::Staking(
+                StakingCall::bond { .. }
+                | StakingCall::bond_extra { .. }
+                | StakingCall::unbond { .. }
+                | StakingCall::rebond { .. }
+                | StakingCall::withdraw_unbonded { .. }
+                | StakingCall::nominate { .. }
+                | StakingCall::chill { .. }
+                | StakingCall::set_payee { .. }
+                | StakingCall::payout_stakers { .. },
+            ) => true,
+            RuntimeCall::NominationPools(
+                NominationPoolsCall::join { .. }
+                | NominationPoolsCall::bond_extra { .. }
+                | NominationPoolsCall::claim_payout { .. }
+                | NominationPoolsCall::unbond { .. }
+                | NominationPoolsCall::withdraw_unbonded { .. },
+            ) => true,
+            RuntimeCall::ConvictionVoting(
+                ConvictionVotingCall::vote { .. }
+                | ConvictionVotingCall::remove_vote { .. }
+                | ConvictionVotingCall::delegate { .. }
+                | ConvictionVotingCall::undelegate { .. }
+                | ConvictionVotingCall::unlock { .. },
+            ) => true,
+            RuntimeCall::Identity(
+                IdentityCall::set_identity { .. }
+                | IdentityCall::clear_identity { .. }
+                | IdentityCall::request_judgement { .. }
+                | IdentityCall::cancel_request { .. },
+            ) => true,
+            _ => false,
+        }
+    }
+}
 
-    fn is_precompile(&self, address: H160, _gas: u64) -> IsPrecompileResult {
-        IsPrecompileResult::Answer {
-            is_precompile: Self::used_addresses().contains(&address),
-            extra_cost: 0,
+impl DispatchValidateT<AccountId, RuntimeCall> for DispatchCallFilter {
+    fn validate_before_dispatch(
+        _origin: &AccountId,
+        call: &RuntimeCall,
+    ) -> Option<PrecompileFailure> {
+        let info = call.get_dispatch_info();
+        if info.class != DispatchClass::Normal || info.pays_fee != Pays::Yes {
+            return Some(PrecompileFailure::Error {
+                exit_status: ExitError::Other("invalid call".into()),
+            });
+        }
+        if !Self::is_allowed(call) {
+            return Some(PrecompileFailure::Error {
+                exit_status: ExitError::Other("call not allowed".into()),
+            });
         }
+        None
     }
 }
 
 fn hash(a: u64) -> H160 {
     H160::from_low_u64_be(a)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrecompileRegistry, Runtime};
+    use pallet_evm::Context;
+    use pallet_evm_test_vector_support::{
+        test_precompile_set_failure_test_vectors, test_precompile_set_test_vectors, MockHandle,
+    };
+    use sp_runtime::BuildStorage;
+
+    const TESTDATA: &str = "../frame/evm/precompile/testdata";
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        pallet_precompile_registry::GenesisConfig::<Runtime>::default()
+            .build_storage()
+            .unwrap()
+            .into()
+    }
+
+    fn run(address: u64, file: &str) -> Result<(), String> {
+        new_test_ext().execute_with(|| {
+            test_precompile_set_test_vectors(
+                &FrontierPrecompiles::<Runtime>::new(),
+                hash(address),
+                FrontierPrecompiles::<Runtime>::registry_read_cost(hash(address)),
+                &format!("{}/{}", TESTDATA, file),
+            )
+        })
+    }
+
+    fn run_failures(address: u64, file: &str) -> Result<(), String> {
+        new_test_ext().execute_with(|| {
+            test_precompile_set_failure_test_vectors(
+                &FrontierPrecompiles::<Runtime>::new(),
+                hash(address),
+                &format!("{}/{}", TESTDATA, file),
+            )
+        })
+    }
+
+    #[test]
+    fn addresses_are_unique() {
+        for (i, (address, _)) in PRECOMPILES.iter().enumerate() {
+            assert!(
+                !PRECOMPILES[i + 1..].iter().any(|(other, _)| other == address),
+                "{} registered twice",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn registry_lookups_are_charged_for_precompile_addresses_only() {
+        new_test_ext().execute_with(|| {
+            let precompiles = FrontierPrecompiles::<Runtime>::new();
+            let extra_cost = |address| match precompiles.is_precompile(address, 0) {
+                IsPrecompileResult::Answer { extra_cost, .. } => extra_cost,
+                _ => unreachable!(),
+            };
+            let read = <Runtime as pallet_evm::Config>::GasWeightMapping::weight_to_gas(
+                <Runtime as frame_system::Config>::DbWeight::get().reads(1),
+            );
+            assert!(read > 0);
+
+            assert_eq!(extra_cost(hash(2)), read);
+            assert_eq!(extra_cost(hash(3000)), read);
+            assert_eq!(extra_cost(hash(0x1_0000)), 0);
+            assert_eq!(extra_cost(H160::repeat_byte(0x11)), 0);
+        })
+    }
+
+    #[test]
+    fn registry_status_is_honoured() {
+        new_test_ext().execute_with(|| {
+            let precompiles = FrontierPrecompiles::<Runtime>::new();
+            let sha256 = hash(2);
+            let is_precompile = |address| match precompiles.is_precompile(address, 0) {
+                IsPrecompileResult::Answer { is_precompile, .. } => is_precompile,
+                _ => unreachable!(),
+            };
+            let mut handle = MockHandle::new(
+                Vec::new(),
+                Some(100_000),
+                Context {
+                    address: sha256,
+                    caller: Default::default(),
+                    apparent_value: Default::default(),
+                },
+            );
+            handle.code_address = sha256;
+            assert!(is_precompile(sha256));
+            assert!(matches!(precompiles.execute(&mut handle), Some(Ok(_))));
+
+            PrecompileRegistry::set_status(
+                crate::RuntimeOrigin::root(),
+                sha256,
+                PrecompileStatus::ChargeOnly,
+            )
+            .unwrap();
+            assert!(is_precompile(sha256));
+            assert!(matches!(
+                precompiles.execute(&mut handle),
+                Some(Err(PrecompileFailure::Error { .. }))
+            ));
+
+            PrecompileRegistry::set_status(
+                crate::RuntimeOrigin::root(),
+                sha256,
+                PrecompileStatus::Disabled,
+            )
+            .unwrap();
+            assert!(!is_precompile(sha256));
+            assert!(precompiles.execute(&mut handle).is_none());
+
+            // Moving the precompile only takes a new registration.
+            PrecompileRegistry::set_precompile(
+                crate::RuntimeOrigin::root(),
+                hash(4096),
+                PrecompileId::Sha256,
+                PrecompileStatus::Active,
+            )
+            .unwrap();
+            assert!(is_precompile(hash(4096)));
+        })
+    }
+
+    #[test]
+    fn asset_addresses_follow_asset_lifecycle() {
+        new_test_ext().execute_with(|| {
+            let precompiles = FrontierPrecompiles::<Runtime>::new();
+            let token = pallet_evm_precompile_assets_erc20::asset_address(AssetsErc20Prefix::get(), 7);
+            let is_precompile = |address| match precompiles.is_precompile(address, 0) {
+                IsPrecompileResult::Answer { is_precompile, .. } => is_precompile,
+                _ => unreachable!(),
+            };
+            assert!(!is_precompile(token));
+
+            crate::Assets::force_create(
+                crate::RuntimeOrigin::root(),
+                7.into(),
+                AccountId::from([1u8; 32]).into(),
+                true,
+                1,
+            )
+            .unwrap();
+            assert!(is_precompile(token));
+            assert_eq!(
+                pallet_evm::AccountCodes::<Runtime>::get(token),
+                precompile_utils::REVERT_CODE.to_vec()
+            );
+
+            let mut handle = MockHandle::new(
+                // totalSupply()
+                vec![0x18, 0x16, 0x0d, 0xdd],
+                Some(100_000),
+                Context {
+                    address: token,
+                    caller: Default::default(),
+                    apparent_value: Default::default(),
+                },
+            );
+            handle.code_address = token;
+            assert!(matches!(precompiles.execute(&mut handle), Some(Ok(_))));
+
+            // The same id under the pool prefix is a different, missing asset.
+            let pool_token =
+                pallet_evm_precompile_assets_erc20::asset_address(PoolAssetsErc20Prefix::get(), 7);
+            assert!(!is_precompile(pool_token));
+        })
+    }
+
+    #[test]
+    fn collection_addresses_follow_collections() {
+        new_test_ext().execute_with(|| {
+            let precompiles = FrontierPrecompiles::<Runtime>::new();
+            let collection =
+                pallet_evm_precompile_nfts_erc721::collection_address(NftsErc721Prefix::get(), 0);
+            let is_precompile = |address| match precompiles.is_precompile(address, 0) {
+                IsPrecompileResult::Answer { is_precompile, .. } => is_precompile,
+                _ => unreachable!(),
+            };
+            assert!(!is_precompile(collection));
+
+            crate::Nfts::force_create(
+                crate::RuntimeOrigin::root(),
+                AccountId::from([1u8; 32]).into(),
+                pallet_nfts::CollectionConfig {
+                    settings: Default::default(),
+                    max_supply: None,
+                    mint_settings: Default::default(),
+                },
+            )
+            .unwrap();
+            assert!(is_precompile(collection));
+        })
+    }
+
+    #[test]
+    fn governance_track_origins_match_tracks() {
+        use pallet_referenda::TracksInfo as _;
+
+        for (track, _) in crate::TracksInfo::tracks() {
+            let origin = GovernanceTrackOrigins::convert(*track).expect("every track has an origin");
+            assert_eq!(crate::TracksInfo::track_for(&origin), Ok(*track));
+        }
+        assert!(GovernanceTrackOrigins::convert(u16::MAX).is_none());
+    }
+
+    #[test]
+    fn dispatch_filter_admits_allowlisted_calls_only() {
+        let who = AccountId::from([1u8; 32]);
+        let chill = RuntimeCall::Staking(pallet_staking::Call::chill {});
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: Vec::new() });
+        let batch = |calls| RuntimeCall::Utility(pallet_utility::Call::batch_all { calls });
+
+        assert!(DispatchCallFilter::validate_before_dispatch(&who, &chill).is_none());
+        assert!(DispatchCallFilter::validate_before_dispatch(&who, &batch(vec![chill.clone()])).is_none());
+        assert!(DispatchCallFilter::validate_before_dispatch(&who, &remark).is_some());
+        assert!(DispatchCallFilter::validate_before_dispatch(&who, &batch(vec![chill, remark])).is_some());
+        assert!(DispatchCallFilter::validate_before_dispatch(
+            &who,
+            &RuntimeCall::Sudo(pallet_sudo::Call::sudo { call: Box::new(batch(Vec::new())) }),
+        )
+        .is_some());
+        assert!(DispatchCallFilter::validate_before_dispatch(
+            &who,
+            &RuntimeCall::Staking(pallet_staking::Call::kick { who: Vec::new() }),
+        )
+        .is_some());
+        assert!(DispatchCallFilter::validate_before_dispatch(
+            &who,
+            &RuntimeCall::PalletCounter(pallet_counter::Call::balance_transfer_new {
+                to: who.clone(),
+                amount: 1,
+                message: Vec::new(),
+            }),
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn dispatch_filter_rejects_operational_and_mandatory_calls() {
+        let who = AccountId::from([1u8; 32]);
+        let invalid =
+            Some(PrecompileFailure::Error { exit_status: ExitError::Other("invalid call".into()) });
+        let operational = RuntimeCall::System(frame_system::Call::set_heap_pages { pages: 1 });
+        let mandatory = RuntimeCall::Timestamp(pallet_timestamp::Call::set { now: 0 });
+        assert_eq!(operational.get_dispatch_info().class, DispatchClass::Operational);
+        assert_eq!(mandatory.get_dispatch_info().class, DispatchClass::Mandatory);
+
+        assert_eq!(DispatchCallFilter::validate_before_dispatch(&who, &operational), invalid);
+        assert_eq!(DispatchCallFilter::validate_before_dispatch(&who, &mandatory), invalid);
+        assert_eq!(
+            DispatchCallFilter::validate_before_dispatch(
+                &who,
+                &RuntimeCall::Utility(pallet_utility::Call::batch_all { calls: vec![operational] }),
+            ),
+            invalid
+        );
+    }
+
+    #[test]
+    fn dispatch_rejects_delegatecall() {
+        new_test_ext().execute_with(|| {
+            let precompiles = FrontierPrecompiles::<Runtime>::new();
+            let dispatch = hash(1043);
+            let contract = H160::repeat_byte(0xcc);
+            let call = |address| {
+                let mut handle = MockHandle::new(
+                    RuntimeCall::Staking(pallet_staking::Call::chill {}).encode(),
+                    Some(1_000_000),
+                    Context {
+                        address,
+                        caller: H160::repeat_byte(0xaa),
+                        apparent_value: Default::default(),
+                    },
+                );
+                handle.code_address = dispatch;
+                precompiles.execute(&mut handle).expect("dispatch is a precompile")
+            };
+
+            assert_eq!(
+                call(contract).unwrap_err(),
+                precompile_utils::revert("cannot be called with DELEGATECALL or CALLCODE")
+            );
+            // Called directly, the same input reaches `Staking`, which finds no ledger.
+            assert!(matches!(call(dispatch), Err(PrecompileFailure::Error { .. })));
+        })
+    }
+
+    #[test]
+    fn ethereum_vectors() -> Result<(), String> {
+        run(1, "ecRecover.json")?;
+        run(2, "common_sha256.json")?;
+        run(3, "common_ripemd.json")?;
+        run(5, "modexp_eip2565.json")?;
+        run(6, "common_bnadd.json")?;
+        run(7, "common_bnmul.json")?;
+        run(8, "common_bnpair.json")?;
+        run(9, "blake2F.json")
+    }
+
+    #[test]
+    fn bls12377_vectors() -> Result<(), String> {
+        run(1029, "bls12377G1Add.json")?;
+        run(1030, "bls12377G1Mul.json")?;
+        run(1031, "bls12377G1MultiExp.json")?;
+        run(1032, "bls12377G2Add.json")?;
+        run(1033, "bls12377G2Mul.json")?;
+        run(1034, "bls12377G2MultiExp.json")?;
+        run_failures(1029, "fail-bls12377G1Add.json")?;
+        run_failures(1030, "fail-bls12377G1Mul.json")?;
+        run_failures(1031, "fail-bls12377G1MultiExp.json")?;
+        run_failures(1032, "fail-bls12377G2Add.json")?;
+        run_failures(1033, "fail-bls12377G2Mul.json")?;
+        run_failures(1034, "fail-bls12377G2MultiExp.json")?;
+        run_failures(1035, "fail-bls12377Pairing.json")
+    }
+
+    #[test]
+    fn bw6761_vectors() -> Result<(), String> {
+        run(1036, "bw6761G1Add.json")?;
+        run(1037, "bw6761G1Mul.json")?;
+        run(1038, "bw6761G1MultiExp.json")?;
+        run(1039, "bw6761G2Add.json")?;
+        run(1040, "bw6761G2Mul.json")?;
+        run(1041, "bw6761G2MultiExp.json")?;
+        run_failures(1036, "fail-bw6761G1Add.json")?;
+        run_failures(1037, "fail-bw6761G1Mul.json")?;
+        run_failures(1038, "fail-bw6761G1MultiExp.json")?;
+        run_failures(1039, "fail-bw6761G2Add.json")?;
+        run_failures(1040, "fail-bw6761G2Mul.json")?;
+        run_failures(1041, "fail-bw6761G2MultiExp.json")?;
+        run_failures(1042, "fail-bw6761Pairing.json")
+    }
+}